@@ -0,0 +1,59 @@
+// Copyright Jeron A. Lau 2017-2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+// Two windows, drawn in turn on the same thread.  Each has it's own OpenGL
+// context, with a model & shape made in a different order, so the GL names
+// in one match different objects in the other.  Both should show a square.
+
+extern crate awi;
+
+use awi::Matrix;
+use awi::render::{ new_display, Display };
+
+const SQUARE: [f32; 16] = [
+	-0.5, -0.5, 0.0, 1.0,
+	0.5, -0.5, 0.0, 1.0,
+	0.5, 0.5, 0.0, 1.0,
+	-0.5, 0.5, 0.0, 1.0,
+];
+
+fn identity() -> Matrix {
+	Matrix::new(
+		1.0, 0.0, 0.0, 0.0,
+		0.0, 1.0, 0.0, 0.0,
+		0.0, 0.0, 1.0, 0.0,
+		0.0, 0.0, 0.0, 1.0,
+	)
+}
+
+fn open(color: [f32; 4], unused_first: bool) -> Box<Display> {
+	let mut display = new_display().unwrap();
+
+	// Take up the first buffer name, so the square's differs.
+	if unused_first {
+		display.model_points(&[0.0, 0.0, 0.0, 1.0]);
+	}
+
+	let square = display.model(&SQUARE, vec![(0, 4)]);
+	display.shape_solid(&square, identity(), color, false, false,
+		false);
+	display
+}
+
+pub fn main() -> () {
+	let mut a = open([1.0, 0.0, 0.0, 1.0], false);
+	let mut b = open([0.0, 0.0, 1.0, 1.0], true);
+
+	loop {
+		for display in [&mut a, &mut b].iter_mut() {
+			while let Some(event) = display.input() {
+				if event == awi::Event::Exit {
+					return;
+				}
+			}
+			display.update();
+		}
+	}
+}
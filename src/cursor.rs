@@ -0,0 +1,31 @@
+// Copyright Jeron A. Lau 2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+/// The shape of the mouse cursor, while it's over the window.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Cursor {
+	/// The normal pointer (default).
+	Arrow,
+	/// Text selection I-beam.
+	IBeam,
+	/// Hand, for links & buttons.
+	Hand,
+	/// Horizontal (left & right) resize arrow.
+	ResizeH,
+	/// Vertical (up & down) resize arrow.
+	ResizeV,
+	/// Crosshair, for precise selection.
+	Crosshair,
+	/// Busy / waiting.
+	Wait,
+	/// No cursor.
+	Hidden,
+}
+
+impl Default for Cursor {
+	fn default() -> Cursor {
+		Cursor::Arrow
+	}
+}
@@ -3,97 +3,267 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-pub mod modifiers;
+//! Keyboard key constants (one for each key `Event`), and their names.
+
+pub(crate) mod modifiers;
 
 const BIT64 : u64 =
 	0b1000000000000000000000000000000000000000000000000000000000000000u64;
-const BIT16 : u16 = 0b1000000000000000u16;
-
-pub(crate) const NUM1: u8 = 0;
-pub(crate) const NUM2: u8 = 1;
-pub(crate) const NUM3: u8 = 2;
-pub(crate) const NUM4: u8 = 3;
-pub(crate) const NUM5: u8 = 4;
-pub(crate) const NUM6: u8 = 5;
-pub(crate) const NUM7: u8 = 6;
-pub(crate) const NUM8: u8 = 7;
-pub(crate) const NUM9: u8 = 8;
-pub(crate) const NUM0: u8 = 9;
-pub(crate) const MINUS: u8 = 10;
-pub(crate) const EQUAL_SIGN: u8 = 11;
-pub(crate) const BACKSPACE: u8 = 12;
-pub(crate) const TAB: u8 = 13;
-pub(crate) const Q: u8 = 14;
-pub(crate) const W: u8 = 15;
-pub(crate) const E: u8 = 16;
-pub(crate) const R: u8 = 17;
-pub(crate) const T: u8 = 18;
-pub(crate) const Y: u8 = 19;
-pub(crate) const U: u8 = 20;
-pub(crate) const I: u8 = 21;
-pub(crate) const O: u8 = 22;
-pub(crate) const P: u8 = 23;
-pub(crate) const BRACKET_OPEN: u8 = 24;
-pub(crate) const BRACKET_CLOSE: u8 = 25;
-pub(crate) const BACKSLASH: u8 = 26;
-pub(crate) const COMPOSE: u8 = 27;
-pub(crate) const A: u8 = 28;
-pub(crate) const S: u8 = 29;
-pub(crate) const D: u8 = 30;
-pub(crate) const F: u8 = 31;
-pub(crate) const G: u8 = 32;
-pub(crate) const H: u8 = 33;
-pub(crate) const J: u8 = 34;
-pub(crate) const K: u8 = 35;
-pub(crate) const L: u8 = 36;
-pub(crate) const SEMICOLON: u8 = 37;
-pub(crate) const APOSTROPHE: u8 = 38;
-pub(crate) const ENTER: u8 = 39;
-pub(crate) const LSHIFT: u8 = 40;
-pub(crate) const Z: u8 = 41;
-pub(crate) const X: u8 = 42;
-pub(crate) const C: u8 = 43;
-pub(crate) const V: u8 = 44;
-pub(crate) const B: u8 = 45;
-pub(crate) const N: u8 = 46;
-pub(crate) const M: u8 = 47;
-pub(crate) const COMMA: u8 = 48;
-pub(crate) const PERIOD: u8 = 49;
-pub(crate) const SLASH: u8 = 50;
-pub(crate) const RSHIFT: u8 = 51;
-pub(crate) const LCTRL: u8 = 52;
-pub(crate) const ALT: u8 = 53;
-pub(crate) const SPACE: u8 = 54;
-pub(crate) const RCTRL: u8 = 55;
-pub(crate) const UP: u8 = 56;
-pub(crate) const DOWN: u8 = 57;
-pub(crate) const LEFT: u8 = 58;
-pub(crate) const RIGHT: u8 = 59;
-
-pub(crate) const EXT_BACKTICK: u8 = 64;
-pub(crate) const EXT_DELETE: u8 = 65;
-pub(crate) const EXT_INSERT: u8 = 66;
-pub(crate) const EXT_NUM_LOCK: u8 = 67;
-pub(crate) const EXT_PAGE_UP: u8 = 68;
-pub(crate) const EXT_PAGE_DOWN: u8 = 69;
-pub(crate) const EXT_HOME: u8 = 70;
-pub(crate) const EXT_END: u8 = 71;
-pub(crate) const EXT_ASTERISK: u8 = 72;
-pub(crate) const EXT_PLUS: u8 = 73;
-pub(crate) const EXT_ALT_GR: u8 = 74;
+
+/// `Event::Num1`
+pub const NUM1: u8 = 0;
+/// `Event::Num2`
+pub const NUM2: u8 = 1;
+/// `Event::Num3`
+pub const NUM3: u8 = 2;
+/// `Event::Num4`
+pub const NUM4: u8 = 3;
+/// `Event::Num5`
+pub const NUM5: u8 = 4;
+/// `Event::Num6`
+pub const NUM6: u8 = 5;
+/// `Event::Num7`
+pub const NUM7: u8 = 6;
+/// `Event::Num8`
+pub const NUM8: u8 = 7;
+/// `Event::Num9`
+pub const NUM9: u8 = 8;
+/// `Event::Num0`
+pub const NUM0: u8 = 9;
+/// `Event::Minus`
+pub const MINUS: u8 = 10;
+/// `Event::EqualSign`
+pub const EQUAL_SIGN: u8 = 11;
+/// `Event::Backspace`
+pub const BACKSPACE: u8 = 12;
+/// `Event::Tab`
+pub const TAB: u8 = 13;
+/// `Event::Q`
+pub const Q: u8 = 14;
+/// `Event::W`
+pub const W: u8 = 15;
+/// `Event::E`
+pub const E: u8 = 16;
+/// `Event::R`
+pub const R: u8 = 17;
+/// `Event::T`
+pub const T: u8 = 18;
+/// `Event::Y`
+pub const Y: u8 = 19;
+/// `Event::U`
+pub const U: u8 = 20;
+/// `Event::I`
+pub const I: u8 = 21;
+/// `Event::O`
+pub const O: u8 = 22;
+/// `Event::P`
+pub const P: u8 = 23;
+/// `Event::BracketOpen`
+pub const BRACKET_OPEN: u8 = 24;
+/// `Event::BracketClose`
+pub const BRACKET_CLOSE: u8 = 25;
+/// `Event::Backslash`
+pub const BACKSLASH: u8 = 26;
+/// `Event::Compose`
+pub const COMPOSE: u8 = 27;
+/// `Event::A`
+pub const A: u8 = 28;
+/// `Event::S`
+pub const S: u8 = 29;
+/// `Event::D`
+pub const D: u8 = 30;
+/// `Event::F`
+pub const F: u8 = 31;
+/// `Event::G`
+pub const G: u8 = 32;
+/// `Event::H`
+pub const H: u8 = 33;
+/// `Event::J`
+pub const J: u8 = 34;
+/// `Event::K`
+pub const K: u8 = 35;
+/// `Event::L`
+pub const L: u8 = 36;
+/// `Event::Semicolon`
+pub const SEMICOLON: u8 = 37;
+/// `Event::Apostrophe`
+pub const APOSTROPHE: u8 = 38;
+/// `Event::Enter`
+pub const ENTER: u8 = 39;
+/// `Event::LShift`
+pub const LSHIFT: u8 = 40;
+/// `Event::Z`
+pub const Z: u8 = 41;
+/// `Event::X`
+pub const X: u8 = 42;
+/// `Event::C`
+pub const C: u8 = 43;
+/// `Event::V`
+pub const V: u8 = 44;
+/// `Event::B`
+pub const B: u8 = 45;
+/// `Event::N`
+pub const N: u8 = 46;
+/// `Event::M`
+pub const M: u8 = 47;
+/// `Event::Comma`
+pub const COMMA: u8 = 48;
+/// `Event::Period`
+pub const PERIOD: u8 = 49;
+/// `Event::Slash`
+pub const SLASH: u8 = 50;
+/// `Event::RShift`
+pub const RSHIFT: u8 = 51;
+/// `Event::LCtrl`
+pub const LCTRL: u8 = 52;
+/// `Event::Alt`
+pub const ALT: u8 = 53;
+/// `Event::Space`
+pub const SPACE: u8 = 54;
+/// `Event::RCtrl`
+pub const RCTRL: u8 = 55;
+/// `Event::Up`
+pub const UP: u8 = 56;
+/// `Event::Down`
+pub const DOWN: u8 = 57;
+/// `Event::Left`
+pub const LEFT: u8 = 58;
+/// `Event::Right`
+pub const RIGHT: u8 = 59;
+
+/// `Event::ExtBacktick`
+pub const EXT_BACKTICK: u8 = 64;
+/// `Event::ExtDelete`
+pub const EXT_DELETE: u8 = 65;
+/// `Event::ExtInsert`
+pub const EXT_INSERT: u8 = 66;
+/// `Event::ExtNumLock`
+pub const EXT_NUM_LOCK: u8 = 67;
+/// `Event::ExtPageUp`
+pub const EXT_PAGE_UP: u8 = 68;
+/// `Event::ExtPageDown`
+pub const EXT_PAGE_DOWN: u8 = 69;
+/// `Event::ExtHome`
+pub const EXT_HOME: u8 = 70;
+/// `Event::ExtEnd`
+pub const EXT_END: u8 = 71;
+/// `Event::ExtAsterisk`
+pub const EXT_ASTERISK: u8 = 72;
+/// `Event::ExtPlus`
+pub const EXT_PLUS: u8 = 73;
+/// `Event::ExtAltGr`
+pub const EXT_ALT_GR: u8 = 74;
+/// `Event::ExtF1`
+pub const EXT_F1: u8 = 75;
+/// `Event::ExtF2`
+pub const EXT_F2: u8 = 76;
+/// `Event::ExtF3`
+pub const EXT_F3: u8 = 77;
+/// `Event::ExtF4`
+pub const EXT_F4: u8 = 78;
+/// `Event::ExtF5`
+pub const EXT_F5: u8 = 79;
+/// `Event::ExtF6`
+pub const EXT_F6: u8 = 80;
+/// `Event::ExtF7`
+pub const EXT_F7: u8 = 81;
+/// `Event::ExtF8`
+pub const EXT_F8: u8 = 82;
+/// `Event::ExtF9`
+pub const EXT_F9: u8 = 83;
+/// `Event::ExtF10`
+pub const EXT_F10: u8 = 84;
+/// `Event::ExtF11`
+pub const EXT_F11: u8 = 85;
+/// `Event::ExtF12`
+pub const EXT_F12: u8 = 86;
+/// `Event::ExtKp0`
+pub const EXT_KP0: u8 = 87;
+/// `Event::ExtKp1`
+pub const EXT_KP1: u8 = 88;
+/// `Event::ExtKp2`
+pub const EXT_KP2: u8 = 89;
+/// `Event::ExtKp3`
+pub const EXT_KP3: u8 = 90;
+/// `Event::ExtKp4`
+pub const EXT_KP4: u8 = 91;
+/// `Event::ExtKp5`
+pub const EXT_KP5: u8 = 92;
+/// `Event::ExtKp6`
+pub const EXT_KP6: u8 = 93;
+/// `Event::ExtKp7`
+pub const EXT_KP7: u8 = 94;
+/// `Event::ExtKp8`
+pub const EXT_KP8: u8 = 95;
+/// `Event::ExtKp9`
+pub const EXT_KP9: u8 = 96;
+/// `Event::ExtKpEnter`
+pub const EXT_KP_ENTER: u8 = 97;
+/// `Event::ExtKpDivide`
+pub const EXT_KP_DIVIDE: u8 = 98;
+/// `Event::ExtKpMinus`
+pub const EXT_KP_MINUS: u8 = 99;
+/// `Event::ExtKpPeriod`
+pub const EXT_KP_PERIOD: u8 = 100;
+
+/// Every key constant, with it's name.
+const KEYS: [(u8, &str); 97] = [
+	(NUM1, "1"), (NUM2, "2"), (NUM3, "3"), (NUM4, "4"), (NUM5, "5"),
+	(NUM6, "6"), (NUM7, "7"), (NUM8, "8"), (NUM9, "9"), (NUM0, "0"),
+	(MINUS, "-"), (EQUAL_SIGN, "="), (BACKSPACE, "Backspace"), (TAB, "Tab"),
+	(Q, "Q"), (W, "W"), (E, "E"), (R, "R"), (T, "T"), (Y, "Y"), (U, "U"),
+	(I, "I"), (O, "O"), (P, "P"), (BRACKET_OPEN, "["), (BRACKET_CLOSE, "]"),
+	(BACKSLASH, "\\"), (COMPOSE, "Compose"), (A, "A"), (S, "S"), (D, "D"),
+	(F, "F"), (G, "G"), (H, "H"), (J, "J"), (K, "K"), (L, "L"),
+	(SEMICOLON, ";"), (APOSTROPHE, "'"), (ENTER, "Enter"),
+	(LSHIFT, "Left Shift"), (Z, "Z"), (X, "X"), (C, "C"), (V, "V"),
+	(B, "B"), (N, "N"), (M, "M"), (COMMA, ","), (PERIOD, "."), (SLASH, "/"),
+	(RSHIFT, "Right Shift"), (LCTRL, "Left Ctrl"), (ALT, "Alt"),
+	(SPACE, "Space"), (RCTRL, "Right Ctrl"), (UP, "Up"), (DOWN, "Down"),
+	(LEFT, "Left"), (RIGHT, "Right"), (EXT_BACKTICK, "`"),
+	(EXT_DELETE, "Delete"), (EXT_INSERT, "Insert"),
+	(EXT_NUM_LOCK, "NumLock"), (EXT_PAGE_UP, "PageUp"),
+	(EXT_PAGE_DOWN, "PageDown"), (EXT_HOME, "Home"), (EXT_END, "End"),
+	(EXT_ASTERISK, "*"), (EXT_PLUS, "+"), (EXT_ALT_GR, "AltGr"),
+	(EXT_F1, "F1"), (EXT_F2, "F2"), (EXT_F3, "F3"), (EXT_F4, "F4"),
+	(EXT_F5, "F5"), (EXT_F6, "F6"), (EXT_F7, "F7"), (EXT_F8, "F8"),
+	(EXT_F9, "F9"), (EXT_F10, "F10"), (EXT_F11, "F11"), (EXT_F12, "F12"),
+	(EXT_KP0, "Kp0"), (EXT_KP1, "Kp1"), (EXT_KP2, "Kp2"), (EXT_KP3, "Kp3"),
+	(EXT_KP4, "Kp4"), (EXT_KP5, "Kp5"), (EXT_KP6, "Kp6"), (EXT_KP7, "Kp7"),
+	(EXT_KP8, "Kp8"), (EXT_KP9, "Kp9"), (EXT_KP_ENTER, "KpEnter"),
+	(EXT_KP_DIVIDE, "Kp/"), (EXT_KP_MINUS, "Kp-"), (EXT_KP_PERIOD, "Kp."),
+];
+
+/// Get the name of a key constant (for showing key bindings), `"Unknown"` if
+/// it's not one.
+pub fn name(key: u8) -> &'static str {
+	KEYS.iter().find(|k| k.0 == key).map(|k| k.1).unwrap_or("Unknown")
+}
+
+/// Get the key constant from it's name (from `name()`, ignoring case), `None`
+/// if no key has that name.
+pub fn from_name(name: &str) -> Option<u8> {
+	KEYS.iter().find(|k| k.1.eq_ignore_ascii_case(name)).map(|k| k.0)
+}
 
 /// A Computer keyboard.
-pub struct Keyboard {
+pub(crate) struct Keyboard {
 	keys: u64,
-	exts: u16,
+	exts: u64,
 	keyo: u64,
-	exto: u16,
+	exto: u64,
+	// Pressed scancodes, now and last frame.
+	raw: Vec<u32>,
+	rawo: Vec<u32>,
+	// Scancodes that aren't any key constant (for `Event::Unknown`).
+	unknown: Vec<u32>,
 }
 
 impl Keyboard {
 	/// Create a keyboard.
 	pub fn new() -> Keyboard {
-		Keyboard { keys: 0u64, exts: 0u16, keyo: 0u64, exto: 0u16 }
+		Keyboard { keys: 0u64, exts: 0u64, keyo: 0u64, exto: 0u64,
+			raw: vec![], rawo: vec![], unknown: vec![] }
 	}
 
 	/// Press a key.
@@ -101,7 +271,7 @@ impl Keyboard {
 		if key < 64 {
 			self.keys |= BIT64 >> key as usize;
 		} else {
-			self.exts |= BIT16 >> (key as usize - 64);
+			self.exts |= BIT64 >> (key as usize - 64);
 		}
 	}
 
@@ -110,44 +280,66 @@ impl Keyboard {
 		if key < 64 {
 			self.keys &= !(BIT64 >> key as usize);
 		} else {
-			self.exts &= !(BIT16 >> (key as usize - 64));
+			self.exts &= !(BIT64 >> (key as usize - 64));
+		}
+	}
+
+	/// Press a physical key, by platform scancode.
+	pub fn press_raw(&mut self, scancode: u32) {
+		if !self.raw.contains(&scancode) {
+			self.raw.push(scancode);
+		}
+	}
+
+	/// Mark a scancode as not being any key constant, so it's sent as
+	/// `Event::Unknown` too.
+	pub fn unknown(&mut self, scancode: u32) {
+		if !self.unknown.contains(&scancode) {
+			self.unknown.push(scancode);
 		}
 	}
 
+	/// Release a physical key, by platform scancode.
+	pub fn release_raw(&mut self, scancode: u32) {
+		self.raw.retain(|k| *k != scancode);
+	}
+
 	/// Get whether a key is pressed (`true`) or not (`false`).
 	pub fn get(&mut self, key: u8) -> bool {
 		if key < 64 {
 			(self.keys & (BIT64 >> key as usize)) != 0
 		} else {
-			(self.exts & (BIT16 >> (key as usize - 64))) != 0
+			(self.exts & (BIT64 >> (key as usize - 64))) != 0
 		}
 	}
 
 	/// Add keyboard input to the input queue.
 	pub fn add(&mut self, queue: &mut ::input::InputQueue) {
-		let variants = [
-			NUM1, NUM2, NUM3, NUM4, NUM5, NUM6, NUM7, NUM8, NUM9,
-			NUM0, MINUS, EQUAL_SIGN, BACKSPACE,
-			TAB, Q, W, E, R, T, Y, U, I, O, P, BRACKET_OPEN,
-			BRACKET_CLOSE, BACKSLASH,
-			COMPOSE, A, S, D, F, G, H, J, K, L, SEMICOLON,
-			APOSTROPHE, ENTER,
-			LSHIFT, Z, X, C, V, B, N, M, COMMA, PERIOD, SLASH,
-			RSHIFT,
-			LCTRL, ALT, SPACE, RCTRL, UP, DOWN,
-			LEFT, RIGHT,
-			EXT_BACKTICK, EXT_DELETE, EXT_INSERT, EXT_NUM_LOCK,
-			EXT_PAGE_UP, EXT_PAGE_DOWN, EXT_HOME, EXT_END,
-			EXT_ASTERISK, EXT_PLUS, EXT_ALT_GR
-		];
-
-		for v in variants.iter() {
-			self.a(queue, *v);
+		for key in KEYS.iter() {
+			self.a(queue, key.0);
+		}
+
+		for k in self.raw.iter() {
+			let state = Some(!self.rawo.contains(k));
+
+			queue.raw_key(*k, state);
+			if self.unknown.contains(k) {
+				queue.unknown_key(*k, state);
+			}
+		}
+		for k in self.rawo.iter() {
+			if !self.raw.contains(k) {
+				queue.raw_key(*k, None);
+				if self.unknown.contains(k) {
+					queue.unknown_key(*k, None);
+				}
+			}
 		}
 
 		// Set old keyboard state.
 		self.keyo = self.keys;
 		self.exto = self.exts;
+		self.rawo = self.raw.clone();
 	}
 
 	fn a(&mut self, queue: &mut ::input::InputQueue, key: u8) {
@@ -162,13 +354,13 @@ impl Keyboard {
 				}
 			}
 		} else {
-			if (self.exts & (BIT16 >> (key as usize - 64))) != 0 {
+			if (self.exts & (BIT64 >> (key as usize - 64))) != 0 {
 				queue.key(key, Some(
-					(self.exto & (BIT16 >> (key as usize - 64)))
+					(self.exto & (BIT64 >> (key as usize - 64)))
 						== 0
 				));
 			} else {
-				if (self.exto & (BIT16 >> (key as usize - 64)))
+				if (self.exto & (BIT64 >> (key as usize - 64)))
 					!= 0
 				{
 					queue.key(key, None);
@@ -177,3 +369,19 @@ impl Keyboard {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{ KEYS, name, from_name };
+
+	#[test]
+	fn name_round_trip() {
+		for &(key, key_name) in KEYS.iter() {
+			assert_eq!(from_name(name(key)), Some(key), "{}",
+				key_name);
+			assert_eq!(from_name(&key_name.to_lowercase()),
+				Some(key));
+		}
+		assert_eq!(from_name("Unknown"), None);
+	}
+}
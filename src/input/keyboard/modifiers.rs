@@ -15,16 +15,31 @@ const CTRL_SHIFT : u8 = CTRL | SHIFT;
 
 pub(crate) struct Modifiers {
 	held: u8,
+	// Whether key combos make shortcut events (like Ctrl-C for `Copy`).
+	shortcuts: bool,
 }
 
 impl Modifiers {
 	pub fn create() -> Modifiers {
-		Modifiers { held: NONE }
+		Modifiers { held: NONE, shortcuts: true }
+	}
+
+	pub fn set_shortcuts(&mut self, enabled: bool) {
+		self.shortcuts = enabled;
+	}
+
+	// The modifier keys held right now, for a click.
+	pub fn click_mods(&self) -> ::input::ClickMods {
+		::input::ClickMods {
+			shift: self.held & SHIFT != 0,
+			ctrl: self.held & CTRL != 0,
+			alt: self.held & ALT != 0,
+		}
 	}
 
 	pub fn update(&mut self, queue: &mut Vec<Event>, input: Event) -> () {
 		match input {
-			Event::Text(_) => match self.held {
+			Event::Text(_) | Event::TextStr(_) => match self.held {
 				NONE | SHIFT => {},
 				_ => return, // Ctrl,Shift,Alt shouldn't print.
 			},
@@ -59,6 +74,8 @@ impl Modifiers {
 					}
 				}
 			},
+			// Only the key events, not what they're shortcuts for.
+			_ if !self.shortcuts => {},
 			Event::A(state) => if state.is_some() { self.a(queue) },
 			Event::B(state) => if state.is_some() { self.b(queue) },
 			Event::C(state) => if state.is_some() { self.c(queue) },
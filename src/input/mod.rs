@@ -3,23 +3,141 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-pub(crate) mod keyboard;
+pub mod keyboard;
 
-/// Window Input Event, put on queue when an event has occurred.
-#[derive(PartialEq, Copy, Clone)]
+use std::time::{ Duration, Instant };
+
+/// Where the deltas of an `Event::Scroll` came from.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+pub enum ScrollKind {
+	/// Smooth sub-tick deltas (touchpad), scaled so about 1.0 is as far as
+	/// one wheel notch.
+	Precise,
+	/// Whole notches of a mouse wheel, 1.0 per notch (the OS may scroll
+	/// 3 lines for each).
+	Line,
+}
+
+/// The modifier keys held down for a click (`Event::ClickDetailed`).
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+pub struct ClickMods {
+	/// Either Shift key
+	pub shift: bool,
+	/// Either Ctrl key
+	pub ctrl: bool,
+	/// Alt
+	pub alt: bool,
+}
+
+/// A controller button, for remapping with `set_controller_map()`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+pub enum ControllerButton {
+	/// `Event::CAccept`
+	Accept,
+	/// `Event::CCancel`
+	Cancel,
+	/// `Event::CExecute`
+	Execute,
+	/// `Event::CAction`
+	Action,
+	/// `Event::CL`, with the button number
+	L(u8),
+	/// `Event::CR`, with the button number
+	R(u8),
+	/// `Event::CMenu`
+	Menu,
+	/// `Event::CUp`
+	Up,
+	/// `Event::CDown`
+	Down,
+	/// `Event::CLeft`
+	Left,
+	/// `Event::CRight`
+	Right,
+	/// `Event::CMoveStick`
+	MoveStick,
+	/// `Event::CCamStick`
+	CamStick,
+}
+
+impl ControllerButton {
+	// The event for this button on controller `js`.
+	fn event(self, js: usize, s: Option<bool>) -> Event {
+		use self::ControllerButton::*;
+
+		match self {
+			Accept => Event::CAccept(js, s),
+			Cancel => Event::CCancel(js, s),
+			Execute => Event::CExecute(js, s),
+			Action => Event::CAction(js, s),
+			L(b) => Event::CL(js, b, s),
+			R(b) => Event::CR(js, b, s),
+			Menu => Event::CMenu(js, s),
+			Up => Event::CUp(js, s),
+			Down => Event::CDown(js, s),
+			Left => Event::CLeft(js, s),
+			Right => Event::CRight(js, s),
+			MoveStick => Event::CMoveStick(js, s),
+			CamStick => Event::CCamStick(js, s),
+		}
+	}
+}
+
+/// What a controller's hardware has, from `controller_capabilities()`.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ControllerCaps {
+	/// Number of axes (sticks & throttles, not counting hats).
+	pub axes: u8,
+	/// Number of buttons.
+	pub buttons: u16,
+	/// Number of hats (D-pads that are axes).
+	pub hats: u8,
+	/// Whether it can rumble.
+	pub rumble: bool,
+}
+
+/// Window Input Event, put on queue when an event has occurred.  With the
+/// "serde" feature, events can be serialized for recording & replaying.
+#[derive(PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
 pub enum Event {
-	/// Timestep event.
+	/// Timestep event.  `Screen` sends one every frame, after the other
+	/// events.  With `set_timestep()`, it's sent at a fixed rate instead
+	/// (maybe more than once per frame, to catch up).
 	Timestep,
 	/// Exit event (Back key / Esc / 'X' button on app's window / Ctrl-Q).
 	Exit,
 	/// The window has just been resized.
 	Resize,
+	/// The window has just been moved (Top-left XY on the screen, in
+	/// pixels).
+	Moved(i16, i16),
+	/// The content scale factor has changed (new scale factor), see
+	/// `scale_factor()`.
+	ScaleChanged(f32),
+	/// The window is on screen, so what's drawn won't be lost (only sent
+	/// once, the first time it's shown).  Wait for it before the first
+	/// heavy draw.
+	Ready,
 	/// The user has switched to this window (in focus).
 	Resume,
 	/// The user has switched to a different window (out of focus).
 	Pause,
-	/// The user has inputted text.
+	/// The user has inputted text (including text committed by an input
+	/// method).
 	Text(char),
+	/// The user has inputted text, all at once instead of `Text` with
+	/// `set_text_str()` (so grapheme clusters aren't split), get it with
+	/// `text_str()` (Index for `text_str()`).
+	TextStr(usize),
+	/// The text being composed with an input method (IME) has changed,
+	/// get it with `preedit()` (Cursor position, in chars).  Empty when
+	/// the composition ends.  Only sent on Windows for now, X11 and
+	/// Wayland only send the committed text.
+	Preedit(usize),
 	/// Keyboard Shortcut - (CTRL-L) Align Left
 	AlignLeft,
 	/// Keyboard Shortcut - (CTRL-;) Align Center
@@ -62,8 +180,8 @@ pub enum Event {
 	Info,
 	/// Keyboard Shortcut - Close (Ctrl-W)
 	Close,
-	/// Keyboard Shortcut - Open (Ctrl-O)
-	Open(Option<&'static str>),
+	/// Keyboard Shortcut - Open (Ctrl-O).  The path isn't serialized.
+	Open(#[cfg_attr(feature="serde", serde(skip))] Option<&'static str>),
 	/// Keyboard Shortcut - Share (Ctrl-S)
 	Share,
 	/// Keyboard Shortcut - Save Copy (Ctrl-Shift-S)
@@ -80,17 +198,59 @@ pub enum Event {
 	Print,
 	/// Cursor moved
 	Cursor(Option<(f32,f32)>),
+	/// Raw mouse motion, in device units without pointer acceleration, and
+	/// not stopped at the window's edges (for mouse-look).  Sent along with
+	/// `Cursor` (Delta XY).
+	MotionRaw(f32, f32),
 	/// Left Click (Some(Just Clicked) = Pressed, Cursor XY)
 	LeftButton(Option<bool>, Option<(f32, f32)>),
 	/// Middle Click (or SHIFT-Click) (Some(Just Clicked) = Pressed, Cursor XY)
 	MiddleButton(Option<bool>, Option<(f32, f32)>),
 	/// Right Click (or CTRL-Click) (Some(Just Clicked) = Pressed, Cursor XY)
 	RightButton(Option<bool>, Option<(f32, f32)>),
-	/// Touch (on a touchscreen) (Some(Just Clicked) = Pressed, Cursor XY)
-	Touch(Option<bool>, Option<(f32, f32)>),
+	/// Mouse button with the modifier keys held when it was pressed or
+	/// released, for Ctrl-Click & Shift-Click.  Sent along with
+	/// `LeftButton`, `MiddleButton` & `RightButton` (Button: 0 is Left, 1
+	/// is Middle, 2 is Right, Some(Just Clicked) = Pressed, Cursor XY,
+	/// Modifiers)
+	ClickDetailed(u8, Option<bool>, Option<(f32, f32)>, ClickMods),
+	/// Touch (on a touchscreen) (Touch ID, Some(Just Touched) = Pressed,
+	/// Touch XY)
+	Touch(u64, Option<bool>, Option<(f32, f32)>),
+	/// Touch with pressure & contact size, sent along with `Touch` (Touch
+	/// ID, Some(Just Touched) = Pressed, Touch XY, Pressure from 0 to 1 (1
+	/// if unknown, 0 when released), Contact Size from 0 to 1 (0 if
+	/// unknown))
+	TouchDetailed(u64, Option<bool>, Option<(f32, f32)>, f32, f32),
+	/// Stylus on a pen tablet moved, pressed or released.  Not sent as
+	/// `Cursor` or `LeftButton`.
+	Pen {
+		/// Pen XY
+		xy: Option<(f32, f32)>,
+		/// Pressure from 0 to 1 (0 when hovering)
+		pressure: f32,
+		/// Tilt XY from -1 to 1 ((0, 0) is upright, or if unknown)
+		tilt: (f32, f32),
+		/// If it's the eraser end of the stylus
+		eraser: bool,
+	},
+	/// Two finger pinch (Scale since the last pinch event: > 1.0 is
+	/// zooming in, Center XY)
+	Pinch(f32, Option<(f32, f32)>),
+	/// Two finger rotation (Radians since the last rotate event, Center XY)
+	Rotate(f32, Option<(f32, f32)>),
 	/// Touchpad / Mousewheel scroll (x, y) - (-1, -1) is up / left, (1, 1)
-	/// is down / right (Scroll XY, Cursor XY)
-	Scroll((f32, f32), Option<(f32, f32)>),
+	/// is down / right, one wheel notch each (Scroll XY, Cursor XY, Kind).
+	/// Up is rolling the wheel away from you, unless the OS has natural
+	/// scrolling on (see `set_natural_scroll()`).
+	Scroll((f32, f32), Option<(f32, f32)>, ScrollKind),
+	/// Physical key, as the platform's scancode, no matter what the
+	/// keyboard layout is (for rebindable controls).  Sent along with the
+	/// key events below (Some(Just Pressed) = Held, None = Released).
+	RawKey(u32, Option<bool>),
+	/// Key that isn't any of the key events below (like media keys), as
+	/// the platform's scancode (Some(Just Pressed) = Held, None = Released)
+	Unknown(u32, Option<bool>),
 	// Note: These rows are not necessarily the rows these keys are found.
 	// Row1
 	/// 1
@@ -234,12 +394,64 @@ pub enum Event {
 	ExtHome(Option<bool>), // = 70,
 	/// End
 	ExtEnd(Option<bool>), // = 71,
-	/// \*
+	/// Numpad \*
 	ExtAsterisk(Option<bool>), // = 72,
-	/// \+
+	/// Numpad \+
 	ExtPlus(Option<bool>), // = 73,
 	/// AltGr (Right Alt)
-	ExtAltGr(Option<bool>), // = 74
+	ExtAltGr(Option<bool>), // = 74,
+	/// F1
+	ExtF1(Option<bool>), // = 75,
+	/// F2
+	ExtF2(Option<bool>), // = 76,
+	/// F3
+	ExtF3(Option<bool>), // = 77,
+	/// F4
+	ExtF4(Option<bool>), // = 78,
+	/// F5
+	ExtF5(Option<bool>), // = 79,
+	/// F6
+	ExtF6(Option<bool>), // = 80,
+	/// F7
+	ExtF7(Option<bool>), // = 81,
+	/// F8
+	ExtF8(Option<bool>), // = 82,
+	/// F9
+	ExtF9(Option<bool>), // = 83,
+	/// F10
+	ExtF10(Option<bool>), // = 84,
+	/// F11
+	ExtF11(Option<bool>), // = 85,
+	/// F12
+	ExtF12(Option<bool>), // = 86,
+	/// Numpad 0
+	ExtKp0(Option<bool>), // = 87,
+	/// Numpad 1
+	ExtKp1(Option<bool>), // = 88,
+	/// Numpad 2
+	ExtKp2(Option<bool>), // = 89,
+	/// Numpad 3
+	ExtKp3(Option<bool>), // = 90,
+	/// Numpad 4
+	ExtKp4(Option<bool>), // = 91,
+	/// Numpad 5
+	ExtKp5(Option<bool>), // = 92,
+	/// Numpad 6
+	ExtKp6(Option<bool>), // = 93,
+	/// Numpad 7
+	ExtKp7(Option<bool>), // = 94,
+	/// Numpad 8
+	ExtKp8(Option<bool>), // = 95,
+	/// Numpad 9
+	ExtKp9(Option<bool>), // = 96,
+	/// Numpad Enter
+	ExtKpEnter(Option<bool>), // = 97,
+	/// Numpad /
+	ExtKpDivide(Option<bool>), // = 98,
+	/// Numpad -
+	ExtKpMinus(Option<bool>), // = 99,
+	/// Numpad . (Decimal)
+	ExtKpPeriod(Option<bool>), // = 100
 	/// Controller: Main joystick movement.
 	CMove(usize, f32, f32),
 	/// Controller: Camera / C joystick movement.
@@ -282,7 +494,8 @@ pub enum Event {
 	CMoveStick(usize, Option<bool>),
 	/// Controller: Camera stick Push
 	CCamStick(usize, Option<bool>),
-	/// Controller: Device Plugged-In
+	/// Controller: Device Plugged-In (a controller plugged back in gets
+	/// the same number it had, see `controller_guid()`)
 	CPluggedIn(usize, i32),
 	/// Controller: Device Un-Plugged
 	CUnPlugged(usize, i32),
@@ -290,16 +503,147 @@ pub enum Event {
 
 use self::Event::*;
 
+impl Event {
+	/// Get the `keyboard::` constant & state of a key event, `None` if it's
+	/// not one.  `keyboard::name()` gets it's label.
+	pub fn key(&self) -> Option<(u8, Option<bool>)> {
+		Some(match *self {
+			Num1(state) => (keyboard::NUM1, state),
+			Num2(state) => (keyboard::NUM2, state),
+			Num3(state) => (keyboard::NUM3, state),
+			Num4(state) => (keyboard::NUM4, state),
+			Num5(state) => (keyboard::NUM5, state),
+			Num6(state) => (keyboard::NUM6, state),
+			Num7(state) => (keyboard::NUM7, state),
+			Num8(state) => (keyboard::NUM8, state),
+			Num9(state) => (keyboard::NUM9, state),
+			Num0(state) => (keyboard::NUM0, state),
+			Minus(state) => (keyboard::MINUS, state),
+			EqualSign(state) => (keyboard::EQUAL_SIGN, state),
+			Backspace(state) => (keyboard::BACKSPACE, state),
+			Tab(state) => (keyboard::TAB, state),
+			Q(state) => (keyboard::Q, state),
+			W(state) => (keyboard::W, state),
+			E(state) => (keyboard::E, state),
+			R(state) => (keyboard::R, state),
+			T(state) => (keyboard::T, state),
+			Y(state) => (keyboard::Y, state),
+			U(state) => (keyboard::U, state),
+			I(state) => (keyboard::I, state),
+			O(state) => (keyboard::O, state),
+			P(state) => (keyboard::P, state),
+			BracketOpen(state) => (keyboard::BRACKET_OPEN, state),
+			BracketClose(state) => (keyboard::BRACKET_CLOSE, state),
+			Backslash(state) => (keyboard::BACKSLASH, state),
+			Compose(state) => (keyboard::COMPOSE, state),
+			A(state) => (keyboard::A, state),
+			S(state) => (keyboard::S, state),
+			D(state) => (keyboard::D, state),
+			F(state) => (keyboard::F, state),
+			G(state) => (keyboard::G, state),
+			H(state) => (keyboard::H, state),
+			J(state) => (keyboard::J, state),
+			K(state) => (keyboard::K, state),
+			L(state) => (keyboard::L, state),
+			Semicolon(state) => (keyboard::SEMICOLON, state),
+			Apostrophe(state) => (keyboard::APOSTROPHE, state),
+			Enter(state) => (keyboard::ENTER, state),
+			LShift(state) => (keyboard::LSHIFT, state),
+			Z(state) => (keyboard::Z, state),
+			X(state) => (keyboard::X, state),
+			C(state) => (keyboard::C, state),
+			V(state) => (keyboard::V, state),
+			B(state) => (keyboard::B, state),
+			N(state) => (keyboard::N, state),
+			M(state) => (keyboard::M, state),
+			Comma(state) => (keyboard::COMMA, state),
+			Period(state) => (keyboard::PERIOD, state),
+			Slash(state) => (keyboard::SLASH, state),
+			RShift(state) => (keyboard::RSHIFT, state),
+			LCtrl(state) => (keyboard::LCTRL, state),
+			Alt(state) => (keyboard::ALT, state),
+			Space(state) => (keyboard::SPACE, state),
+			RCtrl(state) => (keyboard::RCTRL, state),
+			Up(state) => (keyboard::UP, state),
+			Down(state) => (keyboard::DOWN, state),
+			Left(state) => (keyboard::LEFT, state),
+			Right(state) => (keyboard::RIGHT, state),
+			ExtBacktick(state) => (keyboard::EXT_BACKTICK, state),
+			ExtDelete(state) => (keyboard::EXT_DELETE, state),
+			ExtInsert(state) => (keyboard::EXT_INSERT, state),
+			ExtNumLock(state) => (keyboard::EXT_NUM_LOCK, state),
+			ExtPageUp(state) => (keyboard::EXT_PAGE_UP, state),
+			ExtPageDown(state) => (keyboard::EXT_PAGE_DOWN, state),
+			ExtHome(state) => (keyboard::EXT_HOME, state),
+			ExtEnd(state) => (keyboard::EXT_END, state),
+			ExtAsterisk(state) => (keyboard::EXT_ASTERISK, state),
+			ExtPlus(state) => (keyboard::EXT_PLUS, state),
+			ExtAltGr(state) => (keyboard::EXT_ALT_GR, state),
+			ExtF1(state) => (keyboard::EXT_F1, state),
+			ExtF2(state) => (keyboard::EXT_F2, state),
+			ExtF3(state) => (keyboard::EXT_F3, state),
+			ExtF4(state) => (keyboard::EXT_F4, state),
+			ExtF5(state) => (keyboard::EXT_F5, state),
+			ExtF6(state) => (keyboard::EXT_F6, state),
+			ExtF7(state) => (keyboard::EXT_F7, state),
+			ExtF8(state) => (keyboard::EXT_F8, state),
+			ExtF9(state) => (keyboard::EXT_F9, state),
+			ExtF10(state) => (keyboard::EXT_F10, state),
+			ExtF11(state) => (keyboard::EXT_F11, state),
+			ExtF12(state) => (keyboard::EXT_F12, state),
+			ExtKp0(state) => (keyboard::EXT_KP0, state),
+			ExtKp1(state) => (keyboard::EXT_KP1, state),
+			ExtKp2(state) => (keyboard::EXT_KP2, state),
+			ExtKp3(state) => (keyboard::EXT_KP3, state),
+			ExtKp4(state) => (keyboard::EXT_KP4, state),
+			ExtKp5(state) => (keyboard::EXT_KP5, state),
+			ExtKp6(state) => (keyboard::EXT_KP6, state),
+			ExtKp7(state) => (keyboard::EXT_KP7, state),
+			ExtKp8(state) => (keyboard::EXT_KP8, state),
+			ExtKp9(state) => (keyboard::EXT_KP9, state),
+			ExtKpEnter(state) => (keyboard::EXT_KP_ENTER, state),
+			ExtKpDivide(state) => (keyboard::EXT_KP_DIVIDE, state),
+			ExtKpMinus(state) => (keyboard::EXT_KP_MINUS, state),
+			ExtKpPeriod(state) => (keyboard::EXT_KP_PERIOD, state),
+			_ => return None,
+		})
+	}
+}
+
 impl ::std::fmt::Display for Event {
 	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result<> {
 		// TODO: Write in language of the user.
+		fn key(f: &mut ::std::fmt::Formatter, key: u8,
+			state: &Option<bool>) -> ::std::fmt::Result
+		{
+			write!(f, "{} {:?}", keyboard::name(key), state)
+		}
+
 		match self {
 			Timestep => write!(f, "Timestep"),
 			Exit => write!(f, "Exit"),
 			Resize => write!(f, "Resize"),
+			Moved(x, y) => write!(f, "Moved ({}, {})", x, y),
+			ScaleChanged(scale) => write!(f, "Scale Changed {}", scale),
+			Ready => write!(f, "Ready"),
 			Resume => write!(f, "Resume"),
 			Pause => write!(f, "Pause"),
 			Text(chr) => write!(f, "Text {}", chr),
+			TextStr(index) => write!(f, "Text String {}", index),
+			Preedit(cursor) => write!(f, "Preedit {}", cursor),
+			AlignLeft => write!(f, "Align Left"),
+			AlignCenter => write!(f, "Align Center"),
+			AlignRight => write!(f, "Align Right"),
+			AlignJustified => write!(f, "Align Justified"),
+			EmphasisBrokenUnderline => write!(f, "Broken Underline"),
+			EmphasisOverline => write!(f, "Overline"),
+			EmphasisBold => write!(f, "Bold"),
+			EmphasisInvertColor => write!(f, "Invert Color"),
+			EmphasisNone => write!(f, "No Emphasis"),
+			EmphasisStrikeOut => write!(f, "Strike Out"),
+			EmphasisDoubleUnderline => write!(f, "Double Underline"),
+			EmphasisUnderline => write!(f, "Underline"),
+			EmphasisItalic => write!(f, "Italic"),
 			Select => write!(f, "Select"),
 			Copy => write!(f, "Copy"),
 			Cancel => write!(f, "Cancel"),
@@ -317,82 +661,25 @@ impl ::std::fmt::Display for Event {
 			Paste => write!(f, "Paste"),
 			Print => write!(f, "Print"),
 			Cursor(xy) => write!(f, "Cursor {:?}", xy),
+			MotionRaw(x, y) => write!(f, "Raw Motion ({}, {})", x, y),
 			LeftButton(state, xy) => write!(f, "Left Click {:?} {:?}", state, xy),
 			MiddleButton(state, xy) => write!(f, "Middle Click {:?} {:?}", state, xy),
 			RightButton(state, xy) => write!(f, "Right Click {:?} {:?}", state, xy),
-			Touch(state, xy) => write!(f, "Touch {:?} {:?}", state, xy),
-			Scroll(sxy, xy) => write!(f, "Scroll {:?} {:?}",sxy,xy),
-			Num1(state) => write!(f, "1 {:?}", state),
-			Num2(state) => write!(f, "2 {:?}", state),
-			Num3(state) => write!(f, "3 {:?}", state),
-			Num4(state) => write!(f, "4 {:?}", state),
-			Num5(state) => write!(f, "5 {:?}", state),
-			Num6(state) => write!(f, "6 {:?}", state),
-			Num7(state) => write!(f, "7 {:?}", state),
-			Num8(state) => write!(f, "8 {:?}", state),
-			Num9(state) => write!(f, "9 {:?}", state),
-			Num0(state) => write!(f, "0 {:?}", state),
-			Minus(state) => write!(f, "- {:?}", state),
-			EqualSign(state) => write!(f, "= {:?}", state),
-			Backspace(state) => write!(f, "Backspace {:?}", state),
-			Tab(state) => write!(f, "tab {:?}", state),
-			Q(state) => write!(f, "Q {:?}", state),
-			W(state) => write!(f, "W {:?}", state),
-			E(state) => write!(f, "E {:?}", state),
-			R(state) => write!(f, "R {:?}", state),
-			T(state) => write!(f, "T {:?}", state),
-			Y(state) => write!(f, "Y {:?}", state),
-			U(state) => write!(f, "U {:?}", state),
-			I(state) => write!(f, "I {:?}", state),
-			O(state) => write!(f, "O {:?}", state),
-			P(state) => write!(f, "P {:?}", state),
-			BracketOpen(state) => write!(f, "[ {:?}", state),
-			BracketClose(state) => write!(f, "] {:?}", state),
-			Backslash(state) => write!(f, "\\ {:?}", state),
-			Compose(state) => write!(f, "Compose {:?}", state),
-			A(state) => write!(f, "A {:?}", state),
-			S(state) => write!(f, "S {:?}", state),
-			D(state) => write!(f, "D {:?}", state),
-			F(state) => write!(f, "F {:?}", state),
-			G(state) => write!(f, "G {:?}", state),
-			H(state) => write!(f, "H {:?}", state),
-			J(state) => write!(f, "J {:?}", state),
-			K(state) => write!(f, "K {:?}", state),
-			L(state) => write!(f, "L {:?}", state),
-			Semicolon(state) => write!(f, "; {:?}", state),
-			Apostrophe(state) => write!(f, "' {:?}", state),
-			Enter(state) => write!(f, "enter {:?}", state),
-			LShift(state) => write!(f, "Left Shift {:?}", state),
-			RShift(state) => write!(f, "Right Shift {:?}", state),
-			Z(state) => write!(f, "Z {:?}", state),
-			X(state) => write!(f, "X {:?}", state),
-			C(state) => write!(f, "C {:?}", state),
-			V(state) => write!(f, "V {:?}", state),
-			B(state) => write!(f, "B {:?}", state),
-			N(state) => write!(f, "N {:?}", state),
-			M(state) => write!(f, "M {:?}", state),
-			Comma(state) => write!(f, ", {:?}", state),
-			Period(state) => write!(f, ". {:?}", state),
-			Slash(state) => write!(f, "/ {:?}", state),
-			LCtrl(state) => write!(f, "Left Ctrl {:?}", state),
-			RCtrl(state) => write!(f, "Right Ctrl {:?}", state),
-			Alt(state) => write!(f, "Alt {:?}", state),
-			ExtAltGr(state) => write!(f, "AltGr {:?}", state),
-			Space(state) => write!(f, "space {:?}", state),
-			Up(state) => write!(f, "Up {:?}", state),
-			Down(state) => write!(f, "Down {:?}", state),
-			Left(state) => write!(f, "Left {:?}", state),
-			Right(state) => write!(f, "Right {:?}", state),
-			ExtBacktick(state) => write!(f, "` {:?}", state),
-			ExtDelete(state) => write!(f, "Delete {:?}", state),
-			ExtInsert(state) => write!(f, "Insert {:?}", state),
-			ExtNumLock(state) => write!(f, "NumLock {:?}", state),
-			ExtPageUp(state) => write!(f, "PageUp {:?}", state),
-			ExtPageDown(state) => write!(f, "PageDown {:?}", state),
-			ExtHome(state) => write!(f, "Home {:?}", state),
-			ExtEnd(state) => write!(f, "End {:?}", state),
-			ExtAsterisk(state) => write!(f, "* {:?}", state),
-			ExtPlus(state) => write!(f, "+ {:?}", state),
+			ClickDetailed(button, state, xy, mods) => write!(f,
+				"Click {} {:?} {:?} {:?}", button, state, xy,
+				mods),
+			Touch(id, state, xy) => write!(f, "Touch {} {:?} {:?}", id,
+				state, xy),
+			TouchDetailed(id, state, xy, pressure, size) => write!(f,
+				"Touch {} {:?} {:?} Pressure {} Size {}", id,
+				state, xy, pressure, size),
+			Pen { xy, pressure, tilt, eraser } => write!(f,
+				"{} {:?} Pressure {} Tilt {:?}", if *eraser {
+				"Eraser" } else { "Pen" }, xy, pressure, tilt),
+			Pinch(scale, xy) => write!(f, "Pinch {} {:?}", scale, xy),
+			Rotate(angle, xy) => write!(f, "Rotate {} {:?}", angle, xy),
+			Scroll(sxy, xy, kind) => write!(f, "Scroll {:?} {:?} {:?}",
+				sxy, xy, kind),
 			CMove(i, x, y) => write!(f, "C{} Move ({}, {})", i, x, y),
 			CCamera(i, x, y) => write!(f, "C{} Camera ({}, {})", i, x, y),
 			CThrottleL(i, x) => write!(f, "C{} ThrottleL ({})", i, x),
@@ -414,7 +701,106 @@ impl ::std::fmt::Display for Event {
 			CCamStick(i, s) => write!(f, "C{} Camera Stick Push {:?}", i, s),
 			CPluggedIn(i, x) => write!(f, "C{} Device Plugged-In {:x}", i, x),
 			CUnPlugged(i, x) =>  write!(f, "C{} Device Un-Plugged {:x}", i, x),
-			_ => write!(f, "FIXME: Unknown") // FIXME
+			RawKey(code, s) => write!(f, "Raw Key {} {:?}", code, s),
+			Unknown(code, s) => write!(f, "Unknown Key {} {:?}", code, s),
+			// Keys
+			Num1(s) => key(f, keyboard::NUM1, s),
+			Num2(s) => key(f, keyboard::NUM2, s),
+			Num3(s) => key(f, keyboard::NUM3, s),
+			Num4(s) => key(f, keyboard::NUM4, s),
+			Num5(s) => key(f, keyboard::NUM5, s),
+			Num6(s) => key(f, keyboard::NUM6, s),
+			Num7(s) => key(f, keyboard::NUM7, s),
+			Num8(s) => key(f, keyboard::NUM8, s),
+			Num9(s) => key(f, keyboard::NUM9, s),
+			Num0(s) => key(f, keyboard::NUM0, s),
+			Minus(s) => key(f, keyboard::MINUS, s),
+			EqualSign(s) => key(f, keyboard::EQUAL_SIGN, s),
+			Backspace(s) => key(f, keyboard::BACKSPACE, s),
+			Tab(s) => key(f, keyboard::TAB, s),
+			Q(s) => key(f, keyboard::Q, s),
+			W(s) => key(f, keyboard::W, s),
+			E(s) => key(f, keyboard::E, s),
+			R(s) => key(f, keyboard::R, s),
+			T(s) => key(f, keyboard::T, s),
+			Y(s) => key(f, keyboard::Y, s),
+			U(s) => key(f, keyboard::U, s),
+			I(s) => key(f, keyboard::I, s),
+			O(s) => key(f, keyboard::O, s),
+			P(s) => key(f, keyboard::P, s),
+			BracketOpen(s) => key(f, keyboard::BRACKET_OPEN, s),
+			BracketClose(s) => key(f, keyboard::BRACKET_CLOSE, s),
+			Backslash(s) => key(f, keyboard::BACKSLASH, s),
+			Compose(s) => key(f, keyboard::COMPOSE, s),
+			A(s) => key(f, keyboard::A, s),
+			S(s) => key(f, keyboard::S, s),
+			D(s) => key(f, keyboard::D, s),
+			F(s) => key(f, keyboard::F, s),
+			G(s) => key(f, keyboard::G, s),
+			H(s) => key(f, keyboard::H, s),
+			J(s) => key(f, keyboard::J, s),
+			K(s) => key(f, keyboard::K, s),
+			L(s) => key(f, keyboard::L, s),
+			Semicolon(s) => key(f, keyboard::SEMICOLON, s),
+			Apostrophe(s) => key(f, keyboard::APOSTROPHE, s),
+			Enter(s) => key(f, keyboard::ENTER, s),
+			LShift(s) => key(f, keyboard::LSHIFT, s),
+			Z(s) => key(f, keyboard::Z, s),
+			X(s) => key(f, keyboard::X, s),
+			C(s) => key(f, keyboard::C, s),
+			V(s) => key(f, keyboard::V, s),
+			B(s) => key(f, keyboard::B, s),
+			N(s) => key(f, keyboard::N, s),
+			M(s) => key(f, keyboard::M, s),
+			Comma(s) => key(f, keyboard::COMMA, s),
+			Period(s) => key(f, keyboard::PERIOD, s),
+			Slash(s) => key(f, keyboard::SLASH, s),
+			RShift(s) => key(f, keyboard::RSHIFT, s),
+			LCtrl(s) => key(f, keyboard::LCTRL, s),
+			Alt(s) => key(f, keyboard::ALT, s),
+			Space(s) => key(f, keyboard::SPACE, s),
+			RCtrl(s) => key(f, keyboard::RCTRL, s),
+			Up(s) => key(f, keyboard::UP, s),
+			Down(s) => key(f, keyboard::DOWN, s),
+			Left(s) => key(f, keyboard::LEFT, s),
+			Right(s) => key(f, keyboard::RIGHT, s),
+			ExtBacktick(s) => key(f, keyboard::EXT_BACKTICK, s),
+			ExtDelete(s) => key(f, keyboard::EXT_DELETE, s),
+			ExtInsert(s) => key(f, keyboard::EXT_INSERT, s),
+			ExtNumLock(s) => key(f, keyboard::EXT_NUM_LOCK, s),
+			ExtPageUp(s) => key(f, keyboard::EXT_PAGE_UP, s),
+			ExtPageDown(s) => key(f, keyboard::EXT_PAGE_DOWN, s),
+			ExtHome(s) => key(f, keyboard::EXT_HOME, s),
+			ExtEnd(s) => key(f, keyboard::EXT_END, s),
+			ExtAsterisk(s) => key(f, keyboard::EXT_ASTERISK, s),
+			ExtPlus(s) => key(f, keyboard::EXT_PLUS, s),
+			ExtAltGr(s) => key(f, keyboard::EXT_ALT_GR, s),
+			ExtF1(s) => key(f, keyboard::EXT_F1, s),
+			ExtF2(s) => key(f, keyboard::EXT_F2, s),
+			ExtF3(s) => key(f, keyboard::EXT_F3, s),
+			ExtF4(s) => key(f, keyboard::EXT_F4, s),
+			ExtF5(s) => key(f, keyboard::EXT_F5, s),
+			ExtF6(s) => key(f, keyboard::EXT_F6, s),
+			ExtF7(s) => key(f, keyboard::EXT_F7, s),
+			ExtF8(s) => key(f, keyboard::EXT_F8, s),
+			ExtF9(s) => key(f, keyboard::EXT_F9, s),
+			ExtF10(s) => key(f, keyboard::EXT_F10, s),
+			ExtF11(s) => key(f, keyboard::EXT_F11, s),
+			ExtF12(s) => key(f, keyboard::EXT_F12, s),
+			ExtKp0(s) => key(f, keyboard::EXT_KP0, s),
+			ExtKp1(s) => key(f, keyboard::EXT_KP1, s),
+			ExtKp2(s) => key(f, keyboard::EXT_KP2, s),
+			ExtKp3(s) => key(f, keyboard::EXT_KP3, s),
+			ExtKp4(s) => key(f, keyboard::EXT_KP4, s),
+			ExtKp5(s) => key(f, keyboard::EXT_KP5, s),
+			ExtKp6(s) => key(f, keyboard::EXT_KP6, s),
+			ExtKp7(s) => key(f, keyboard::EXT_KP7, s),
+			ExtKp8(s) => key(f, keyboard::EXT_KP8, s),
+			ExtKp9(s) => key(f, keyboard::EXT_KP9, s),
+			ExtKpEnter(s) => key(f, keyboard::EXT_KP_ENTER, s),
+			ExtKpDivide(s) => key(f, keyboard::EXT_KP_DIVIDE, s),
+			ExtKpMinus(s) => key(f, keyboard::EXT_KP_MINUS, s),
+			ExtKpPeriod(s) => key(f, keyboard::EXT_KP_PERIOD, s),
 		}
 	}
 }
@@ -431,6 +817,35 @@ impl CoordToFloat for i16 {
 	fn to_f32(self) -> f32 { self as f32 }
 }
 
+impl CoordToFloat for f32 {
+	fn to_f32(self) -> f32 { self }
+}
+
+/// Zoom a view by `delta` (the Y of `Event::Scroll`: up, negative, zooms
+/// in), keeping the point under `cursor_ndc` (it's cursor XY) in place.
+/// The view draws a point at `point * scale + offset`.  Each 1.0 of `delta`
+/// zooms by 1.25x.  Returns the new `(scale, offset)`.
+pub fn zoom_about(current_scale: f32, current_offset: (f32, f32),
+	cursor_ndc: (f32, f32), delta: f32) -> (f32, (f32, f32))
+{
+	let scale = current_scale * 1.25f32.powf(-delta);
+	let ratio = scale / current_scale;
+
+	// The offset moves so the cursor is over the same point as before.
+	(scale, (
+		cursor_ndc.0 - (cursor_ndc.0 - current_offset.0) * ratio,
+		cursor_ndc.1 - (cursor_ndc.1 - current_offset.1) * ratio,
+	))
+}
+
+// Distance & angle between two fingers.
+fn gesture(fingers: ((f32, f32), (f32, f32))) -> (f32, f32) {
+	let x = (fingers.1).0 - (fingers.0).0;
+	let y = (fingers.1).1 - (fingers.0).1;
+
+	((x * x + y * y).sqrt(), y.atan2(x))
+}
+
 fn cursor_coordinates<T, U>(wh: (T, T), xy: (U, U)) -> Option<(f32, f32)>
 	where U: CoordToFloat, T: CoordToFloat
 {
@@ -449,7 +864,57 @@ fn cursor_coordinates<T, U>(wh: (T, T), xy: (U, U)) -> Option<(f32, f32)>
 
 pub struct InputQueue {
 	queue: Vec<Event>,
+	// When each event in `queue` happened.
+	times: Vec<Instant>,
+	// When the event being handled happened (from the platform), `None`
+	// for now.
+	stamp: Option<Instant>,
 	mods: keyboard::modifiers::Modifiers,
+	preedit: String,
+	// Touch points that are down (Touch ID, XY in pixels, (Pressure,
+	// Contact Size)).
+	touches: Vec<(u64, (f32, f32), (f32, f32))>,
+	// Last pen pressure & tilt.
+	pen: (f32, (f32, f32)),
+	// Fixed timestep (zero for none), time not stepped yet, & last update.
+	timestep: Duration,
+	timestep_left: Duration,
+	timestep_last: Instant,
+	// Whether the fixed timestep stops between `Pause` & `Resume`, and if
+	// it's between them now.
+	pause_halts: bool,
+	paused: bool,
+	// Whether `request_frame()` was called since the last `timestep()`.
+	frame_requested: bool,
+	// Whether `Event::Ready` was sent yet.
+	ready: bool,
+	// Stick deadzones (inner, outer), indexed by controller.
+	deadzones: Vec<(f32, f32)>,
+	// Button remapping (from, to), indexed by controller.
+	maps: Vec<Vec<(ControllerButton, ControllerButton)>>,
+	// Which way the stick is pushed as a D-pad (x, y: -1, 0 or 1), and
+	// when to repeat it next, `None` if off, indexed by controller.
+	stick_dpad: Vec<Option<(i8, i8, Instant)>>,
+	// Device ID & `stick` index (while plugged in), indexed by controller.
+	controllers: Vec<(i32, Option<usize>)>,
+	// Whether to flip the sign of `Event::Scroll`.
+	natural_scroll: bool,
+	// Whether to send `Event::Text` & `Event::Preedit`.
+	text_input: bool,
+	// Whether to send `Event::TextStr` instead of `Event::Text`, and the
+	// text of each (cleared once they're handled).
+	text_str: bool,
+	texts: Vec<String>,
+	// Bitset of held keys, indexed by `keyboard::` constant.
+	held: u128,
+	// What has to be redrawn since `take_damage()` (x, y, w, h), pixels.
+	damage: Option<(u16, u16, u16, u16)>,
+	// Whether there's been damage since the last frame was drawn.
+	redraw: bool,
+	// Popped events, oldest first (up to twice `history_len`, so it's only
+	// shifted down once in a while), & how many to keep (0 for off).
+	history: Vec<Event>,
+	history_len: usize,
 }
 
 impl InputQueue {
@@ -457,9 +922,39 @@ impl InputQueue {
 	#[inline(always)]
 	pub fn new() -> InputQueue {
 		let queue = Vec::new();
+		let times = Vec::new();
+		let stamp = None;
 		let mods = keyboard::modifiers::Modifiers::create();
+		let preedit = String::new();
+		let touches = Vec::new();
+		let pen = (0.0, (0.0, 0.0));
+		let timestep = Duration::new(0, 0);
+		let timestep_left = Duration::new(0, 0);
+		let timestep_last = Instant::now();
+		let pause_halts = false;
+		let paused = false;
+		let frame_requested = false;
+		let ready = false;
+		let deadzones = Vec::new();
+		let maps = Vec::new();
+		let stick_dpad = Vec::new();
+		let controllers = Vec::new();
+		let natural_scroll = false;
+		let text_input = true;
+		let text_str = false;
+		let texts = Vec::new();
+		let held = 0;
+		let damage = None;
+		let redraw = false;
+		let history = Vec::new();
+		let history_len = 0;
 
-		InputQueue { queue, mods }
+		InputQueue { queue, times, stamp, mods, preedit, touches, pen,
+			timestep, timestep_left, timestep_last, pause_halts,
+			paused, frame_requested, ready, deadzones, maps,
+			stick_dpad, controllers, natural_scroll, text_input,
+			text_str, texts, held, damage, redraw, history,
+			history_len }
 	}
 
 	#[inline(always)]
@@ -474,7 +969,92 @@ impl InputQueue {
 
 	#[inline(always)]
 	pub fn pop(&mut self) -> Option<Event> {
-		self.queue.pop()
+		self.times.pop();
+		let event = self.queue.pop()?;
+
+		self.remember(event);
+		Some(event)
+	}
+
+	/// Pop an event, with when it happened.
+	#[inline(always)]
+	pub fn pop_timed(&mut self) -> Option<(Instant, Event)> {
+		let time = self.times.pop()?;
+		let event = self.queue.pop().unwrap();
+
+		self.remember(event);
+		Some((time, event))
+	}
+
+	/// Pop the first event that `pred` matches, in the same order as
+	/// `pop()` (so of the matching events, the one `pop()` would return
+	/// first), leaving the others in the queue.  Also returns when it
+	/// happened.
+	pub fn pop_matching<F: Fn(&Event) -> bool>(&mut self, pred: F)
+		-> Option<(Instant, Event)>
+	{
+		let index = self.queue.iter().rposition(pred)?;
+		let event = self.queue.remove(index);
+		let time = self.times.remove(index);
+
+		self.remember(event);
+		Some((time, event))
+	}
+
+	/// Keep the last `capacity` popped events for `history()`, to dump
+	/// when debugging input (0, the default, turns it off).
+	pub fn set_history(&mut self, capacity: usize) {
+		let start = self.history.len().saturating_sub(capacity);
+
+		self.history.drain(..start);
+		self.history_len = capacity;
+	}
+
+	/// Get the last popped events, oldest first (see `set_history()`).
+	pub fn history(&self) -> &[Event] {
+		let start = self.history.len().saturating_sub(self.history_len);
+
+		&self.history[start..]
+	}
+
+	// Add a popped event to the history.
+	fn remember(&mut self, event: Event) {
+		if self.history_len == 0 {
+			return;
+		}
+
+		if self.history.len() >= self.history_len * 2 {
+			let start = self.history.len() - self.history_len;
+
+			self.history.drain(..start);
+		}
+		self.history.push(event);
+	}
+
+	/// Set when the events being added happened, from the platform's
+	/// timestamp.  `None` to use the time they're added.
+	pub fn set_stamp(&mut self, stamp: Option<Instant>) {
+		self.stamp = stamp;
+	}
+
+	// Push an event without going through the modifiers.
+	fn push(&mut self, event: Event) {
+		self.queue.push(event);
+		self.times.push(self.stamp.unwrap_or_else(Instant::now));
+	}
+
+	/// Pop all of the events, in the same order as `pop()`.
+	#[inline(always)]
+	pub fn drain(&mut self) -> Drain {
+		Drain { queue: self, time: None }
+	}
+
+	/// Like `drain()`, but also set `time` to when each event happened.
+	#[inline(always)]
+	pub(crate) fn drain_timed<'a>(&'a mut self, time: &'a mut Instant)
+		-> Drain<'a>
+	{
+		Drain { queue: self, time: Some(time) }
 	}
 
 	#[inline(always)]
@@ -487,11 +1067,81 @@ impl InputQueue {
 		// Only if new dimensions differ from old.
 		if *wh != d {
 			*wh = d;
+			self.damage((0, 0, d.0, d.1));
 			self.input(Event::Resize);
 		}
 	}
 
+	/// Add a rectangle of the window `(x, y, w, h)` that has to be redrawn
+	/// (uncovered, or resized).
+	#[inline(always)]
+	pub fn damage(&mut self, rect: (u16, u16, u16, u16)) {
+		// The bounding box of both, `u32` so the sums don't overflow.
+		let (x, y) = (rect.0 as u32, rect.1 as u32);
+		let (r, b) = (x + rect.2 as u32, y + rect.3 as u32);
+		let (x, y, r, b) = match self.damage {
+			Some((ox, oy, ow, oh)) => {
+				let (ox, oy) = (ox as u32, oy as u32);
+				(x.min(ox), y.min(oy), r.max(ox + ow as u32),
+					b.max(oy + oh as u32))
+			}
+			None => (x, y, r, b),
+		};
+
+		self.damage = Some((x as u16, y as u16, (r - x) as u16,
+			(b - y) as u16));
+		self.redraw = true;
+	}
+
+	/// Get what has to be redrawn since the last call, `None` if nothing.
+	pub fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)> {
+		self.damage.take()
+	}
+
+	/// Whether there's been damage since the last call (separate from
+	/// `take_damage()`, for the renderer to skip frames without it).
+	pub fn take_redraw(&mut self) -> bool {
+		::std::mem::replace(&mut self.redraw, false)
+	}
+
+	pub fn moved(&mut self, xy: &mut (i16, i16), d: (i16, i16)) {
+		// Only if new position differs from old.
+		if *xy != d {
+			*xy = d;
+			self.input(Event::Moved(d.0, d.1));
+		}
+	}
+
+	pub fn scale(&mut self, scale: &mut f32, s: f32) {
+		// Only if new scale differs from old.
+		if *scale != s {
+			*scale = s;
+			self.input(Event::ScaleChanged(s));
+		}
+	}
+
+	pub fn raw_key(&mut self, scancode: u32, state: Option<bool>) {
+		self.input(Event::RawKey(scancode, state));
+	}
+
+	pub fn unknown_key(&mut self, scancode: u32, state: Option<bool>) {
+		self.input(Event::Unknown(scancode, state));
+	}
+
+	/// Whether a key (`keyboard::` constant) is held down right now.
+	pub fn is_key_down(&self, key: u8) -> bool {
+		key < 128 && self.held & (1 << key) != 0
+	}
+
 	pub fn key(&mut self, key: u8, state: Option<bool>) {
+		if key < 128 {
+			if state.is_some() {
+				self.held |= 1 << key;
+			} else {
+				self.held &= !(1 << key);
+			}
+		}
+
 		self.input(match key {
 			keyboard::NUM1 => Event::Num1(state),
 			keyboard::NUM2 => Event::Num2(state),
@@ -564,17 +1214,48 @@ impl InputQueue {
 			keyboard::EXT_ASTERISK => Event::ExtAsterisk(state),
 			keyboard::EXT_PLUS => Event::ExtPlus(state),
 			keyboard::EXT_ALT_GR => Event::ExtAltGr(state),
+			keyboard::EXT_F1 => Event::ExtF1(state),
+			keyboard::EXT_F2 => Event::ExtF2(state),
+			keyboard::EXT_F3 => Event::ExtF3(state),
+			keyboard::EXT_F4 => Event::ExtF4(state),
+			keyboard::EXT_F5 => Event::ExtF5(state),
+			keyboard::EXT_F6 => Event::ExtF6(state),
+			keyboard::EXT_F7 => Event::ExtF7(state),
+			keyboard::EXT_F8 => Event::ExtF8(state),
+			keyboard::EXT_F9 => Event::ExtF9(state),
+			keyboard::EXT_F10 => Event::ExtF10(state),
+			keyboard::EXT_F11 => Event::ExtF11(state),
+			keyboard::EXT_F12 => Event::ExtF12(state),
+			keyboard::EXT_KP0 => Event::ExtKp0(state),
+			keyboard::EXT_KP1 => Event::ExtKp1(state),
+			keyboard::EXT_KP2 => Event::ExtKp2(state),
+			keyboard::EXT_KP3 => Event::ExtKp3(state),
+			keyboard::EXT_KP4 => Event::ExtKp4(state),
+			keyboard::EXT_KP5 => Event::ExtKp5(state),
+			keyboard::EXT_KP6 => Event::ExtKp6(state),
+			keyboard::EXT_KP7 => Event::ExtKp7(state),
+			keyboard::EXT_KP8 => Event::ExtKp8(state),
+			keyboard::EXT_KP9 => Event::ExtKp9(state),
+			keyboard::EXT_KP_ENTER => Event::ExtKpEnter(state),
+			keyboard::EXT_KP_DIVIDE => Event::ExtKpDivide(state),
+			keyboard::EXT_KP_MINUS => Event::ExtKpMinus(state),
+			keyboard::EXT_KP_PERIOD => Event::ExtKpPeriod(state),
 			_ => return,
 		})
 	}
 
 	#[inline(always)]
 	pub fn scroll(&mut self, wh: (u16, u16), c: (i16, i16),
-		scrolling: (f32, f32))
+		scrolling: (f32, f32), kind: ScrollKind)
 	{
 		let xy = cursor_coordinates(wh, c);
+		let scrolling = if self.natural_scroll {
+			(-scrolling.0, -scrolling.1)
+		} else {
+			scrolling
+		};
 
-		self.input(Event::Scroll(scrolling, xy))
+		self.input(Event::Scroll(scrolling, xy, kind))
 	}
 
 	#[inline(always)]
@@ -582,6 +1263,8 @@ impl InputQueue {
 		let xy = cursor_coordinates(wh, c);
 
 		self.input(Event::LeftButton(None, xy));
+		let mods = self.mods.click_mods();
+		self.input(Event::ClickDetailed(0, None, xy, mods));
 	}
 
 	#[inline(always)]
@@ -589,6 +1272,8 @@ impl InputQueue {
 		let xy = cursor_coordinates(wh, c);
 
 		self.input(Event::MiddleButton(None, xy));
+		let mods = self.mods.click_mods();
+		self.input(Event::ClickDetailed(1, None, xy, mods));
 	}
 
 	#[inline(always)]
@@ -596,13 +1281,22 @@ impl InputQueue {
 		let xy = cursor_coordinates(wh, c);
 
 		self.input(Event::RightButton(None, xy));
+		let mods = self.mods.click_mods();
+		self.input(Event::ClickDetailed(2, None, xy, mods));
 	}
 
+	/// Release touch `id`, where it last was.
 	#[inline(always)]
-	pub fn touch_release(&mut self, wh: (u16, u16), c: (i16, i16)) {
+	pub fn touch_release(&mut self, wh: (u16, u16), id: u64) {
+		let index = match self.touches.iter().position(|t| t.0 == id) {
+			Some(index) => index,
+			None => return,
+		};
+		let (_, c, (_, size)) = self.touches.remove(index);
 		let xy = cursor_coordinates(wh, c);
 
-		self.input(Event::Touch(None, xy));
+		self.input(Event::Touch(id, None, xy));
+		self.input(Event::TouchDetailed(id, None, xy, 0.0, size));
 	}
 
 	#[inline(always)]
@@ -610,6 +1304,8 @@ impl InputQueue {
 		let xy = cursor_coordinates(wh, c);
 
 		self.input(Event::LeftButton(Some(true), xy));
+		let mods = self.mods.click_mods();
+		self.input(Event::ClickDetailed(0, Some(true), xy, mods));
 	}
 
 	#[inline(always)]
@@ -617,6 +1313,8 @@ impl InputQueue {
 		let xy = cursor_coordinates(wh, c);
 
 		self.input(Event::MiddleButton(Some(true), xy));
+		let mods = self.mods.click_mods();
+		self.input(Event::ClickDetailed(1, Some(true), xy, mods));
 	}
 
 	#[inline(always)]
@@ -624,13 +1322,114 @@ impl InputQueue {
 		let xy = cursor_coordinates(wh, c);
 
 		self.input(Event::RightButton(Some(true), xy));
+		let mods = self.mods.click_mods();
+		self.input(Event::ClickDetailed(2, Some(true), xy, mods));
+	}
+
+	/// Press touch `id`.  `detail` is the pressure & contact size from 0
+	/// to 1, if the device has them.
+	#[inline(always)]
+	pub fn touch_press(&mut self, wh: (u16, u16), id: u64, c: (i16, i16),
+		detail: (Option<f32>, Option<f32>))
+	{
+		let xy = cursor_coordinates(wh, c);
+		let (pressure, size) = (detail.0.unwrap_or(1.0),
+			detail.1.unwrap_or(0.0));
+
+		self.touches.retain(|t| t.0 != id);
+		self.touches.push((id, (c.0 as f32, c.1 as f32),
+			(pressure, size)));
+		self.input(Event::Touch(id, Some(true), xy));
+		self.input(Event::TouchDetailed(id, Some(true), xy, pressure,
+			size));
+	}
+
+	/// Move touch `id`, and recognize pinch & rotate gestures when two
+	/// fingers are down.  `detail` is like `touch_press()`, `None` if it
+	/// didn't change.
+	#[inline(always)]
+	pub fn touch_move(&mut self, wh: (u16, u16), id: u64, c: (i16, i16),
+		detail: (Option<f32>, Option<f32>))
+	{
+		let before = self.fingers();
+
+		let (pressure, size) = match self.touches.iter_mut()
+			.find(|t| t.0 == id)
+		{
+			Some(touch) => {
+				touch.1 = (c.0 as f32, c.1 as f32);
+				if let Some(pressure) = detail.0 {
+					(touch.2).0 = pressure;
+				}
+				if let Some(size) = detail.1 {
+					(touch.2).1 = size;
+				}
+				touch.2
+			}
+			None => return,
+		};
+
+		let xy = cursor_coordinates(wh, c);
+		self.input(Event::Touch(id, Some(false), xy));
+		self.input(Event::TouchDetailed(id, Some(false), xy, pressure,
+			size));
+
+		let (a, b) = match (before, self.fingers()) {
+			(Some(a), Some(b)) => (a, b),
+			_ => return,
+		};
+
+		let center = cursor_coordinates(wh, (((b.0).0 + (b.1).0) / 2.0,
+			((b.0).1 + (b.1).1) / 2.0));
+		let (da, ra) = gesture(a);
+		let (db, rb) = gesture(b);
+
+		if da > 0.0 && db != da {
+			self.input(Event::Pinch(db / da, center));
+		}
+
+		// Keep within -PI to PI.
+		let mut angle = rb - ra;
+		if angle > ::std::f32::consts::PI {
+			angle -= 2.0 * ::std::f32::consts::PI;
+		} else if angle < -::std::f32::consts::PI {
+			angle += 2.0 * ::std::f32::consts::PI;
+		}
+		if angle != 0.0 {
+			self.input(Event::Rotate(angle, center));
+		}
+	}
+
+	// The two touch points, when there's exactly two.
+	fn fingers(&self) -> Option<((f32, f32), (f32, f32))> {
+		if self.touches.len() == 2 {
+			Some((self.touches[0].1, self.touches[1].1))
+		} else {
+			None
+		}
 	}
 
+	/// Pen moved, pressed or released.  `pressure` & `tilt` are from 0 to
+	/// 1 and -1 to 1, `None` if they didn't change.
 	#[inline(always)]
-	pub fn touch_press(&mut self, wh: (u16, u16), c: (i16, i16)) {
+	pub fn pen(&mut self, wh: (u16, u16), c: (i16, i16),
+		pressure: Option<f32>, tilt: (Option<f32>, Option<f32>),
+		eraser: bool)
+	{
 		let xy = cursor_coordinates(wh, c);
 
-		self.input(Event::Touch(Some(true), xy));
+		if let Some(pressure) = pressure {
+			self.pen.0 = pressure;
+		}
+		if let Some(x) = tilt.0 {
+			(self.pen.1).0 = x;
+		}
+		if let Some(y) = tilt.1 {
+			(self.pen.1).1 = y;
+		}
+
+		self.input(Event::Pen { xy, pressure: self.pen.0,
+			tilt: self.pen.1, eraser });
 	}
 
 	#[inline(always)]
@@ -640,6 +1439,11 @@ impl InputQueue {
 		self.input(Event::Cursor(xy));
 	}
 
+	#[inline(always)]
+	pub fn motion_raw(&mut self, delta: (f32, f32)) {
+		self.input(Event::MotionRaw(delta.0, delta.1));
+	}
+
 	#[inline(always)]
 	pub fn cursor_leave(&mut self) {
 		self.input(Event::Cursor(None));
@@ -647,14 +1451,31 @@ impl InputQueue {
 
 	#[inline(always)]
 	pub fn pause(&mut self) {
+		// Releases won't be seen while out of focus.
+		self.held = 0;
+		self.paused = true;
 		self.input(Event::Pause);
 	}
 
 	#[inline(always)]
 	pub fn resume(&mut self) {
+		// Don't catch up on the time spent paused.
+		if self.paused && self.pause_halts {
+			self.timestep_last = Instant::now();
+		}
+		self.paused = false;
 		self.input(Event::Resume);
 	}
 
+	#[inline(always)]
+	pub fn ready(&mut self) {
+		// Only the first time the window is shown.
+		if !self.ready {
+			self.ready = true;
+			self.input(Event::Ready);
+		}
+	}
+
 	#[inline(always)]
 	pub fn exit(&mut self) {
 		self.input(Event::Exit);
@@ -662,6 +1483,21 @@ impl InputQueue {
 
 	#[inline(always)]
 	pub fn text(&mut self, string: String) {
+		if !self.text_input {
+			return;
+		}
+
+		if self.text_str {
+			// Old texts were handled if there's no events left.
+			if self.queue.is_empty() {
+				self.texts.clear();
+			}
+			self.texts.push(string);
+			let index = self.texts.len() - 1;
+			self.input(Event::TextStr(index));
+			return;
+		}
+
 		let chars = string.char_indices();
 
 		for c in chars {
@@ -669,39 +1505,502 @@ impl InputQueue {
 		}
 	}
 
+	/// Update the input method's composition text, `cursor` is in chars.
+	pub fn preedit(&mut self, string: String, cursor: usize) {
+		if !self.text_input {
+			return;
+		}
+
+		self.preedit = string;
+		self.push(Event::Preedit(cursor));
+	}
+
+	/// Turn `Event::Text` & `Event::Preedit` on or off.
+	pub fn set_text_input(&mut self, enabled: bool) {
+		self.text_input = enabled;
+		if !enabled && !self.preedit.is_empty() {
+			self.preedit.clear();
+			self.push(Event::Preedit(0));
+		}
+	}
+
+	/// Get the input method's composition text.
+	pub fn get_preedit(&self) -> &str {
+		&self.preedit
+	}
+
+	/// Send `Event::TextStr` instead of `Event::Text` (default off).
+	pub fn set_text_str(&mut self, enabled: bool) {
+		self.text_str = enabled;
+	}
+
+	/// Make shortcut events from Ctrl & Alt key combos, like `Copy` for
+	/// Ctrl-C (default on).
+	pub fn set_shortcuts_enabled(&mut self, enabled: bool) {
+		self.mods.set_shortcuts(enabled);
+	}
+
+	/// Flip the direction of `Event::Scroll` (default off), for when the OS
+	/// has natural scrolling on, so (-1, -1) is up / left again.
+	pub fn set_natural_scroll(&mut self, natural: bool) {
+		self.natural_scroll = natural;
+	}
+
+	/// Get the text of an `Event::TextStr`, empty if `index` is old.
+	pub fn get_text_str(&self, index: usize) -> &str {
+		self.texts.get(index).map(|t| t.as_str()).unwrap_or("")
+	}
+
+	/// Send `Event::Timestep` every `step` (zero to turn off).
+	pub fn set_timestep(&mut self, step: Duration) {
+		self.timestep = step;
+		self.timestep_left = Duration::new(0, 0);
+		self.timestep_last = Instant::now();
+	}
+
+	/// Stop sending `Event::Timestep` (and counting time for it) between
+	/// `Event::Pause` & `Event::Resume` (default off).
+	pub fn set_pause_halts_timestep(&mut self, halts: bool) {
+		self.pause_halts = halts;
+	}
+
+	/// Whether a fixed timestep is set.
+	pub fn has_timestep(&self) -> bool {
+		self.timestep != Duration::new(0, 0)
+	}
+
+	/// Send one `Event::Timestep` on the next update, waking up
+	/// `wait_event()`, for animations while there's no input.  Call it
+	/// again each frame to keep animating.  With a fixed timestep, they
+	/// already come at that rate, so this does nothing.
+	pub fn request_frame(&mut self) {
+		self.frame_requested = true;
+	}
+
+	/// How long until the next `Event::Timestep`, `None` if there's no
+	/// fixed timestep (or requested frame).
+	pub fn next_timestep(&self) -> Option<Duration> {
+		if self.frame_requested && !self.has_timestep() {
+			return Some(Duration::new(0, 0));
+		}
+
+		if !self.has_timestep() || (self.paused && self.pause_halts) {
+			return None;
+		}
+
+		let elapsed = self.timestep_left + self.timestep_last.elapsed();
+
+		Some(if elapsed >= self.timestep {
+			Duration::new(0, 0)
+		} else {
+			self.timestep - elapsed
+		})
+	}
+
+	/// Send a `Event::Timestep` for every `step` since the last call (or
+	/// one for `request_frame()`).
+	pub fn timestep(&mut self) {
+		// Don't try to catch up forever if a frame took too long.
+		const MAX_STEPS: u32 = 8;
+
+		// One for `request_frame()`, without a fixed timestep.
+		if self.frame_requested {
+			self.frame_requested = false;
+			if !self.has_timestep() {
+				self.input(Event::Timestep);
+			}
+		}
+
+		if !self.has_timestep() {
+			return;
+		}
+
+		let now = Instant::now();
+		if self.paused && self.pause_halts {
+			self.timestep_last = now;
+			return;
+		}
+
+		self.timestep_left += now.duration_since(self.timestep_last);
+		self.timestep_last = now;
+
+		// Drop the steps past the limit, so it's not looping over them.
+		let max = self.timestep * MAX_STEPS;
+		if self.timestep_left > max {
+			self.timestep_left = max;
+		}
+
+		while self.timestep_left >= self.timestep {
+			self.timestep_left -= self.timestep;
+			self.input(Event::Timestep);
+		}
+	}
+
 	#[inline(always)]
 	fn input(&mut self, input: Event) -> () {
-		self.mods.update(&mut self.queue, input)
+		let len = self.queue.len();
+		let time = self.stamp.unwrap_or_else(Instant::now);
+
+		self.mods.update(&mut self.queue, input);
+
+		// The same time for every event `input` turned into.
+		let len = self.queue.len() - len;
+		self.times.extend(::std::iter::repeat(time).take(len));
+	}
+
+	/// Set the deadzone for a controller's sticks & throttles: values
+	/// within `inner` become 0, and `inner` to `outer` is rescaled to 0 to
+	/// 1.  Panics unless `0 <= inner < outer <= 1`.
+	pub fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32)
+	{
+		// Also false for NaN.
+		assert!(0.0 <= inner && inner < outer && outer <= 1.0,
+			"awi: Stick deadzone needs 0 <= inner < outer <= 1, \
+			not {} & {}!", inner, outer);
+
+		if self.deadzones.len() <= controller {
+			self.deadzones.resize(controller + 1, (0.0, 1.0));
+		}
+		self.deadzones[controller] = (inner, outer);
+	}
+
+	// Apply the deadzone to a stick (radially, so diagonals aren't cut).
+	fn deadzone(&self, controller: usize, x: f32, y: f32) -> (f32, f32) {
+		let (inner, outer) = self.deadzones.get(controller).cloned()
+			.unwrap_or((0.0, 1.0));
+		let length = (x * x + y * y).sqrt();
+
+		if length <= inner {
+			return (0.0, 0.0);
+		}
+		if inner == 0.0 && outer == 1.0 {
+			return (x, y);
+		}
+
+		let scaled = ((length - inner) / (outer - inner)).min(1.0);
+
+		(x / length * scaled, y / length * scaled)
+	}
+
+	/// Remap a controller's buttons, `(from, to)`.  Buttons not in `map`
+	/// stay the same, so an empty `map` is the default layout.
+	pub fn set_controller_map(&mut self, controller: usize,
+		map: &[(ControllerButton, ControllerButton)])
+	{
+		if self.maps.len() <= controller {
+			self.maps.resize(controller + 1, Vec::new());
+		}
+		self.maps[controller] = map.to_vec();
+	}
+
+	/// Also send `CUp`, `CDown`, `CLeft` & `CRight` when a controller's
+	/// main stick is pushed past halfway, for menus.  One press when it
+	/// crosses, and the release once it's back under a third (so it
+	/// doesn't bounce).  Held, it repeats (`Some(false)`) like a key.  Off
+	/// by default.
+	pub fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool) {
+		if self.stick_dpad.len() <= controller {
+			self.stick_dpad.resize(controller + 1, None);
+		}
+		// Release what's held, so it isn't stuck down.
+		self.stick_dpad(controller, 0.0, 0.0);
+		self.stick_dpad[controller] = if enabled {
+			Some((0, 0, Instant::now()))
+		} else {
+			None
+		};
+	}
+
+	// Press & release D-pad buttons as the stick crosses the thresholds.
+	fn stick_dpad(&mut self, js: usize, x: f32, y: f32) {
+		use self::ControllerButton as Button;
+
+		let (old_x, old_y, repeat) = match self.stick_dpad.get(js) {
+			Some(&Some(old)) => old,
+			_ => return,
+		};
+		let new = (dpad_direction(old_x, x), dpad_direction(old_y, y));
+
+		// Wait longer before the first repeat.
+		let repeat = if new != (old_x, old_y) {
+			Instant::now() + Duration::from_millis(DPAD_DELAY)
+		} else {
+			repeat
+		};
+
+		self.stick_dpad[js] = Some((new.0, new.1, repeat));
+		self.dpad_axis(js, old_x, new.0, Button::Left, Button::Right);
+		self.dpad_axis(js, old_y, new.1, Button::Up, Button::Down);
+	}
+
+	// Repeat the D-pad buttons of sticks that are held pushed.
+	fn stick_dpad_repeat(&mut self) {
+		use self::ControllerButton as Button;
+
+		let now = Instant::now();
+		for js in 0..self.stick_dpad.len() {
+			let (x, y, repeat) = match self.stick_dpad[js] {
+				Some(dpad) => dpad,
+				None => continue,
+			};
+			if (x, y) == (0, 0) || now < repeat {
+				continue;
+			}
+
+			self.stick_dpad[js] = Some((x, y,
+				now + Duration::from_millis(DPAD_RATE)));
+			let held = Some(false);
+			match x {
+				-1 => self.button(js, Button::Left, held),
+				1 => self.button(js, Button::Right, held),
+				_ => {}
+			}
+			match y {
+				-1 => self.button(js, Button::Up, held),
+				1 => self.button(js, Button::Down, held),
+				_ => {}
+			}
+		}
+	}
+
+	// Send the release of the old direction & press of the new one.
+	fn dpad_axis(&mut self, js: usize, old: i8, new: i8,
+		neg: ControllerButton, pos: ControllerButton)
+	{
+		if old == new {
+			return;
+		}
+		match old {
+			-1 => self.button(js, neg, None),
+			1 => self.button(js, pos, None),
+			_ => {}
+		}
+		match new {
+			-1 => self.button(js, neg, Some(true)),
+			1 => self.button(js, pos, Some(true)),
+			_ => {}
+		}
+	}
+
+	/// Get the GUID of a controller's device, `None` if there's no
+	/// controller with that number yet.  Controllers with the same GUID
+	/// take the same numbers when plugged back in.  It's only the vendor
+	/// & product ID (all `stick` gives), so it's the same for two of the
+	/// same kind of controller.
+	pub fn controller_guid(&self, controller: usize) -> Option<String> {
+		self.controllers.get(controller).map(|c| format!("{:08x}", c.0))
+	}
+
+	/// Get the `stick` index of a controller, `None` if unplugged.
+	pub fn controller_index(&self, controller: usize) -> Option<usize> {
+		self.controllers.get(controller).and_then(|c| c.1)
+	}
+
+	// Get the controller number of a `stick` index.
+	fn controller(&mut self, js: usize) -> usize {
+		match self.controllers.iter().position(|c| c.1 == Some(js)) {
+			Some(controller) => controller,
+			None => self.plug(js, 0),
+		}
+	}
+
+	// Give a plugged in controller a number, the same as last time if it
+	// was plugged in before.
+	fn plug(&mut self, js: usize, id: i32) -> usize {
+		for c in self.controllers.iter_mut() {
+			if c.1 == Some(js) {
+				c.1 = None;
+			}
+		}
+
+		let controller = match self.controllers.iter()
+			.position(|c| c.1.is_none() && c.0 == id)
+		{
+			Some(controller) => controller,
+			None => {
+				self.controllers.push((id, None));
+				self.controllers.len() - 1
+			}
+		};
+
+		self.controllers[controller].1 = Some(js);
+		controller
+	}
+
+	// Send a button event, after remapping.
+	fn button(&mut self, js: usize, button: ControllerButton,
+		s: Option<bool>)
+	{
+		let button = self.maps.get(js)
+			.and_then(|map| map.iter().find(|m| m.0 == button))
+			.map(|m| m.1)
+			.unwrap_or(button);
+
+		self.input(button.event(js, s))
 	}
 
 	#[inline(always)]
 	pub fn stick(&mut self, cm: &mut ::stick::ControllerManager) {
+		self.stick_dpad_repeat();
+
 		while let Some((js, i)) = cm.update() {
 			use ::stick::Input::*;
+			use self::ControllerButton as Button;
+
+			// The same number for a controller plugged back in
+			// (`stick` might give it a different index).
+			let js = match i {
+				PluggedIn(id) => self.plug(js, id),
+				_ => self.controller(js),
+			};
 
 			match i {
-				Move(x, y) => self.input(Event::CMove(js, x, y)),
-				Camera(x, y) => self.input(Event::CCamera(js, x, y)),
-				ThrottleL(x) => self.input(Event::CThrottleL(js, x)),
-				ThrottleR(x) => self.input(Event::CThrottleR(js, x)),
-				Accept(s) => self.input(Event::CAccept(js, s)),
-				Cancel(s) => self.input(Event::CCancel(js, s)),
-				Execute(s) => self.input(Event::CExecute(js, s)),
-				Action(s) => self.input(Event::CAction(js, s)),
-				L(b, s) => self.input(Event::CL(js, b, s)),
-				R(b, s) => self.input(Event::CR(js, b, s)),
-				Menu(s) => self.input(Event::CMenu(js, s)),
+				Move(x, y) => {
+					let (x, y) = self.deadzone(js, x, y);
+					self.input(Event::CMove(js, x, y));
+					self.stick_dpad(js, x, y)
+				}
+				Camera(x, y) => {
+					let (x, y) = self.deadzone(js, x, y);
+					self.input(Event::CCamera(js, x, y))
+				}
+				ThrottleL(x) => {
+					let x = self.deadzone(js, x, 0.0).0;
+					self.input(Event::CThrottleL(js, x))
+				}
+				ThrottleR(x) => {
+					let x = self.deadzone(js, x, 0.0).0;
+					self.input(Event::CThrottleR(js, x))
+				}
+				Accept(s) => self.button(js, Button::Accept, s),
+				Cancel(s) => self.button(js, Button::Cancel, s),
+				Execute(s) => self.button(js, Button::Execute, s),
+				Action(s) => self.button(js, Button::Action, s),
+				L(b, s) => self.button(js, Button::L(b), s),
+				R(b, s) => self.button(js, Button::R(b), s),
+				Menu(s) => self.button(js, Button::Menu, s),
 				Controls => self.input(Event::CControls(js)),
 				Exit => self.input(Event::CExit(js)),
-				Up(s) => self.input(Event::CUp(js, s)),
-				Down(s) => self.input(Event::CDown(js, s)),
-				Left(s) => self.input(Event::CLeft(js, s)),
-				Right(s) => self.input(Event::CRight(js, s)),
-				MoveStick(s) => self.input(Event::CMoveStick(js, s)),
-				CamStick(s) => self.input(Event::CCamStick(js, s)),
+				Up(s) => self.button(js, Button::Up, s),
+				Down(s) => self.button(js, Button::Down, s),
+				Left(s) => self.button(js, Button::Left, s),
+				Right(s) => self.button(js, Button::Right, s),
+				MoveStick(s) => self.button(js, Button::MoveStick, s),
+				CamStick(s) => self.button(js, Button::CamStick, s),
 				PluggedIn(i) => self.input(Event::CPluggedIn(js, i)),
-				UnPlugged(i) => self.input(Event::CUnPlugged(js, i)),
+				UnPlugged(i) => {
+					self.controllers[js].1 = None;
+					self.stick_dpad(js, 0.0, 0.0);
+					self.input(Event::CUnPlugged(js, i))
+				}
 			}
 		}
 	}
 }
+
+// How long a stick is held as a D-pad before it repeats, then how often (ms).
+const DPAD_DELAY: u64 = 400;
+const DPAD_RATE: u64 = 100;
+
+// Which way a stick axis points as a D-pad (-1, 0 or 1), staying pressed
+// until it's let back further than where it was pushed.
+fn dpad_direction(old: i8, value: f32) -> i8 {
+	const PRESS: f32 = 0.5;
+	const RELEASE: f32 = 0.3;
+
+	if value <= -PRESS {
+		-1
+	} else if value >= PRESS {
+		1
+	} else if value * old as f32 > RELEASE {
+		old
+	} else {
+		0
+	}
+}
+
+/// Iterator over the events popped from an `InputQueue`.
+pub struct Drain<'a> {
+	queue: &'a mut InputQueue,
+	time: Option<&'a mut Instant>,
+}
+
+impl<'a> Iterator for Drain<'a> {
+	type Item = Event;
+
+	#[inline(always)]
+	fn next(&mut self) -> Option<Event> {
+		let (time, event) = self.queue.pop_timed()?;
+
+		if let Some(ref mut t) = self.time {
+			**t = time;
+		}
+		Some(event)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.queue.len(), Some(self.queue.len()))
+	}
+}
+
+impl<'a> IntoIterator for &'a mut InputQueue {
+	type Item = Event;
+	type IntoIter = Drain<'a>;
+
+	fn into_iter(self) -> Drain<'a> {
+		self.drain()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ InputQueue, Event };
+
+	#[test]
+	fn drain_order() {
+		let events = [Event::Resize, Event::Text('a'), Event::Pause,
+			Event::Text('b'), Event::Exit];
+		let mut popped = InputQueue::new();
+		let mut drained = InputQueue::new();
+
+		for event in events.iter() {
+			popped.push(*event);
+			drained.push(*event);
+		}
+
+		let mut expected = vec![];
+		while let Some(event) = popped.pop() {
+			expected.push(event);
+		}
+		assert_eq!(expected.len(), events.len());
+		assert_eq!(drained.drain().collect::<Vec<_>>(), expected);
+		assert!(drained.pop().is_none());
+	}
+
+	#[test]
+	fn deadzone() {
+		let mut queue = InputQueue::new();
+
+		queue.set_stick_deadzone(0, 0.2, 0.8);
+		assert_eq!(queue.deadzone(0, 0.1, 0.0), (0.0, 0.0));
+		// Halfway between `inner` & `outer`.
+		let (x, y) = queue.deadzone(0, 0.5, 0.0);
+		assert!((x - 0.5).abs() < 0.0001 && y == 0.0);
+		assert_eq!(queue.deadzone(0, 0.0, -0.9), (0.0, -1.0));
+		// Other controllers have none.
+		assert_eq!(queue.deadzone(1, 0.1, 0.0), (0.1, 0.0));
+	}
+
+	#[test]
+	#[should_panic(expected = "awi: Stick deadzone")]
+	fn deadzone_inverted() {
+		InputQueue::new().set_stick_deadzone(0, 0.8, 0.2);
+	}
+
+	#[test]
+	#[should_panic(expected = "awi: Stick deadzone")]
+	fn deadzone_nan() {
+		InputQueue::new().set_stick_deadzone(0, ::std::f32::NAN, 1.0);
+	}
+}
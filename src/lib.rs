@@ -18,6 +18,8 @@
 extern crate stick;
 pub extern crate afi;
 #[cfg(target_os="windows")] extern crate winapi;
+#[cfg(feature="raw-window-handle")] extern crate raw_window_handle;
+#[cfg(feature="serde")] #[macro_use] extern crate serde;
 #[cfg(not(target_arch="wasm32"))] #[macro_use] extern crate dl_api;
 #[cfg(target_arch="wasm32")] #[macro_use] extern crate stdweb;
 #[cfg(target_arch="wasm32")] #[macro_use] extern crate stdweb_derive;
@@ -25,6 +27,8 @@ pub extern crate afi;
 pub mod screen;
 
 mod window_connection;
+mod cursor;
+mod monitor;
 pub(crate) mod input;
 #[cfg(not(target_arch="wasm32"))] pub(crate) mod window;
 #[cfg(not(target_arch="wasm32"))] pub(crate) mod window_ops;
@@ -48,7 +52,17 @@ pub(crate) use std::os::raw::c_void;
 pub(crate) use input::keyboard::Keyboard;
 
 pub use input::Event;
-#[cfg(not(target_arch="wasm32"))] pub(crate) use window_connection::WindowConnection;
+pub use input::Drain;
+pub use input::ScrollKind;
+pub use input::ControllerButton;
+pub use input::ControllerCaps;
+pub use input::ClickMods;
+pub use input::zoom_about;
+pub use input::keyboard;
+pub use cursor::Cursor;
+pub use monitor::{Monitor, monitors};
+#[cfg(not(target_arch="wasm32"))] pub use window_connection::WindowConnection;
 #[cfg(not(target_arch="wasm32"))] pub(crate) use window::Window;
+#[cfg(not(target_arch="wasm32"))] pub(crate) use window::WindowHints;
 
 pub use ami::*;
@@ -0,0 +1,36 @@
+// Copyright Jeron A. Lau 2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+/// A monitor connected to the computer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Monitor {
+	/// Resolution in pixels.
+	pub size: (u16, u16),
+	/// Position of the top left corner on the virtual screen, in pixels.
+	pub position: (i16, i16),
+	/// HiDPI scale factor (1.0 is normal).
+	pub scale: f32,
+	/// Whether this is the primary monitor.
+	pub primary: bool,
+}
+
+/// Get the list of monitors, so a window can be sized & centered relative to
+/// one before it's created.  Empty if they can't be detected.
+pub fn monitors() -> Vec<Monitor> {
+	#[cfg(any(target_os = "linux", target_os = "freebsd",
+		target_os = "dragonfly", target_os = "bitrig",
+		target_os = "openbsd", target_os = "netbsd"))]
+	{
+		::os::monitors()
+	}
+
+	#[cfg(not(any(target_os = "linux", target_os = "freebsd",
+		target_os = "dragonfly", target_os = "bitrig",
+		target_os = "openbsd", target_os = "netbsd")))]
+	{
+		// TODO: Other platforms.
+		vec![]
+	}
+}
@@ -4,66 +4,977 @@
 // https://www.boost.org/LICENSE_1_0.txt)
 
 use input::keyboard;
+use ScrollKind;
 use c_void;
 use std::ptr::null_mut;
+use std::cell::RefCell;
+use std::collections::VecDeque;
 
-pub struct Window {
+extern crate libc;
+
+mod wayland;
+
+pub enum Window {
+	Xcb(XcbWindow),
+	Wayland(wayland::Window),
+}
+
+impl Window {
+	pub fn new(v: Option<i32>, hints: &::WindowHints) -> Self {
+		if !Self::needs_visual() {
+			if let Some(window) = wayland::Window::new(hints) {
+				return Window::Wayland(window);
+			}
+		}
+
+		Window::Xcb(XcbWindow::new(v, hints))
+	}
+
+	/// XCB needs the visual from EGL.  Wayland is only used without an X
+	/// server (or XWayland), since the Vulkan renderer only supports XCB.
+	pub fn needs_visual() -> bool {
+		::std::env::var_os("DISPLAY").is_some()
+	}
+
+	pub fn poll_event(&mut self, input: &mut ::input::InputQueue,
+		keyboard: &mut ::input::keyboard::Keyboard)
+		-> bool
+	{
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.poll_event(input, keyboard)
+			}
+			Window::Wayland(ref mut window) => {
+				window.poll_event(input, keyboard)
+			}
+		}
+	}
+
+	/// Block until there might be events to poll, or `timeout`.
+	pub fn wait(&mut self, timeout: Option<::std::time::Duration>) {
+		match *self {
+			Window::Xcb(ref mut window) => window.wait(timeout),
+			Window::Wayland(ref mut window) => window.wait(timeout),
+		}
+	}
+
+	/// Flush, and get the file descriptor to wait on for events.
+	pub fn fd(&mut self) -> i32 {
+		match *self {
+			Window::Xcb(ref mut window) => unsafe {
+				(window.xcb.xcb_flush)(window.connection);
+				(window.xcb.xcb_get_file_descriptor)(
+					window.connection)
+			},
+			Window::Wayland(ref mut window) => window.fd(),
+		}
+	}
+
+	pub fn get_connection(&self) -> ::WindowConnection {
+		match *self {
+			Window::Xcb(ref window) => window.get_connection(),
+			Window::Wayland(ref window) => window.get_connection(),
+		}
+	}
+
+	pub fn wh(&self) -> (u16, u16) {
+		match *self {
+			Window::Xcb(ref window) => window.wh,
+			Window::Wayland(ref window) => window.wh(),
+		}
+	}
+
+	pub fn position(&self) -> (i16, i16) {
+		match *self {
+			Window::Xcb(ref window) => window.xy,
+			Window::Wayland(ref window) => window.position(),
+		}
+	}
+
+	pub fn set_position(&mut self, xy: (i16, i16)) {
+		match *self {
+			Window::Xcb(ref mut window) => window.set_position(xy),
+			Window::Wayland(ref mut window) => {
+				window.set_position(xy)
+			}
+		}
+	}
+
+	pub fn set_cursor_position(&mut self, xy: (i16, i16)) {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_cursor_position(xy)
+			}
+			// TODO: zwp_locked_pointer_v1.set_cursor_position_hint,
+			// only while the pointer is locked.
+			Window::Wayland(_) => {}
+		}
+	}
+
+	pub fn set_text_input_rect(&mut self,
+		_rect: Option<(i16, i16, u16, u16)>)
+	{
+		match *self {
+			// TODO: XIM spot location (with xcb-imdkit).
+			Window::Xcb(_) => {}
+			// TODO: zwp_text_input_v3 enable / disable &
+			// set_cursor_rectangle.
+			Window::Wayland(_) => {}
+		}
+	}
+
+	pub fn scale_factor(&self) -> f32 {
+		match *self {
+			Window::Xcb(ref window) => window.scale,
+			Window::Wayland(ref window) => window.scale_factor(),
+		}
+	}
+
+	pub fn has_focus(&self) -> bool {
+		match *self {
+			Window::Xcb(ref window) => window.focus,
+			Window::Wayland(ref window) => window.has_focus(),
+		}
+	}
+
+	pub fn clipboard_set(&mut self, text: &str) {
+		match *self {
+			Window::Xcb(ref mut window) => window.clipboard_set(text),
+			Window::Wayland(ref mut window) => {
+				window.clipboard_set(text)
+			}
+		}
+	}
+
+	pub fn clipboard_get(&self) -> Option<String> {
+		match *self {
+			Window::Xcb(ref window) => window.clipboard_get(),
+			Window::Wayland(ref window) => window.clipboard_get(),
+		}
+	}
+
+	pub fn set_cursor(&mut self, cursor: ::Cursor) {
+		match *self {
+			Window::Xcb(ref mut window) => window.set_cursor(cursor),
+			Window::Wayland(ref mut window) => {
+				window.set_cursor(cursor)
+			}
+		}
+	}
+
+	pub fn set_min_size(&mut self, wh: (u16, u16)) {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.min_wh = wh;
+				window.size_hints();
+			}
+			Window::Wayland(ref mut window) => {
+				window.set_min_size(wh)
+			}
+		}
+	}
+
+	pub fn set_max_size(&mut self, wh: (u16, u16)) {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.max_wh = wh;
+				window.size_hints();
+			}
+			Window::Wayland(ref mut window) => {
+				window.set_max_size(wh)
+			}
+		}
+	}
+
+	pub fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.aspect = aspect;
+				window.size_hints();
+				true
+			}
+			Window::Wayland(ref mut window) => {
+				window.set_aspect_ratio(aspect)
+			}
+		}
+	}
+
+	pub fn set_always_on_top(&mut self, above: bool) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_always_on_top(above)
+			}
+			Window::Wayland(ref mut window) => {
+				window.set_always_on_top(above)
+			}
+		}
+	}
+
+	pub fn set_opacity(&mut self, opacity: f32) {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_opacity(opacity)
+			}
+			// TODO: wp_alpha_modifier_v1.
+			Window::Wayland(_) => {}
+		}
+	}
+
+	pub fn set_class(&mut self, instance: &str, class: &str) {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_class(instance, class)
+			}
+			Window::Wayland(ref mut window) => {
+				window.set_app_id(class)
+			}
+		}
+	}
+
+	pub fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_input_shape(region)
+			}
+			Window::Wayland(ref mut window) => {
+				window.set_input_shape(region)
+			}
+		}
+	}
+
+	pub fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_keyboard_grab(grab)
+			}
+			// TODO: zwp_keyboard_shortcuts_inhibit_manager_v1.
+			Window::Wayland(_) => false,
+		}
+	}
+
+	pub fn set_decorated(&mut self, decorated: bool) {
+		match *self {
+			Window::Xcb(ref mut window) => {
+				window.set_decorated(decorated)
+			}
+			// TODO: zxdg_decoration_manager_v1.
+			Window::Wayland(_) => {}
+		}
+	}
+
+	pub fn begin_drag(&mut self) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => window.begin_drag(),
+			// TODO: xdg_toplevel.move, with the button press serial.
+			Window::Wayland(_) => false,
+		}
+	}
+
+	pub fn minimize(&mut self) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => window.minimize(),
+			Window::Wayland(ref mut window) => window.minimize(),
+		}
+	}
+
+	pub fn maximize(&mut self) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => window.maximize(true),
+			Window::Wayland(ref mut window) => window.maximize(true),
+		}
+	}
+
+	pub fn restore(&mut self) -> bool {
+		match *self {
+			Window::Xcb(ref mut window) => window.restore(),
+			Window::Wayland(ref mut window) => window.maximize(false),
+		}
+	}
+
+	pub fn close(&mut self) {
+		match *self {
+			Window::Xcb(ref mut window) => window.close(),
+			Window::Wayland(ref mut window) => window.close(),
+		}
+	}
+}
+
+pub struct XcbWindow {
 	// Keyboard (XKB)
 	keymap: *mut c_void,
 	context: *mut c_void,
 	state: *mut c_void,
 	xkb: XkbCommonX11,
+	// Dead keys & compose sequences (null if no compose table for locale).
+	compose_table: *mut c_void,
+	compose: *mut c_void,
 	// Window (XCB)
 	window: u32,
 	connection: *mut c_void,
 	wh: (u16, u16),
+	// Top-left of the window, on the root window.
+	xy: (i16, i16),
+	// Content scale factor, from `Xft.dpi`.
+	scale: f32,
+	// Whether the window has keyboard focus.
+	focus: bool,
+	// Whether the keyboard is grabbed.
+	keyboard_grab: bool,
+	// Size constraints, (0, 0) for none.
+	min_wh: (u16, u16),
+	max_wh: (u16, u16),
+	// Aspect ratio constraint (width, height).
+	aspect: Option<(u16, u16)>,
 	xcb: Xcb,
+	// Clipboard (CLIPBOARD selection)
+	clipboard: Clipboard,
+	// Events read while waiting for the clipboard, handled next poll.
+	pending: RefCell<VecDeque<XcbGenericEvent>>,
+	// XInput2 major opcode (for touch events), 0 if unavailable.
+	xi_opcode: u8,
+	// The pressure, contact size & tilt valuators of each XInput2 device.
+	xi_axes: Vec<XiAxes>,
+	// The XShape extension, `None` if unavailable.
+	shape: Option<XcbShape>,
+	// X server time of the last pen event, to leave out the pointer events
+	// it also makes.
+	pen_time: Option<u32>,
+	// When X server time `.1` was, to convert event times.
+	time_base: Option<(::std::time::Instant, u32)>,
+	// Where the cursor was warped to, so the motion it makes is left out.
+	warp: Option<(i16, i16)>,
 }
 
-impl Window {
-	pub fn new(v: Option<i32>) -> Self {
-		// TODO: Try Wayland first
-
+impl XcbWindow {
+	fn new(v: Option<i32>, hints: &::WindowHints) -> Self {
 		let (xcb, xkb) = xcb_load();
 		let connection = xcb_connect(&xcb);
 		let mut screen = xcb_screen(connection, &xcb);
-		let window = xcb_window(connection, &xcb, &mut screen, v);
+		let window = xcb_window(connection, &xcb, &mut screen, v,
+			hints);
 		let (state, keymap, context) = xkb_keyboard(connection, &xkb);
-		let wh = (screen.width_in_pixels, screen.height_in_pixels);
+		let (compose_table, compose) = xkb_compose(&xkb, context);
+		let wh = hints.wh.unwrap_or((screen.width_in_pixels,
+			screen.height_in_pixels));
+		let clipboard = Clipboard::new(connection, &xcb);
+		let pending = RefCell::new(VecDeque::new());
+		let xi_opcode = xcb_touch(connection, &xcb, window,
+			screen.root);
+		let xi_axes = if xi_opcode != 0 {
+			xi_axes(connection, &xcb, window)
+		} else {
+			vec![]
+		};
+		let shape = xcb_shape(connection, &xcb);
+		let scale = xcb_scale(connection, &xcb);
+		xcb_watch_root(connection, &xcb, screen.root);
+		let xy = xcb_position(connection, &xcb, window);
+		let focus = xcb_focus(connection, &xcb) == window;
 
-		Window {
-			state, keymap, context, xkb, window, connection, wh,
-			xcb
-		}
+		let mut xcb_window = XcbWindow {
+			state, keymap, context, xkb, compose_table, compose,
+			window, connection, wh, xy, scale, focus,
+			keyboard_grab: false,
+			min_wh: (0, 0), max_wh: (0, 0), aspect: None, xcb,
+			clipboard, pending,
+			xi_opcode, xi_axes, shape, pen_time: None,
+			time_base: None,
+			warp: None,
+		};
+		xcb_window.set_cursor(::Cursor::Arrow);
+		xcb_window
 	}
 
-	pub fn poll_event(&mut self, input: &mut ::input::InputQueue,
+	fn poll_event(&mut self, input: &mut ::input::InputQueue,
 		keyboard: &mut ::input::keyboard::Keyboard)
 		-> bool
 	{
 		unsafe { (self.xcb.xcb_flush)(self.connection) };
-		xcb_poll_for_event(self.connection, &self.xcb, &self.xkb,
-			self.state, input, &mut self.wh,
-			keyboard)
+		let event = if let Some(event) =
+			self.pending.borrow_mut().pop_front()
+		{
+			event
+		} else if let Some(event) = xcb_next_event(self.connection,
+			&self.xcb, self.xi_opcode, &self.xi_axes, false)
+		{
+			event
+		} else {
+			return false;
+		};
+
+		// Motion from `set_cursor_position()`, not the user.
+		const CURSOR_MOVE: u8 = 6;
+		const BUTTON_PRESS: u8 = 4;
+		const GAIN_FOCUS: u8 = 9;
+		const LOSE_FOCUS: u8 = 10;
+		const UNMAP: u8 = 18;
+		if let Some(warp) = self.warp {
+			if event.response_type & 0x7F == CURSOR_MOVE {
+				self.warp = None;
+				if (event.event_x, event.event_y) == warp {
+					return true;
+				}
+			}
+		}
+
+		// Pen events come before the pointer events they make.
+		match event.response_type & 0x7F {
+			GE_GENERIC => match event.detail {
+				XI_BUTTON_PRESS..=XI_MOTION => {
+					self.pen_time = Some(event.timestamp);
+				}
+				_ => {}
+			},
+			BUTTON_PRESS..=CURSOR_MOVE => {
+				if self.pen_time == Some(event.timestamp) {
+					return true;
+				}
+			}
+			_ => {}
+		}
+
+		// Key, button, cursor & enter / leave events have the X server
+		// time (in milliseconds).
+		input.set_stamp(match event.response_type & 0x7F {
+			2..=8 => Some(self.event_time(event.timestamp)),
+			_ => None,
+		});
+
+		// While the keyboard is grabbed, focus events with mode
+		// `NotifyGrab` or `NotifyUngrab` (right after the event window)
+		// are from the grab, and aren't focus changes.
+		if self.keyboard_grab {
+			let mode = event.root as u8;
+			let focus_event = match event.response_type & 0x7F {
+				GAIN_FOCUS | LOSE_FOCUS => true,
+				_ => false,
+			};
+
+			if focus_event && (mode == 1 || mode == 2) {
+				return true;
+			}
+		}
+
+		let response_type = event.response_type & 0x7F;
+
+		xcb_handle_event(self.connection, &self.xcb, &self.xkb,
+			self.state, self.compose, input, &mut self.wh,
+			&mut self.xy, &mut self.scale, &mut self.focus,
+			keyboard, &mut self.clipboard, event);
+
+		input.set_stamp(None);
+
+		// Don't keep the keyboard once the window is in the background.
+		if self.keyboard_grab && (!self.focus || response_type == UNMAP)
+		{
+			self.set_keyboard_grab(false);
+		}
+
+		true
 	}
 
-	pub fn get_connection(&self) -> ::WindowConnection {
-		::WindowConnection::Xcb(self.connection, self.window)
+	fn wait(&mut self, timeout: Option<::std::time::Duration>) {
+		// Events read while waiting for the clipboard are ready now.
+		if !self.pending.borrow().is_empty() {
+			return;
+		}
+
+		// Events already read from the connection won't wake `poll()`.
+		if let Some(event) = xcb_next_event(self.connection, &self.xcb,
+			self.xi_opcode, &self.xi_axes, true)
+		{
+			self.pending.borrow_mut().push_back(event);
+			return;
+		}
+
+		unsafe {
+			(self.xcb.xcb_flush)(self.connection);
+			wait_fd((self.xcb.xcb_get_file_descriptor)(
+				self.connection), timeout);
+		}
 	}
 
-	pub fn wh(&self) -> (u16, u16) {
-		self.wh
+	// Convert X server time (milliseconds) to an `Instant`.
+	fn event_time(&mut self, time: u32) -> ::std::time::Instant {
+		use std::time::{ Duration, Instant };
+
+		let now = Instant::now();
+		let (base, base_time) = *self.time_base.get_or_insert((now, time));
+		// Wrapping, since the X server's time wraps after ~49 days.
+		let since = time.wrapping_sub(base_time);
+
+		// Older than the base, or wrapped around.
+		if since > ::std::u32::MAX / 2 {
+			self.time_base = Some((now, time));
+			return now;
+		}
+
+		let instant = base + Duration::from_millis(since as u64);
+		if instant > now {
+			// The server's clock drifted from ours.
+			self.time_base = Some((now, time));
+			return now;
+		}
+		instant
+	}
+
+	fn clipboard_set(&mut self, text: &str) {
+		self.clipboard.text = Some(text.to_string());
+
+		unsafe {
+			(self.xcb.xcb_set_selection_owner)(self.connection,
+				self.window, self.clipboard.clipboard, 0);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	fn close(&mut self) {
+		if self.window == 0 {
+			return;
+		}
+
+		unsafe {
+			(self.xcb.xcb_destroy_window)(self.connection,
+				self.window);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+		self.window = 0;
+	}
+
+	// Update WM_NORMAL_HINTS from `min_wh`, `max_wh` & `aspect`.
+	fn size_hints(&mut self) {
+		const WM_NORMAL_HINTS: u32 = 40;
+		const WM_SIZE_HINTS: u32 = 41;
+		const P_MIN_SIZE: u32 = 16;
+		const P_MAX_SIZE: u32 = 32;
+		const P_ASPECT: u32 = 128;
+
+		// flags, (obsolete) x, y, w, h, min w, h, max w, h, w & h
+		// increments, min aspect w, h, max aspect w, h, etc.
+		let mut hints = [0u32; 18];
+		if self.min_wh != (0, 0) {
+			hints[0] |= P_MIN_SIZE;
+			hints[5] = self.min_wh.0 as u32;
+			hints[6] = self.min_wh.1 as u32;
+		}
+		if self.max_wh != (0, 0) {
+			hints[0] |= P_MAX_SIZE;
+			hints[7] = self.max_wh.0 as u32;
+			hints[8] = self.max_wh.1 as u32;
+		}
+		if let Some((w, h)) = self.aspect {
+			hints[0] |= P_ASPECT;
+			hints[11] = w as u32;
+			hints[12] = h as u32;
+			hints[13] = w as u32;
+			hints[14] = h as u32;
+		}
+
+		unsafe {
+			(self.xcb.xcb_change_property)(self.connection, 0,
+				self.window, WM_NORMAL_HINTS, WM_SIZE_HINTS, 32,
+				hints.len() as u32,
+				hints.as_ptr() as *const c_void);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	fn set_always_on_top(&mut self, above: bool) -> bool {
+		let state_above = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_STATE_ABOVE");
+
+		self.net_wm_state(above, state_above, 0)
+	}
+
+	// Set _NET_WM_WINDOW_OPACITY, which only a compositor looks at.
+	fn set_opacity(&mut self, opacity: f32) {
+		const CARDINAL: u32 = 6;
+
+		let atom = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_WINDOW_OPACITY");
+		// 0xFFFFFFFF is opaque.
+		let opacity = (opacity.max(0.0).min(1.0) as f64
+			* ::std::u32::MAX as f64) as u32;
+
+		unsafe {
+			(self.xcb.xcb_change_property)(self.connection, 0,
+				self.window, atom, CARDINAL, 32, 1,
+				&opacity as *const _ as *const c_void);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	// Set where clicks go to the window with the XShape extension (the
+	// rest go through to what's behind it), `None` for the whole window.
+	fn set_input_shape(&mut self, region: Option<&[(i16, i16, u16, u16)]>)
+		-> bool
+	{
+		const SO_SET: u8 = 0;
+		const SK_INPUT: u8 = 2;
+		const UNSORTED: u8 = 0;
+
+		let shape = match self.shape {
+			Some(ref shape) => shape,
+			None => return false,
+		};
+
+		unsafe {
+			match region {
+				Some(rects) => {
+					let rects: Vec<XcbRectangle> = rects
+						.iter()
+						.map(|&(x, y, width, height)| {
+							XcbRectangle {
+								x, y, width,
+								height,
+							}
+						})
+						.collect();
+
+					(shape.xcb_shape_rectangles)(
+						self.connection, SO_SET,
+						SK_INPUT, UNSORTED, self.window,
+						0, 0, rects.len() as u32,
+						rects.as_ptr());
+				}
+				// No pixmap resets it.
+				None => {
+					(shape.xcb_shape_mask)(self.connection,
+						SO_SET, SK_INPUT, self.window,
+						0, 0, 0);
+				}
+			}
+			(self.xcb.xcb_flush)(self.connection);
+		}
+		true
+	}
+
+	// Grab the keyboard (so the window manager doesn't get shortcuts), or
+	// let go of it.
+	fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		extern { fn free(this: *mut XcbGrabKeyboardReply) -> (); }
+
+		const CURRENT_TIME: u32 = 0;
+		const GRAB_MODE_ASYNC: u8 = 1;
+		const GRAB_STATUS_SUCCESS: u8 = 0;
+
+		if grab == self.keyboard_grab {
+			return true;
+		}
+
+		unsafe {
+			if !grab {
+				self.keyboard_grab = false;
+				(self.xcb.xcb_ungrab_keyboard)(self.connection,
+					CURRENT_TIME);
+				(self.xcb.xcb_flush)(self.connection);
+				return true;
+			}
+
+			// Only the focused window should take the keyboard.
+			if !self.focus {
+				return false;
+			}
+
+			let cookie = (self.xcb.xcb_grab_keyboard)(
+				self.connection, 1, self.window, CURRENT_TIME,
+				GRAB_MODE_ASYNC, GRAB_MODE_ASYNC);
+			let reply = (self.xcb.xcb_grab_keyboard_reply)(
+				self.connection, cookie, null_mut());
+			if reply.is_null() {
+				return false;
+			}
+			let status = (*reply).status;
+
+			free(reply);
+			self.keyboard_grab = status == GRAB_STATUS_SUCCESS;
+			self.keyboard_grab
+		}
+	}
+
+	// Set WM_CLASS, the instance & class names (null-terminated each).
+	fn set_class(&mut self, instance: &str, class: &str) {
+		const WM_CLASS: u32 = 67;
+		const STRING: u32 = 31;
+
+		let mut value = Vec::new();
+		value.extend_from_slice(instance.as_bytes());
+		value.push(0);
+		value.extend_from_slice(class.as_bytes());
+		value.push(0);
+
+		unsafe {
+			(self.xcb.xcb_change_property)(self.connection, 0,
+				self.window, WM_CLASS, STRING, 8,
+				value.len() as u32,
+				value.as_ptr() as *const c_void);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	fn set_position(&mut self, xy: (i16, i16)) {
+		const CONFIG_WINDOW_X: u16 = 1;
+		const CONFIG_WINDOW_Y: u16 = 2;
+
+		// Sign extended, like xcb does for INT16s.
+		let values = [xy.0 as i32 as u32, xy.1 as i32 as u32];
+
+		unsafe {
+			(self.xcb.xcb_configure_window)(self.connection,
+				self.window, CONFIG_WINDOW_X | CONFIG_WINDOW_Y,
+				values.as_ptr());
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	// Move the cursor to `xy` (pixels) in the window.
+	fn set_cursor_position(&mut self, xy: (i16, i16)) {
+		unsafe {
+			(self.xcb.xcb_warp_pointer)(self.connection, 0,
+				self.window, 0, 0, 0, 0, xy.0, xy.1);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+		self.warp = Some(xy);
+	}
+
+	// Title bar & borders, with `_MOTIF_WM_HINTS`.
+	fn set_decorated(&mut self, decorated: bool) {
+		const MWM_HINTS_DECORATIONS: u32 = 2;
+
+		let motif_wm_hints = get_atom(self.connection, &self.xcb,
+			b"_MOTIF_WM_HINTS");
+		// Flags, functions, decorations, input mode, status.
+		let hints = [MWM_HINTS_DECORATIONS, 0, decorated as u32, 0, 0];
+
+		unsafe {
+			(self.xcb.xcb_change_property)(self.connection, 0,
+				self.window, motif_wm_hints, motif_wm_hints, 32,
+				hints.len() as u32,
+				hints.as_ptr() as *const c_void);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+	}
+
+	// Let the window manager move the window, following the cursor, with
+	// `_NET_WM_MOVERESIZE`.
+	fn begin_drag(&mut self) -> bool {
+		const MOVERESIZE_MOVE: u32 = 8;
+
+		extern { fn free(this: *mut XcbQueryPointerReply) -> (); }
+
+		let moveresize = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_MOVERESIZE");
+		let root_xy = unsafe {
+			let cookie = (self.xcb.xcb_query_pointer)(self.connection,
+				self.window);
+			let reply = (self.xcb.xcb_query_pointer_reply)(
+				self.connection, cookie, null_mut());
+			if reply.is_null() {
+				return false;
+			}
+			let xy = ((*reply).root_x, (*reply).root_y);
+			free(reply);
+			xy
+		};
+
+		// The window manager can't grab the pointer while we have it,
+		// from the button press.
+		unsafe {
+			(self.xcb.xcb_ungrab_pointer)(self.connection, 0);
+		}
+
+		// X & Y on the root, direction, button, source (application).
+		self.client_message(moveresize, [root_xy.0 as u32,
+			root_xy.1 as u32, MOVERESIZE_MOVE, 1, 1])
+	}
+
+	// Iconify, with `WM_CHANGE_STATE` (what `XIconifyWindow()` does).
+	fn minimize(&mut self) -> bool {
+		const ICONIC_STATE: u32 = 3;
+
+		let change_state = get_atom(self.connection, &self.xcb,
+			b"WM_CHANGE_STATE");
+
+		self.client_message(change_state, [ICONIC_STATE, 0, 0, 0, 0])
+	}
+
+	fn maximize(&mut self, maximized: bool) -> bool {
+		let vert = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_STATE_MAXIMIZED_VERT");
+		let horz = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_STATE_MAXIMIZED_HORZ");
+
+		self.net_wm_state(maximized, vert, horz)
+	}
+
+	// Un-maximize, and map again if iconified.
+	fn restore(&mut self) -> bool {
+		unsafe {
+			(self.xcb.xcb_map_window)(self.connection, self.window);
+		}
+
+		self.maximize(false)
+	}
+
+	// Ask the window manager to add or remove 1 or 2 `_NET_WM_STATE`s.
+	fn net_wm_state(&mut self, add: bool, a: u32, b: u32) -> bool {
+		let state = get_atom(self.connection, &self.xcb,
+			b"_NET_WM_STATE");
+
+		if a == 0 {
+			return false;
+		}
+
+		// _NET_WM_STATE_REMOVE = 0, _NET_WM_STATE_ADD = 1, property,
+		// 2nd property, source (1 = application).
+		self.client_message(state, [add as u32, a, b, 1, 0])
+	}
+
+	// Send a client message about our window to the window manager.
+	fn client_message(&mut self, kind: u32, data: [u32; 5]) -> bool {
+		const CLIENT_MESSAGE: u8 = 33;
+		const SUBSTRUCTURE_NOTIFY: u32 = 0x80000;
+		const SUBSTRUCTURE_REDIRECT: u32 = 0x100000;
+
+		if kind == 0 {
+			return false;
+		}
+
+		let root = xcb_screen(self.connection, &self.xcb).root;
+		let message = XcbClientMessageEvent {
+			response_type: CLIENT_MESSAGE,
+			format: 32,
+			sequence: 0,
+			window: self.window,
+			kind,
+			data,
+		};
+
+		unsafe {
+			(self.xcb.xcb_send_event)(self.connection, 0, root,
+				SUBSTRUCTURE_NOTIFY | SUBSTRUCTURE_REDIRECT,
+				&message as *const _ as *const u8);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+
+		true
+	}
+
+	fn set_cursor(&mut self, cursor: ::Cursor) {
+		use Cursor::*;
+
+		// Glyphs from the X11 cursor font (X11/cursorfont.h).
+		let glyph = match cursor {
+			Arrow => 68, // left_ptr
+			IBeam => 152, // xterm
+			Hand => 60, // hand2
+			ResizeH => 108, // sb_h_double_arrow
+			ResizeV => 116, // sb_v_double_arrow
+			Crosshair => 34, // crosshair
+			Wait => 150, // watch
+			Hidden => {
+				let cursor = xcb_blank_cursor(self.connection,
+					&self.xcb, self.window);
+				xcb_use_cursor(self.connection, &self.xcb,
+					self.window, cursor);
+				return;
+			}
+		};
+
+		let xcb = &self.xcb;
+		let connection = self.connection;
+
+		unsafe {
+			let font = (xcb.xcb_generate_id)(connection);
+			let name = b"cursor";
+			(xcb.xcb_open_font)(connection, font, name.len() as u16,
+				name.as_ptr());
+
+			let cursor = (xcb.xcb_generate_id)(connection);
+			(xcb.xcb_create_glyph_cursor)(connection, cursor, font,
+				font, glyph, glyph + 1, 0, 0, 0,
+				0xFFFF, 0xFFFF, 0xFFFF);
+			(xcb.xcb_close_font)(connection, font);
+
+			xcb_use_cursor(connection, xcb, self.window, cursor);
+		}
+	}
+
+	fn clipboard_get(&self) -> Option<String> {
+		use std::time::{ Duration, Instant };
+
+		// Don't round trip to the X server for our own selection.
+		if let Some(ref text) = self.clipboard.text {
+			return Some(text.clone());
+		}
+
+		unsafe {
+			(self.xcb.xcb_convert_selection)(self.connection,
+				self.window, self.clipboard.clipboard,
+				self.clipboard.utf8_string,
+				self.clipboard.property, 0);
+			(self.xcb.xcb_flush)(self.connection);
+		}
+
+		// Wait for the owner to reply, or give up.
+		let start = Instant::now();
+		while start.elapsed() < Duration::from_millis(500) {
+			let event = match xcb_next_event(self.connection,
+				&self.xcb, self.xi_opcode, &self.xi_axes, false)
+			{
+				Some(event) => event,
+				None => {
+					::std::thread::sleep(
+						Duration::from_millis(1));
+					continue;
+				}
+			};
+
+			if event.response_type != SELECTION_NOTIFY {
+				self.pending.borrow_mut().push_back(event);
+				continue;
+			}
+
+			let notify = unsafe {
+				&*(&event as *const _
+					as *const XcbSelectionNotifyEvent)
+			};
+
+			// No owner, or couldn't convert to UTF-8.
+			if notify.property == 0 {
+				return None;
+			}
+
+			return Some(xcb_get_text(self.connection, &self.xcb,
+				self.window, notify.property));
+		}
+
+		None
+	}
+
+	fn get_connection(&self) -> ::WindowConnection {
+		::WindowConnection::Xcb(self.connection, self.window)
 	}
 }
 
-impl Drop for Window {
+impl Drop for XcbWindow {
 	fn drop(&mut self) {
 		unsafe {
+			if !self.compose.is_null() {
+				(self.xkb.xkb_compose_state_unref)(self.compose);
+				(self.xkb.xkb_compose_table_unref)(
+					self.compose_table);
+			}
 			(self.xkb.xkb_state_unref)(self.state);
 			(self.xkb.xkb_keymap_unref)(self.keymap);
 			(self.xkb.xkb_context_unref)(self.context);
-			(self.xcb.xcb_destroy_window)(self.connection,
-				self.window);
+			// Unless it was already closed.
+			if self.window != 0 {
+				(self.xcb.xcb_destroy_window)(self.connection,
+					self.window);
+			}
 			(self.xcb.xcb_disconnect)(self.connection);
 		}
 	}
@@ -71,7 +982,9 @@ impl Drop for Window {
 
 dl_api!(Xcb, "libxcb.so.1",
 	fn xcb_poll_for_event(*mut c_void) -> *mut XcbGenericEvent,
+	fn xcb_poll_for_queued_event(*mut c_void) -> *mut XcbGenericEvent,
 	fn xcb_flush(*mut c_void) -> i32,
+	fn xcb_get_file_descriptor(*mut c_void) -> i32,
 	fn xcb_intern_atom(*mut c_void, u8, u16, *const u8) -> u32,
 	fn xcb_intern_atom_reply(*mut c_void, u32, *mut c_void)
 		-> *mut XcbInternAtomReply,
@@ -80,12 +993,100 @@ dl_api!(Xcb, "libxcb.so.1",
 	fn xcb_map_window(*mut c_void, u32) -> u32,
 	fn xcb_get_setup(*mut c_void) -> *mut c_void,
 	fn xcb_setup_roots_iterator(*mut c_void) -> XcbScreenIterator,
+	fn xcb_screen_allowed_depths_iterator(*const XcbScreen)
+		-> XcbDepthIterator,
+	fn xcb_depth_next(*mut XcbDepthIterator) -> (),
+	fn xcb_depth_visuals(*const XcbDepth) -> *const XcbVisualtype,
+	fn xcb_depth_visuals_length(*const XcbDepth) -> i32,
+	fn xcb_create_colormap(*mut c_void, u8, u32, u32, u32) -> u32,
 	fn xcb_generate_id(*mut c_void) -> u32,
 	fn xcb_create_window(*mut c_void, u8, u32, u32, i16, i16, u16, u16, u16,
 		u16, u32, u32, *mut u32) -> u32,
 	fn xcb_connect(*mut c_void, *mut c_void) -> *mut c_void,
 	fn xcb_destroy_window(*mut c_void, u32) -> u32,
-	fn xcb_disconnect(*mut c_void) -> ()
+	fn xcb_disconnect(*mut c_void) -> (),
+	fn xcb_set_selection_owner(*mut c_void, u32, u32, u32) -> u32,
+	fn xcb_convert_selection(*mut c_void, u32, u32, u32, u32, u32) -> u32,
+	fn xcb_send_event(*mut c_void, u8, u32, u32, *const u8) -> u32,
+	fn xcb_get_property(*mut c_void, u8, u32, u32, u32, u32, u32) -> u32,
+	fn xcb_get_property_reply(*mut c_void, u32, *mut c_void)
+		-> *mut c_void,
+	fn xcb_get_property_value(*mut c_void) -> *mut u8,
+	fn xcb_get_property_value_length(*mut c_void) -> i32,
+	fn xcb_change_window_attributes(*mut c_void, u32, u32, *const u32)
+		-> u32,
+	fn xcb_open_font(*mut c_void, u32, u16, *const u8) -> u32,
+	fn xcb_close_font(*mut c_void, u32) -> u32,
+	fn xcb_create_glyph_cursor(*mut c_void, u32, u32, u32, u16, u16, u16,
+		u16, u16, u16, u16, u16) -> u32,
+	fn xcb_create_cursor(*mut c_void, u32, u32, u32, u16, u16, u16, u16,
+		u16, u16, u16, u16) -> u32,
+	fn xcb_free_cursor(*mut c_void, u32) -> u32,
+	fn xcb_create_pixmap(*mut c_void, u8, u32, u32, u16, u16) -> u32,
+	fn xcb_free_pixmap(*mut c_void, u32) -> u32,
+	fn xcb_create_gc(*mut c_void, u32, u32, u32, *const u32) -> u32,
+	fn xcb_free_gc(*mut c_void, u32) -> u32,
+	fn xcb_poly_fill_rectangle(*mut c_void, u32, u32, u32,
+		*const XcbRectangle) -> u32,
+	fn xcb_configure_window(*mut c_void, u32, u16, *const u32) -> u32,
+	fn xcb_query_pointer(*mut c_void, u32) -> u32,
+	fn xcb_query_pointer_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbQueryPointerReply,
+	fn xcb_ungrab_pointer(*mut c_void, u32) -> u32,
+	fn xcb_grab_keyboard(*mut c_void, u8, u32, u32, u8, u8) -> u32,
+	fn xcb_grab_keyboard_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbGrabKeyboardReply,
+	fn xcb_ungrab_keyboard(*mut c_void, u32) -> u32,
+	fn xcb_warp_pointer(*mut c_void, u32, u32, i16, i16, u16, u16, i16,
+		i16) -> u32,
+	fn xcb_get_input_focus(*mut c_void) -> u32,
+	fn xcb_get_input_focus_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbGetInputFocusReply,
+	fn xcb_translate_coordinates(*mut c_void, u32, u32, i16, i16) -> u32,
+	fn xcb_translate_coordinates_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbTranslateCoordinatesReply,
+	fn xcb_query_extension(*mut c_void, u16, *const u8) -> u32,
+	fn xcb_query_extension_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbQueryExtensionReply
+);
+
+dl_api!(XcbInput, "libxcb-xinput.so.0",
+	fn xcb_input_xi_query_version(*mut c_void, u16, u16) -> u32,
+	fn xcb_input_xi_query_version_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbInputXiQueryVersionReply,
+	fn xcb_input_xi_query_device(*mut c_void, u16) -> u32,
+	fn xcb_input_xi_query_device_reply(*mut c_void, u32, *mut c_void)
+		-> *mut c_void,
+	fn xcb_input_xi_query_device_infos_iterator(*const c_void)
+		-> XcbInputXiDeviceInfoIterator,
+	fn xcb_input_xi_device_info_next(*mut XcbInputXiDeviceInfoIterator)
+		-> (),
+	fn xcb_input_xi_device_info_name(*const XcbInputXiDeviceInfo)
+		-> *const u8,
+	fn xcb_input_xi_device_info_classes_iterator(
+		*const XcbInputXiDeviceInfo) -> XcbInputDeviceClassIterator,
+	fn xcb_input_device_class_next(*mut XcbInputDeviceClassIterator)
+		-> (),
+	fn xcb_input_xi_select_events(*mut c_void, u32, u16,
+		*const XcbInputEventMask) -> u32
+);
+
+dl_api!(XcbShape, "libxcb-shape.so.0",
+	fn xcb_shape_rectangles(*mut c_void, u8, u8, u8, u32, i16, i16, u32,
+		*const XcbRectangle) -> u32,
+	fn xcb_shape_mask(*mut c_void, u8, u8, u32, i16, i16, u32) -> u32
+);
+
+dl_api!(XcbRandr, "libxcb-randr.so.0",
+	fn xcb_randr_query_version(*mut c_void, u32, u32) -> u32,
+	fn xcb_randr_query_version_reply(*mut c_void, u32, *mut c_void)
+		-> *mut XcbRandrQueryVersionReply,
+	fn xcb_randr_get_monitors(*mut c_void, u32, u8) -> u32,
+	fn xcb_randr_get_monitors_reply(*mut c_void, u32, *mut c_void)
+		-> *mut c_void,
+	fn xcb_randr_get_monitors_monitors_iterator(*const c_void)
+		-> XcbRandrMonitorInfoIterator,
+	fn xcb_randr_monitor_info_next(*mut XcbRandrMonitorInfoIterator) -> ()
 );
 
 dl_api!(XkbCommonX11, "libxkbcommon-x11.so.0",
@@ -101,7 +1102,17 @@ dl_api!(XkbCommonX11, "libxkbcommon-x11.so.0",
 	fn xkb_x11_keymap_new_from_device(*mut c_void, *mut c_void, i32,
 		CompileFlags) -> *mut c_void,
 	fn xkb_context_new(ContextFlags) -> *mut c_void,
-	fn xkb_x11_get_core_keyboard_device_id(*mut c_void) -> i32
+	fn xkb_x11_get_core_keyboard_device_id(*mut c_void) -> i32,
+	fn xkb_state_key_get_one_sym(*mut c_void, u32) -> u32,
+	fn xkb_compose_table_new_from_locale(*mut c_void, *const i8, i32)
+		-> *mut c_void,
+	fn xkb_compose_table_unref(*mut c_void) -> (),
+	fn xkb_compose_state_new(*mut c_void, i32) -> *mut c_void,
+	fn xkb_compose_state_unref(*mut c_void) -> (),
+	fn xkb_compose_state_feed(*mut c_void, u32) -> i32,
+	fn xkb_compose_state_reset(*mut c_void) -> (),
+	fn xkb_compose_state_get_status(*mut c_void) -> i32,
+	fn xkb_compose_state_get_utf8(*mut c_void, *mut u8, usize) -> i32
 );
 
 #[allow(dead_code)]
@@ -114,6 +1125,53 @@ enum KeyDirection {
 	Down,
 }
 
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbQueryPointerReply {
+	response_type: u8,
+	same_screen: u8,
+	sequence: u16,
+	length: u32,
+	root: u32,
+	child: u32,
+	root_x: i16,
+	root_y: i16,
+	win_x: i16,
+	win_y: i16,
+	mask: u16,
+	pad0: [u8; 2],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbGrabKeyboardReply {
+	response_type: u8,
+	status: u8,
+	sequence: u16,
+	length: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbGetInputFocusReply {
+	response_type: u8,
+	revert_to: u8,
+	sequence: u16,
+	length: u32,
+	focus: u32,
+}
+
+#[repr(C)]
+struct XcbTranslateCoordinatesReply {
+	response_type: u8,
+	same_screen: u8,
+	sequence: u16,
+	length: u32,
+	child: u32,
+	dst_x: i16,
+	dst_y: i16,
+}
+
 #[repr(C)]
 struct XcbInternAtomReply {
 	response_type: u8,
@@ -149,6 +1207,212 @@ struct XcbScreen {
 	allowed_depths_len: u8,
 }
 
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbQueryExtensionReply {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	length: u32,
+	present: u8,
+	major_opcode: u8,
+	first_event: u8,
+	first_error: u8,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbInputXiQueryVersionReply {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	length: u32,
+	major_version: u16,
+	minor_version: u16,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbRandrQueryVersionReply {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	length: u32,
+	major_version: u32,
+	minor_version: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbRandrMonitorInfo {
+	name: u32,
+	primary: u8,
+	automatic: u8,
+	n_output: u16,
+	x: i16,
+	y: i16,
+	width: u16,
+	height: u16,
+	width_in_millimeters: u32,
+	height_in_millimeters: u32,
+}
+
+#[repr(C)]
+struct XcbRandrMonitorInfoIterator {
+	data: *mut XcbRandrMonitorInfo,
+	rem: i32,
+	index: i32,
+}
+
+#[repr(C)]
+struct XcbInputEventMask {
+	deviceid: u16,
+	mask_len: u16,
+	mask: u32,
+}
+
+// xcb_input_raw_motion_event_t (without the trailing lists).
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbInputRawEvent {
+	response_type: u8,
+	extension: u8,
+	sequence: u16,
+	length: u32,
+	event_type: u16,
+	deviceid: u16,
+	time: u32,
+	detail: u32,
+	sourceid: u16,
+	valuators_len: u16,
+	flags: u32,
+	pad0: [u8; 4],
+	full_sequence: u32,
+}
+
+// xcb_input_touch_begin/update/end_event_t (without the trailing lists), also
+// the layout of xcb_input_button_press/button_release/motion_event_t.
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbInputTouchEvent {
+	response_type: u8,
+	extension: u8,
+	sequence: u16,
+	length: u32,
+	event_type: u16,
+	deviceid: u16,
+	time: u32,
+	detail: u32, // Touch ID, or button
+	root: u32,
+	event: u32,
+	child: u32,
+	full_sequence: u32,
+	root_x: i32, // 16.16 fixed point
+	root_y: i32,
+	event_x: i32,
+	event_y: i32,
+	buttons_len: u16,
+	valuators_len: u16,
+	sourceid: u16,
+	pad0: [u8; 2],
+	flags: u32,
+	mods: [u32; 4],
+	group: [u8; 4],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbInputXiDeviceInfo {
+	deviceid: u16,
+	kind: u16,
+	attachment: u16,
+	num_classes: u16,
+	name_len: u16,
+	enabled: u8,
+	pad0: u8,
+}
+
+#[repr(C)]
+struct XcbInputXiDeviceInfoIterator {
+	data: *mut XcbInputXiDeviceInfo,
+	rem: i32,
+	index: i32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbInputDeviceClass {
+	kind: u16,
+	len: u16,
+	sourceid: u16,
+	pad0: [u8; 2],
+}
+
+#[repr(C)]
+struct XcbInputDeviceClassIterator {
+	data: *mut XcbInputDeviceClass,
+	rem: i32,
+	index: i32,
+}
+
+// 32.32 fixed point.
+#[repr(C)]
+struct XcbInputFp3232 {
+	integral: i32,
+	frac: u32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbInputValuatorClass {
+	kind: u16,
+	len: u16,
+	sourceid: u16,
+	number: u16,
+	label: u32,
+	min: XcbInputFp3232,
+	max: XcbInputFp3232,
+	value: XcbInputFp3232,
+	resolution: u32,
+	mode: u8,
+	pad0: [u8; 3],
+}
+
+// A valuator (axis) of an XInput2 device, with it's range.
+struct XiValuator {
+	number: u16,
+	min: f64,
+	max: f64,
+}
+
+impl XiValuator {
+	// Get the value from 0 to 1 in `event`, `None` if it's not in it.
+	unsafe fn get(&self, event: *const XcbInputTouchEvent) -> Option<f32> {
+		let value = match xi_valuator(event, self.number) {
+			Some(value) => value,
+			None => return None,
+		};
+
+		Some(if self.max > self.min {
+			((value - self.min) / (self.max - self.min))
+				.max(0.0).min(1.0) as f32
+		} else {
+			value as f32
+		})
+	}
+}
+
+// The valuators of an XInput2 device that events have more details from.
+struct XiAxes {
+	device: u16,
+	pressure: Option<XiValuator>,
+	size: Option<XiValuator>, // Touch major axis.
+	tilt: (Option<XiValuator>, Option<XiValuator>),
+	// Pen tablet stylus (and if it's the eraser end).
+	pen: bool,
+	eraser: bool,
+}
+
 #[repr(C)]
 struct XcbScreenIterator {
 	data: *mut XcbScreen,
@@ -156,6 +1420,122 @@ struct XcbScreenIterator {
 	index: i32,
 }
 
+#[repr(C)]
+struct XcbDepthIterator {
+	data: *mut XcbDepth,
+	rem: i32,
+	index: i32,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbDepth {
+	depth: u8,
+	pad0: u8,
+	visuals_len: u16,
+	pad1: [u8; 4],
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbVisualtype {
+	visual_id: u32,
+	class: u8,
+	bits_per_rgb_value: u8,
+	colormap_entries: u16,
+	red_mask: u32,
+	green_mask: u32,
+	blue_mask: u32,
+	pad0: [u8; 4],
+}
+
+#[repr(C)]
+struct XcbRectangle {
+	x: i16,
+	y: i16,
+	width: u16,
+	height: u16,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbSelectionRequestEvent {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	time: u32,
+	owner: u32,
+	requestor: u32,
+	selection: u32,
+	target: u32,
+	property: u32,
+}
+
+// 32 bytes, for `xcb_send_event()`.
+#[repr(C)]
+struct XcbClientMessageEvent {
+	response_type: u8,
+	format: u8,
+	sequence: u16,
+	window: u32,
+	kind: u32,
+	data: [u32; 5],
+}
+
+// Padded to 32 bytes, since `xcb_send_event()` always sends that much.
+#[allow(dead_code)]
+#[repr(C)]
+struct XcbSelectionNotifyEvent {
+	response_type: u8,
+	pad0: u8,
+	sequence: u16,
+	time: u32,
+	requestor: u32,
+	selection: u32,
+	target: u32,
+	property: u32,
+	pad1: [u32; 2],
+}
+
+/// Atoms, and what we own for the CLIPBOARD selection.
+struct Clipboard {
+	// `None` if another client owns the clipboard.
+	text: Option<String>,
+	clipboard: u32,
+	utf8_string: u32,
+	targets: u32,
+	// Where the owner puts the text for us.
+	property: u32,
+}
+
+impl Clipboard {
+	fn new(connection: *mut c_void, xcb: &Xcb) -> Self {
+		Clipboard {
+			text: None,
+			clipboard: get_atom(connection, xcb, b"CLIPBOARD"),
+			utf8_string: get_atom(connection, xcb, b"UTF8_STRING"),
+			targets: get_atom(connection, xcb, b"TARGETS"),
+			property: get_atom(connection, xcb, b"AWI_CLIPBOARD"),
+		}
+	}
+}
+
+const ATOM_ATOM: u32 = 4;
+const ATOM_STRING: u32 = 31;
+const ATOM_RESOURCE_MANAGER: u32 = 23;
+const PROPERTY_NOTIFY: u8 = 28;
+const SELECTION_CLEAR: u8 = 29;
+const SELECTION_REQUEST: u8 = 30;
+const SELECTION_NOTIFY: u8 = 31;
+const GE_GENERIC: u8 = 35;
+const XI_BUTTON_PRESS: u8 = 4;
+const XI_BUTTON_RELEASE: u8 = 5;
+const XI_MOTION: u8 = 6;
+const XI_RAW_MOTION: u8 = 17;
+const XI_TOUCH_BEGIN: u8 = 18;
+const XI_TOUCH_UPDATE: u8 = 19;
+const XI_TOUCH_END: u8 = 20;
+
 #[repr(C)] #[derive(Clone)]
 struct XcbGenericEvent {
 	response_type: u8,
@@ -201,37 +1581,119 @@ fn xcb_screen(connection: *mut c_void, xcb: &Xcb) -> XcbScreen {
 }
 
 fn xcb_window(connection: *mut c_void, xcb: &Xcb, screen: &mut XcbScreen,
-	v: Option<i32>) -> u32
+	v: Option<i32>, hints: &::WindowHints) -> u32
 {
+	const CARDINAL: u32 = 6;
+	const WM_NAME: u32 = 39;
+
+
 	let atom1 = get_atom(connection, xcb, b"_MOTIF_WM_HINTS");
 	let atom2 = get_atom(connection, xcb, b"_NET_WM_STATE");
 	let atom3 = get_atom(connection, xcb, b"_NET_WM_STATE_MAXIMIZED_VERT");
 	let atom4 = get_atom(connection, xcb, b"_NET_WM_STATE_MAXIMIZED_HORZ");
 	let atom5 = get_atom(connection, xcb, b"WM_PROTOCOLS");
 	let atom6 = get_atom(connection, xcb, b"WM_DELETE_WINDOW");
+	let net_wm_name = get_atom(connection, xcb, b"_NET_WM_NAME");
+	let net_wm_icon = get_atom(connection, xcb, b"_NET_WM_ICON");
+	let utf8_string = get_atom(connection, xcb, b"UTF8_STRING");
 	let window = unsafe { (xcb.xcb_generate_id)(connection) };
-	let mut value_list = [ 0b01000100000000001101111 ];
+	let (w, h) = hints.wh.unwrap_or((screen.width_in_pixels,
+		screen.height_in_pixels));
+	let event_mask = 0b01000101000000001101111;
+	let mut depth = 0; // Copy from parent
 	if let Some(v) = v {
 		screen.root_visual = unsafe { ::std::mem::transmute(v) };
+		depth = xcb_visual_depth(connection, xcb, screen.root_visual);
 	}
 	unsafe {
-		(xcb.xcb_create_window)(
-			connection, 0, window, screen.root, 0, 0,
-			screen.width_in_pixels, screen.height_in_pixels, 0, 1,
-			screen.root_visual, 2048, &mut value_list[0]
-		);
+		if depth == 0 || depth == screen.root_depth {
+			let mut value_list = [ event_mask ];
+
+			(xcb.xcb_create_window)(
+				connection, 0, window, screen.root, 0, 0,
+				w, h, 0, 1, screen.root_visual, 2048,
+				&mut value_list[0]
+			);
+		} else {
+			// A visual with alpha (depth 32) needs its own colormap
+			// and border pixel, since they can't come from the root.
+			const BORDER_PIXEL: u32 = 8;
+			const EVENT_MASK: u32 = 2048;
+			const COLORMAP: u32 = 8192;
+
+			let colormap = (xcb.xcb_generate_id)(connection);
+			(xcb.xcb_create_colormap)(connection, 0, colormap,
+				screen.root, screen.root_visual);
+
+			let mut value_list = [ 0, event_mask, colormap ];
+
+			(xcb.xcb_create_window)(
+				connection, depth, window, screen.root, 0, 0,
+				w, h, 0, 1, screen.root_visual,
+				BORDER_PIXEL | EVENT_MASK | COLORMAP,
+				&mut value_list[0]
+			);
+		}
 		(xcb.xcb_change_property)(connection, 0, window, atom1,
-			atom1, 32, 5, &[2u32, 0, 0, 0, 0] as *const _ as *const c_void);
-		(xcb.xcb_change_property)(connection, 0, window, atom2,
-			4, 32, 2, [atom3, atom4].as_ptr() as *const _ as *const c_void);
+			atom1, 32, 5, &[2u32, 0, hints.decorated as u32, 0, 0]
+			as *const _ as *const c_void);
+		// Maximized, unless there's a size.
+		if hints.wh.is_none() {
+			(xcb.xcb_change_property)(connection, 0, window, atom2,
+				4, 32, 2, [atom3, atom4].as_ptr() as *const _
+				as *const c_void);
+		}
 		(xcb.xcb_change_property)(connection, 0, window, atom5,
 			4, 32, 1, [atom6].as_ptr() as *const _ as *const c_void);
+		if !hints.title.is_empty() {
+			let title = hints.title.as_bytes();
+
+			(xcb.xcb_change_property)(connection, 0, window,
+				WM_NAME, ATOM_STRING, 8, title.len() as u32,
+				title.as_ptr() as *const c_void);
+			(xcb.xcb_change_property)(connection, 0, window,
+				net_wm_name, utf8_string, 8, title.len() as u32,
+				title.as_ptr() as *const c_void);
+		}
+		if let Some((w, h, ref pixels)) = hints.icon {
+			// Width, height, then the ARGB pixels.
+			let mut icon = vec![w as u32, h as u32];
+			icon.extend_from_slice(pixels);
+
+			(xcb.xcb_change_property)(connection, 0, window,
+				net_wm_icon, CARDINAL, 32, icon.len() as u32,
+				icon.as_ptr() as *const c_void);
+		}
 		(xcb.xcb_map_window)(connection, window);
 		(xcb.xcb_flush)(connection);
 	}
 	window
 }
 
+// Find the depth of a visual on the default screen, 0 if not found.
+fn xcb_visual_depth(connection: *mut c_void, xcb: &Xcb, visual: u32) -> u8 {
+	unsafe {
+		let setup = (xcb.xcb_get_setup)(connection);
+		let screen = (xcb.xcb_setup_roots_iterator)(setup).data;
+		let mut depths = (xcb.xcb_screen_allowed_depths_iterator)(screen);
+
+		while depths.rem > 0 {
+			let visuals = ::std::slice::from_raw_parts(
+				(xcb.xcb_depth_visuals)(depths.data),
+				(xcb.xcb_depth_visuals_length)(depths.data)
+					as usize);
+
+			if visuals.iter().any(|v| v.visual_id == visual) {
+				return (*depths.data).depth;
+			}
+
+			(xcb.xcb_depth_next)(&mut depths);
+		}
+	}
+
+	0
+}
+
 fn get_atom(connection: *mut c_void, xcb: &Xcb, name: &[u8]) -> u32 {
 	let atom = unsafe {
 		(xcb.xcb_intern_atom)(
@@ -277,24 +1739,411 @@ fn xkb_keyboard(connection: *mut c_void, xkb: &XkbCommonX11)
 	(state, keymap, context)
 }
 
-fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
-	xkb: &XkbCommonX11, state: *mut c_void, queue: &mut ::input::InputQueue,
-	wh: &mut (u16, u16), keyboard: &mut ::Keyboard) -> bool
+// Load the compose table for the user's locale (for dead keys).  Returns null
+// pointers if there isn't one.
+fn xkb_compose(xkb: &XkbCommonX11, context: *mut c_void)
+	-> (*mut c_void, *mut c_void)
 {
-	use std::string::String;
+	use std::env::var;
+
+	let locale = var("LC_ALL").ok().filter(|l| !l.is_empty())
+		.or_else(|| var("LC_CTYPE").ok().filter(|l| !l.is_empty()))
+		.or_else(|| var("LANG").ok().filter(|l| !l.is_empty()))
+		.unwrap_or_else(|| "C".to_string());
+	let locale = match ::std::ffi::CString::new(locale) {
+		Ok(locale) => locale,
+		Err(_) => return (null_mut(), null_mut()),
+	};
+
+	unsafe {
+		let table = (xkb.xkb_compose_table_new_from_locale)(context,
+			locale.as_ptr(), 0);
+		if table.is_null() {
+			return (null_mut(), null_mut());
+		}
+		let compose = (xkb.xkb_compose_state_new)(table, 0);
+		if compose.is_null() {
+			(xkb.xkb_compose_table_unref)(table);
+			return (null_mut(), null_mut());
+		}
+		(table, compose)
+	}
+}
+
+// Load the XShape extension, `None` if the library or the server doesn't
+// have it.
+fn xcb_shape(connection: *mut c_void, xcb: &Xcb) -> Option<XcbShape> {
+	extern { fn free(this: *mut c_void) -> (); }
+
+	let name = b"SHAPE";
+	let present = unsafe {
+		let reply = (xcb.xcb_query_extension_reply)(connection,
+			(xcb.xcb_query_extension)(connection,
+				name.len() as u16, name.as_ptr()),
+			null_mut());
+		if reply.is_null() {
+			return None;
+		}
+		let present = (*reply).present != 0;
+		free(reply as *mut c_void);
+		present
+	};
+
+	if present {
+		unsafe { XcbShape::new() }.ok()
+	} else {
+		None
+	}
+}
+
+// Select XInput2 touch events (real multi-touch, instead of emulated pointer
+// events) and raw motion events (only sent to the root window).  Returns the
+// XInput major opcode, 0 if it's not available.
+fn xcb_touch(connection: *mut c_void, xcb: &Xcb, window: u32, root: u32)
+	-> u8
+{
+	extern { fn free(this: *mut c_void) -> (); }
+
+	let name = b"XInputExtension";
+	let opcode = unsafe {
+		let reply = (xcb.xcb_query_extension_reply)(connection,
+			(xcb.xcb_query_extension)(connection,
+				name.len() as u16, name.as_ptr()),
+			null_mut());
+		if reply.is_null() {
+			return 0;
+		}
+		let opcode = if (*reply).present != 0 {
+			(*reply).major_opcode
+		} else {
+			0
+		};
+		free(reply as *mut c_void);
+		opcode
+	};
+	if opcode == 0 {
+		return 0;
+	}
+
+	let xi = match unsafe { XcbInput::new() } {
+		Ok(xi) => xi,
+		Err(_) => return 0,
+	};
+
+	unsafe {
+		// Touch events are new in XInput 2.2.
+		let reply = (xi.xcb_input_xi_query_version_reply)(connection,
+			(xi.xcb_input_xi_query_version)(connection, 2, 2),
+			null_mut());
+		if reply.is_null() {
+			return 0;
+		}
+		let version = ((*reply).major_version, (*reply).minor_version);
+		free(reply as *mut c_void);
+		if version < (2, 2) {
+			return 0;
+		}
+
+		const ALL_MASTER_DEVICES: u16 = 1;
+
+		(xi.xcb_input_xi_select_events)(connection, window, 1,
+			&XcbInputEventMask {
+				deviceid: ALL_MASTER_DEVICES,
+				mask_len: 1,
+				mask: 1 << XI_TOUCH_BEGIN | 1 << XI_TOUCH_UPDATE
+					| 1 << XI_TOUCH_END,
+			});
+		(xi.xcb_input_xi_select_events)(connection, root, 1,
+			&XcbInputEventMask {
+				deviceid: ALL_MASTER_DEVICES,
+				mask_len: 1,
+				mask: 1 << XI_RAW_MOTION,
+			});
+		(xcb.xcb_flush)(connection);
+	}
+
+	// Keep it loaded, libxcb caches a pointer to it's extension data.
+	::std::mem::forget(xi);
+
+	opcode
+}
+
+// Find the pressure, contact size & tilt valuators of each XInput2 device, and
+// select events from pen tablets (`XiAxes.pen`) on `window`.
+fn xi_axes(connection: *mut c_void, xcb: &Xcb, window: u32) -> Vec<XiAxes> {
+	extern { fn free(this: *mut c_void) -> (); }
+
+	const ALL_DEVICES: u16 = 0;
+	const SLAVE_POINTER: u16 = 3;
+	const VALUATOR_CLASS: u16 = 2;
+
+	let mut devices = vec![];
+	let xi = match unsafe { XcbInput::new() } {
+		Ok(xi) => xi,
+		Err(_) => return devices,
+	};
+	let pressure = (get_atom(connection, xcb, b"Abs MT Pressure"),
+		get_atom(connection, xcb, b"Abs Pressure"));
+	let size = get_atom(connection, xcb, b"Abs MT Touch Major");
+	let tilt = (get_atom(connection, xcb, b"Abs Tilt X"),
+		get_atom(connection, xcb, b"Abs Tilt Y"));
+	let fp3232 = |v: &XcbInputFp3232| {
+		v.integral as f64 + v.frac as f64 / 4294967296.0
+	};
+
+	unsafe {
+		let reply = (xi.xcb_input_xi_query_device_reply)(connection,
+			(xi.xcb_input_xi_query_device)(connection, ALL_DEVICES),
+			null_mut());
+		if reply.is_null() {
+			return devices;
+		}
+
+		let mut infos =
+			(xi.xcb_input_xi_query_device_infos_iterator)(reply);
+		while infos.rem > 0 {
+			let mut axes = XiAxes {
+				device: (*infos.data).deviceid,
+				pressure: None,
+				size: None,
+				tilt: (None, None),
+				pen: false,
+				eraser: false,
+			};
+			let mut classes = (xi
+				.xcb_input_xi_device_info_classes_iterator)(
+				infos.data);
+			while classes.rem > 0 {
+				if (*classes.data).kind == VALUATOR_CLASS {
+					let class = &*(classes.data
+						as *const XcbInputValuatorClass);
+					let valuator = XiValuator {
+						number: class.number,
+						min: fp3232(&class.min),
+						max: fp3232(&class.max),
+					};
+
+					if class.label == pressure.0
+						|| class.label == pressure.1
+					{
+						axes.pressure = Some(valuator);
+					} else if class.label == size {
+						axes.size = Some(valuator);
+					} else if class.label == tilt.0 {
+						axes.tilt.0 = Some(valuator);
+					} else if class.label == tilt.1 {
+						axes.tilt.1 = Some(valuator);
+					}
+				}
+				(xi.xcb_input_device_class_next)(&mut classes);
+			}
+
+			// Tablet drivers name the stylus & eraser devices.
+			let name = ::std::slice::from_raw_parts(
+				(xi.xcb_input_xi_device_info_name)(infos.data),
+				(*infos.data).name_len as usize);
+			let name = String::from_utf8_lossy(name).to_lowercase();
+			axes.eraser = name.contains("eraser");
+			axes.pen = (*infos.data).kind == SLAVE_POINTER
+				&& (axes.tilt.0.is_some() || axes.eraser
+					|| name.contains("stylus")
+					|| name.contains("pen"));
+
+			if axes.pen {
+				(xi.xcb_input_xi_select_events)(connection,
+					window, 1, &XcbInputEventMask {
+						deviceid: axes.device,
+						mask_len: 1,
+						mask: 1 << XI_BUTTON_PRESS
+							| 1 << XI_BUTTON_RELEASE
+							| 1 << XI_MOTION,
+					});
+			}
+
+			devices.push(axes);
+			(xi.xcb_input_xi_device_info_next)(&mut infos);
+		}
+		free(reply);
+		(xcb.xcb_flush)(connection);
+	}
+
+	devices
+}
+
+// Get valuator `number` of an XInput2 device event (32.32 fixed point), `None`
+// if it's not in the event.
+unsafe fn xi_valuator(event: *const XcbInputTouchEvent, number: u16)
+	-> Option<f64>
+{
+	let valuators_len = (*event).valuators_len as usize;
+	let number = number as usize;
+	// The button mask follows the event, then the valuator mask, then the
+	// values (one per set bit).
+	let mask = ((event as *const u8).offset(::std::mem::size_of::<
+		XcbInputTouchEvent>() as isize) as *const u32)
+		.offset((*event).buttons_len as isize);
+	let mask = ::std::slice::from_raw_parts(mask, valuators_len);
+	let bit = |axis: usize| mask[axis / 32] & (1 << (axis % 32)) != 0;
+
+	if number >= valuators_len * 32 || !bit(number) {
+		return None;
+	}
+
+	let index = (0..number).filter(|axis| bit(*axis)).count() as isize;
+	let values = mask.as_ptr().offset(valuators_len as isize)
+		as *const i32;
+
+	Some(*values.offset(index * 2) as f64
+		+ *(values.offset(index * 2 + 1) as *const u32) as f64
+		/ 4294967296.0)
+}
+
+// Get the unaccelerated X & Y deltas from a raw motion event.
+unsafe fn xi_raw_motion(event: *const XcbInputRawEvent) -> (f32, f32) {
+	let valuators_len = (*event).valuators_len as usize;
+	// The valuator mask follows the event, then the accelerated values,
+	// then the raw values (both 32.32 fixed point, one per set bit).
+	let mask = (event as *const u8).offset(36) as *const u32;
+	let mask = ::std::slice::from_raw_parts(mask, valuators_len);
+	let count: u32 = mask.iter().map(|bits| bits.count_ones()).sum();
+	let raw = (mask.as_ptr().offset(valuators_len as isize) as *const i32)
+		.offset(count as isize * 2);
+
+	let mut delta = [0.0f32; 2];
+	let mut n = 0;
+	for axis in 0..(valuators_len * 32).min(2) {
+		if mask[axis / 32] & (1 << (axis % 32)) != 0 {
+			delta[axis] = *raw.offset(n * 2) as f32
+				+ *(raw.offset(n * 2 + 1) as *const u32) as f32
+				/ 4294967296.0;
+			n += 1;
+		}
+	}
+	(delta[0], delta[1])
+}
+
+// Block until `fd` can be read from, or `timeout` (`None` for forever).
+// Returns `false` if it timed out.
+fn wait_fd(fd: i32, timeout: Option<::std::time::Duration>) -> bool {
+	let timeout = match timeout {
+		// Round up, so it doesn't wake up just before.
+		Some(t) => (t.as_secs().saturating_mul(1000)
+			+ (t.subsec_nanos() as u64 + 999_999) / 1_000_000)
+			.min(::std::i32::MAX as u64) as i32,
+		None => -1,
+	};
+	let mut fd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+
+	unsafe { libc::poll(&mut fd, 1, timeout) > 0 }
+}
 
+// Get the next event, only from the ones already read if `queued`.
+fn xcb_next_event(connection: *mut c_void, xcb: &Xcb, xi_opcode: u8,
+	xi_axes: &[XiAxes], queued: bool) -> Option<XcbGenericEvent>
+{
 	extern { fn free(event: *mut XcbGenericEvent) -> (); }
 
-	let event = unsafe { (xcb.xcb_poll_for_event)(connection) };
-	let event = if event.is_null() {
-		return false;
+	let event = unsafe {
+		if queued {
+			(xcb.xcb_poll_for_queued_event)(connection)
+		} else {
+			(xcb.xcb_poll_for_event)(connection)
+		}
+	};
+	if event.is_null() {
+		None
 	} else {
 		unsafe {
-			let r_event = (*event).clone();
+			let mut r_event = (*event).clone();
+
+			let xi = r_event.response_type & 0x7f == GE_GENERIC
+				&& xi_opcode != 0 && r_event.detail == xi_opcode;
+
+			// XInput2 raw motion events have a list of changed axes
+			// (repack, detail = event type, root & event = delta
+			// XY as `f32` bits).
+			if xi && (*(event as *const XcbInputRawEvent))
+				.event_type == XI_RAW_MOTION as u16
+			{
+				let (dx, dy) = xi_raw_motion(event
+					as *const XcbInputRawEvent);
+
+				r_event.response_type = GE_GENERIC;
+				r_event.detail = XI_RAW_MOTION;
+				r_event.root = dx.to_bits();
+				r_event.event = dy.to_bits();
+			}
+			// XInput2 touch & pen events are longer than 32 bytes,
+			// so repack them (detail = event type, timestamp =
+			// touch ID or time, event XY, root = pressure, event =
+			// contact size or tilt X, child = tilt Y as `f32`
+			// bits, NaN if not in the event, same_screen = eraser).
+			else if xi {
+				let touch = &*(event as *const _
+					as *const XcbInputTouchEvent);
+				let axes = xi_axes.iter()
+					.find(|a| a.device == touch.sourceid);
+				let get = |v: Option<&XiValuator>| {
+					v.and_then(|v| v.get(touch))
+						.unwrap_or(::std::f32::NAN)
+				};
+				let pressure = get(axes.and_then(|a| a.pressure
+					.as_ref()));
+				let pen = touch.event_type >= XI_BUTTON_PRESS
+					as u16 && touch.event_type <= XI_MOTION
+					as u16;
+
+				r_event.response_type = GE_GENERIC;
+				r_event.detail = touch.event_type as u8;
+				r_event.event_x = (touch.event_x >> 16) as i16;
+				r_event.event_y = (touch.event_y >> 16) as i16;
+				r_event.root = pressure.to_bits();
+				if pen {
+					// Tilt from -1 to 1.
+					let tilt_x = get(axes.and_then(|a| a
+						.tilt.0.as_ref())) * 2.0 - 1.0;
+					let tilt_y = get(axes.and_then(|a| a
+						.tilt.1.as_ref())) * 2.0 - 1.0;
+
+					r_event.timestamp = touch.time;
+					r_event.event = tilt_x.to_bits();
+					r_event.child = tilt_y.to_bits();
+					r_event.same_screen = axes.map_or(false,
+						|a| a.eraser) as u8;
+				} else {
+					let size = get(axes.and_then(|a| a.size
+						.as_ref()));
+
+					r_event.timestamp = touch.detail;
+					r_event.event = size.to_bits();
+				}
+			}
+
 			free(event);
-			r_event
+			Some(r_event)
 		}
-	};
+	}
+}
+
+// Get the pressure & contact size of a repacked touch event.
+fn touch_detail(event: &XcbGenericEvent) -> (Option<f32>, Option<f32>) {
+	(touch_value(event.root), touch_value(event.event))
+}
+
+// Get a value repacked as `f32` bits, `None` if it's NaN (not in the event).
+fn touch_value(bits: u32) -> Option<f32> {
+	let value = f32::from_bits(bits);
+	if value.is_nan() { None } else { Some(value) }
+}
+
+fn xcb_handle_event(connection: *mut c_void, xcb: &Xcb,
+	xkb: &XkbCommonX11, state: *mut c_void, compose: *mut c_void,
+	queue: &mut ::input::InputQueue, wh: &mut (u16, u16),
+	xy: &mut (i16, i16), scale: &mut f32, focus: &mut bool,
+	keyboard: &mut ::Keyboard, clipboard: &mut Clipboard,
+	event: XcbGenericEvent)
+{
+	use std::string::String;
 
 	let response_type = event.response_type;
 	let detail = event.detail;
@@ -303,18 +2152,19 @@ fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
 
 	let string = match response_type {
 		2 => {
-			Some(match detail {
+			let string = match detail {
 				// Enter: Keyboard & NumPad
 				36 | 104 => String::from("\n"),
-				// Left & Right Shift, Alt Gr & NumLock & Esc
-				50 | 62 | 108 | 77 | 9 => {
-					xkb_state_update_key(xkb, state, detail,
-						true);
-					String::from("")
-				},
-				// Everything else
-				_ => xkb_state_key_get_utf8(xkb,state,detail)
-			})
+				// Esc
+				9 => String::from(""),
+				// Everything else, through the keymap (so Shift,
+				// AltGr & dead keys apply).
+				_ => xkb_text(xkb, state, compose, detail)
+			};
+			// Update after translating, so modifiers apply to the
+			// next key and not themselves.
+			xkb_state_update_key(xkb, state, detail, true);
+			Some(string)
 		},
 		3 => {
 			xkb_state_update_key(xkb, state, detail, false);
@@ -331,27 +2181,44 @@ fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
 	const CURSOR_LEAVE: u8 = 8;
 	const GAIN_FOCUS: u8 = 9;
 	const LOSE_FOCUS: u8 = 10;
+	const EXPOSE: u8 = 12;
+	const UNMAP: u8 = 18;
+	const MAP: u8 = 19;
 	const WINDOW_RESIZE: u8 = 22;
-	const WINDOW_SELECT: u8 = 31;
+	// ConfigureNotify sent by the window manager, in root coordinates.
+	const WINDOW_MOVED: u8 = 128 | 22;
 	const WINDOW_CLOSE: u8 = 128 | 33;
 
 	match response_type {
-		KEY_DOWN => if let Some(key) = key(detail) {
-			keyboard.press(key);
-		} else if detail == 9 {
-			queue.exit();
-		},
-		KEY_UP => if let Some(key) = key(detail) {
-			keyboard.release(key);
-		},
+		KEY_DOWN => {
+			keyboard.press_raw(detail as u32);
+			if let Some(key) = key(detail) {
+				keyboard.press(key);
+			} else {
+				keyboard.unknown(detail as u32);
+				if detail == 9 {
+					queue.exit();
+				}
+			}
+		}
+		KEY_UP => {
+			keyboard.release_raw(detail as u32);
+			if let Some(key) = key(detail) {
+				keyboard.release(key);
+			}
+		}
 		BUTTON_DOWN => match detail {
 			1 => queue.left_button_press(*wh, event_xy),
 			2 => queue.middle_button_press(*wh, event_xy),
 			3 => queue.right_button_press(*wh, event_xy),
-			4 => queue.scroll(*wh, event_xy, (0.0, -1.0)),
-			5 => queue.scroll(*wh, event_xy, (0.0, 1.0)),
-			6 => queue.scroll(*wh, event_xy, (-1.0, 0.0)),
-			7 => queue.scroll(*wh, event_xy, (1.0, 0.0)),
+			4 => queue.scroll(*wh, event_xy, (0.0, -1.0),
+				ScrollKind::Line),
+			5 => queue.scroll(*wh, event_xy, (0.0, 1.0),
+				ScrollKind::Line),
+			6 => queue.scroll(*wh, event_xy, (-1.0, 0.0),
+				ScrollKind::Line),
+			7 => queue.scroll(*wh, event_xy, (1.0, 0.0),
+				ScrollKind::Line),
 			uc => panic!("awi: Unknown Click {}!", uc)
 		},
 		BUTTON_UP => match detail {
@@ -362,19 +2229,298 @@ fn xcb_poll_for_event(connection: *mut c_void, xcb: &Xcb,
 		},
 		CURSOR_MOVE => queue.cursor_move(*wh, event_xy),
 		CURSOR_LEAVE => queue.cursor_leave(),
-		GAIN_FOCUS => queue.resume(),
-		LOSE_FOCUS => queue.pause(),
-		WINDOW_RESIZE => queue.resize(wh, root_xy),
-		WINDOW_SELECT => println!("!SELECT!"),
+		GAIN_FOCUS => {
+			*focus = true;
+			queue.resume()
+		}
+		LOSE_FOCUS => {
+			*focus = false;
+			queue.pause()
+		}
+		// Minimized & restored.
+		UNMAP => queue.pause(),
+		MAP => queue.resume(),
+		// Mapped & drawable (after MapNotify), or uncovered.
+		EXPOSE => {
+			// Expose has x & y, then w & h where `root` & `event`
+			// are.
+			queue.damage((event.root as u16,
+				(event.root >> 16) as u16, event.event as u16,
+				(event.event >> 16) as u16));
+			queue.ready()
+		}
+		// Only ask the server where the window is when it's resized
+		// (maybe from the top-left), the window manager sends
+		// `WINDOW_MOVED` when it's only moved.
+		WINDOW_RESIZE => if *wh != root_xy {
+			queue.resize(wh, root_xy);
+			// `event.root` is the window for ConfigureNotify.
+			queue.moved(xy, xcb_position(connection, xcb,
+				event.root));
+		},
+		// `event.child` is the x & y for ConfigureNotify.
+		WINDOW_MOVED => {
+			queue.resize(wh, root_xy);
+			queue.moved(xy, (event.child as i16,
+				(event.child >> 16) as i16));
+		}
+		// `event.timestamp` is the window & `event.root` the atom for
+		// PropertyNotify.  Only the root window's is selected, so this
+		// is `Xft.dpi` changing.
+		PROPERTY_NOTIFY if event.root == ATOM_RESOURCE_MANAGER => {
+			queue.scale(scale, xcb_scale(connection, xcb));
+		}
+		SELECTION_CLEAR => clipboard.text = None,
+		SELECTION_REQUEST => clipboard_send(connection, xcb, clipboard,
+			&event),
+		// A reply that came after `clipboard_get()` gave up.
+		SELECTION_NOTIFY => {},
+		// Repacked XInput2 touch & pen events (see `xcb_next_event()`).
+		GE_GENERIC => match detail {
+			XI_RAW_MOTION => queue.motion_raw((
+				f32::from_bits(event.root),
+				f32::from_bits(event.event))),
+			XI_TOUCH_BEGIN => queue.touch_press(*wh,
+				event.timestamp as u64, event_xy,
+				touch_detail(&event)),
+			XI_TOUCH_UPDATE => queue.touch_move(*wh,
+				event.timestamp as u64, event_xy,
+				touch_detail(&event)),
+			XI_TOUCH_END => queue.touch_release(*wh,
+				event.timestamp as u64),
+			XI_BUTTON_PRESS..=XI_MOTION => {
+				let tilt = (touch_value(event.event),
+					touch_value(event.child));
+
+				queue.pen(*wh, event_xy,
+					touch_value(event.root), tilt,
+					event.same_screen != 0);
+			}
+			_ => {},
+		},
 		WINDOW_CLOSE => { queue.exit() }
 		a => { println!("a {}", a); } // ignore all other messages
 	}
 
+	// TODO: XIM (xcb-imdkit), for `Event::Preedit`.
 	if let Some(string) = string {
 		queue.text(string);
 	}
+}
+
+// Answer another client asking for our clipboard.
+fn clipboard_send(connection: *mut c_void, xcb: &Xcb, clipboard: &Clipboard,
+	event: &XcbGenericEvent)
+{
+	let request = unsafe {
+		&*(event as *const _ as *const XcbSelectionRequestEvent)
+	};
+	// Obsolete clients don't set a property, use the target instead.
+	let property = if request.property == 0 {
+		request.target
+	} else {
+		request.property
+	};
+
+	let sent = match clipboard.text {
+		Some(_) if request.target == clipboard.targets => {
+			let targets = [clipboard.utf8_string, ATOM_STRING];
+			unsafe {
+				(xcb.xcb_change_property)(connection, 0,
+					request.requestor, property, ATOM_ATOM,
+					32, targets.len() as u32,
+					targets.as_ptr() as *const c_void);
+			}
+			true
+		}
+		Some(ref text) if request.target == clipboard.utf8_string
+			|| request.target == ATOM_STRING =>
+		{
+			unsafe {
+				(xcb.xcb_change_property)(connection, 0,
+					request.requestor, property,
+					request.target, 8, text.len() as u32,
+					text.as_ptr() as *const c_void);
+			}
+			true
+		}
+		_ => false,
+	};
+
+	let notify = XcbSelectionNotifyEvent {
+		response_type: SELECTION_NOTIFY,
+		pad0: 0,
+		sequence: 0,
+		time: request.time,
+		requestor: request.requestor,
+		selection: request.selection,
+		target: request.target,
+		// No property tells the requestor it's been refused.
+		property: if sent { property } else { 0 },
+		pad1: [0; 2],
+	};
+
+	unsafe {
+		(xcb.xcb_send_event)(connection, 0, request.requestor, 0,
+			&notify as *const _ as *const u8);
+		(xcb.xcb_flush)(connection);
+	}
+}
+
+// Set the window's cursor, and free our handle to it.
+fn xcb_use_cursor(connection: *mut c_void, xcb: &Xcb, window: u32,
+	cursor: u32)
+{
+	const CW_CURSOR: u32 = 0x4000;
+
+	unsafe {
+		(xcb.xcb_change_window_attributes)(connection, window,
+			CW_CURSOR, &cursor);
+		(xcb.xcb_free_cursor)(connection, cursor);
+		(xcb.xcb_flush)(connection);
+	}
+}
+
+// A cursor with a 1x1 fully transparent image.
+fn xcb_blank_cursor(connection: *mut c_void, xcb: &Xcb, window: u32) -> u32 {
+	unsafe {
+		let pixmap = (xcb.xcb_generate_id)(connection);
+		(xcb.xcb_create_pixmap)(connection, 1, pixmap, window, 1, 1);
+
+		// New pixmaps aren't cleared, so clear it (foreground is 0).
+		let gc = (xcb.xcb_generate_id)(connection);
+		(xcb.xcb_create_gc)(connection, gc, pixmap, 0, null_mut());
+		(xcb.xcb_poly_fill_rectangle)(connection, pixmap, gc, 1,
+			&XcbRectangle { x: 0, y: 0, width: 1, height: 1 });
+		(xcb.xcb_free_gc)(connection, gc);
+
+		let cursor = (xcb.xcb_generate_id)(connection);
+		(xcb.xcb_create_cursor)(connection, cursor, pixmap, pixmap,
+			0, 0, 0, 0, 0, 0, 0, 0);
+		(xcb.xcb_free_pixmap)(connection, pixmap);
+
+		cursor
+	}
+}
+
+// Get where the window's top-left is on the root window.  ConfigureNotify's
+// XY is relative to the window manager's frame, so ask the server instead.
+fn xcb_position(connection: *mut c_void, xcb: &Xcb, window: u32)
+	-> (i16, i16)
+{
+	extern { fn free(this: *mut XcbTranslateCoordinatesReply) -> (); }
+
+	let root = xcb_screen(connection, xcb).root;
+
+	unsafe {
+		let cookie = (xcb.xcb_translate_coordinates)(connection,
+			window, root, 0, 0);
+		let reply = (xcb.xcb_translate_coordinates_reply)(connection,
+			cookie, null_mut());
+		if reply.is_null() {
+			return (0, 0);
+		}
+		let xy = ((*reply).dst_x, (*reply).dst_y);
+
+		free(reply);
+		xy
+	}
+}
+
+// Get the window that has keyboard focus (0 for none).
+fn xcb_focus(connection: *mut c_void, xcb: &Xcb) -> u32 {
+	extern { fn free(this: *mut XcbGetInputFocusReply) -> (); }
+
+	unsafe {
+		let cookie = (xcb.xcb_get_input_focus)(connection);
+		let reply = (xcb.xcb_get_input_focus_reply)(connection,
+			cookie, null_mut());
+		if reply.is_null() {
+			return 0;
+		}
+		let focus = (*reply).focus;
+
+		free(reply);
+		focus
+	}
+}
+
+// Get PropertyNotify for the root window, so changes to RESOURCE_MANAGER
+// (`Xft.dpi`) are seen without asking for it again on every resize.
+fn xcb_watch_root(connection: *mut c_void, xcb: &Xcb, root: u32) {
+	const CW_EVENT_MASK: u32 = 0x800;
+	const PROPERTY_CHANGE: u32 = 0x400000;
+
+	unsafe {
+		(xcb.xcb_change_window_attributes)(connection, root,
+			CW_EVENT_MASK, &PROPERTY_CHANGE);
+	}
+}
+
+// Get the content scale factor from `Xft.dpi` in the root window's
+// RESOURCE_MANAGER property (96 DPI is 1.0).
+fn xcb_scale(connection: *mut c_void, xcb: &Xcb) -> f32 {
+	extern { fn free(this: *mut c_void) -> (); }
+
+	let root = xcb_screen(connection, xcb).root;
+	let resources = unsafe {
+		let cookie = (xcb.xcb_get_property)(connection, 0, root,
+			ATOM_RESOURCE_MANAGER, ATOM_STRING, 0,
+			::std::u32::MAX / 4);
+		let reply = (xcb.xcb_get_property_reply)(connection, cookie,
+			null_mut());
+		if reply.is_null() {
+			return 1.0;
+		}
+
+		let value = ::std::slice::from_raw_parts(
+			(xcb.xcb_get_property_value)(reply),
+			(xcb.xcb_get_property_value_length)(reply) as usize);
+		let resources = String::from_utf8_lossy(value).into_owned();
+
+		free(reply);
+		resources
+	};
+
+	for line in resources.lines() {
+		let mut kv = line.splitn(2, ':');
+		if kv.next().map(|k| k.trim()) != Some("Xft.dpi") {
+			continue;
+		}
+		if let Some(Ok(dpi)) = kv.next().map(|v| v.trim().parse::<f32>()) {
+			if dpi > 0.0 {
+				return dpi / 96.0;
+			}
+		}
+	}
+
+	1.0
+}
+
+// Read (and delete) the text a selection owner put in `property`.
+fn xcb_get_text(connection: *mut c_void, xcb: &Xcb, window: u32,
+	property: u32) -> String
+{
+	extern { fn free(this: *mut c_void) -> (); }
+
+	// TODO: INCR, for text that doesn't fit in one request.
+	unsafe {
+		let cookie = (xcb.xcb_get_property)(connection, 1, window,
+			property, 0, 0, ::std::u32::MAX / 4);
+		let reply = (xcb.xcb_get_property_reply)(connection, cookie,
+			null_mut());
+		if reply.is_null() {
+			return String::new();
+		}
 
-	true
+		let value = ::std::slice::from_raw_parts(
+			(xcb.xcb_get_property_value)(reply),
+			(xcb.xcb_get_property_value_length)(reply) as usize);
+		let text = String::from_utf8_lossy(value).into_owned();
+
+		free(reply);
+		text
+	}
 }
 
 fn xkb_state_update_key(xkb: &XkbCommonX11, state: *mut c_void,
@@ -389,6 +2535,51 @@ fn xkb_state_update_key(xkb: &XkbCommonX11, state: *mut c_void,
 	}
 }
 
+// Translate a key press to text, going through compose sequences if there's a
+// compose table.
+fn xkb_text(xkb: &XkbCommonX11, state: *mut c_void, compose: *mut c_void,
+	key: u8) -> String
+{
+	const FEED_ACCEPTED: i32 = 1;
+	const COMPOSING: i32 = 1;
+	const COMPOSED: i32 = 2;
+	const CANCELLED: i32 = 3;
+
+	if compose.is_null() {
+		return xkb_state_key_get_utf8(xkb, state, key);
+	}
+
+	unsafe {
+		let keysym = (xkb.xkb_state_key_get_one_sym)(state, key as u32);
+
+		if (xkb.xkb_compose_state_feed)(compose, keysym) != FEED_ACCEPTED {
+			return xkb_state_key_get_utf8(xkb, state, key);
+		}
+
+		match (xkb.xkb_compose_state_get_status)(compose) {
+			// Dead key pressed, wait for the next key.
+			COMPOSING => String::new(),
+			COMPOSED => {
+				let size = (xkb.xkb_compose_state_get_utf8)(
+					compose, null_mut(), 0) as usize + 1;
+				let mut utf8 = vec![b'\0'; size];
+				(xkb.xkb_compose_state_get_utf8)(compose,
+					utf8.as_mut_ptr(), size);
+				utf8.pop();
+				(xkb.xkb_compose_state_reset)(compose);
+
+				String::from_utf8(utf8).unwrap_or_default()
+			}
+			CANCELLED => {
+				(xkb.xkb_compose_state_reset)(compose);
+				String::new()
+			}
+			// Not part of a sequence.
+			_ => xkb_state_key_get_utf8(xkb, state, key),
+		}
+	}
+}
+
 fn xkb_state_key_get_utf8(xkb: &XkbCommonX11, state: *mut c_void,
 	key: u8) -> String
 {
@@ -412,26 +2603,202 @@ fn xkb_state_key_get_utf8(xkb: &XkbCommonX11, state: *mut c_void,
 	::std::string::String::from_utf8(utf8).unwrap()
 }
 
+/// Get the battery level (0 to 1) of the `controller`th joystick, from the
+/// power supply (scope "Device") the kernel's HID driver makes for the same
+/// HID device.  `None` if there isn't one (it's wired).
+#[cfg(target_os = "linux")]
+pub fn controller_battery(controller: usize) -> Option<f32> {
+	use std::fs;
+
+	let read = |path: &::std::path::Path, file: &str| {
+		fs::read_to_string(path.join(file)).ok()
+			.map(|text| text.trim().to_string())
+	};
+
+	// The input device's parent is the HID device.
+	let hid = fs::canonicalize(joysticks()?.get(controller)?).ok()?
+		.parent()?.to_path_buf();
+
+	let supply = fs::read_dir("/sys/class/power_supply").ok()?
+		.filter_map(|entry| entry.ok())
+		.map(|entry| entry.path())
+		.find(|path| {
+			read(path, "type").as_ref().map(|t| t.as_str())
+				== Some("Battery")
+			&& read(path, "scope").as_ref().map(|s| s.as_str())
+				== Some("Device")
+			&& fs::canonicalize(path.join("device")).ok()
+				== Some(hid.clone())
+		})?;
+	let capacity: f32 = read(&supply, "capacity")?.parse().ok()?;
+
+	Some((capacity / 100.0).max(0.0).min(1.0))
+}
+
+// The input devices of event devices with a joystick device too, in event
+// device order.
+#[cfg(target_os = "linux")]
+fn joysticks() -> Option<Vec<::std::path::PathBuf>> {
+	use std::fs;
+
+	let mut devices: Vec<_> = fs::read_dir("/sys/class/input").ok()?
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_name().to_string_lossy()
+			.starts_with("event"))
+		.map(|entry| entry.path().join("device"))
+		.filter(|device| fs::read_dir(device).map(|entries| {
+			entries.filter_map(|entry| entry.ok()).any(|entry| {
+				entry.file_name().to_string_lossy()
+					.starts_with("js")
+			})
+		}).unwrap_or(false))
+		.collect();
+	// By number, so that event10 comes after event9.
+	devices.sort_by_key(|device| device.parent()
+		.and_then(|event| event.file_name())
+		.and_then(|event| event.to_string_lossy()["event".len()..]
+			.parse::<u32>().ok())
+		.unwrap_or(::std::u32::MAX));
+
+	Some(devices)
+}
+
+/// Get what the `controller`th joystick has, from the kernel's event device
+/// capabilities (joysticks in event device order).  `None` if there isn't
+/// one.
+#[cfg(target_os = "linux")]
+pub fn controller_caps(controller: usize) -> Option<::ControllerCaps> {
+	use std::fs;
+	use std::path::Path;
+
+	const ABS_HAT0X: usize = 0x10;
+	const ABS_HAT3Y: usize = 0x17;
+	const BTN_MISC: usize = 0x100;
+	const FF_RUMBLE: usize = 0x50;
+
+	// A bitmask (hex `long`s, most significant first) as bit numbers.
+	let bits = |path: &Path, file: &str| -> Vec<usize> {
+		let long = ::std::mem::size_of::<usize>() * 8;
+		let text = fs::read_to_string(path.join("capabilities")
+			.join(file)).unwrap_or_default();
+
+		let mut bits = vec![];
+		for (i, word) in text.trim().split(' ').rev().enumerate() {
+			let word = u64::from_str_radix(word, 16).unwrap_or(0);
+			for bit in 0..long {
+				if word & (1 << bit) != 0 {
+					bits.push(i * long + bit);
+				}
+			}
+		}
+		bits
+	};
+
+	let devices = joysticks()?;
+	let device = devices.get(controller)?;
+	let abs = bits(device, "abs");
+	let hat = |a: &&usize| **a >= ABS_HAT0X && **a <= ABS_HAT3Y;
+
+	Some(::ControllerCaps {
+		axes: abs.iter().filter(|a| !hat(a)).count() as u8,
+		buttons: bits(device, "key").iter()
+			.filter(|k| **k >= BTN_MISC).count() as u16,
+		// Each hat has an X & a Y axis.
+		hats: (abs.iter().filter(hat).count() / 2) as u8,
+		rumble: bits(device, "ff").contains(&FF_RUMBLE),
+	})
+}
+
+/// Get the monitors from XRandR (1.5), or the whole screen as one primary
+/// monitor if it's not available.  Empty if there's no X server.
+pub fn monitors() -> Vec<::Monitor> {
+	extern { fn free(this: *mut c_void) -> (); }
+
+	let xcb = match unsafe { Xcb::new() } {
+		Ok(xcb) => xcb,
+		Err(_) => return vec![],
+	};
+	let connection = unsafe { (xcb.xcb_connect)(null_mut(), null_mut()) };
+	if connection.is_null() {
+		return vec![];
+	}
+	let screen = xcb_screen(connection, &xcb);
+	let scale = xcb_scale(connection, &xcb);
+
+	let mut monitors = vec![];
+	if let Ok(randr) = unsafe { XcbRandr::new() } { unsafe {
+		// GetMonitors is new in RandR 1.5.
+		let reply = (randr.xcb_randr_query_version_reply)(connection,
+			(randr.xcb_randr_query_version)(connection, 1, 5),
+			null_mut());
+		let version = if reply.is_null() {
+			(0, 0)
+		} else {
+			let version = ((*reply).major_version,
+				(*reply).minor_version);
+			free(reply as *mut c_void);
+			version
+		};
+
+		let reply = if version >= (1, 5) {
+			(randr.xcb_randr_get_monitors_reply)(connection,
+				(randr.xcb_randr_get_monitors)(connection,
+					screen.root, 1),
+				null_mut())
+		} else {
+			null_mut()
+		};
+		if !reply.is_null() {
+			let mut iter = (randr
+				.xcb_randr_get_monitors_monitors_iterator)(reply);
+			while iter.rem > 0 {
+				let info = &*iter.data;
+				monitors.push(::Monitor {
+					size: (info.width, info.height),
+					position: (info.x, info.y),
+					scale,
+					primary: info.primary != 0,
+				});
+				(randr.xcb_randr_monitor_info_next)(&mut iter);
+			}
+			free(reply);
+		}
+	} }
+
+	if monitors.is_empty() {
+		monitors.push(::Monitor {
+			size: (screen.width_in_pixels, screen.height_in_pixels),
+			position: (0, 0),
+			scale,
+			primary: true,
+		});
+	}
+
+	unsafe { (xcb.xcb_disconnect)(connection) };
+
+	monitors
+}
+
 // Keycode translator
 fn key(physical_key: u8) -> Option<u8> {
 	Some(match physical_key {
 		49 => keyboard::EXT_BACKTICK,
 		86 => keyboard::EXT_PLUS,
 		63 => keyboard::EXT_ASTERISK,
-		61 | 106 => keyboard::SLASH,
-		36 | 104 => keyboard::ENTER,
-		10 | 87 => keyboard::NUM1,
-		11 | 88 => keyboard::NUM2,
-		12 | 89 => keyboard::NUM3,
-		13 | 83 => keyboard::NUM4,
-		14 | 84 => keyboard::NUM5,
-		15 | 85 => keyboard::NUM6,
-		16 | 79 => keyboard::NUM7,
-		17 | 80 => keyboard::NUM8,
-		18 | 81 => keyboard::NUM9,
-		19 | 90 => keyboard::NUM0,
-		60 | 91 => keyboard::PERIOD,
-		20 | 82 => keyboard::MINUS,
+		61 => keyboard::SLASH,
+		36 => keyboard::ENTER,
+		10 => keyboard::NUM1,
+		11 => keyboard::NUM2,
+		12 => keyboard::NUM3,
+		13 => keyboard::NUM4,
+		14 => keyboard::NUM5,
+		15 => keyboard::NUM6,
+		16 => keyboard::NUM7,
+		17 => keyboard::NUM8,
+		18 => keyboard::NUM9,
+		19 => keyboard::NUM0,
+		60 => keyboard::PERIOD,
+		20 => keyboard::MINUS,
 		21 => keyboard::EQUAL_SIGN,
 		22 => keyboard::BACKSPACE,
 		23 => keyboard::TAB,
@@ -486,6 +2853,32 @@ fn key(physical_key: u8) -> Option<u8> {
 		113 => keyboard::LEFT,
 		114 => keyboard::RIGHT,
 		116 => keyboard::DOWN,
+		67 => keyboard::EXT_F1,
+		68 => keyboard::EXT_F2,
+		69 => keyboard::EXT_F3,
+		70 => keyboard::EXT_F4,
+		71 => keyboard::EXT_F5,
+		72 => keyboard::EXT_F6,
+		73 => keyboard::EXT_F7,
+		74 => keyboard::EXT_F8,
+		75 => keyboard::EXT_F9,
+		76 => keyboard::EXT_F10,
+		95 => keyboard::EXT_F11,
+		96 => keyboard::EXT_F12,
+		90 => keyboard::EXT_KP0,
+		87 => keyboard::EXT_KP1,
+		88 => keyboard::EXT_KP2,
+		89 => keyboard::EXT_KP3,
+		83 => keyboard::EXT_KP4,
+		84 => keyboard::EXT_KP5,
+		85 => keyboard::EXT_KP6,
+		79 => keyboard::EXT_KP7,
+		80 => keyboard::EXT_KP8,
+		81 => keyboard::EXT_KP9,
+		104 => keyboard::EXT_KP_ENTER,
+		106 => keyboard::EXT_KP_DIVIDE,
+		82 => keyboard::EXT_KP_MINUS,
+		91 => keyboard::EXT_KP_PERIOD,
 		_ => return None,
 	} )
 }
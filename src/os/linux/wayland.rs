@@ -3,31 +3,1231 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-#[link(name = "wayland-client")]
-pub mod window {
-	use std::ptr::null as null;
+//! Wayland window (wl_surface + xdg_toplevel), used when there's no X server.
 
-	pub enum WaylandDisplay { }
-	pub enum WaylandEventQueue { }
+extern crate libc;
 
-	extern {
-		pub fn wl_display_connect(name: *const i8) -> *mut WaylandDisplay;
-		pub fn wl_display_disconnect(display: *mut WaylandDisplay) -> ();
-		pub fn wl_display_get_error(display: *mut WaylandDisplay) -> i32;
+use c_void;
+use std::ffi::CStr;
+use std::ptr::{ null, null_mut };
+
+use input::InputQueue;
+use ScrollKind;
+use Keyboard;
+
+/// Size of the window until the compositor tells us otherwise.
+const DEFAULT_WH: (u16, u16) = (640, 360);
+
+pub struct Window {
+	display: *mut c_void,
+	registry: *mut c_void,
+	surface: *mut c_void,
+	xdg_surface: *mut c_void,
+	toplevel: *mut c_void,
+	// TODO: Share with other clients (wl_data_device).
+	clipboard: Option<String>,
+	// Shared with the listeners, freed on drop.
+	state: *mut State,
+}
+
+impl Window {
+	/// Connect to the compositor and open a window, `None` if there's no
+	/// Wayland compositor (or it doesn't support xdg-shell).
+	pub fn new(hints: &::WindowHints) -> Option<Self> {
+		let (wl, egl, xkb) = unsafe { load() }.ok()?;
+		let display = unsafe { (wl.wl_display_connect)(null()) };
+		if display.is_null() {
+			return None;
+		}
+		let context = unsafe { (xkb.xkb_context_new)(0) };
+
+		let state = Box::into_raw(Box::new(State {
+			wl, egl, xkb,
+			compositor: null_mut(),
+			wm_base: null_mut(),
+			seat: null_mut(),
+			pointer: null_mut(),
+			keyboard: null_mut(),
+			touch: null_mut(),
+			context,
+			keymap: null_mut(),
+			xkb_state: null_mut(),
+			queue: null_mut(),
+			keys: null_mut(),
+			egl_window: null_mut(),
+			wh: hints.wh.unwrap_or(DEFAULT_WH),
+			pending_wh: None,
+			pointer_xy: (0, 0),
+			focus: false,
+		}));
+
+		unsafe {
+			let wl = &(*state).wl;
+
+			// Find the compositor, xdg_wm_base & seat.
+			let registry = constructor(wl, display,
+				WL_DISPLAY_GET_REGISTRY,
+				&mut [WlArgument { n: 0 }], &WL_REGISTRY);
+			listen(wl, registry, &REGISTRY_LISTENER, state);
+			(wl.wl_display_roundtrip)(display);
+
+			if (*state).compositor.is_null()
+				|| (*state).wm_base.is_null()
+			{
+				Self::free(display, registry, state);
+				return None;
+			}
+
+			// Create the window.
+			let surface = constructor(wl, (*state).compositor,
+				WL_COMPOSITOR_CREATE_SURFACE,
+				&mut [WlArgument { n: 0 }], &WL_SURFACE);
+			let xdg_surface = constructor(wl, (*state).wm_base,
+				XDG_WM_BASE_GET_XDG_SURFACE, &mut [
+					WlArgument { n: 0 },
+					WlArgument { o: surface },
+				], &XDG_SURFACE);
+			listen(wl, xdg_surface, &XDG_SURFACE_LISTENER, state);
+			let toplevel = constructor(wl, xdg_surface,
+				XDG_SURFACE_GET_TOPLEVEL,
+				&mut [WlArgument { n: 0 }], &XDG_TOPLEVEL);
+			listen(wl, toplevel, &XDG_TOPLEVEL_LISTENER, state);
+			// TODO: Decorations (zxdg_decoration_manager_v1), and
+			// the icon (xdg_toplevel_icon_v1).
+			if !hints.title.is_empty() {
+				let mut title = hints.title.as_bytes().to_vec();
+				title.push(0);
+
+				request(wl, toplevel, XDG_TOPLEVEL_SET_TITLE,
+					&mut [WlArgument {
+						s: title.as_ptr()
+					}]);
+			}
+			if hints.wh.is_none() {
+				request(wl, toplevel,
+					XDG_TOPLEVEL_SET_MAXIMIZED, &mut []);
+			}
+			request(wl, surface, WL_SURFACE_COMMIT, &mut []);
+
+			// Wait for the first configure, so the size is known.
+			(wl.wl_display_roundtrip)(display);
+
+			let wh = (*state).wh;
+			(*state).egl_window = ((*state).egl.wl_egl_window_create)(
+				surface, wh.0 as i32, wh.1 as i32);
+
+			Some(Window {
+				display, registry, surface, xdg_surface, toplevel,
+				clipboard: None, state,
+			})
+		}
+	}
+
+	pub fn poll_event(&mut self, input: &mut InputQueue,
+		keyboard: &mut Keyboard) -> bool
+	{
+		let display = self.display;
+		let state = self.state;
+
+		// `new()` waited for the first configure, so it's shown.
+		input.ready();
+
+		unsafe {
+			// The listeners push events straight into the queue.
+			(*state).queue = input;
+			(*state).keys = keyboard;
+
+			// Read without blocking, then dispatch.
+			while ((*state).wl.wl_display_prepare_read)(display) != 0
+			{
+				((*state).wl.wl_display_dispatch_pending)(
+					display);
+			}
+			((*state).wl.wl_display_flush)(display);
+			let mut fd = libc::pollfd {
+				fd: ((*state).wl.wl_display_get_fd)(display),
+				events: libc::POLLIN,
+				revents: 0,
+			};
+			if libc::poll(&mut fd, 1, 0) > 0 {
+				((*state).wl.wl_display_read_events)(display);
+			} else {
+				((*state).wl.wl_display_cancel_read)(display);
+			}
+			((*state).wl.wl_display_dispatch_pending)(display);
+
+			(*state).queue = null_mut();
+			(*state).keys = null_mut();
+		}
+
+		// Everything was handled in one go.
+		false
+	}
+
+	pub fn wait(&mut self, timeout: Option<::std::time::Duration>) {
+		let display = self.display;
+		let state = self.state;
+
+		unsafe {
+			let wl = &(*state).wl;
+
+			// Events already read (by EGL) won't wake `poll()`, and
+			// `poll_event()` dispatches them.
+			if (wl.wl_display_prepare_read)(display) != 0 {
+				return;
+			}
+			(wl.wl_display_flush)(display);
+			if super::wait_fd((wl.wl_display_get_fd)(display),
+				timeout)
+			{
+				(wl.wl_display_read_events)(display);
+			} else {
+				(wl.wl_display_cancel_read)(display);
+			}
+		}
+	}
+
+	pub fn fd(&mut self) -> i32 {
+		unsafe {
+			((*self.state).wl.wl_display_flush)(self.display);
+			((*self.state).wl.wl_display_get_fd)(self.display)
+		}
+	}
+
+	pub fn get_connection(&self) -> ::WindowConnection {
+		::WindowConnection::Wayland(self.display, self.surface,
+			unsafe { (*self.state).egl_window })
+	}
+
+	pub fn wh(&self) -> (u16, u16) {
+		unsafe { (*self.state).wh }
+	}
+
+	// Wayland doesn't tell clients where their windows are.
+	pub fn position(&self) -> (i16, i16) {
+		(0, 0)
 	}
 
-	pub fn init() {
-		// Call wayland libary's init.
-		let wayland_display:*mut _;
+	pub fn set_position(&mut self, _xy: (i16, i16)) {
+		// Only the compositor can move windows.
+	}
+
+	pub fn scale_factor(&self) -> f32 {
+		// TODO: wl_output.scale, for the outputs the surface is on.
+		1.0
+	}
+
+	pub fn has_focus(&self) -> bool {
+		unsafe { (*self.state).focus }
+	}
+
+	pub fn clipboard_set(&mut self, text: &str) {
+		self.clipboard = Some(text.to_string());
+	}
+
+	pub fn clipboard_get(&self) -> Option<String> {
+		self.clipboard.clone()
+	}
+
+	pub fn set_cursor(&mut self, _cursor: ::Cursor) {
+		// TODO: wl_pointer.set_cursor with a libwayland-cursor theme.
+	}
+
+	/// Unmap the window.  The `wl_surface` (and EGL window) stay around
+	/// until drop, since EGL still has a surface for them.
+	pub fn close(&mut self) {
+		if self.toplevel.is_null() {
+			return;
+		}
+
 		unsafe {
-			wayland_display = wl_display_connect(null());
-		};
-		if wayland_display as *const _ == null() {
-			panic!("Couldn't Connect: Wayland server not running?");
+			let wl = &(*self.state).wl;
+
+			destroy(wl, self.toplevel, Some(XDG_TOPLEVEL_DESTROY));
+			destroy(wl, self.xdg_surface, Some(XDG_SURFACE_DESTROY));
+			(wl.wl_display_flush)(self.display);
+		}
+		self.toplevel = null_mut();
+		self.xdg_surface = null_mut();
+	}
+
+	pub fn set_always_on_top(&mut self, _above: bool) -> bool {
+		// xdg_toplevel doesn't have a way to ask for this.
+		false
+	}
+
+	pub fn minimize(&mut self) -> bool {
+		self.state_request(XDG_TOPLEVEL_SET_MINIMIZED)
+	}
+
+	pub fn maximize(&mut self, maximized: bool) -> bool {
+		self.state_request(if maximized {
+			XDG_TOPLEVEL_SET_MAXIMIZED
+		} else {
+			XDG_TOPLEVEL_UNSET_MAXIMIZED
+		})
+	}
+
+	// The compositor answers with a configure (for the new size), but
+	// there's no way to tell when the window got minimized.
+	fn state_request(&mut self, opcode: u32) -> bool {
+		if self.toplevel.is_null() {
+			return false;
+		}
+
+		unsafe {
+			let wl = &(*self.state).wl;
+
+			request(wl, self.toplevel, opcode, &mut []);
+		}
+
+		true
+	}
+
+	// Set where pointer & touch input goes to the window, `None` for the
+	// whole window (a null region).
+	pub fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		unsafe {
+			let wl = &(*self.state).wl;
+			let compositor = (*self.state).compositor;
+
+			let wl_region = match region {
+				Some(rects) => {
+					new_region(wl, compositor, rects)
+				}
+				None => null_mut(),
+			};
+
+			request(wl, self.surface, WL_SURFACE_SET_INPUT_REGION,
+				&mut [WlArgument { o: wl_region }]);
+			request(wl, self.surface, WL_SURFACE_COMMIT, &mut []);
+			if !wl_region.is_null() {
+				destroy(wl, wl_region, Some(WL_REGION_DESTROY));
+			}
+		}
+		true
+	}
+
+	pub fn set_app_id(&mut self, app_id: &str) {
+		if self.toplevel.is_null() {
+			return;
+		}
+
+		let mut app_id = app_id.as_bytes().to_vec();
+		app_id.push(0);
+
+		unsafe {
+			let wl = &(*self.state).wl;
+
+			request(wl, self.toplevel, XDG_TOPLEVEL_SET_APP_ID,
+				&mut [WlArgument { s: app_id.as_ptr() }]);
+		}
+	}
+
+	pub fn set_min_size(&mut self, wh: (u16, u16)) {
+		self.size(XDG_TOPLEVEL_SET_MIN_SIZE, wh)
+	}
+
+	pub fn set_max_size(&mut self, wh: (u16, u16)) {
+		self.size(XDG_TOPLEVEL_SET_MAX_SIZE, wh)
+	}
+
+	pub fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		// TODO: xdg_toplevel has no aspect ratio, resize in configure.
+		aspect.is_none()
+	}
+
+	// Set a size constraint, (0, 0) is none for xdg_toplevel too.
+	fn size(&mut self, opcode: u32, wh: (u16, u16)) {
+		if self.toplevel.is_null() {
+			return;
+		}
+
+		unsafe {
+			let wl = &(*self.state).wl;
+
+			request(wl, self.toplevel, opcode, &mut [
+				WlArgument { i: wh.0 as i32 },
+				WlArgument { i: wh.1 as i32 },
+			]);
+			request(wl, self.surface, WL_SURFACE_COMMIT, &mut []);
+		}
+	}
+
+	/// Disconnect & free everything left after the registry roundtrip.
+	unsafe fn free(display: *mut c_void, registry: *mut c_void,
+		state: *mut State)
+	{
+		let state = Box::from_raw(state);
+		let wl = &state.wl;
+
+		for device in &[state.pointer, state.keyboard, state.touch] {
+			if !device.is_null() {
+				(wl.wl_proxy_destroy)(*device);
+			}
+		}
+		if !state.seat.is_null() {
+			(wl.wl_proxy_destroy)(state.seat);
+		}
+		if !state.wm_base.is_null() {
+			destroy(wl, state.wm_base, Some(XDG_WM_BASE_DESTROY));
+		}
+		if !state.compositor.is_null() {
+			(wl.wl_proxy_destroy)(state.compositor);
+		}
+		(wl.wl_proxy_destroy)(registry);
+
+		if !state.xkb_state.is_null() {
+			(state.xkb.xkb_state_unref)(state.xkb_state);
+		}
+		if !state.keymap.is_null() {
+			(state.xkb.xkb_keymap_unref)(state.keymap);
+		}
+		(state.xkb.xkb_context_unref)(state.context);
+		(wl.wl_display_disconnect)(display);
+	}
+}
+
+impl Drop for Window {
+	fn drop(&mut self) {
+		unsafe {
+			let wl = &(*self.state).wl;
+
+			((*self.state).egl.wl_egl_window_destroy)(
+				(*self.state).egl_window);
+			self.close();
+			destroy(wl, self.surface, Some(WL_SURFACE_DESTROY));
+			Self::free(self.display, self.registry, self.state);
+		}
+	}
+}
+
+/// Window state, shared with the listeners through their `data` pointer.
+struct State {
+	wl: WaylandClient,
+	egl: WaylandEgl,
+	xkb: XkbCommon,
+	// Globals
+	compositor: *mut c_void,
+	wm_base: *mut c_void,
+	seat: *mut c_void,
+	// Input devices
+	pointer: *mut c_void,
+	keyboard: *mut c_void,
+	touch: *mut c_void,
+	// Keyboard (XKB)
+	context: *mut c_void,
+	keymap: *mut c_void,
+	xkb_state: *mut c_void,
+	// Only set during `poll_event()`.
+	queue: *mut InputQueue,
+	keys: *mut Keyboard,
+	// Window
+	egl_window: *mut c_void,
+	wh: (u16, u16),
+	pending_wh: Option<(u16, u16)>,
+	pointer_xy: (i16, i16),
+	// Keyboard focus (between `keyboard_enter` & `keyboard_leave`).
+	focus: bool,
+}
+
+unsafe fn load() -> Result<(WaylandClient, WaylandEgl, XkbCommon),
+	::dl_api::Error>
+{
+	Ok((WaylandClient::new()?, WaylandEgl::new()?, XkbCommon::new()?))
+}
+
+dl_api!(WaylandClient, "libwayland-client.so.0",
+	fn wl_display_connect(*const i8) -> *mut c_void,
+	fn wl_display_disconnect(*mut c_void) -> (),
+	fn wl_display_get_fd(*mut c_void) -> i32,
+	fn wl_display_roundtrip(*mut c_void) -> i32,
+	fn wl_display_prepare_read(*mut c_void) -> i32,
+	fn wl_display_read_events(*mut c_void) -> i32,
+	fn wl_display_cancel_read(*mut c_void) -> (),
+	fn wl_display_dispatch_pending(*mut c_void) -> i32,
+	fn wl_display_flush(*mut c_void) -> i32,
+	fn wl_proxy_marshal_array(*mut c_void, u32, *mut WlArgument) -> (),
+	fn wl_proxy_marshal_array_constructor(*mut c_void, u32,
+		*mut WlArgument, *const WlInterface) -> *mut c_void,
+	fn wl_proxy_marshal_array_constructor_versioned(*mut c_void, u32,
+		*mut WlArgument, *const WlInterface, u32) -> *mut c_void,
+	fn wl_proxy_add_listener(*mut c_void, *const c_void, *mut c_void)
+		-> i32,
+	fn wl_proxy_destroy(*mut c_void) -> ()
+);
+
+dl_api!(WaylandEgl, "libwayland-egl.so.1",
+	fn wl_egl_window_create(*mut c_void, i32, i32) -> *mut c_void,
+	fn wl_egl_window_resize(*mut c_void, i32, i32, i32, i32) -> (),
+	fn wl_egl_window_destroy(*mut c_void) -> ()
+);
+
+dl_api!(XkbCommon, "libxkbcommon.so.0",
+	fn xkb_context_new(i32) -> *mut c_void,
+	fn xkb_context_unref(*mut c_void) -> (),
+	fn xkb_keymap_new_from_string(*mut c_void, *const i8, i32, i32)
+		-> *mut c_void,
+	fn xkb_keymap_unref(*mut c_void) -> (),
+	fn xkb_state_new(*mut c_void) -> *mut c_void,
+	fn xkb_state_unref(*mut c_void) -> (),
+	fn xkb_state_update_mask(*mut c_void, u32, u32, u32, u32, u32, u32)
+		-> i32,
+	fn xkb_state_key_get_utf8(*mut c_void, u32, *mut u8, usize) -> i32
+);
+
+// Send a request.
+unsafe fn request(wl: &WaylandClient, proxy: *mut c_void, opcode: u32,
+	args: &mut [WlArgument])
+{
+	(wl.wl_proxy_marshal_array)(proxy, opcode, args.as_mut_ptr());
+}
+
+// Create a `wl_region` from rectangles `(x, y, w, h)`.
+unsafe fn new_region(wl: &WaylandClient, compositor: *mut c_void,
+	rects: &[(i16, i16, u16, u16)]) -> *mut c_void
+{
+	let region = constructor(wl, compositor, WL_COMPOSITOR_CREATE_REGION,
+		&mut [WlArgument { n: 0 }], &WL_REGION);
+
+	for &(x, y, w, h) in rects {
+		request(wl, region, WL_REGION_ADD, &mut [
+			WlArgument { i: x as i32 },
+			WlArgument { i: y as i32 },
+			WlArgument { i: w as i32 },
+			WlArgument { i: h as i32 },
+		]);
+	}
+	region
+}
+
+// Send a request that creates a new object.
+unsafe fn constructor(wl: &WaylandClient, proxy: *mut c_void, opcode: u32,
+	args: &mut [WlArgument], interface: &'static WlInterface) -> *mut c_void
+{
+	(wl.wl_proxy_marshal_array_constructor)(proxy, opcode,
+		args.as_mut_ptr(), interface)
+}
+
+// Send the destructor request (if there is one), and free the proxy.
+unsafe fn destroy(wl: &WaylandClient, proxy: *mut c_void, opcode: Option<u32>)
+{
+	if let Some(opcode) = opcode {
+		request(wl, proxy, opcode, &mut []);
+	}
+	(wl.wl_proxy_destroy)(proxy);
+}
+
+unsafe fn listen<T>(wl: &WaylandClient, proxy: *mut c_void,
+	listener: &'static T, state: *mut State)
+{
+	(wl.wl_proxy_add_listener)(proxy, listener as *const _ as *const c_void,
+		state as *mut c_void);
+}
+
+// Bind a global (version 1, that's all the tables below describe).
+unsafe fn bind(wl: &WaylandClient, registry: *mut c_void, name: u32,
+	interface: &'static WlInterface) -> *mut c_void
+{
+	(wl.wl_proxy_marshal_array_constructor_versioned)(registry,
+		WL_REGISTRY_BIND, [
+			WlArgument { u: name },
+			WlArgument { s: interface.name },
+			WlArgument { u: 1 },
+			WlArgument { n: 0 },
+		].as_mut_ptr(), interface, 1)
+}
+
+// wl_fixed_t (24.8) surface coordinates to pixels.
+fn fixed(x: i32, y: i32) -> (i16, i16) {
+	((x / 256) as i16, (y / 256) as i16)
+}
+
+// Listeners
+
+unsafe extern "C" fn registry_global(data: *mut c_void, registry: *mut c_void,
+	name: u32, interface: *const i8, _version: u32)
+{
+	let state = &mut *(data as *mut State);
+	let interface = CStr::from_ptr(interface).to_bytes();
+
+	if interface == &b"wl_compositor"[..] {
+		state.compositor = bind(&state.wl, registry, name,
+			&WL_COMPOSITOR);
+	} else if interface == &b"xdg_wm_base"[..] {
+		state.wm_base = bind(&state.wl, registry, name, &XDG_WM_BASE);
+		listen(&state.wl, state.wm_base, &XDG_WM_BASE_LISTENER,
+			data as *mut State);
+	} else if interface == &b"wl_seat"[..] && state.seat.is_null() {
+		state.seat = bind(&state.wl, registry, name, &WL_SEAT);
+		listen(&state.wl, state.seat, &SEAT_LISTENER,
+			data as *mut State);
+	}
+}
+
+unsafe extern "C" fn registry_global_remove(_: *mut c_void, _: *mut c_void,
+	_name: u32) {}
+
+unsafe extern "C" fn wm_base_ping(data: *mut c_void, wm_base: *mut c_void,
+	serial: u32)
+{
+	let state = &*(data as *mut State);
+
+	request(&state.wl, wm_base, XDG_WM_BASE_PONG,
+		&mut [WlArgument { u: serial }]);
+}
+
+unsafe extern "C" fn xdg_surface_configure(data: *mut c_void,
+	xdg_surface: *mut c_void, serial: u32)
+{
+	let state = &mut *(data as *mut State);
+
+	request(&state.wl, xdg_surface, XDG_SURFACE_ACK_CONFIGURE,
+		&mut [WlArgument { u: serial }]);
+
+	if let Some(wh) = state.pending_wh.take() {
+		if !state.egl_window.is_null() {
+			(state.egl.wl_egl_window_resize)(state.egl_window,
+				wh.0 as i32, wh.1 as i32, 0, 0);
+		}
+		if let Some(queue) = state.queue.as_mut() {
+			queue.resize(&mut state.wh, wh);
+		} else {
+			state.wh = wh;
+		}
+	}
+}
+
+unsafe extern "C" fn toplevel_configure(data: *mut c_void, _: *mut c_void,
+	w: i32, h: i32, _states: *mut c_void)
+{
+	let state = &mut *(data as *mut State);
+
+	// 0 means we pick the size, so keep the old one.
+	if w > 0 && h > 0 {
+		state.pending_wh = Some((w as u16, h as u16));
+	}
+}
+
+unsafe extern "C" fn toplevel_close(data: *mut c_void, _: *mut c_void) {
+	let state = &mut *(data as *mut State);
+
+	if let Some(queue) = state.queue.as_mut() {
+		queue.exit();
+	}
+}
+
+unsafe extern "C" fn seat_capabilities(data: *mut c_void, seat: *mut c_void,
+	capabilities: u32)
+{
+	let state = &mut *(data as *mut State);
+
+	const POINTER: u32 = 1;
+	const KEYBOARD: u32 = 2;
+	const TOUCH: u32 = 4;
+
+	let has_pointer = capabilities & POINTER != 0;
+	if has_pointer && state.pointer.is_null() {
+		state.pointer = constructor(&state.wl, seat, WL_SEAT_GET_POINTER,
+			&mut [WlArgument { n: 0 }], &WL_POINTER);
+		listen(&state.wl, state.pointer, &POINTER_LISTENER,
+			data as *mut State);
+	} else if !has_pointer && !state.pointer.is_null() {
+		(state.wl.wl_proxy_destroy)(state.pointer);
+		state.pointer = null_mut();
+	}
+
+	let has_keyboard = capabilities & KEYBOARD != 0;
+	if has_keyboard && state.keyboard.is_null() {
+		state.keyboard = constructor(&state.wl, seat,
+			WL_SEAT_GET_KEYBOARD, &mut [WlArgument { n: 0 }],
+			&WL_KEYBOARD);
+		listen(&state.wl, state.keyboard, &KEYBOARD_LISTENER,
+			data as *mut State);
+	} else if !has_keyboard && !state.keyboard.is_null() {
+		(state.wl.wl_proxy_destroy)(state.keyboard);
+		state.keyboard = null_mut();
+	}
+
+	let has_touch = capabilities & TOUCH != 0;
+	if has_touch && state.touch.is_null() {
+		state.touch = constructor(&state.wl, seat, WL_SEAT_GET_TOUCH,
+			&mut [WlArgument { n: 0 }], &WL_TOUCH);
+		listen(&state.wl, state.touch, &TOUCH_LISTENER,
+			data as *mut State);
+	} else if !has_touch && !state.touch.is_null() {
+		(state.wl.wl_proxy_destroy)(state.touch);
+		state.touch = null_mut();
+	}
+}
+
+unsafe extern "C" fn pointer_enter(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _surface: *mut c_void, x: i32, y: i32)
+{
+	// TODO: Set the cursor image (libwayland-cursor).
+	pointer_motion(data, null_mut(), 0, x, y);
+}
+
+unsafe extern "C" fn pointer_leave(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _surface: *mut c_void)
+{
+	let state = &mut *(data as *mut State);
+
+	if let Some(queue) = state.queue.as_mut() {
+		queue.cursor_leave();
+	}
+}
+
+unsafe extern "C" fn pointer_motion(data: *mut c_void, _: *mut c_void,
+	_time: u32, x: i32, y: i32)
+{
+	let state = &mut *(data as *mut State);
+
+	// TODO: zwp_relative_pointer_v1, for `Event::MotionRaw`.
+	state.pointer_xy = fixed(x, y);
+	if let Some(queue) = state.queue.as_mut() {
+		queue.cursor_move(state.wh, state.pointer_xy);
+	}
+}
+
+unsafe extern "C" fn pointer_button(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _time: u32, button: u32, button_state: u32)
+{
+	let state = &mut *(data as *mut State);
+	let (wh, xy) = (state.wh, state.pointer_xy);
+
+	// Linux input event codes.
+	const BTN_LEFT: u32 = 0x110;
+	const BTN_RIGHT: u32 = 0x111;
+	const BTN_MIDDLE: u32 = 0x112;
+
+	if let Some(queue) = state.queue.as_mut() {
+		match (button, button_state == 1) {
+			(BTN_LEFT, true) => queue.left_button_press(wh, xy),
+			(BTN_MIDDLE, true) => queue.middle_button_press(wh, xy),
+			(BTN_RIGHT, true) => queue.right_button_press(wh, xy),
+			(BTN_LEFT, false) => queue.left_button_release(wh, xy),
+			(BTN_MIDDLE, false) => queue.middle_button_release(wh,xy),
+			(BTN_RIGHT, false) => queue.right_button_release(wh, xy),
+			_ => {},
 		}
 	}
+}
+
+unsafe extern "C" fn pointer_axis(data: *mut c_void, _: *mut c_void,
+	_time: u32, axis: u32, value: i32)
+{
+	let state = &mut *(data as *mut State);
+	// Compositors send about 10 pixels per wheel click.
+	let value = value as f32 / (256.0 * 10.0);
+
+	if let Some(queue) = state.queue.as_mut() {
+		// TODO: wl_pointer version 5 axis_source, to tell wheels
+		// apart (as ScrollKind::Line).
+		queue.scroll(state.wh, state.pointer_xy, match axis {
+			0 => (0.0, value),
+			_ => (value, 0.0),
+		}, ScrollKind::Precise);
+	}
+}
+
+unsafe extern "C" fn keyboard_keymap(data: *mut c_void, _: *mut c_void,
+	format: u32, fd: i32, size: u32)
+{
+	let state = &mut *(data as *mut State);
+
+	const XKB_V1: u32 = 1;
+
+	if format == XKB_V1 {
+		let map = libc::mmap(null_mut(), size as usize, libc::PROT_READ,
+			libc::MAP_PRIVATE, fd, 0);
+
+		if map != libc::MAP_FAILED {
+			let keymap = (state.xkb.xkb_keymap_new_from_string)(
+				state.context, map as *const i8, 1, 0);
+			libc::munmap(map, size as usize);
+
+			if !keymap.is_null() {
+				if !state.xkb_state.is_null() {
+					(state.xkb.xkb_state_unref)(
+						state.xkb_state);
+					(state.xkb.xkb_keymap_unref)(
+						state.keymap);
+				}
+				state.keymap = keymap;
+				state.xkb_state =
+					(state.xkb.xkb_state_new)(keymap);
+			}
+		}
+	}
+
+	libc::close(fd);
+}
+
+unsafe extern "C" fn keyboard_enter(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _surface: *mut c_void, _keys: *mut c_void)
+{
+	let state = &mut *(data as *mut State);
+
+	state.focus = true;
+	if let Some(queue) = state.queue.as_mut() {
+		queue.resume();
+	}
+}
+
+unsafe extern "C" fn keyboard_leave(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _surface: *mut c_void)
+{
+	let state = &mut *(data as *mut State);
+
+	state.focus = false;
+	if let Some(queue) = state.queue.as_mut() {
+		queue.pause();
+	}
+}
+
+unsafe extern "C" fn keyboard_key(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _time: u32, key: u32, key_state: u32)
+{
+	let state = &mut *(data as *mut State);
+	// Wayland sends evdev codes, XKB (and `super::key()`) adds 8.
+	let detail = (key + 8) as u8;
+
+	let (queue, keys) = match (state.queue.as_mut(), state.keys.as_mut()) {
+		(Some(queue), Some(keys)) => (queue, keys),
+		_ => return,
+	};
+
+	// Same scancodes as XCB.
+	if key_state == 0 {
+		keys.release_raw(detail as u32);
+		if let Some(key) = super::key(detail) {
+			keys.release(key);
+		}
+		return;
+	}
+
+	let string = match detail {
+		// Enter: Keyboard & NumPad
+		36 | 104 => String::from("\n"),
+		// Left & Right Shift, Alt Gr & NumLock & Esc
+		50 | 62 | 108 | 77 | 9 => String::from(""),
+		// Everything else
+		// TODO: Compose sequences (dead keys), like on XCB.
+		_ => key_text(&state.xkb, state.xkb_state, detail),
+	};
+
+	keys.press_raw(detail as u32);
+	if let Some(key) = super::key(detail) {
+		keys.press(key);
+	} else {
+		keys.unknown(detail as u32);
+		if detail == 9 {
+			queue.exit();
+		}
+	}
+
+	// TODO: zwp_text_input_v3, for `Event::Preedit`.
+	queue.text(string);
+}
+
+unsafe extern "C" fn keyboard_modifiers(data: *mut c_void, _: *mut c_void,
+	_serial: u32, depressed: u32, latched: u32, locked: u32, group: u32)
+{
+	let state = &mut *(data as *mut State);
 
-	pub fn kill() {
-		
+	if !state.xkb_state.is_null() {
+		(state.xkb.xkb_state_update_mask)(state.xkb_state, depressed,
+			latched, locked, 0, 0, group);
 	}
 }
+
+unsafe extern "C" fn touch_down(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _time: u32, _surface: *mut c_void, id: i32, x: i32,
+	y: i32)
+{
+	let state = &mut *(data as *mut State);
+
+	if let Some(queue) = state.queue.as_mut() {
+		// wl_touch doesn't have pressure.
+		queue.touch_press(state.wh, id as u32 as u64, fixed(x, y),
+			(None, None));
+	}
+}
+
+unsafe extern "C" fn touch_up(data: *mut c_void, _: *mut c_void,
+	_serial: u32, _time: u32, id: i32)
+{
+	let state = &mut *(data as *mut State);
+
+	if let Some(queue) = state.queue.as_mut() {
+		queue.touch_release(state.wh, id as u32 as u64);
+	}
+}
+
+unsafe extern "C" fn touch_motion(data: *mut c_void, _: *mut c_void,
+	_time: u32, id: i32, x: i32, y: i32)
+{
+	let state = &mut *(data as *mut State);
+
+	if let Some(queue) = state.queue.as_mut() {
+		queue.touch_move(state.wh, id as u32 as u64, fixed(x, y),
+			(None, None));
+	}
+}
+
+unsafe extern "C" fn touch_frame(_: *mut c_void, _: *mut c_void) {}
+
+unsafe extern "C" fn touch_cancel(_: *mut c_void, _: *mut c_void) {}
+
+fn key_text(xkb: &XkbCommon, state: *mut c_void, key: u8) -> String {
+	if state.is_null() {
+		return String::new();
+	}
+
+	let size = unsafe {
+		(xkb.xkb_state_key_get_utf8)(state, key as u32, null_mut(), 0)
+			as usize + 1
+	};
+	let mut utf8 = Vec::new();
+
+	utf8.resize(size, b'\0'); // Size + 1 to include NULL byte from XKB.
+
+	unsafe {
+		(xkb.xkb_state_key_get_utf8)(state, key as u32,
+			utf8.as_mut_ptr(), size);
+	}
+
+	utf8.pop();
+
+	String::from_utf8(utf8).unwrap_or_default()
+}
+
+#[repr(C)]
+struct RegistryListener {
+	global: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *const i8,
+		u32),
+	global_remove: unsafe extern "C" fn(*mut c_void, *mut c_void, u32),
+}
+
+#[repr(C)]
+struct WmBaseListener {
+	ping: unsafe extern "C" fn(*mut c_void, *mut c_void, u32),
+}
+
+#[repr(C)]
+struct XdgSurfaceListener {
+	configure: unsafe extern "C" fn(*mut c_void, *mut c_void, u32),
+}
+
+#[repr(C)]
+struct ToplevelListener {
+	configure: unsafe extern "C" fn(*mut c_void, *mut c_void, i32, i32,
+		*mut c_void),
+	close: unsafe extern "C" fn(*mut c_void, *mut c_void),
+}
+
+#[repr(C)]
+struct SeatListener {
+	capabilities: unsafe extern "C" fn(*mut c_void, *mut c_void, u32),
+}
+
+#[repr(C)]
+struct PointerListener {
+	enter: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut c_void,
+		i32, i32),
+	leave: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut c_void),
+	motion: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, i32, i32),
+	button: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, u32, u32,
+		u32),
+	axis: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, u32, i32),
+}
+
+#[repr(C)]
+struct KeyboardListener {
+	keymap: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, i32, u32),
+	enter: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut c_void,
+		*mut c_void),
+	leave: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut c_void),
+	key: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, u32, u32, u32),
+	modifiers: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, u32, u32,
+		u32, u32),
+}
+
+#[repr(C)]
+struct TouchListener {
+	down: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, u32,
+		*mut c_void, i32, i32, i32),
+	up: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, u32, i32),
+	motion: unsafe extern "C" fn(*mut c_void, *mut c_void, u32, i32, i32,
+		i32),
+	frame: unsafe extern "C" fn(*mut c_void, *mut c_void),
+	cancel: unsafe extern "C" fn(*mut c_void, *mut c_void),
+}
+
+static REGISTRY_LISTENER: RegistryListener = RegistryListener {
+	global: registry_global,
+	global_remove: registry_global_remove,
+};
+
+static XDG_WM_BASE_LISTENER: WmBaseListener = WmBaseListener {
+	ping: wm_base_ping,
+};
+
+static XDG_SURFACE_LISTENER: XdgSurfaceListener = XdgSurfaceListener {
+	configure: xdg_surface_configure,
+};
+
+static XDG_TOPLEVEL_LISTENER: ToplevelListener = ToplevelListener {
+	configure: toplevel_configure,
+	close: toplevel_close,
+};
+
+static SEAT_LISTENER: SeatListener = SeatListener {
+	capabilities: seat_capabilities,
+};
+
+static POINTER_LISTENER: PointerListener = PointerListener {
+	enter: pointer_enter,
+	leave: pointer_leave,
+	motion: pointer_motion,
+	button: pointer_button,
+	axis: pointer_axis,
+};
+
+static KEYBOARD_LISTENER: KeyboardListener = KeyboardListener {
+	keymap: keyboard_keymap,
+	enter: keyboard_enter,
+	leave: keyboard_leave,
+	key: keyboard_key,
+	modifiers: keyboard_modifiers,
+};
+
+static TOUCH_LISTENER: TouchListener = TouchListener {
+	down: touch_down,
+	up: touch_up,
+	motion: touch_motion,
+	frame: touch_frame,
+	cancel: touch_cancel,
+};
+
+// Protocol tables (what wayland-scanner would generate).  libwayland-client
+// only exports the core interfaces as data, which `dl_api` can't load, and
+// xdg-shell isn't in it at all, so all of the used interfaces are here.
+
+#[repr(C)] #[derive(Copy, Clone)] #[allow(dead_code)]
+union WlArgument {
+	i: i32,
+	u: u32,
+	s: *const u8,
+	o: *mut c_void,
+	n: u32,
+}
+
+#[repr(C)] #[allow(dead_code)]
+struct WlMessage {
+	name: *const u8,
+	signature: *const u8,
+	types: *const Option<&'static WlInterface>,
+}
+
+#[repr(C)] #[allow(dead_code)]
+struct WlInterface {
+	name: *const u8,
+	version: i32,
+	method_count: i32,
+	methods: *const WlMessage,
+	event_count: i32,
+	events: *const WlMessage,
+}
+
+unsafe impl Sync for WlMessage {}
+unsafe impl Sync for WlInterface {}
+
+// No object arguments are type checked, so every message can share these.
+static NO_TYPES: [Option<&'static WlInterface>; 8] = [None; 8];
+static NO_MESSAGES: [WlMessage; 0] = [];
+
+macro_rules! message {
+	($name:expr, $signature:expr) => (WlMessage {
+		name: concat!($name, "\0") as *const str as *const u8,
+		signature: concat!($signature, "\0") as *const str as *const u8,
+		types: &NO_TYPES as *const _
+			as *const Option<&'static WlInterface>,
+	})
+}
+
+macro_rules! interface {
+	($name:expr, $methods:ident: $mc:expr, $events:ident: $ec:expr) => (
+		WlInterface {
+			name: concat!($name, "\0") as *const str as *const u8,
+			version: 1,
+			method_count: $mc,
+			methods: &$methods as *const _ as *const WlMessage,
+			event_count: $ec,
+			events: &$events as *const _ as *const WlMessage,
+		}
+	)
+}
+
+const WL_DISPLAY_GET_REGISTRY: u32 = 1;
+const WL_REGISTRY_BIND: u32 = 0;
+const WL_COMPOSITOR_CREATE_SURFACE: u32 = 0;
+const WL_COMPOSITOR_CREATE_REGION: u32 = 1;
+const WL_SURFACE_DESTROY: u32 = 0;
+const WL_SURFACE_SET_INPUT_REGION: u32 = 5;
+const WL_SURFACE_COMMIT: u32 = 6;
+const WL_REGION_DESTROY: u32 = 0;
+const WL_REGION_ADD: u32 = 1;
+const WL_SEAT_GET_POINTER: u32 = 0;
+const WL_SEAT_GET_KEYBOARD: u32 = 1;
+const WL_SEAT_GET_TOUCH: u32 = 2;
+const XDG_WM_BASE_DESTROY: u32 = 0;
+const XDG_WM_BASE_GET_XDG_SURFACE: u32 = 2;
+const XDG_WM_BASE_PONG: u32 = 3;
+const XDG_SURFACE_DESTROY: u32 = 0;
+const XDG_SURFACE_GET_TOPLEVEL: u32 = 1;
+const XDG_SURFACE_ACK_CONFIGURE: u32 = 4;
+const XDG_TOPLEVEL_DESTROY: u32 = 0;
+const XDG_TOPLEVEL_SET_TITLE: u32 = 2;
+const XDG_TOPLEVEL_SET_APP_ID: u32 = 3;
+const XDG_TOPLEVEL_SET_MAX_SIZE: u32 = 7;
+const XDG_TOPLEVEL_SET_MIN_SIZE: u32 = 8;
+const XDG_TOPLEVEL_SET_MAXIMIZED: u32 = 9;
+const XDG_TOPLEVEL_UNSET_MAXIMIZED: u32 = 10;
+const XDG_TOPLEVEL_SET_MINIMIZED: u32 = 13;
+
+static WL_REGISTRY_REQUESTS: [WlMessage; 1] = [
+	message!("bind", "usun"),
+];
+static WL_REGISTRY_EVENTS: [WlMessage; 2] = [
+	message!("global", "usu"),
+	message!("global_remove", "u"),
+];
+static WL_REGISTRY: WlInterface = interface!("wl_registry",
+	WL_REGISTRY_REQUESTS: 1, WL_REGISTRY_EVENTS: 2);
+
+static WL_COMPOSITOR_REQUESTS: [WlMessage; 2] = [
+	message!("create_surface", "n"),
+	message!("create_region", "n"),
+];
+static WL_COMPOSITOR: WlInterface = interface!("wl_compositor",
+	WL_COMPOSITOR_REQUESTS: 2, NO_MESSAGES: 0);
+
+static WL_SURFACE_REQUESTS: [WlMessage; 7] = [
+	message!("destroy", ""),
+	message!("attach", "?oii"),
+	message!("damage", "iiii"),
+	message!("frame", "n"),
+	message!("set_opaque_region", "?o"),
+	message!("set_input_region", "?o"),
+	message!("commit", ""),
+];
+static WL_SURFACE_EVENTS: [WlMessage; 2] = [
+	message!("enter", "o"),
+	message!("leave", "o"),
+];
+static WL_SURFACE: WlInterface = interface!("wl_surface",
+	WL_SURFACE_REQUESTS: 7, WL_SURFACE_EVENTS: 2);
+
+static WL_REGION_REQUESTS: [WlMessage; 3] = [
+	message!("destroy", ""),
+	message!("add", "iiii"),
+	message!("subtract", "iiii"),
+];
+static WL_REGION: WlInterface = interface!("wl_region",
+	WL_REGION_REQUESTS: 3, NO_MESSAGES: 0);
+
+static WL_SEAT_REQUESTS: [WlMessage; 3] = [
+	message!("get_pointer", "n"),
+	message!("get_keyboard", "n"),
+	message!("get_touch", "n"),
+];
+static WL_SEAT_EVENTS: [WlMessage; 1] = [
+	message!("capabilities", "u"),
+];
+static WL_SEAT: WlInterface = interface!("wl_seat",
+	WL_SEAT_REQUESTS: 3, WL_SEAT_EVENTS: 1);
+
+static WL_POINTER_REQUESTS: [WlMessage; 1] = [
+	message!("set_cursor", "u?oii"),
+];
+static WL_POINTER_EVENTS: [WlMessage; 5] = [
+	message!("enter", "uoff"),
+	message!("leave", "uo"),
+	message!("motion", "uff"),
+	message!("button", "uuuu"),
+	message!("axis", "uuf"),
+];
+static WL_POINTER: WlInterface = interface!("wl_pointer",
+	WL_POINTER_REQUESTS: 1, WL_POINTER_EVENTS: 5);
+
+static WL_KEYBOARD_EVENTS: [WlMessage; 5] = [
+	message!("keymap", "uhu"),
+	message!("enter", "uoa"),
+	message!("leave", "uo"),
+	message!("key", "uuuu"),
+	message!("modifiers", "uuuuu"),
+];
+static WL_KEYBOARD: WlInterface = interface!("wl_keyboard",
+	NO_MESSAGES: 0, WL_KEYBOARD_EVENTS: 5);
+
+static WL_TOUCH_EVENTS: [WlMessage; 5] = [
+	message!("down", "uuoiff"),
+	message!("up", "uui"),
+	message!("motion", "uiff"),
+	message!("frame", ""),
+	message!("cancel", ""),
+];
+static WL_TOUCH: WlInterface = interface!("wl_touch",
+	NO_MESSAGES: 0, WL_TOUCH_EVENTS: 5);
+
+static XDG_WM_BASE_REQUESTS: [WlMessage; 4] = [
+	message!("destroy", ""),
+	message!("create_positioner", "n"),
+	message!("get_xdg_surface", "no"),
+	message!("pong", "u"),
+];
+static XDG_WM_BASE_EVENTS: [WlMessage; 1] = [
+	message!("ping", "u"),
+];
+static XDG_WM_BASE: WlInterface = interface!("xdg_wm_base",
+	XDG_WM_BASE_REQUESTS: 4, XDG_WM_BASE_EVENTS: 1);
+
+static XDG_SURFACE_REQUESTS: [WlMessage; 5] = [
+	message!("destroy", ""),
+	message!("get_toplevel", "n"),
+	message!("get_popup", "n?oo"),
+	message!("set_window_geometry", "iiii"),
+	message!("ack_configure", "u"),
+];
+static XDG_SURFACE_EVENTS: [WlMessage; 1] = [
+	message!("configure", "u"),
+];
+static XDG_SURFACE: WlInterface = interface!("xdg_surface",
+	XDG_SURFACE_REQUESTS: 5, XDG_SURFACE_EVENTS: 1);
+
+static XDG_TOPLEVEL_REQUESTS: [WlMessage; 14] = [
+	message!("destroy", ""),
+	message!("set_parent", "?o"),
+	message!("set_title", "s"),
+	message!("set_app_id", "s"),
+	message!("show_window_menu", "ouii"),
+	message!("move", "ou"),
+	message!("resize", "ouu"),
+	message!("set_max_size", "ii"),
+	message!("set_min_size", "ii"),
+	message!("set_maximized", ""),
+	message!("unset_maximized", ""),
+	message!("set_fullscreen", "?o"),
+	message!("unset_fullscreen", ""),
+	message!("set_minimized", ""),
+];
+static XDG_TOPLEVEL_EVENTS: [WlMessage; 2] = [
+	message!("configure", "iia"),
+	message!("close", ""),
+];
+static XDG_TOPLEVEL: WlInterface = interface!("xdg_toplevel",
+	XDG_TOPLEVEL_REQUESTS: 14, XDG_TOPLEVEL_EVENTS: 2);
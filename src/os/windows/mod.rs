@@ -39,11 +39,43 @@ impl Class {
 }
 struct NativeWindow { native: HWND }
 impl NativeWindow {
-	fn create(connection: &Connection, class: Class) -> NativeWindow {
+	fn create(connection: &Connection, class: Class,
+		wh: Option<(u16, u16)>) -> NativeWindow
+	{
 		let c = connection.native;
 		let name = class.name;
 
-		NativeWindow { native: window_create::window_create(c, name) }
+		NativeWindow {
+			native: window_create::window_create(c, name, wh)
+		}
+	}
+}
+
+// Set the title bar & taskbar icon, from `(width, height, ARGB pixels)`.
+fn window_icon(window: HWND, icon: &(u16, u16, Vec<u32>)) {
+	use winapi::um::winuser::{
+		CreateIcon, SendMessageW, WM_SETICON, ICON_BIG, ICON_SMALL
+	};
+
+	let (w, h, ref pixels) = *icon;
+	if pixels.len() < w as usize * h as usize {
+		return;
+	}
+	// Little-endian ARGB is the BGRA a 32-bit icon wants.  The AND mask
+	// (1 bit per pixel, rows padded to 16 bits) is unused with alpha.
+	let mask = vec![0u8; (w as usize + 15) / 16 * 2 * h as usize];
+
+	unsafe {
+		let icon = CreateIcon(::std::ptr::null_mut(), w as c_int,
+			h as c_int, 1, 32, mask.as_ptr(),
+			pixels.as_ptr() as *const u8);
+		if icon.is_null() {
+			return;
+		}
+		SendMessageW(window, WM_SETICON, ICON_BIG as WPARAM,
+			icon as LPARAM);
+		SendMessageW(window, WM_SETICON, ICON_SMALL as WPARAM,
+			icon as LPARAM);
 	}
 }
 
@@ -54,19 +86,42 @@ pub struct Window {
 	wh: (u16, u16),
 }
 impl Window {
-	pub fn new(_title: &str, _icon: &::afi::Video, _v: Option<i32>) -> Self
-	{
+	pub fn new(_v: Option<i32>, hints: &::WindowHints) -> Self {
+		use winapi::shared::windef::RECT;
+		use winapi::um::winuser::GetClientRect;
+
+		// TODO: Decorations from `hints` (the title bar is always
+		// shown for now).
+		let title = if hints.title.is_empty() {
+			"awi"
+		} else {
+			&hints.title
+		};
 		let connection = Connection::create();
-		let class = Class::create(&connection, _title,
+		let class = Class::create(&connection, title,
 			window_poll_event::wnd_proc);
 		let window = NativeWindow::create(&connection,
-			class);
+			class, hints.wh);
+
+		if let Some(ref icon) = hints.icon {
+			window_icon(window.native, icon);
+		}
+
+		// Size inside the borders (maximized if there's no size).
+		let mut rect = RECT { left: 0, top: 0, right: 0, bottom: 0 };
+		unsafe { GetClientRect(window.native, &mut rect) };
+		let wh = ((rect.right - rect.left) as u16,
+			(rect.bottom - rect.top) as u16);
 
 		Window { connection: connection, window: window, miw: true,
-			wh: (640, 360),
+			wh,
 		}
 	}
 
+	pub fn needs_visual() -> bool {
+		true
+	}
+
 	pub fn poll_event(&mut self, input: &mut InputQueue,
 		keyboard: &mut ::Keyboard) -> bool
 	{
@@ -77,6 +132,27 @@ impl Window {
 			keyboard, &mut self.wh)
 	}
 
+	/// Block until there's a message to poll, or `timeout`.
+	pub fn wait(&mut self, timeout: Option<::std::time::Duration>) {
+		use winapi::um::winuser::{
+			MsgWaitForMultipleObjects, QS_ALLINPUT
+		};
+
+		const INFINITE: u32 = 0xFFFFFFFF;
+
+		let timeout = match timeout {
+			Some(t) => (t.as_secs().saturating_mul(1000)
+				+ (t.subsec_nanos() as u64 + 999_999) / 1_000_000)
+				.min(INFINITE as u64 - 1) as u32,
+			None => INFINITE,
+		};
+
+		unsafe {
+			MsgWaitForMultipleObjects(0, ::std::ptr::null(), 0,
+				timeout, QS_ALLINPUT);
+		}
+	}
+
 	pub fn get_connection(&self) -> ::WindowConnection {
 		::WindowConnection::Windows(self.connection.native as *mut c_void,
 			self.window.native as *mut c_void)
@@ -85,6 +161,229 @@ impl Window {
 	pub fn wh(&self) -> (u16, u16) {
 		self.wh
 	}
+
+	pub fn clipboard_set(&mut self, _text: &str) {
+		// TODO: SetClipboardData(CF_UNICODETEXT)
+	}
+
+	pub fn clipboard_get(&self) -> Option<String> {
+		// TODO: GetClipboardData(CF_UNICODETEXT)
+		None
+	}
+
+	pub fn position(&self) -> (i16, i16) {
+		use winapi::shared::windef::RECT;
+
+		let mut rect = RECT { left: 0, top: 0, right: 0, bottom: 0 };
+		unsafe {
+			::winapi::um::winuser::GetWindowRect(self.window.native,
+				&mut rect);
+		}
+		(rect.left as i16, rect.top as i16)
+	}
+
+	pub fn set_position(&mut self, xy: (i16, i16)) {
+		use winapi::um::winuser::{
+			SetWindowPos, SWP_NOSIZE, SWP_NOZORDER
+		};
+
+		// TODO: WM_MOVE, for `Event::Moved`.
+		unsafe {
+			SetWindowPos(self.window.native, ::std::ptr::null_mut(),
+				xy.0 as c_int, xy.1 as c_int, 0, 0,
+				SWP_NOSIZE | SWP_NOZORDER);
+		}
+	}
+
+	pub fn set_text_input_rect(&mut self,
+		rect: Option<(i16, i16, u16, u16)>)
+	{
+		use winapi::um::imm::{
+			ImmGetContext, ImmReleaseContext, ImmSetCompositionWindow,
+			COMPOSITIONFORM, CFS_POINT
+		};
+		use winapi::shared::windef::{ POINT, RECT };
+
+		// Turning off is done by not sending the events.
+		let (x, y, _, h) = match rect {
+			Some(rect) => rect,
+			None => return,
+		};
+
+		unsafe {
+			let himc = ImmGetContext(self.window.native);
+			let mut form = COMPOSITIONFORM {
+				dwStyle: CFS_POINT,
+				// Below the text.
+				ptCurrentPos: POINT {
+					x: x as i32,
+					y: y as i32 + h as i32,
+				},
+				rcArea: RECT { left: 0, top: 0, right: 0, bottom: 0 },
+			};
+			ImmSetCompositionWindow(himc, &mut form);
+			ImmReleaseContext(self.window.native, himc);
+		}
+	}
+
+	pub fn scale_factor(&self) -> f32 {
+		// TODO: GetDpiForWindow & WM_DPICHANGED
+		1.0
+	}
+
+	pub fn has_focus(&self) -> bool {
+		use winapi::um::winuser::GetForegroundWindow;
+
+		unsafe { GetForegroundWindow() == self.window.native }
+	}
+
+	pub fn set_cursor(&mut self, _cursor: ::Cursor) {
+		// TODO: LoadCursorW & SetCursor
+	}
+
+	pub fn set_cursor_position(&mut self, xy: (i16, i16)) {
+		use winapi::shared::windef::POINT;
+		use winapi::um::winuser::{ ClientToScreen, SetCursorPos };
+
+		// TODO: Leave out the WM_MOUSEMOVE this makes.
+		let mut point = POINT { x: xy.0 as i32, y: xy.1 as i32 };
+		unsafe {
+			ClientToScreen(self.window.native, &mut point);
+			SetCursorPos(point.x, point.y);
+		}
+	}
+
+	pub fn close(&mut self) {
+		unsafe {
+			::winapi::um::winuser::DestroyWindow(self.window.native);
+		}
+	}
+
+	pub fn set_always_on_top(&mut self, above: bool) -> bool {
+		use winapi::um::winuser::{
+			SetWindowPos, HWND_TOPMOST, HWND_NOTOPMOST, SWP_NOMOVE,
+			SWP_NOSIZE
+		};
+
+		unsafe {
+			SetWindowPos(self.window.native, if above {
+				HWND_TOPMOST
+			} else {
+				HWND_NOTOPMOST
+			}, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE) != 0
+		}
+	}
+
+	pub fn set_opacity(&mut self, opacity: f32) {
+		use winapi::um::winuser::{
+			GetWindowLongW, SetWindowLongW,
+			SetLayeredWindowAttributes, GWL_EXSTYLE, WS_EX_LAYERED,
+			LWA_ALPHA
+		};
+
+		let alpha = (opacity.max(0.0).min(1.0) * 255.0) as u8;
+
+		unsafe {
+			// Only layered windows can be see-through.
+			let style = GetWindowLongW(self.window.native,
+				GWL_EXSTYLE) as u32;
+			SetWindowLongW(self.window.native, GWL_EXSTYLE,
+				(style | WS_EX_LAYERED) as i32);
+			SetLayeredWindowAttributes(self.window.native, 0, alpha,
+				LWA_ALPHA);
+		}
+	}
+
+	pub fn set_class(&mut self, _instance: &str, _class: &str) {
+		// TODO: The taskbar groups by AppUserModelID, which is set
+		// with the window's property store
+		// (SHGetPropertyStoreForWindow).
+	}
+
+	pub fn set_input_shape(&mut self,
+		_region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		// TODO: Hit-test with WM_NCHITTEST (HTTRANSPARENT only passes
+		// clicks to windows of the same thread), or a layered window
+		// with a color key.
+		false
+	}
+
+	pub fn set_keyboard_grab(&mut self, _grab: bool) -> bool {
+		// TODO: A low-level keyboard hook (WH_KEYBOARD_LL), to keep
+		// the Windows key & Alt-Tab.
+		false
+	}
+
+	pub fn set_decorated(&mut self, decorated: bool) {
+		use winapi::um::winuser::{
+			GetWindowLongW, SetWindowLongW, SetWindowPos, GWL_STYLE,
+			WS_OVERLAPPEDWINDOW, WS_POPUP, SWP_FRAMECHANGED, SWP_NOMOVE,
+			SWP_NOSIZE, SWP_NOZORDER
+		};
+
+		unsafe {
+			let style = GetWindowLongW(self.window.native, GWL_STYLE)
+				as u32;
+			let style = if decorated {
+				(style & !WS_POPUP) | WS_OVERLAPPEDWINDOW
+			} else {
+				(style & !WS_OVERLAPPEDWINDOW) | WS_POPUP
+			};
+			SetWindowLongW(self.window.native, GWL_STYLE,
+				style as i32);
+			SetWindowPos(self.window.native, ::std::ptr::null_mut(),
+				0, 0, 0, 0, SWP_FRAMECHANGED | SWP_NOMOVE
+				| SWP_NOSIZE | SWP_NOZORDER);
+		}
+	}
+
+	pub fn begin_drag(&mut self) -> bool {
+		use winapi::um::winuser::{
+			ReleaseCapture, SendMessageW, WM_NCLBUTTONDOWN, HTCAPTION
+		};
+
+		// Pretend the title bar was clicked.
+		unsafe {
+			ReleaseCapture();
+			SendMessageW(self.window.native, WM_NCLBUTTONDOWN,
+				HTCAPTION as WPARAM, 0);
+		}
+		true
+	}
+
+	pub fn minimize(&mut self) -> bool {
+		self.show(::winapi::um::winuser::SW_MINIMIZE)
+	}
+
+	pub fn maximize(&mut self) -> bool {
+		self.show(::winapi::um::winuser::SW_MAXIMIZE)
+	}
+
+	pub fn restore(&mut self) -> bool {
+		self.show(::winapi::um::winuser::SW_RESTORE)
+	}
+
+	// WM_SIZE & WM_ACTIVATE come after, same as when the user does it.
+	fn show(&mut self, cmd: c_int) -> bool {
+		unsafe {
+			::winapi::um::winuser::ShowWindow(self.window.native, cmd);
+		}
+		true
+	}
+
+	pub fn set_min_size(&mut self, _wh: (u16, u16)) {
+		// TODO: WM_GETMINMAXINFO
+	}
+
+	pub fn set_max_size(&mut self, _wh: (u16, u16)) {
+		// TODO: WM_GETMINMAXINFO
+	}
+
+	pub fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		// TODO: WM_SIZING
+		aspect.is_none()
+	}
 }
 
 // Keycode translator
@@ -92,25 +391,26 @@ fn key(physical_key: c_int) -> Option<u8> {
 	const RCTRL: c_int = (17 | (0b_1_0001_1101 << 16));
 	const RSHIFT: c_int = (16 | (0b_0011_0110 << 16));
 	const ALT_GR: c_int = (18 | (0b_1_0011_1000 << 16));
+	const KP_ENTER: c_int = (13 | (0b_1_0001_1100 << 16));
 
 	Some(match physical_key {
 		192 => keyboard::EXT_BACKTICK,
 		107 => keyboard::EXT_PLUS,
 		106 => keyboard::EXT_ASTERISK,
-		111 | 191 => keyboard::SLASH,
+		191 => keyboard::SLASH,
 		13 => keyboard::ENTER,
-		49 | 97 => keyboard::NUM1,
-		50 | 98 => keyboard::NUM2,
-		51 | 99 => keyboard::NUM3,
-		52 | 100 => keyboard::NUM4,
-		53 | 101 => keyboard::NUM5,
-		54 | 102 => keyboard::NUM6,
-		55 | 103 => keyboard::NUM7,
-		56 | 104 => keyboard::NUM8,
-		57 | 105 => keyboard::NUM9,
-		48 | 96 => keyboard::NUM0,
-		190 | 110 => keyboard::PERIOD,
-		189 | 109 => keyboard::MINUS,
+		49 => keyboard::NUM1,
+		50 => keyboard::NUM2,
+		51 => keyboard::NUM3,
+		52 => keyboard::NUM4,
+		53 => keyboard::NUM5,
+		54 => keyboard::NUM6,
+		55 => keyboard::NUM7,
+		56 => keyboard::NUM8,
+		57 => keyboard::NUM9,
+		48 => keyboard::NUM0,
+		190 => keyboard::PERIOD,
+		189 => keyboard::MINUS,
 		187 => keyboard::EQUAL_SIGN,
 		8 => keyboard::BACKSPACE,
 		9 => keyboard::TAB,
@@ -165,6 +465,33 @@ fn key(physical_key: c_int) -> Option<u8> {
 		37 => keyboard::LEFT,
 		39 => keyboard::RIGHT,
 		40 => keyboard::DOWN,
+		112 => keyboard::EXT_F1,
+		113 => keyboard::EXT_F2,
+		114 => keyboard::EXT_F3,
+		115 => keyboard::EXT_F4,
+		116 => keyboard::EXT_F5,
+		117 => keyboard::EXT_F6,
+		118 => keyboard::EXT_F7,
+		119 => keyboard::EXT_F8,
+		120 => keyboard::EXT_F9,
+		121 => keyboard::EXT_F10,
+		122 => keyboard::EXT_F11,
+		123 => keyboard::EXT_F12,
+		// TODO: With NumLock off, numpad sends VK_HOME, etc.
+		96 => keyboard::EXT_KP0,
+		97 => keyboard::EXT_KP1,
+		98 => keyboard::EXT_KP2,
+		99 => keyboard::EXT_KP3,
+		100 => keyboard::EXT_KP4,
+		101 => keyboard::EXT_KP5,
+		102 => keyboard::EXT_KP6,
+		103 => keyboard::EXT_KP7,
+		104 => keyboard::EXT_KP8,
+		105 => keyboard::EXT_KP9,
+		KP_ENTER => keyboard::EXT_KP_ENTER,
+		111 => keyboard::EXT_KP_DIVIDE,
+		109 => keyboard::EXT_KP_MINUS,
+		110 => keyboard::EXT_KP_PERIOD,
 		_ => return None,
 	} )
 }
\ No newline at end of file
@@ -6,18 +6,21 @@
 use std::ptr::null_mut;
 
 use winapi::um::winuser::{
-	WS_OVERLAPPEDWINDOW, WS_VISIBLE, WS_SYSMENU,
-	CreateWindowExW, AdjustWindowRect
+	WS_OVERLAPPEDWINDOW, WS_VISIBLE, WS_SYSMENU, SW_MAXIMIZE,
+	CreateWindowExW, AdjustWindowRect, ShowWindow
 };
 use winapi::shared::windef::{ HWND, RECT };
 use winapi::shared::minwindef::HINSTANCE;
 
 const WS_FLAGS : u32 = WS_OVERLAPPEDWINDOW | WS_VISIBLE | WS_SYSMENU;
 
+// `wh` is the size inside the borders, `None` for maximized.
 pub fn window_create(connection: HINSTANCE,
-	name: [u8; 80]) -> HWND
+	name: [u8; 80], wh: Option<(u16, u16)>) -> HWND
 {
-	let mut wr = RECT { left: 0, top: 0, right: 640, bottom: 480 };
+	let (w, h) = wh.unwrap_or((640, 480));
+	let mut wr = RECT { left: 0, top: 0, right: w as i32,
+		bottom: h as i32 };
 	unsafe {
 		AdjustWindowRect(&mut wr, WS_OVERLAPPEDWINDOW, 0)
 	};
@@ -27,8 +30,8 @@ pub fn window_create(connection: HINSTANCE,
 		&name as *const _ as *const _,		// app name TODO: should be utf16?
 		WS_FLAGS,	// window style
 		0, 0,		// x/y coords
-		640 as i32,	// width, TODO: need to pass in Width?
-		480 as i32,	// height, TODO: need to pass in Height?
+		wr.right - wr.left,	// width, with borders
+		wr.bottom - wr.top,	// height, with borders
 		null_mut(),	// handle to parent
 		null_mut(),	// handle to menu
 		connection,	// hInstance
@@ -37,5 +40,8 @@ pub fn window_create(connection: HINSTANCE,
 	if window.is_null() {
 		panic!("Couldn't Create a Window!");
 	}
+	if wh.is_none() {
+		unsafe { ShowWindow(window, SW_MAXIMIZE) };
+	}
 	window
 }
@@ -6,6 +6,7 @@
 use std::ptr::null_mut;
 
 use input;
+use ScrollKind;
 use super::key;
 // use input::keyboard::{ english, FSC, ESC }; TODO
 
@@ -14,19 +15,28 @@ use winapi::um::winuser::{
 	WM_RBUTTONDOWN, WM_RBUTTONUP, WM_CLOSE, WM_MOUSEMOVE, WM_HSCROLL, 
 	WM_CHAR, WM_SYSCHAR, VK_RSHIFT,
 	PeekMessageW, TranslateMessage, DispatchMessageW, GetCursorPos,
-	ScreenToClient, PostQuitMessage, DefWindowProcW,
+	ScreenToClient, PostQuitMessage, DefWindowProcW, GetUpdateRect,
 	MSG,
 };
+use winapi::um::imm::{
+	ImmGetContext, ImmGetCompositionStringW, ImmReleaseContext,
+	GCS_COMPSTR, GCS_CURSORPOS,
+};
 use winapi::ctypes::c_int;
-use winapi::shared::windef::{ HWND, POINT };
+use winapi::shared::windef::{ HWND, POINT, RECT };
 use winapi::shared::minwindef::{ WPARAM, LPARAM, LRESULT, HIWORD, LOWORD, DWORD };
 
+// TODO: Per-window (GWLP_USERDATA), so more than one window works.
 static mut ADI_WNDPROCMSG : u8 = 0b0000_0000;
 static mut AWI_DIMENSIONS: (u16, u16) = (0, 0);
+static mut AWI_PREEDIT: Option<(String, usize)> = None;
+static mut AWI_DAMAGE: Vec<(u16, u16, u16, u16)> = Vec::new();
 
 const RESIZED: u8 = 0b1000_0000;
 const PAUSED: u8 = 0b0100_0000;
 const RESUMED: u8 = 0b0010_0000;
+const PREEDIT: u8 = 0b0001_0000;
+const PAINTED: u8 = 0b0000_1000;
 
 pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 	l_param: LPARAM) -> LRESULT
@@ -34,6 +44,19 @@ pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 	match u_msg {
 		0x0007 => unsafe { ADI_WNDPROCMSG |= RESUMED },
 		0x0008 => unsafe { ADI_WNDPROCMSG |= PAUSED },
+		// WM_PAINT (DefWindowProcW validates it, so get the rectangle
+		// that has to be redrawn first)
+		0x000F => unsafe {
+			let mut rect: RECT = ::std::mem::zeroed();
+
+			if GetUpdateRect(h_wnd, &mut rect, 0) != 0 {
+				AWI_DAMAGE.push((rect.left as u16,
+					rect.top as u16,
+					(rect.right - rect.left) as u16,
+					(rect.bottom - rect.top) as u16));
+			}
+			ADI_WNDPROCMSG |= PAINTED;
+		},
 		0x0010 => {
 			unsafe { PostQuitMessage(0) }; // Successful exit
 			return 1; // TRUE = Don't Close Window Yet
@@ -48,6 +71,18 @@ pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 
 			return 0;
 		},
+		// WM_IME_COMPOSITION (DefWindowProcW makes WM_CHAR for results)
+		0x010F => if l_param as u32 & GCS_COMPSTR != 0 {
+			unsafe {
+				AWI_PREEDIT = Some(composition(h_wnd));
+				ADI_WNDPROCMSG |= PREEDIT;
+			}
+		},
+		// WM_IME_ENDCOMPOSITION
+		0x010E => unsafe {
+			AWI_PREEDIT = Some((String::new(), 0));
+			ADI_WNDPROCMSG |= PREEDIT;
+		},
 		_ => {},
 	}
 
@@ -56,6 +91,34 @@ pub extern "system" fn wnd_proc(h_wnd: HWND, u_msg: u32, w_param: WPARAM,
 	}
 }
 
+// Notched wheels send multiples of WHEEL_DELTA (120), touchpads send less.
+fn scroll_kind(delta: i16) -> ScrollKind {
+	if delta % 120 == 0 {
+		ScrollKind::Line
+	} else {
+		ScrollKind::Precise
+	}
+}
+
+// Get the IME composition string, and cursor position (in chars).
+unsafe fn composition(window: HWND) -> (String, usize) {
+	let himc = ImmGetContext(window);
+	// Length is in bytes.
+	let len = ImmGetCompositionStringW(himc, GCS_COMPSTR, null_mut(), 0);
+	let mut utf16 = vec![0u16; len.max(0) as usize / 2];
+	ImmGetCompositionStringW(himc, GCS_COMPSTR,
+		utf16.as_mut_ptr() as *mut _, len.max(0) as u32);
+	// Cursor position is in UTF-16 code units.
+	let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, null_mut(),
+		0).max(0) as usize;
+	ImmReleaseContext(window, himc);
+
+	let cursor = String::from_utf16_lossy(&utf16[..cursor.min(utf16.len())])
+		.chars().count();
+
+	(String::from_utf16_lossy(&utf16), cursor)
+}
+
 fn get_mouse(window: HWND, is_miw: &mut bool) -> (i16, i16, bool) {
 	let (w, h) = unsafe { AWI_DIMENSIONS };
 	let mut pos = POINT { x: 0, y: 0 };
@@ -76,9 +139,15 @@ fn get_mouse(window: HWND, is_miw: &mut bool) -> (i16, i16, bool) {
 	(pos.x as i16, pos.y as i16, miw_changed)
 }
 
+// The scancode (with the extended bit) from a key message's lParam.
+fn scancode(l: LPARAM) -> u32 {
+	((l >> 16) & 0b1_11111111) as u32
+}
+
 fn create_key_id(w: WPARAM, l: LPARAM) -> c_int {
 	const RCONTROL : c_int = 17 | (0b_1_0001_1101 << 16);
 	const RALT: c_int = 18 | (0b_1_0011_1000 << 16);
+	const KP_ENTER: c_int = 13 | (0b_1_0001_1100 << 16);
 
 	let scan = l & 0b00000001_11111111_00000000_00000000;
 	
@@ -86,6 +155,7 @@ fn create_key_id(w: WPARAM, l: LPARAM) -> c_int {
 		d @ VK_RSHIFT => d,
 		d @ RCONTROL => d,
 		d @ RALT => d,
+		d @ KP_ENTER => d,
 		_ => (w as c_int),
 	}
 }
@@ -106,6 +176,23 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 		return true;
 	}
 	
+	if unsafe { ADI_WNDPROCMSG & PREEDIT != 0 } {
+		if let Some((text, cursor)) = unsafe { AWI_PREEDIT.take() } {
+			queue.preedit(text, cursor);
+		}
+		unsafe { ADI_WNDPROCMSG &= !PREEDIT };
+		return true;
+	}
+
+	if unsafe { ADI_WNDPROCMSG & PAINTED != 0 } {
+		for rect in unsafe { AWI_DAMAGE.drain(..) } {
+			queue.damage(rect);
+		}
+		queue.ready();
+		unsafe { ADI_WNDPROCMSG &= !PAINTED };
+		return true;
+	}
+
 	if unsafe { ADI_WNDPROCMSG & RESUMED != 0 } {
 		queue.resume();
 		unsafe { ADI_WNDPROCMSG &= !RESUMED };
@@ -132,6 +219,8 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 
 	match msg.message {
 		WM_CLOSE => queue.back(),
+		// TODO: RegisterRawInputDevices & WM_INPUT, for
+		// `Event::MotionRaw`.
 		WM_MOUSEMOVE => queue.cursor_move(wh, (x, y)),
 		WM_LBUTTONDOWN => queue.left_button_press(wh, (x, y)),
 		WM_LBUTTONUP => queue.left_button_release(wh, (x, y)),
@@ -142,13 +231,17 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 		0x0100 | 0x0104 => {
 			let detail = create_key_id(msg.wParam, msg.lParam);
 			
+			keyboard.press_raw(scancode(msg.lParam));
 			if let Some(key) = key(detail) {
 				keyboard.press(key);
 
 				// Required to generate CHAR & SYSCHAR
 				unsafe { TranslateMessage(&msg); }
-			} else if detail == 27 { // escape
-				queue.back();
+			} else {
+				keyboard.unknown(scancode(msg.lParam));
+				if detail == 27 { // escape
+					queue.back();
+				}
 			}
 		}
 		0x0101 | 0x0105 => {
@@ -165,6 +258,7 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 //				keyboard.release(input::key(RSHIFT).unwrap());
 //			}
 
+			keyboard.release_raw(scancode(msg.lParam));
 			if let Some(key) = key(detail) {
 				keyboard.release(key);
 			}
@@ -181,25 +275,17 @@ pub fn window_poll_event(window: HWND, queue: &mut input::InputQueue,
 			let a = (((msg.wParam as u32) >> 16) & 0xFFFF)
 				as i16;
 
-			if a > 0 {
-				queue.scroll(wh, (x, y),
-					(a as f32 / -120.0, 0.0));
-			} else {
-				queue.scroll(wh, (x, y),
-					(a as f32 / 120.0, 0.0));
-			}
+			// Positive is right.
+			queue.scroll(wh, (x, y), (a as f32 / 120.0, 0.0),
+				scroll_kind(a));
 		}
 		WM_VSCROLL => {
 			let a = (((msg.wParam as u32) >> 16) & 0xFFFF)
 				as i16;
 
-			if a > 0 {
-				queue.scroll(wh, (x, y),
-					(0.0, a as f32 / -120.0));
-			} else {
-				queue.scroll(wh, (x, y),
-					(0.0, a as f32 / 120.0));
-			}
+			// Positive is away from the user (up).
+			queue.scroll(wh, (x, y), (0.0, a as f32 / -120.0),
+				scroll_kind(a));
 		}
 		// ignore all other messages
 		_ => {
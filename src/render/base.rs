@@ -10,10 +10,14 @@
 extern crate ami;
 
 use std::cmp::Ordering;
+use std::time::Duration;
 
 pub use	afi;
 pub use afi::VFrame;
 pub use Event;
+pub use Drain;
+pub use Cursor;
+pub use ControllerButton;
 pub use self::ami::*;
 pub use std::f32::consts::PI;
 
@@ -24,44 +28,281 @@ pub trait Display {
 	/// * `color`: The background color for the display.
 	fn color(&mut self, color: (u8, u8, u8)) -> ();
 
+	/// Set the background color with alpha, so the window can be see-through
+	/// (if there's a compositor, and it was built with
+	/// `DisplayBuilder::transparent()`).
+	fn color_rgba(&mut self, color: (u8, u8, u8, u8)) -> ();
+
+	/// Set the fog for the `Display`, used by shapes that have `fog` set.
+	///
+	/// * `color`: The color of the fog (usually the same as the background).
+	/// * `near`: The distance fog starts.
+	/// * `far`: The distance where the fog completely covers the shape.
+	fn fog(&mut self, color: [f32; 4], near: f32, far: f32) -> ();
+
+	/// Set the camera for the `Display`, used by shapes that have `camera`
+	/// set.
+	///
+	/// * `position`: The position of the camera.
+	/// * `rotation`: The rotation of the camera (radians around the X, Y
+	///   and Z axis).
+	fn camera(&mut self, position: Vector, rotation: Vector) -> ();
+
+	/// Set the projection for the `Display` (default is perspective, with
+	/// a 90° field of view).
+	fn set_projection(&mut self, projection: Projection) -> ();
+
+	/// Set the distances of the near & far clip planes of perspective
+	/// projections (default 0.1 & 100).  Keep `far / near` small, since
+	/// depth precision is spread between them (less z-fighting).
+	fn set_clip_planes(&mut self, near: f32, far: f32) -> ();
+
+	/// Get the current projection matrix (from `set_projection()` and the
+	/// window's aspect ratio).
+	fn projection(&self) -> Matrix;
+
+	/// Get the current view matrix (from `camera()`).
+	fn view(&self) -> Matrix;
+
+	/// Get the point in the world at cursor coordinates `ndc` (-1.0 to 1.0,
+	/// like `Event::Cursor`) and `depth` (-1.0 at the near plane to 1.0 at
+	/// the far plane), by inverting projection × view.  Cast a ray from
+	/// depth -1.0 to 1.0 for click-to-world picking.
+	fn unproject(&self, ndc: (f32, f32), depth: f32) -> Vector {
+		let matrix: [f32; 16] = (self.projection() * self.view()).into();
+
+		unproject(&matrix, ndc, depth)
+	}
+
+	/// Get the shape that's drawn at cursor coordinates `x`, `y` (-1.0 to
+	/// 1.0), by drawing every shape's ID offscreen.  `None` if there isn't
+	/// one.
+	fn pick(&mut self, x: f32, y: f32) -> Option<Shape>;
+
+	/// Set whether opaque shapes are sorted nearest first (default true).
+	/// They're drawn correctly either way, with depth testing.
+	fn set_opaque_sort(&mut self, sort: bool) -> ();
+
+	/// Send `Event::Timestep` every `step`, instead of once per frame by
+	/// `Screen` (zero to go back).
+	fn set_timestep(&mut self, step: Duration) -> ();
+
+	/// Stop the fixed timestep between `Event::Pause` & `Event::Resume`,
+	/// instead of catching up on resume (default off).
+	fn set_pause_halts_timestep(&mut self, halts: bool) -> ();
+
+	/// Turn vsync on or off (default on).  Returns `false` if it couldn't
+	/// be changed (some drivers ignore it).
+	fn set_vsync(&mut self, vsync: bool) -> bool;
+
+	/// Choose between vsync (`LatencyMode::Smooth`, default) and adaptive
+	/// vsync (`LatencyMode::LowLatency`).  Returns `false` if adaptive
+	/// vsync isn't supported, and it falls back to vsync.
+	fn set_latency_mode(&mut self, mode: LatencyMode) -> bool;
+
+	/// Set how shapes with `blending` are blended (default is
+	/// `BlendMode::Straight`).
+	fn set_blend_mode(&mut self, mode: BlendMode) -> ();
+
+	/// Turn gamma-correct (sRGB) rendering on or off (default off).
+	/// Blending happens in linear space, and textures made after turning it
+	/// on are decoded from sRGB.  `pick()` still works, since it draws the
+	/// IDs into a linear target with sRGB encoding off (they'd decode wrong
+	/// if gamma-encoded).  Returns `false` if it's not supported.
+	fn set_srgb(&mut self, srgb: bool) -> bool;
+
+	/// Draw the edges of triangles instead of filling them, for debugging
+	/// (default off).  Returns `false` if it's not supported.
+	fn set_wireframe(&mut self, wireframe: bool) -> bool;
+
+	/// Turn a shape's alpha into how much of each pixel it covers, for
+	/// alpha shapes (default off).  Cutouts like foliage then don't need
+	/// sorting, but it only works on a multisampled window.  Returns
+	/// `false` if it's not supported.
+	fn set_alpha_to_coverage(&mut self, enabled: bool) -> bool;
+
+	/// Set which faces aren't drawn (default `Cull::Back`), for shapes
+	/// without their own from `set_shape_cull()`.  Returns `false` if it's
+	/// not supported.
+	fn set_cull(&mut self, cull: Cull) -> bool;
+
+	/// Send rendering errors (and the driver's debug messages, if it has
+	/// them) to `callback`, instead of panicking in debug builds and
+	/// ignoring them in release builds.  Returns `false` if it's not
+	/// supported.
+	fn set_debug_callback(&mut self, callback: Box<Fn(&str)>) -> bool;
+
 	/// Get input, if there's any.
 	fn input(&mut self) -> Option<Event>;
 
-	/// Update the `Display`.
-	fn update(&mut self) -> f32;
+	/// Block until there's input, instead of polling like `input()`.
+	/// `None` once the window is closed.
+	fn wait_event(&mut self) -> Option<Event>;
+
+	/// Block until there's input, or return `None` after `timeout`.
+	fn wait_event_timeout(&mut self, timeout: Duration) -> Option<Event>;
+
+	/// Get all of the input at once, in the same order as `input()`.
+	fn drain(&mut self) -> Drain;
+
+	/// Get the first input that `pred` matches (in the same order as
+	/// `input()`), leaving the rest for `input()`.
+	fn pop_matching(&mut self, pred: &Fn(&Event) -> bool) -> Option<Event>;
+
+	/// Send one `Event::Timestep` on the next update, waking up
+	/// `wait_event()`, so animations (spinners, transitions) can run while
+	/// there's no input.  Call it every frame that's still animating.
+	fn request_frame(&mut self);
+
+	/// Keep the last `capacity` events from `input()` (default 0, off), so
+	/// they can be printed with `event_history()` when input misbehaves.
+	fn set_event_history(&mut self, capacity: usize);
+
+	/// Get the events kept by `set_event_history()`, oldest first.
+	fn event_history(&self) -> &[Event];
+
+	/// Get the file descriptor of the window system's connection (after
+	/// flushing it), for registering with an async reactor: when it can be
+	/// read, `input()` has events.  `None` if there isn't one.
+	#[cfg(feature = "async")]
+	fn fd(&mut self) -> Option<i32>;
+
+	/// Wake `waker` when `input()` might have events (from one thread per
+	/// window, for `next_event()`).  Returns `false` if there's nothing to
+	/// wait on.
+	#[cfg(feature = "async")]
+	fn wake_on_input(&mut self, waker: ::std::task::Waker) -> bool;
+
+	/// Update the `Display`, same as `render()` then `present()`.  Returns
+	/// the frame's delta time, and whether frames are taking longer than
+	/// `set_target_fps()` allows.
+	fn update(&mut self) -> FrameStatus {
+		self.render();
+		self.present()
+	}
+
+	/// Draw the shapes, without showing them yet (so more can be drawn on
+	/// top with the graphics API).  If nothing changed since the last
+	/// frame, and none of the window was uncovered, the last frame is kept
+	/// instead (and `present()` doesn't swap).  With OpenGL, the bound
+	/// program, texture & buffer, blending and the viewport are set again
+	/// after raw GL calls, but other state (like vertex attribute arrays
+	/// and the framebuffer) has to be put back the way it was.
+	fn render(&mut self);
+
+	/// Show what's been drawn (swap buffers).  Returns the same as
+	/// `update()`.
+	fn present(&mut self) -> FrameStatus;
+
+	/// Draw the shapes into `texture` instead of the window (for mirrors,
+	/// minimaps or post-processing), at the texture's size & aspect ratio.
+	/// Shapes drawn this way shouldn't use `texture`.  Returns `false`
+	/// (drawing nothing) if it's not supported.
+	fn render_to(&mut self, texture: &Texture) -> bool;
+
+	/// Get the average time it takes to render a frame, over the last few
+	/// frames.
+	fn frame_time(&self) -> Duration;
+
+	/// Get the average frames per second, over the last few frames.
+	fn fps(&self) -> f32;
+
+	/// Set the frame rate to keep up with, for `FrameStatus::over_budget`
+	/// (0 for none, the default).
+	fn set_target_fps(&mut self, fps: f32) -> ();
 
 	/// Create a new `Model` for this `Display`.
 	fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>) -> Model;
 
+	/// Create a new `Model` of lines (each pair of vertices is a line).
+	fn model_lines(&mut self, vertices: &[f32]) -> Model;
+
+	/// Create a new `Model` of points (one per vertex).
+	fn model_points(&mut self, vertices: &[f32]) -> Model;
+
 	/// Create a new `Texture` for this `Display`.
 	fn texture(&mut self, wh: (u16,u16), graphic: &VFrame) -> Texture;
 
+	/// Create a new `Texture` from already compressed blocks, which stay
+	/// compressed on the GPU.  Fails if the `Display` can't use `format`,
+	/// or `data` is the wrong size.
+	fn texture_compressed(&mut self, wh: (u16,u16),
+		format: CompressedFormat, data: &[u8])
+		-> Result<Texture, String>;
+
+	/// Create a new `Texture` from pixels in another `format` (like BGRA
+	/// from a screenshot, or one channel for masks).  They're converted to
+	/// RGBA first, unless the GPU can upload them as they are.  Fails if
+	/// `data` is the wrong size.
+	fn texture_fmt(&mut self, wh: (u16,u16), format: PixelFormat,
+		data: &[u8]) -> Result<Texture, String>
+	{
+		format.check(wh, data)?;
+
+		Ok(self.texture(wh, &VFrame(format.to_rgba(data))))
+	}
+
 	/// Create a new `Gradient` for this `Display`.
 	fn gradient(&mut self, colors: &[f32]) -> Gradient;
 
 	/// Create new `TexCoords` for this `Display`.
 	fn texcoords(&mut self, texcoords: &[(f32, f32)]) -> TexCoords;
 
+	/// Create new `TexCoords` for a quad showing the pixel rectangle `(x,
+	/// y, w, h)` of `tex` (a sprite in an atlas).  The corners are top-left,
+	/// top-right, bottom-right, bottom-left.  They're inset by half a pixel,
+	/// so neighboring sprites don't bleed in at the edges.
+	fn atlas_texcoords(&mut self, tex: &Texture,
+		pixel_rect: (u16, u16, u16, u16)) -> TexCoords
+	{
+		let (w, h) = (tex.1 as f32, tex.2 as f32);
+		let (x, y) = (pixel_rect.0 as f32, pixel_rect.1 as f32);
+		let left = (x + 0.5) / w;
+		let top = (y + 0.5) / h;
+		let right = (x + pixel_rect.2 as f32 - 0.5) / w;
+		let bottom = (y + pixel_rect.3 as f32 - 0.5) / h;
+
+		self.texcoords(&[(left, top), (right, top), (right, bottom),
+			(left, bottom)])
+	}
+
 	/// Set the pixels for a `Texture`.
 	fn set_texture(&mut self, texture: &mut Texture, wh: (u16,u16),
 		graphic: &VFrame) -> ();
 
+	/// Read back the pixels of a `Texture` (width, height & RGBA pixels,
+	/// in the same order as `set_texture()`).  No pixels if it's not
+	/// supported.
+	fn read_texture(&self, texture: &Texture) -> (u16, u16, Vec<[u8; 4]>);
+
+	/// Replace the vertices & fans of a `Model` in place (to animate a
+	/// deforming mesh).  Shapes made from the `Model` change too.
+	fn set_model(&mut self, model: &Model, vertices: &[f32],
+		fans: Vec<(u32, u32)>) -> ();
+
 	/// Create a new shape with a solid color.
 	fn shape_solid(&mut self, model: &Model, transform: Matrix,
 		color: [f32; 4], blending: bool, fog: bool, camera: bool)
 		-> Shape;
 
-	/// Create a new shape shaded by a gradient (1 color per vertex).
+	/// Create many shapes with solid colors at once (like calling
+	/// `shape_solid()` for each, but only allocating once).
+	fn shapes_solid(&mut self, shapes: &[(Model, Matrix, [f32; 4])],
+		blending: bool, fog: bool, camera: bool) -> Vec<Shape>;
+
+	/// Create a new shape shaded by a gradient (1 color per vertex).  Fails
+	/// if the `Gradient` doesn't have a color for each vertex (the same goes
+	/// for the `TexCoords` of the textured shapes below).
 	fn shape_gradient(&mut self, model: &Model, transform: Matrix,
 		gradient: Gradient, blending: bool, fog: bool, camera: bool)
-		-> Shape;
+		-> Result<Shape, ShapeError>;
 
 	/// Create a new shape shaded by a texture using texture coordinates.
 	///
 	/// Texture Coordinates follow this format (X, Y, UNUSED(1.0), ALPHA)
 	fn shape_texture(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, blending: bool,
-		fog: bool, camera: bool) -> Shape;
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>;
 
 	/// Create a new shape shaded by a texture using texture coordinates
 	/// and alpha.
@@ -69,7 +310,7 @@ pub trait Display {
 	/// Texture Coordinates follow this format (X, Y, UNUSED(1.0), ALPHA)
 	fn shape_faded(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, alpha: f32,
-		fog: bool, camera: bool) -> Shape;
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>;
 
 	/// Create a new shape shaded by a texture using texture coordinates
 	/// and tint.
@@ -77,7 +318,46 @@ pub trait Display {
 	/// Texture Coordinates follow this format (X, Y, UNUSED(1.0), ALPHA)
 	fn shape_tinted(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, tint: [f32; 4],
-		blending: bool, fog: bool, camera: bool) -> Shape;
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>;
+
+	/// Create a new shape of text, from a model & texture coordinates
+	/// made with `text_model()`.  `texture` is the glyph atlas: pixels
+	/// with alpha under 0.5 are left out, and the rest are `color`.
+	fn shape_text(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, color: [f32; 4],
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>;
+
+	/// Make a model & texture coordinates for a line of text, with one
+	/// quad per glyph: `(rect in the atlas, rect to draw at)`, both as
+	/// `(x, y, w, h)`.  Draw it with `shape_text()`.
+	fn text_model(&mut self,
+		glyphs: &[((f32, f32, f32, f32), (f32, f32, f32, f32))])
+		-> (Model, TexCoords)
+	{
+		let mut vertices = Vec::with_capacity(glyphs.len() * 16);
+		let mut texcoords = Vec::with_capacity(glyphs.len() * 4);
+		let mut fans = Vec::with_capacity(glyphs.len());
+
+		for (i, &((u, v, tw, th), (x, y, w, h))) in glyphs.iter()
+			.enumerate()
+		{
+			vertices.extend_from_slice(&[
+				x, y, 0.0, 1.0,
+				x + w, y, 0.0, 1.0,
+				x + w, y + h, 0.0, 1.0,
+				x, y + h, 0.0, 1.0,
+			]);
+			texcoords.extend_from_slice(&[
+				(u, v), (u + tw, v),
+				(u + tw, v + th), (u, v + th),
+			]);
+			fans.push((i as u32 * 4, 4));
+		}
+
+		(self.model(&vertices, fans), self.texcoords(&texcoords))
+	}
 
 	/// Create a new shape shaded by a texture using texture coordinates
 	/// and tint per vertex.
@@ -86,34 +366,340 @@ pub trait Display {
 	fn shape_complex(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords,
 		gradient: Gradient, blending: bool,
-		fog: bool, camera: bool) -> Shape;
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>;
+
+	/// Compile a custom style from GLSL ES 1.00 shaders, for
+	/// `shape_custom()`.  They get the same inputs as the built-in styles:
+	/// attribute `position`, and uniforms `models_tfm` (the shape's
+	/// transform), `matrix` (the camera), `has_camera` (0 or 1),
+	/// `has_fog`, `fog` and `range`.  Fails with the GLSL info log.
+	fn add_style(&mut self, vert: &[u8], frag: &[u8])
+		-> Result<StyleId, String>;
+
+	/// Create a new shape drawn with a style from `add_style()`.  The
+	/// `uniforms` are set (by name) each time it's drawn.
+	fn shape_custom(&mut self, model: &Model, transform: Matrix,
+		style: StyleId, uniforms: &[(&str, UniformValue)],
+		blending: bool, fog: bool, camera: bool) -> Shape;
+
+	/// Set a uniform of a style from `add_style()` for every shape drawn
+	/// with it (like a time or a color).  Names that aren't in the shaders
+	/// are ignored.  The `uniforms` of `shape_custom()` win.
+	fn set_uniform(&mut self, style: StyleId, name: &str,
+		value: UniformValue) -> ();
 
 	/// Drop a shape (don't draw it anymore).
 	fn drop_shape(&mut self, shape: &Shape);
 
+	/// Drop a `Model`, freeing it's GPU memory.  The index is recycled for
+	/// the next `Model`, so using a dropped `Model` is a logic error.
+	fn drop_model(&mut self, model: Model);
+
+	/// Drop a `Texture`, freeing it's GPU memory.  The index is recycled
+	/// for the next `Texture`, so using a dropped `Texture` is a logic
+	/// error.
+	fn drop_texture(&mut self, texture: Texture);
+
+	/// Drop a `Gradient`, freeing it's GPU memory.  The index is recycled
+	/// for the next `Gradient`, so using a dropped `Gradient` is a logic
+	/// error.
+	fn drop_gradient(&mut self, gradient: Gradient);
+
+	/// Drop `TexCoords`, freeing it's GPU memory.  The index is recycled
+	/// for the next `TexCoords`, so using dropped `TexCoords` is a logic
+	/// error.
+	fn drop_texcoords(&mut self, texcoords: TexCoords);
+
 	/// Transform the shape.
 	fn transform(&self, shape: &Shape, transform: Matrix);
 
+	/// Show or hide the shape, without dropping it (it's skipped when
+	/// drawing while hidden).
+	fn set_visible(&mut self, shape: &Shape, visible: bool);
+
+	/// Set the layer of a shape made without `camera` (GUI), default 0.
+	/// Higher layers are drawn in front, and shapes on the same layer are
+	/// drawn in the order they were made.
+	fn set_layer(&mut self, shape: &Shape, layer: i32);
+
+	/// Only draw the part of the shape inside the clip rectangle `(x, y,
+	/// w, h)`, in the same -1 to 1 coordinates as `Event::Cursor`.  `None`
+	/// clears the clip.
+	fn set_clip(&mut self, shape: &Shape, clip: Option<(f32,f32,f32,f32)>);
+
+	/// Set which faces of a shape aren't drawn, so double-sided shapes can
+	/// opt out of culling.  `None` uses the one from `set_cull()`.
+	/// Returns `false` if it's not supported.
+	fn set_shape_cull(&mut self, shape: &Shape, cull: Option<Cull>)
+		-> bool;
+
+	/// Multiply a shape's color by `tint` (RGBA), whatever its style, like
+	/// flashing a sprite white when it's hit.  `None` removes it.  Returns
+	/// `false` if it's not supported.
+	fn set_tint(&mut self, shape: &Shape, tint: Option<[f32; 4]>) -> bool;
+
 	/// Resize the display.
 	fn resize(&mut self, wh: (u16, u16)) -> ();
 
+	/// Keep a fixed aspect ratio (width / height), drawing in the centered
+	/// part of the window that fits it with black bars around it
+	/// (letterboxing).  `None` stretches to fill the window (default).
+	/// Returns `false` if it's not supported.
+	fn set_fixed_aspect(&mut self, aspect: Option<f32>) -> bool;
+
 	/// Get the width and height of the window, as a tuple.
 	fn wh(&self) -> (u16, u16);
 
+	/// Put `text` on the system clipboard.
+	fn clipboard_set(&mut self, text: &str) -> ();
+
+	/// Get the text on the system clipboard, `None` if there isn't any.
+	fn clipboard_get(&self) -> Option<String>;
+
+	/// Set the shape of the mouse cursor over the window.
+	fn set_cursor(&mut self, cursor: Cursor) -> ();
+
+	/// Move the mouse cursor to `xy`, in the same -1 to 1 coordinates as
+	/// `Event::Cursor` (like for re-centering in mouse-look).  No
+	/// `Event::Cursor` is sent for the move.
+	fn set_cursor_position(&mut self, xy: (f32, f32)) -> ();
+
+	/// Set the minimum size of the window, `(0, 0)` for no minimum.
+	fn set_min_size(&mut self, wh: (u16, u16)) -> ();
+
+	/// Set the maximum size of the window, `(0, 0)` for no maximum.
+	fn set_max_size(&mut self, wh: (u16, u16)) -> ();
+
+	/// Keep the window's aspect ratio `(w, h)` while the user resizes it,
+	/// `None` for any (default).  Returns `false` if the window manager
+	/// couldn't be asked.
+	fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool;
+
+	/// Close the window.  `input()` returns `Event::Exit` once, then only
+	/// `None`, and `update()` stops drawing.
+	fn close(&mut self) -> ();
+
+	/// Whether the window has been closed with `close()`.
+	fn is_closed(&self) -> bool;
+
+	/// Get the part of the window `(x, y, w, h)`, in pixels, that the
+	/// window system lost (uncovered, or resized) since the last call.
+	/// `update()` redraws it anyway, so this is for apps that draw parts
+	/// of the window themselves.
+	fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)>;
+
+	/// Get the text being composed with an input method (IME), see
+	/// `Event::Preedit`.  Always empty on X11 and Wayland for now.
+	fn preedit(&self) -> &str;
+
+	/// Send committed text as one `Event::TextStr`, instead of an
+	/// `Event::Text` for each `char` (default off).
+	fn set_text_str(&mut self, enabled: bool) -> ();
+
+	/// Make shortcut events (like `Event::Copy` for Ctrl-C) from Ctrl &
+	/// Alt key combos (default on).  Turn it off for your own shortcuts,
+	/// the key events are still sent.
+	fn set_shortcuts_enabled(&mut self, enabled: bool) -> ();
+
+	/// Flip the direction of `Event::Scroll` (default off).  There's no way
+	/// to tell if the OS has natural scrolling on (which flips it first),
+	/// so turn this on from a setting to get (-1, -1) as up / left again.
+	fn set_natural_scroll(&mut self, natural: bool) -> ();
+
+	/// Get the text of an `Event::TextStr`, while handling it.
+	fn text_str(&self, index: usize) -> &str;
+
+	/// Whether a key is held down right now, by the number of it's `Event`
+	/// (like 15 for `Event::W`).  Keys are let go when the window loses
+	/// focus (`Event::Pause`).
+	fn is_key_down(&self, key: u8) -> bool;
+
+	/// Keep the window above other windows (or stop).  Returns `false` if
+	/// it couldn't be done.
+	fn set_always_on_top(&mut self, above: bool) -> bool;
+
+	/// Set the opacity of the whole window, from 0 (invisible) to 1
+	/// (opaque, the default), for fading it in & out.  Silently does
+	/// nothing without a compositor.
+	fn set_opacity(&mut self, opacity: f32) -> ();
+
+	/// Set the window's class (`WM_CLASS` on X11, app ID on Wayland), which
+	/// the taskbar uses to group windows and find the icon.  `class` should
+	/// match the name of the app's `.desktop` file.
+	fn set_class(&mut self, instance: &str, class: &str) -> ();
+
+	/// Set where clicks & touches go to the window, as rectangles in pixels
+	/// `(x, y, w, h)` (default `None`, the whole window).  Input outside of
+	/// them goes through to what's behind, which (with a transparent
+	/// `color_rgba()`) makes non-rectangular windows.  Returns `false` if
+	/// it's not supported (X11 needs the XShape extension).
+	fn set_input_shape(&mut self, region: Option<&[(i16, i16, u16, u16)]>)
+		-> bool;
+
+	/// Grab the keyboard, so shortcuts like the Super key go to the window
+	/// instead of the window manager (X11 only).  If the app hangs or
+	/// crashes while it's grabbed, the keyboard can't be used until it's
+	/// killed, so only grab while needed.  It's let go on `Event::Pause`
+	/// (call again on `Event::Resume` to grab it back).  Returns `false` if
+	/// it couldn't be grabbed (like when the window doesn't have focus).
+	fn set_keyboard_grab(&mut self, grab: bool) -> bool;
+
+	/// Minimize the window.  Returns `false` if it couldn't be done.
+	fn minimize(&mut self) -> bool;
+
+	/// Maximize the window.  Returns `false` if it couldn't be done.
+	fn maximize(&mut self) -> bool;
+
+	/// Restore the window after `minimize()` or `maximize()`.  Returns
+	/// `false` if it couldn't be done.
+	fn restore(&mut self) -> bool;
+
+	/// Get the content scale factor for HiDPI (1.0 at 96 DPI), see
+	/// `Event::ScaleChanged`.
+	fn scale_factor(&self) -> f32;
+
+	/// Set the deadzone for a controller's sticks & throttles: values
+	/// within `inner` snap to 0, and `inner` to `outer` is rescaled to 0 to
+	/// 1 (default is 0 & 1, no deadzone).  Both are from 0 to 1, and
+	/// `inner` has to be less than `outer` (panics otherwise).
+	fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32);
+
+	/// Remap a controller's buttons, `(from, to)`, like swapping Accept &
+	/// Cancel.  Buttons not in `map` stay the same.
+	fn set_controller_map(&mut self, controller: usize,
+		map: &[(ControllerButton, ControllerButton)]);
+
+	/// Also send `CUp`, `CDown`, `CLeft` & `CRight` (once per push, then
+	/// repeating while it's held) when a controller's main stick is pushed
+	/// past halfway, for menus.
+	fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool);
+
+	/// Get the battery level of a controller, from 0.0 (empty) to 1.0
+	/// (full), to warn about low battery.  `None` if it's unknown, or the
+	/// controller is wired.
+	fn controller_battery(&self, controller: usize) -> Option<f32>;
+
+	/// Get the GUID of a controller's device (for saving which player it
+	/// is).  A controller plugged back in gets the number it had before,
+	/// by it's GUID.  `None` if there's no controller with that number.
+	/// The GUID is only the vendor & product, so two of the same kind of
+	/// controller have the same GUID (they get numbers in plug-in order).
+	fn controller_guid(&self, controller: usize) -> Option<String>;
+
+	/// Get the hardware a controller has (number of axes, buttons & hats,
+	/// and rumble), for showing it on a controls screen.  `None` if it's
+	/// unplugged, or it's unknown.
+	fn controller_capabilities(&self, controller: usize)
+		-> Option<::ControllerCaps>;
+
+	/// Get where the window's top-left is on the screen, in pixels (see
+	/// `Event::Moved`).
+	fn position(&self) -> (i16, i16);
+
+	/// Move the window's top-left to `xy` on the screen, in pixels.
+	fn set_position(&mut self, xy: (i16, i16));
+
+	/// Set where text is being entered `(x, y, w, h)`, in pixels, so the
+	/// input method's popup goes there.  `None` turns off text input, so
+	/// there's no `Event::Text` or `Event::Preedit` (on by default).  The
+	/// popup position is only used on Windows for now.
+	fn set_text_input_rect(&mut self, rect: Option<(i16, i16, u16, u16)>);
+
+	/// Get when the last event from `input()` happened (from the platform's
+	/// timestamp when there is one).
+	fn event_time(&self) -> ::std::time::Instant;
+
+	/// Show or hide the title bar & borders (windows start without them on
+	/// X11).  Call right after making the `Display` to pick it before the
+	/// first frame.
+	fn set_decorated(&mut self, decorated: bool) -> ();
+
+	/// Start moving the window with the cursor, from a mouse button press
+	/// on a custom title bar.  Returns `false` if it couldn't be done.
+	fn begin_drag(&mut self) -> bool;
+
+	/// Whether the window has keyboard focus right now (`Event::Pause` &
+	/// `Event::Resume` are sent when it changes).
+	fn has_focus(&self) -> bool;
+
+	/// Get the pixels of the last frame (RGBA, top row first), `None` if
+	/// they can't be read back (only the software renderer can for now).
+	fn pixels(&self) -> Option<Vec<u8>>;
+
 	// 
 	fn draw(&self, writer: &Fn(u16, u16) -> [u8; 4]) -> ();
 }
 
 /// Handle for shape.
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub(crate) enum ShapeHandle {
 	Alpha(u32),
 	Opaque(u32),
+	Gui(u32), // Made without `camera`.
 }
 
-/// A renderable object that exists on the `Display`.
+/// A renderable object that exists on the `Display`.  Compare with the
+/// result of `pick()` to find which shape it is.
+#[derive(PartialEq)]
 pub struct Shape(ShapeHandle);
 
+/// Why a shape couldn't be made.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ShapeError {
+	/// The `TexCoords` don't have one coordinate per vertex.
+	TexCoordsLength {
+		/// Number of vertices in the `Model`.
+		vertices: u32,
+		/// Number of texture coordinates.
+		texcoords: u32,
+	},
+	/// The `Gradient` doesn't have one color per vertex.
+	GradientLength {
+		/// Number of vertices in the `Model`.
+		vertices: u32,
+		/// Number of colors.
+		colors: u32,
+	},
+}
+
+impl ::std::fmt::Display for ShapeError {
+	fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+		match *self {
+			ShapeError::TexCoordsLength { vertices, texcoords } => {
+				write!(f, "TexCoords length ({}) doesn't match \
+					vertex length ({})", texcoords, vertices)
+			}
+			ShapeError::GradientLength { vertices, colors } => {
+				write!(f, "Gradient length ({}) doesn't match \
+					vertex length ({})", colors, vertices)
+			}
+		}
+	}
+}
+
+impl ::std::error::Error for ShapeError {}
+
+/// Check that there's one texture coordinate per vertex.
+pub(crate) fn check_texcoords(vertices: u32, texcoords: u32)
+	-> Result<(), ShapeError>
+{
+	if vertices != texcoords {
+		return Err(ShapeError::TexCoordsLength { vertices, texcoords });
+	}
+	Ok(())
+}
+
+/// Check that there's one color per vertex.
+pub(crate) fn check_gradient(vertices: u32, colors: u32)
+	-> Result<(), ShapeError>
+{
+	if vertices != colors {
+		return Err(ShapeError::GradientLength { vertices, colors });
+	}
+	Ok(())
+}
+
 /// A list of vertices that make a shape.
 #[derive(Copy, Clone)]
 pub struct Model(pub usize); // TODO: unsafe
@@ -129,6 +715,186 @@ pub struct TexCoords(pub usize); // TODO: unsafe
 /// A Texture
 pub struct Texture(pub usize, pub u16, pub u16); // TODO: unsafe
 
+/// A custom style (shader program), from `add_style()`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StyleId(pub usize); // TODO: unsafe
+
+/// The value of a uniform in a custom style.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UniformValue {
+	/// `int`
+	Int(i32),
+	/// `float`
+	Float(f32),
+	/// `vec2`
+	Vec2([f32; 2]),
+	/// `vec3`
+	Vec3([f32; 3]),
+	/// `vec4`
+	Vec4([f32; 4]),
+	/// `mat4` (column-major)
+	Mat4([f32; 16]),
+}
+
+/// A list of GPU resources, where the index of a dropped resource is reused so
+/// that handles to the other resources stay valid.
+pub(crate) struct Slots<T> {
+	list: Vec<Option<T>>,
+	free: Vec<usize>,
+}
+
+impl<T> Slots<T> {
+	/// Create an empty list.
+	pub fn new() -> Self {
+		Slots { list: vec![], free: vec![] }
+	}
+
+	/// Add a resource, returning it's index.
+	pub fn add(&mut self, resource: T) -> usize {
+		if let Some(index) = self.free.pop() {
+			self.list[index] = Some(resource);
+			index
+		} else {
+			self.list.push(Some(resource));
+			self.list.len() - 1
+		}
+	}
+
+	/// Remove a resource, and free it's index to be reused.
+	pub fn remove(&mut self, index: usize) -> Option<T> {
+		let resource = self.list[index].take();
+
+		if resource.is_some() {
+			self.free.push(index);
+		}
+
+		resource
+	}
+}
+
+impl<T> ::std::ops::Index<usize> for Slots<T> {
+	type Output = T;
+
+	fn index(&self, index: usize) -> &T {
+		self.list[index].as_ref().expect("awi: Used a dropped handle!")
+	}
+}
+
+impl<T> ::std::ops::IndexMut<usize> for Slots<T> {
+	fn index_mut(&mut self, index: usize) -> &mut T {
+		self.list[index].as_mut().expect("awi: Used a dropped handle!")
+	}
+}
+
+/// How many frames to average for `Display::frame_time()` and
+/// `Display::fps()`.
+const FRAME_HISTORY: usize = 60;
+
+/// What `Display::update()` measured about the frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameStatus {
+	/// Time in seconds since the last update (the frame's delta time).
+	pub dt: f32,
+	/// Average time it takes to render a frame, over the last few frames.
+	pub frame_time: Duration,
+	/// If `frame_time` is longer than `set_target_fps()` allows (lower
+	/// the quality to catch up).
+	pub over_budget: bool,
+}
+
+/// A rolling average of the time each frame takes.
+pub(crate) struct FrameTimes {
+	times: [f32; FRAME_HISTORY],
+	index: usize,
+	count: usize,
+	// Target time per frame in seconds, 0 for none.
+	target: f32,
+}
+
+impl FrameTimes {
+	/// Create an empty history.
+	pub fn new() -> Self {
+		FrameTimes { times: [0.0; FRAME_HISTORY], index: 0, count: 0,
+			target: 0.0 }
+	}
+
+	/// Set the target frames per second (0 for none).
+	pub fn set_target(&mut self, fps: f32) {
+		self.target = if fps > 0.0 { 1.0 / fps } else { 0.0 };
+	}
+
+	/// Add the delta time (in seconds) of the latest frame.
+	pub fn push(&mut self, dt: f32) -> FrameStatus {
+		self.times[self.index] = dt;
+		self.index = (self.index + 1) % FRAME_HISTORY;
+		if self.count < FRAME_HISTORY {
+			self.count += 1;
+		}
+
+		FrameStatus {
+			dt,
+			frame_time: self.frame_time(),
+			over_budget: self.target > 0.0
+				&& self.average() > self.target,
+		}
+	}
+
+	/// Average frame time in seconds.
+	fn average(&self) -> f32 {
+		if self.count == 0 {
+			return 0.0;
+		}
+
+		self.times[..self.count].iter().sum::<f32>() / self.count as f32
+	}
+
+	/// Average frame time.
+	pub fn frame_time(&self) -> Duration {
+		let nanos = (self.average() * 1_000_000_000.0) as u64;
+
+		Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+	}
+
+	/// Average frames per second.
+	pub fn fps(&self) -> f32 {
+		let average = self.average();
+
+		if average == 0.0 {
+			0.0
+		} else {
+			1.0 / average
+		}
+	}
+}
+
+/// Get the viewport (X & Y from the bottom left, width & height in pixels) that
+/// fits `aspect` centered in `wh`, or fills it if `None`.
+pub(crate) fn letterbox(wh: (u16, u16), aspect: Option<f32>)
+	-> (i32, i32, i32, i32)
+{
+	let (w, h) = (wh.0 as i32, wh.1 as i32);
+	let aspect = match aspect {
+		Some(aspect) if aspect > 0.0 => aspect,
+		_ => return (0, 0, w, h),
+	};
+
+	if w as f32 > h as f32 * aspect {
+		// Bars on the left & right.
+		let vw = (h as f32 * aspect).round() as i32;
+		((w - vw) / 2, 0, vw, h)
+	} else {
+		// Bars on the top & bottom.
+		let vh = (w as f32 / aspect).round() as i32;
+		(0, (h - vh) / 2, w, vh)
+	}
+}
+
+/// Get the time in seconds, as a float.
+pub(crate) fn seconds(duration: Duration) -> f32 {
+	duration.as_secs() as f32
+		+ duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
+
 /// Create a new shape
 pub(crate) fn new_shape(i: ShapeHandle) -> Shape {
 	Shape(i)
@@ -139,8 +905,227 @@ pub(crate) fn get_shape(s: &Shape) -> ShapeHandle {
 	s.0.clone()
 }
 
+/// How the colors of shapes with `blending` are combined with what's behind
+/// them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlendMode {
+	/// Colors aren't multiplied by alpha yet (default).
+	Straight,
+	/// Colors are already multiplied by alpha (common in UI atlases).
+	Premultiplied,
+}
+
+impl Default for BlendMode {
+	fn default() -> Self {
+		BlendMode::Straight
+	}
+}
+
+/// Which faces of triangles aren't drawn, with `set_cull()`.  Front faces
+/// wind counter-clockwise.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Cull {
+	/// Draw both sides (for double-sided shapes like cards & cloth).
+	None,
+	/// Don't draw back faces (default).
+	Back,
+	/// Don't draw front faces.
+	Front,
+}
+
+impl Default for Cull {
+	fn default() -> Self {
+		Cull::Back
+	}
+}
+
+/// Which OpenGL API & version (major, minor) to ask for with
+/// `new_opengl_display()`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GlApi {
+	/// Desktop OpenGL, core profile from 3.2 up.  The built-in shaders are
+	/// GLSL ES 1.00, so the driver needs GL_ARB_ES2_compatibility.
+	Gl(u8, u8),
+	/// OpenGLES, like on embedded boards (the default is 2.0).
+	Gles(u8, u8),
+}
+
+/// How the data for `Display::texture_compressed()` is compressed.  Each is
+/// made of 4x4 pixel blocks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CompressedFormat {
+	/// DXT1 (BC1), RGB with 1-bit alpha, desktop.
+	Dxt1,
+	/// DXT5 (BC3), RGBA, desktop.
+	Dxt5,
+	/// ETC2, RGB, OpenGLES 3.
+	Etc2,
+	/// ETC2 with EAC alpha, RGBA, OpenGLES 3.
+	Etc2Alpha,
+}
+
+impl CompressedFormat {
+	/// Get how many bytes of data a `wh` texture needs.
+	pub fn size(self, wh: (u16, u16)) -> usize {
+		use self::CompressedFormat::*;
+
+		let blocks = ((wh.0 as usize + 3) / 4)
+			* ((wh.1 as usize + 3) / 4);
+
+		match self {
+			Dxt1 | Etc2 => blocks * 8,
+			Dxt5 | Etc2Alpha => blocks * 16,
+		}
+	}
+}
+
+/// The layout of the pixels for `Display::texture_fmt()`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PixelFormat {
+	/// Red, green, blue & alpha bytes (like `VFrame`).
+	Rgba8,
+	/// Blue, green, red & alpha bytes (common on X11 & Windows).
+	Bgra8,
+	/// One byte, used for red, green, blue & alpha (masks & fonts).
+	R8,
+}
+
+impl PixelFormat {
+	/// Get how many bytes of data a `wh` texture needs.
+	pub fn size(self, wh: (u16, u16)) -> usize {
+		let pixels = wh.0 as usize * wh.1 as usize;
+
+		match self {
+			PixelFormat::Rgba8 | PixelFormat::Bgra8 => pixels * 4,
+			PixelFormat::R8 => pixels,
+		}
+	}
+
+	// Fail if `data` is the wrong size for a `wh` texture.
+	pub(crate) fn check(self, wh: (u16, u16), data: &[u8])
+		-> Result<(), String>
+	{
+		let size = self.size(wh);
+
+		if data.len() != size {
+			return Err(format!("{:?} texture data is {} bytes, \
+				should be {}", self, data.len(), size));
+		}
+
+		Ok(())
+	}
+
+	// Convert the pixels to RGBA.
+	pub(crate) fn to_rgba(self, data: &[u8]) -> Vec<u8> {
+		match self {
+			PixelFormat::Rgba8 => data.to_vec(),
+			PixelFormat::Bgra8 => {
+				let mut px = Vec::with_capacity(data.len());
+				for p in data.chunks(4) {
+					px.extend_from_slice(&[p[2], p[1],
+						p[0], p[3]]);
+				}
+				px
+			}
+			PixelFormat::R8 => {
+				let mut px = Vec::with_capacity(data.len() * 4);
+				for &v in data {
+					px.extend_from_slice(&[v; 4]);
+				}
+				px
+			}
+		}
+	}
+}
+
+/// How frames are synced to the monitor, see `Display::set_latency_mode()`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LatencyMode {
+	/// Vsync: wait for the monitor, never tearing (default).
+	Smooth,
+	/// Adaptive vsync: don't wait when a frame is late, tearing instead
+	/// of adding a frame of latency.
+	LowLatency,
+}
+
+impl Default for LatencyMode {
+	fn default() -> Self {
+		LatencyMode::Smooth
+	}
+}
+
+/// How the scene is projected onto the `Display`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+	/// 3D, with a vertical field of view (radians).
+	Perspective {
+		/// Vertical field of view (radians).
+		fov: f32,
+	},
+	/// 2D, no perspective.  Set `height` to the window's height in
+	/// pixels for pixel-accurate placement.
+	Orthographic {
+		/// Visible height (the width follows the aspect ratio).
+		height: f32,
+	},
+}
+
+impl Default for Projection {
+	fn default() -> Self {
+		Projection::Perspective { fov: 0.5 * PI }
+	}
+}
+
+/// The default near & far clip planes of perspective projections.
+pub(crate) const CLIP_PLANES: (f32, f32) = (0.1, 100.0);
+
+impl Projection {
+	/// Generate the projection matrix for aspect ratio `ratiox`.
+	pub fn matrix(&self, ratiox: f32) -> Matrix {
+		self.clipped(ratiox, CLIP_PLANES)
+	}
+
+	/// Generate the projection matrix for aspect ratio `ratiox`, with the
+	/// near & far clip planes `clip` (only for perspective).
+	pub fn clipped(&self, ratiox: f32, clip: (f32, f32)) -> Matrix {
+		match *self {
+			Projection::Perspective { fov } => {
+				perspective(ratiox, fov, clip.0, clip.1)
+			}
+			Projection::Orthographic { height } => {
+				orthographic(ratiox, height)
+			}
+		}
+	}
+}
+
+/// Generate an orthographic projection matrix.
+pub fn orthographic(ratiox: f32, height: f32) -> Matrix {
+	let near = -100.0;
+	let far = 100.0;
+
+	matrix!()
+		.m(Matrix::new(
+			1.0, 0.0, 0.0, 0.0,
+			0.0, 1.0, 0.0, 0.0,
+			0.0, 0.0, -1.0, 0.0,
+			0.0, 0.0, 0.0, 1.0,
+		))
+		.m(Matrix::new(
+			2.0 / (height * ratiox), 0.0, 0.0, 0.0,
+			0.0, 2.0 / height, 0.0, 0.0,
+			0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near),
+			0.0, 0.0, 0.0, 1.0,
+		))
+}
+
 /// Generate a projection matrix.
 pub fn projection(ratiox: f32, fovy: f32) -> Matrix {
+	perspective(ratiox, fovy, CLIP_PLANES.0, CLIP_PLANES.1)
+}
+
+/// Generate a projection matrix, with near & far clip planes.
+pub fn perspective(ratiox: f32, fovy: f32, near: f32, far: f32) -> Matrix {
 	matrix!()
 		// TODO: is this the right order to multiply stuff?
 		.m(Matrix::new(
@@ -152,11 +1137,133 @@ pub fn projection(ratiox: f32, fovy: f32) -> Matrix {
 		.m(Matrix::finite_perspective_projection(
 			fovy,
 			ratiox,
-			0.1, // Near
-			100.0, // Far
+			near,
+			far,
 		))
 }
 
+/// Get the point that column-major `matrix` transforms to `ndc` & `depth`, the
+/// origin if it can't be inverted.
+fn unproject(matrix: &[f32; 16], ndc: (f32, f32), depth: f32) -> Vector {
+	let inverse = match invert(matrix) {
+		Some(inverse) => inverse,
+		None => return vector!(0.0, 0.0, 0.0),
+	};
+	let v = [ndc.0, ndc.1, depth, 1.0];
+	let mut p = [0.0; 4];
+
+	for row in 0..4 {
+		for column in 0..4 {
+			p[row] += inverse[column * 4 + row] * v[column];
+		}
+	}
+
+	vector!(p[0] / p[3], p[1] / p[3], p[2] / p[3])
+}
+
+/// Invert a column-major 4x4 matrix, `None` if it can't be.
+fn invert(m: &[f32; 16]) -> Option<[f32; 16]> {
+	let mut inv = [0.0; 16];
+
+	inv[0] = m[5] * m[10] * m[15] - m[5] * m[11] * m[14]
+		- m[9] * m[6] * m[15] + m[9] * m[7] * m[14]
+		+ m[13] * m[6] * m[11] - m[13] * m[7] * m[10];
+	inv[4] = -m[4] * m[10] * m[15] + m[4] * m[11] * m[14]
+		+ m[8] * m[6] * m[15] - m[8] * m[7] * m[14]
+		- m[12] * m[6] * m[11] + m[12] * m[7] * m[10];
+	inv[8] = m[4] * m[9] * m[15] - m[4] * m[11] * m[13]
+		- m[8] * m[5] * m[15] + m[8] * m[7] * m[13]
+		+ m[12] * m[5] * m[11] - m[12] * m[7] * m[9];
+	inv[12] = -m[4] * m[9] * m[14] + m[4] * m[10] * m[13]
+		+ m[8] * m[5] * m[14] - m[8] * m[6] * m[13]
+		- m[12] * m[5] * m[10] + m[12] * m[6] * m[9];
+	inv[1] = -m[1] * m[10] * m[15] + m[1] * m[11] * m[14]
+		+ m[9] * m[2] * m[15] - m[9] * m[3] * m[14]
+		- m[13] * m[2] * m[11] + m[13] * m[3] * m[10];
+	inv[5] = m[0] * m[10] * m[15] - m[0] * m[11] * m[14]
+		- m[8] * m[2] * m[15] + m[8] * m[3] * m[14]
+		+ m[12] * m[2] * m[11] - m[12] * m[3] * m[10];
+	inv[9] = -m[0] * m[9] * m[15] + m[0] * m[11] * m[13]
+		+ m[8] * m[1] * m[15] - m[8] * m[3] * m[13]
+		- m[12] * m[1] * m[11] + m[12] * m[3] * m[9];
+	inv[13] = m[0] * m[9] * m[14] - m[0] * m[10] * m[13]
+		- m[8] * m[1] * m[14] + m[8] * m[2] * m[13]
+		+ m[12] * m[1] * m[10] - m[12] * m[2] * m[9];
+	inv[2] = m[1] * m[6] * m[15] - m[1] * m[7] * m[14]
+		- m[5] * m[2] * m[15] + m[5] * m[3] * m[14]
+		+ m[13] * m[2] * m[7] - m[13] * m[3] * m[6];
+	inv[6] = -m[0] * m[6] * m[15] + m[0] * m[7] * m[14]
+		+ m[4] * m[2] * m[15] - m[4] * m[3] * m[14]
+		- m[12] * m[2] * m[7] + m[12] * m[3] * m[6];
+	inv[10] = m[0] * m[5] * m[15] - m[0] * m[7] * m[13]
+		- m[4] * m[1] * m[15] + m[4] * m[3] * m[13]
+		+ m[12] * m[1] * m[7] - m[12] * m[3] * m[5];
+	inv[14] = -m[0] * m[5] * m[14] + m[0] * m[6] * m[13]
+		+ m[4] * m[1] * m[14] - m[4] * m[2] * m[13]
+		- m[12] * m[1] * m[6] + m[12] * m[2] * m[5];
+	inv[3] = -m[1] * m[6] * m[11] + m[1] * m[7] * m[10]
+		+ m[5] * m[2] * m[11] - m[5] * m[3] * m[10]
+		- m[9] * m[2] * m[7] + m[9] * m[3] * m[6];
+	inv[7] = m[0] * m[6] * m[11] - m[0] * m[7] * m[10]
+		- m[4] * m[2] * m[11] + m[4] * m[3] * m[10]
+		+ m[8] * m[2] * m[7] - m[8] * m[3] * m[6];
+	inv[11] = -m[0] * m[5] * m[11] + m[0] * m[7] * m[9]
+		+ m[4] * m[1] * m[11] - m[4] * m[3] * m[9]
+		- m[8] * m[1] * m[7] + m[8] * m[3] * m[5];
+	inv[15] = m[0] * m[5] * m[10] - m[0] * m[6] * m[9]
+		- m[4] * m[1] * m[10] + m[4] * m[2] * m[9]
+		+ m[8] * m[1] * m[6] - m[8] * m[2] * m[5];
+
+	let det = m[0] * inv[0] + m[1] * inv[4] + m[2] * inv[8]
+		+ m[3] * inv[12];
+
+	if det == 0.0 {
+		return None;
+	}
+
+	for i in inv.iter_mut() {
+		*i /= det;
+	}
+
+	Some(inv)
+}
+
+/// Generate a camera matrix from the camera's position, and rotation (radians
+/// around the X, Y and Z axis).
+pub fn camera(position: Vector, rotation: Vector) -> Matrix {
+	// Move the world the opposite way of the camera.
+	let (sx, cx) = (-rotation.x).sin_cos();
+	let (sy, cy) = (-rotation.y).sin_cos();
+	let (sz, cz) = (-rotation.z).sin_cos();
+
+	let translate = Matrix::new(
+		1.0, 0.0, 0.0, -position.x,
+		0.0, 1.0, 0.0, -position.y,
+		0.0, 0.0, 1.0, -position.z,
+		0.0, 0.0, 0.0, 1.0,
+	);
+	let rotate_x = Matrix::new(
+		1.0, 0.0, 0.0, 0.0,
+		0.0, cx, -sx, 0.0,
+		0.0, sx, cx, 0.0,
+		0.0, 0.0, 0.0, 1.0,
+	);
+	let rotate_y = Matrix::new(
+		cy, 0.0, sy, 0.0,
+		0.0, 1.0, 0.0, 0.0,
+		-sy, 0.0, cy, 0.0,
+		0.0, 0.0, 0.0, 1.0,
+	);
+	let rotate_z = Matrix::new(
+		cz, -sz, 0.0, 0.0,
+		sz, cz, 0.0, 0.0,
+		0.0, 0.0, 1.0, 0.0,
+		0.0, 0.0, 0.0, 1.0,
+	);
+
+	rotate_z * rotate_x * rotate_y * translate
+}
+
 pub trait Point {
 	fn point(&self) -> Vector;
 }
@@ -165,7 +1272,8 @@ pub trait Point {
 pub fn zsort<T: Point>(sorted: &mut Vec<u32>, points: &Vec<T>, nr: bool,
 	position: Vector)
 {
-	sorted.sort_unstable_by(|a, b| {
+	// Stable, so equally distant shapes don't flicker.
+	sorted.sort_by(|a, b| {
 		let p1 = points[*a as usize].point() - position;
 		let p2 = points[*b as usize].point() - position;
 
@@ -178,3 +1286,110 @@ pub fn zsort<T: Point>(sorted: &mut Vec<u32>, points: &Vec<T>, nr: bool,
 		}
 	});
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{ letterbox, unproject, perspective, PixelFormat, Slots };
+
+	// Transform `p` by column-major `m`, to normalized device coordinates.
+	fn project(m: &[f32; 16], p: [f32; 3]) -> [f32; 3] {
+		let v = [p[0], p[1], p[2], 1.0];
+		let mut c = [0.0; 4];
+
+		for row in 0..4 {
+			for column in 0..4 {
+				c[row] += m[column * 4 + row] * v[column];
+			}
+		}
+
+		[c[0] / c[3], c[1] / c[3], c[2] / c[3]]
+	}
+
+	#[test]
+	fn unproject_round_trip() {
+		let m: [f32; 16] = perspective(1.5, 1.0, 0.1, 100.0).into();
+
+		for &depth in [-1.0, 0.0, 1.0].iter() {
+			let p = unproject(&m, (0.25, -0.5), depth);
+			let ndc = project(&m, [p.x, p.y, p.z]);
+
+			assert!((ndc[0] - 0.25).abs() < 0.001, "{:?}", ndc);
+			assert!((ndc[1] + 0.5).abs() < 0.001, "{:?}", ndc);
+			assert!((ndc[2] - depth).abs() < 0.001, "{:?}", ndc);
+		}
+	}
+
+	#[test]
+	fn unproject_singular() {
+		let p = unproject(&[0.0; 16], (0.0, 0.0), 0.0);
+
+		assert!(p.x == 0.0 && p.y == 0.0 && p.z == 0.0);
+	}
+
+	#[test]
+	fn letterbox_square() {
+		assert_eq!(letterbox((800, 600), Some(1.0)),
+			(100, 0, 600, 600));
+		assert_eq!(letterbox((600, 800), Some(1.0)),
+			(0, 100, 600, 600));
+	}
+
+	#[test]
+	fn letterbox_wide() {
+		assert_eq!(letterbox((800, 600), Some(2.0)),
+			(0, 100, 800, 400));
+	}
+
+	#[test]
+	fn letterbox_tall() {
+		assert_eq!(letterbox((800, 600), Some(0.5)),
+			(250, 0, 300, 600));
+	}
+
+	#[test]
+	fn letterbox_fill() {
+		assert_eq!(letterbox((800, 600), Some(4.0 / 3.0)),
+			(0, 0, 800, 600));
+		assert_eq!(letterbox((800, 600), None), (0, 0, 800, 600));
+		assert_eq!(letterbox((800, 600), Some(0.0)),
+			(0, 0, 800, 600));
+	}
+
+	#[test]
+	fn to_rgba_bgra() {
+		let bgra = [1, 2, 3, 4, 5, 6, 7, 8];
+
+		assert_eq!(PixelFormat::Bgra8.to_rgba(&bgra),
+			vec![3, 2, 1, 4, 7, 6, 5, 8]);
+		assert_eq!(PixelFormat::Rgba8.to_rgba(&bgra), bgra.to_vec());
+	}
+
+	#[test]
+	fn to_rgba_r8() {
+		assert_eq!(PixelFormat::R8.to_rgba(&[9, 200]),
+			vec![9, 9, 9, 9, 200, 200, 200, 200]);
+	}
+
+	#[test]
+	fn slots_reuse() {
+		let mut slots = Slots::new();
+		let a = slots.add('a');
+		let b = slots.add('b');
+
+		assert_eq!(slots.remove(a), Some('a'));
+		assert_eq!(slots.remove(a), None);
+		assert_eq!(slots.add('c'), a);
+		assert_eq!(slots.add('d'), 2);
+		assert_eq!((slots[a], slots[b]), ('c', 'b'));
+	}
+
+	#[test]
+	#[should_panic(expected = "awi: Used a dropped handle!")]
+	fn slots_stale() {
+		let mut slots = Slots::new();
+		let a = slots.add(());
+
+		slots.remove(a);
+		slots[a]
+	}
+}
@@ -6,8 +6,11 @@
 //! Interface with the GPU to render graphics or do fast calculations.
 
 mod base;
+#[cfg(feature = "async")] mod next_event;
 
 pub use self::base::*;
+#[cfg(feature = "async")] pub use self::next_event::NextEvent;
+#[cfg(feature = "async")] pub(crate) use self::next_event::Waiter;
 
 #[cfg(any(
 	target_os="macos", target_os="android", target_os="linux",
@@ -19,36 +22,246 @@ pub use self::base::*;
 	target_os="web"
 ))] mod opengl;
 
+#[cfg(not(target_arch="wasm32"))] mod software;
+
 /// Create a new Vulkan / OpenGL Display.
+///
+/// Each call opens another window, with its own input (`input()`) and
+/// rendering context, so more than one can be used at once.  Update each
+/// `Display` in turn, on the same thread.  Models, textures and shapes belong
+/// to the `Display` that made them, and can't be used with another one.  Only
+/// one window is supported on Windows for now.  Use `DisplayBuilder` to pick
+/// the title, size & icon before the window is shown.
 pub fn new_display() -> Result<Box<Display>, String> {
-	let mut err = "".to_string();
+	DisplayBuilder::new().build()
+}
+
+/// Settings for a new Vulkan / OpenGL Display, picked before the window is
+/// shown (so it doesn't flicker from changing them after).  Chain the
+/// setters, then call `build()`.
+#[derive(Clone)]
+pub struct DisplayBuilder {
+	title: String,
+	wh: Option<(u16, u16)>,
+	icon: Option<(u16, u16, Vec<u32>)>,
+	decorated: bool,
+	vsync: bool,
+	srgb: bool,
+	msaa: u8,
+	transparent: bool,
+}
+
+impl DisplayBuilder {
+	/// Start with the same settings as `new_display()`: no title,
+	/// maximized, no icon, no title bar on X11, vsync on, sRGB, MSAA &
+	/// transparency off.
+	pub fn new() -> Self {
+		DisplayBuilder {
+			title: String::new(),
+			wh: None,
+			icon: None,
+			decorated: false,
+			vsync: true,
+			srgb: false,
+			msaa: 0,
+			transparent: false,
+		}
+	}
+
+	/// Set the window title.
+	pub fn title(mut self, title: &str) -> Self {
+		self.title = title.to_string();
+		self
+	}
+
+	/// Set the window's width & height, instead of starting maximized.
+	pub fn size(mut self, wh: (u16, u16)) -> Self {
+		self.wh = Some(wh);
+		self
+	}
+
+	/// Set the window icon, as `(width, height, pixels)` with ARGB pixels
+	/// (not shown on Wayland yet).
+	pub fn icon(mut self, icon: (u16, u16, Vec<u32>)) -> Self {
+		self.icon = Some(icon);
+		self
+	}
+
+	/// Show or hide the title bar & borders (see
+	/// `Display::set_decorated()`).  Always shown on Windows for now.
+	pub fn decorated(mut self, decorated: bool) -> Self {
+		self.decorated = decorated;
+		self
+	}
+
+	/// Turn vsync on or off (see `Display::set_vsync()`).
+	pub fn vsync(mut self, vsync: bool) -> Self {
+		self.vsync = vsync;
+		self
+	}
+
+	/// Turn gamma-correct rendering on or off (see `Display::set_srgb()`).
+	/// If it's not supported, the display is built without it.
+	pub fn srgb(mut self, srgb: bool) -> Self {
+		self.srgb = srgb;
+		self
+	}
+
+	/// Set the number of samples per pixel for multisample anti-aliasing
+	/// (0 for off).  OpenGL falls back on no MSAA if the driver doesn't
+	/// support it.  Ignored on Windows for now, and Vulkan always uses 8.
+	pub fn msaa(mut self, samples: u8) -> Self {
+		self.msaa = samples;
+		self
+	}
+
+	/// Let `Display::color_rgba()` make the window see-through (if there's
+	/// a compositor).  Off by default, since it needs a framebuffer with
+	/// alpha, which compositors have to blend.  Only on OpenGL for now.
+	pub fn transparent(mut self, transparent: bool) -> Self {
+		self.transparent = transparent;
+		self
+	}
+
+	/// Create the Display, like `new_display()`.
+	pub fn build(self) -> Result<Box<Display>, String> {
+		let mut display = self.backend()?;
+
+		if !self.vsync {
+			display.set_vsync(false);
+		}
+		if self.srgb {
+			display.set_srgb(true);
+		}
+
+		Ok(display)
+	}
+
+	// Try each backend, with the window hints.
+	fn backend(&self) -> Result<Box<Display>, String> {
+		let mut err = "".to_string();
+
+		#[cfg(not(target_arch="wasm32"))]
+		let hints = ::WindowHints {
+			title: self.title.clone(),
+			wh: self.wh,
+			icon: self.icon.clone(),
+			decorated: self.decorated,
+		};
 
-	// Try Vulkan first.
+		// Try Vulkan first.
+		#[cfg(any(
+			target_os="macos", target_os="android",
+			target_os="linux", target_os="windows",
+			target_os="nintendo_switch"
+		))]
+		{
+			match vulkan::new(&hints) {
+				Ok(vulkan) => return Ok(vulkan),
+				Err(vulkan) => err.push_str(&vulkan),
+			}
+			err.push('\n');
+		}
+
+		// Fallback on OpenGL/OpenGLES
+		#[cfg(any(
+			target_os="android", target_os="linux",
+			target_os="windows",
+		))]
+		{
+			match opengl::new(GlApi::Gles(2, 0), &hints, self.msaa,
+				self.transparent)
+			{
+				Ok(opengl) => return Ok(opengl),
+				Err(opengl) => err.push_str(&opengl),
+			}
+			err.push('\n');
+		}
+
+		// Give up
+		err.push_str("No more backend options");
+		Err(err)
+	}
+}
+
+/// Create a new OpenGL Display, asking for a specific API & version instead of
+/// trying Vulkan first (like `GlApi::Gles(2, 0)` on embedded boards, or
+/// `GlApi::Gl(3, 3)` for a core context).  Falls back on OpenGLES 2.0 if the
+/// driver doesn't support it.  Windows always gets the driver's default
+/// context for now.
+pub fn new_opengl_display(api: GlApi) -> Result<Box<Display>, String> {
 	#[cfg(any(
-		target_os="macos", target_os="android", target_os="linux",
-		target_os="windows", target_os="nintendo_switch"
+		target_os="android", target_os="linux", target_os="windows",
 	))]
 	{
-		match vulkan::new() {
-			Ok(vulkan) => return Ok(vulkan),
-			Err(vulkan) => err.push_str(&vulkan),
+		opengl::new(api, &::WindowHints::default(), 0, false)
+	}
+
+	#[cfg(not(any(
+		target_os="android", target_os="linux", target_os="windows",
+	)))]
+	{
+		let _ = api;
+		Err("No OpenGL backend for this platform".to_string())
+	}
+}
+
+/// Create a new OpenGL Display that renders offscreen, without a window
+/// (`input()` only returns `None`).  Falls back on the software renderer if
+/// there's no OpenGL.
+pub fn new_headless_display(wh: (u16, u16)) -> Result<Box<Display>, String> {
+	#[cfg(any(
+		target_os="android", target_os="linux", target_os="windows",
+	))]
+	{
+		if let Ok(opengl) = opengl::new_headless(wh) {
+			return Ok(opengl);
 		}
-		err.push('\n');
 	}
 
-	// Fallback on OpenGL/OpenGLES
+	new_software_display(wh)
+}
+
+/// Create a new OpenGL Display that renders into a window that the app already
+/// made with another library (like SDL).  awi doesn't get input for it, so
+/// call `resize()` when it changes size.  awi makes it's own OpenGL ES
+/// context and surface for the window, separate from any the app has (so
+/// don't also make the app's context current on that window).
+#[cfg(not(target_arch="wasm32"))]
+pub fn new_existing_display(connection: ::WindowConnection, wh: (u16, u16))
+	-> Result<Box<Display>, String>
+{
 	#[cfg(any(
 		target_os="android", target_os="linux", target_os="windows",
 	))]
 	{
-		match opengl::new() {
-			Ok(opengl) => return Ok(opengl),
-			Err(opengl) => err.push_str(opengl),
+		opengl::from_existing(connection, wh)
+	}
+
+	#[cfg(not(any(
+		target_os="android", target_os="linux", target_os="windows",
+	)))]
+	{
+		let _ = (connection, wh);
+		Err("No OpenGL backend for this platform".to_string())
+	}
+}
+
+/// Create a new Display that renders on the CPU, offscreen, for when there's
+/// no GPU (like on servers, or for tests).  It's slow, but `pixels()` can
+/// read back what it draws.
+pub fn new_software_display(wh: (u16, u16)) -> Result<Box<Display>, String> {
+	#[cfg(not(target_arch="wasm32"))]
+	{
+		match software::new(wh) {
+			Ok(software) => Ok(software),
+			Err(software) => Err(software.to_string()),
 		}
-		err.push('\n');
 	}
 
-	// Give up
-	err.push_str("No more backend options");
-	Err(err)
+	#[cfg(target_arch="wasm32")]
+	{
+		let _ = wh;
+		Err("No software backend for this platform".to_string())
+	}
 }
@@ -0,0 +1,149 @@
+// Copyright Jeron A. Lau 2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Getting input with async / await (the "async" feature).
+
+#[cfg(target_os = "linux")] extern crate libc;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{ Arc, Condvar, Mutex };
+use std::task::{ Context, Poll, Waker };
+
+use super::Display;
+use Event;
+
+/// The next input event of a `Display`, from `next_event()`.  `None` once
+/// the window is closed, or if there's nothing to wait on (headless, and on
+/// Windows for now).
+pub struct NextEvent<'a> {
+	display: &'a mut Display,
+}
+
+impl Display {
+	/// Get the next input event with `.await`, instead of polling
+	/// `input()` in a loop.  While waiting, a thread (one per window)
+	/// blocks on the window system's connection, and wakes the task when
+	/// there's something to read (see `fd()` to register it with a reactor
+	/// instead).  Controllers & fixed timesteps aren't checked while
+	/// waiting.
+	pub fn next_event(&mut self) -> NextEvent {
+		NextEvent { display: self }
+	}
+}
+
+impl<'a> Future for NextEvent<'a> {
+	type Output = Option<Event>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context)
+		-> Poll<Option<Event>>
+	{
+		let display = &mut *self.display;
+
+		// `input()` ends a frame with `None`, then reads new events.
+		for _ in 0..2 {
+			if let Some(event) = display.input() {
+				return Poll::Ready(Some(event));
+			}
+		}
+
+		if display.is_closed() {
+			return Poll::Ready(None);
+		}
+
+		// TODO: Nothing to wait on (Windows), instead of spinning.
+		if display.wake_on_input(cx.waker().clone()) {
+			Poll::Pending
+		} else {
+			Poll::Ready(None)
+		}
+	}
+}
+
+/// A thread that waits on a window system's connection, waking the task that
+/// asked last.  It stops when this is dropped (with the window).
+pub(crate) struct Waiter {
+	shared: Arc<(Mutex<WaiterState>, Condvar)>,
+}
+
+struct WaiterState {
+	waker: Option<Waker>,
+	stop: bool,
+}
+
+impl Waiter {
+	/// Start the thread, waiting on `fd`.
+	pub fn new(fd: i32) -> Waiter {
+		let shared = Arc::new((Mutex::new(WaiterState {
+			waker: None,
+			stop: false,
+		}), Condvar::new()));
+		let thread = shared.clone();
+
+		::std::thread::spawn(move || loop {
+			// Sleep until a task is waiting.
+			{
+				let (ref lock, ref cvar) = *thread;
+				let mut state = lock.lock().unwrap();
+
+				while state.waker.is_none() && !state.stop {
+					state = cvar.wait(state).unwrap();
+				}
+				if state.stop {
+					return;
+				}
+			}
+
+			// Check for `stop` now & then, since the fd can't be
+			// waited on after it's closed.
+			while !wait_fd(fd) {
+				if thread.0.lock().unwrap().stop {
+					return;
+				}
+			}
+
+			let waker = thread.0.lock().unwrap().waker.take();
+			if let Some(waker) = waker {
+				waker.wake();
+			}
+		});
+
+		Waiter { shared }
+	}
+
+	/// Wake `waker` once the fd can be read (replacing the last one).
+	pub fn wake_on_input(&self, waker: Waker) {
+		let (ref lock, ref cvar) = *self.shared;
+
+		lock.lock().unwrap().waker = Some(waker);
+		cvar.notify_one();
+	}
+}
+
+impl Drop for Waiter {
+	fn drop(&mut self) {
+		let (ref lock, ref cvar) = *self.shared;
+
+		lock.lock().unwrap().stop = true;
+		cvar.notify_one();
+	}
+}
+
+// Block until `fd` can be read, `false` after 100 milliseconds.
+fn wait_fd(fd: i32) -> bool {
+	#[cfg(target_os = "linux")]
+	{
+		let mut fd = libc::pollfd { fd, events: libc::POLLIN,
+			revents: 0 };
+
+		unsafe { libc::poll(&mut fd, 1, 100) != 0 }
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	{
+		let _ = fd;
+		true
+	}
+}
@@ -9,6 +9,11 @@ use super::types::*;
 
 static mut CURRENT_BUFFER: GLuint = ::std::u32::MAX; // No current buffer
 
+// Forget the bound buffer, so the next `bind()` binds it again.
+pub(super) fn forget() {
+	unsafe { CURRENT_BUFFER = ::std::u32::MAX; }
+}
+
 /// An OpenGL buffer, usually a VBO.
 #[derive(Clone)] pub struct Buffer(pub(crate) Rc<BufferContext>);
 
@@ -23,6 +28,7 @@ impl Buffer {
 
 	/// Bind this buffer.
 	pub(crate) fn bind(&self) {
+		(*self.0).1.make_current();
 		let buffer = self.get();
 
 		if buffer != unsafe { CURRENT_BUFFER } {
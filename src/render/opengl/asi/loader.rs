@@ -6,6 +6,7 @@
 use c_void;
 use std::{ mem, ptr };
 use super::types::*;
+use render::GlApi;
 
 #[cfg(windows)]
 use winapi::shared::{
@@ -35,9 +36,13 @@ dl_api!(UnixEGL, "libEGL.so.1",
 	fn eglGetProcAddress(*const i8) -> *mut c_void,
 	fn eglCreateWindowSurface(EGLDisplay, EGLConfig, EGLNativeWindowType,
 		*const EGLint) -> EGLSurface,
+	fn eglCreatePbufferSurface(EGLDisplay, EGLConfig, *const EGLint)
+		-> EGLSurface,
 	fn eglMakeCurrent(EGLDisplay, EGLSurface, EGLSurface, EGLContext)
 		-> EGLBoolean,
-	fn eglSwapInterval(EGLDisplay, EGLint) -> EGLBoolean
+	fn eglSwapInterval(EGLDisplay, EGLint) -> EGLBoolean,
+	fn eglDestroySurface(EGLDisplay, EGLSurface) -> EGLBoolean,
+	fn eglQueryString(EGLDisplay, EGLint) -> *const i8
 );
 
 #[cfg(windows)]
@@ -53,6 +58,8 @@ extern "system" {
 #[cfg(windows)]
 pub struct Display {
 	dc: Option<ptr::NonNull<c_void>>, // A Windows Device Context
+	context: *mut c_void, // The WGL rendering context
+	alpha: bool, // Pixel format has alpha (transparent windows).
 }
 
 #[cfg(windows)]
@@ -73,6 +80,7 @@ pub struct Display {
 	surface: Option<ptr::NonNull<c_void>>,
 	config: *mut c_void,
 	context: *mut c_void,
+	window: Option<EGLNativeWindowType>, // `None` if headless.
 }
 
 #[cfg(not(windows))]
@@ -106,18 +114,30 @@ impl Lib {
 
 	/// Initialize the opengl (connect to the display)
 	#[cfg(windows)]
-	pub fn init(&self) -> (Display, i32) {
+	pub fn init(&self, _native: EGLNativeDisplayType, _headless: bool,
+		_api: GlApi, _msaa: u8, alpha: bool) -> (Display, i32)
+	{
+		// TODO: Choosing the version needs wglCreateContextAttribsARB,
+		// and MSAA needs wglChoosePixelFormatARB.  Until then, it's
+		// always the driver's default context without MSAA.
 		(Display {
 			dc: None,
+			context: ptr::null_mut(),
+			alpha,
 		}, 0)
 	}
 	
-	/// Initialize the opengl (connect to the display)
+	/// Initialize the opengl (connect to the display).  `native` is the
+	/// native display, or null for the default one.  If `headless`, pick a
+	/// config for offscreen (pbuffer) surfaces instead of windows.  `api`
+	/// is the context to ask for (OpenGLES 2.0 if it's not supported).
+	/// `msaa` is the number of samples per pixel (0 for none).  If `alpha`,
+	/// pick a config with alpha (for transparent windows).
 	#[cfg(not(windows))]
-	pub fn init(&self) -> (Display, i32) {
-		let display = unsafe {
-			(self.gl.eglGetDisplay)(ptr::null_mut())
-		};
+	pub fn init(&self, native: EGLNativeDisplayType, headless: bool,
+		api: GlApi, msaa: u8, alpha: bool) -> (Display, i32)
+	{
+		let display = unsafe { (self.gl.eglGetDisplay)(native) };
 		if display.is_null() {
 			panic!("EGL: Couldn't load display.");
 		}
@@ -129,43 +149,20 @@ impl Lib {
 			panic!("Couldn't initialize EGL");
 		}
 
-		// Config
-		let mut config = ptr::null_mut();
-		let mut nconfigs = unsafe { mem::uninitialized() };
-
-		if unsafe {
-			(self.gl.eglChooseConfig)(display, [
-				EGL_RED_SIZE, 8,
-				EGL_GREEN_SIZE, 8,
-				EGL_BLUE_SIZE, 8,
-				EGL_DEPTH_SIZE, 24,
-				EGL_NONE
-			].as_ptr(), &mut config, 1, &mut nconfigs)
-		} == 0 {
-			panic!("Couldn't choose the config");
-		}
-
-		if nconfigs == 0 {
-			panic!("No configs!");
-		}
-
-		if unsafe { (self.gl.eglBindAPI)(EGL_OPENGL_ES_API) } == 0 {
-			panic!("Couldn't bind OpenGLES");
-		}
-
-		// Create an EGL rendering context.
-		let context = unsafe {
-			(self.gl.eglCreateContext)(display, config,
-				ptr::null_mut(),
-				[EGL_CONTEXT_CLIENT_VERSION, 2, EGL_NONE]
-					.as_ptr()
-			)
+		// Config & context, falling back on OpenGLES 2.0 (without
+		// MSAA).
+		let (config, context) = match self.context(display, headless,
+			api, msaa, alpha)
+		{
+			Some(context) => context,
+			None => {
+				self.context(display, headless,
+					GlApi::Gles(2, 0), 0, alpha)
+					.expect("Couldn't create EGL \
+						rendering context.")
+			}
 		};
 
-		if context.is_null() {
-			panic!("Couldn't create EGL rendering context.");
-		}
-
 		let surface = None;
 
 		// Get visual id
@@ -182,9 +179,93 @@ impl Lib {
 			surface,
 			config,
 			context,
+			window: None,
 		}, visual_id)
 	}
 	
+	// Choose a config & create a context for `api`.  `None` if the driver
+	// doesn't support it.
+	#[cfg(not(windows))]
+	fn context(&self, display: EGLDisplay, headless: bool, api: GlApi,
+		msaa: u8, alpha: bool) -> Option<(EGLConfig, EGLContext)>
+	{
+		let (bind, renderable, major, minor) = match api {
+			GlApi::Gl(major, minor) => (EGL_OPENGL_API,
+				EGL_OPENGL_BIT, major, minor),
+			GlApi::Gles(major, minor) => (EGL_OPENGL_ES_API,
+				if major >= 3 {
+					EGL_OPENGL_ES3_BIT
+				} else {
+					EGL_OPENGL_ES2_BIT
+				}, major, minor),
+		};
+
+		let mut config = ptr::null_mut();
+		let mut nconfigs = 0;
+		let mut config_attribs = vec![
+			EGL_RED_SIZE, 8,
+			EGL_GREEN_SIZE, 8,
+			EGL_BLUE_SIZE, 8,
+			// For transparent windows (an ARGB visual).
+			EGL_ALPHA_SIZE, if alpha { 8 } else { 0 },
+			EGL_DEPTH_SIZE, 24,
+			EGL_SURFACE_TYPE, if headless {
+				EGL_PBUFFER_BIT
+			} else {
+				EGL_WINDOW_BIT
+			},
+			EGL_RENDERABLE_TYPE, renderable,
+		];
+		if msaa > 1 {
+			config_attribs.extend_from_slice(&[
+				EGL_SAMPLE_BUFFERS, 1,
+				EGL_SAMPLES, msaa as i32]);
+		}
+		config_attribs.push(EGL_NONE);
+
+		if unsafe {
+			(self.gl.eglChooseConfig)(display,
+				config_attribs.as_ptr(), &mut config, 1,
+				&mut nconfigs)
+		} == 0 || nconfigs == 0 {
+			return None;
+		}
+
+		if unsafe { (self.gl.eglBindAPI)(bind) } == 0 {
+			return None;
+		}
+
+		// Only ask for a minor version & profile when needed, since
+		// EGL 1.4 without EGL_KHR_create_context doesn't know them.
+		let mut attribs = vec![EGL_CONTEXT_MAJOR_VERSION, major as i32];
+		if minor != 0 {
+			attribs.extend_from_slice(&[EGL_CONTEXT_MINOR_VERSION,
+				minor as i32]);
+		}
+		if let GlApi::Gl(..) = api {
+			let profile = if (major, minor) >= (3, 2) {
+				EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT
+			} else {
+				EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT
+			};
+			attribs.extend_from_slice(&[
+				EGL_CONTEXT_OPENGL_PROFILE_MASK, profile]);
+		}
+		attribs.push(EGL_NONE);
+
+		// Create an EGL rendering context.
+		let context = unsafe {
+			(self.gl.eglCreateContext)(display, config,
+				ptr::null_mut(), attribs.as_ptr())
+		};
+
+		if context.is_null() {
+			None
+		} else {
+			Some((config, context))
+		}
+	}
+
 	#[cfg(windows)]
 	pub fn init2(&self, display: &mut Display, window: *mut c_void) {
 		let dc = unsafe { GetDC(window) };
@@ -200,7 +281,8 @@ impl Lib {
 			c_color_bits: 24,
 			c_red_bits: 0, c_red_shift: 0, c_green_bits: 0,
 			c_green_shift: 0, c_blue_bits: 0, c_blue_shift: 0,
-			c_alpha_bits: 0, c_alpha_shift: 0, c_accum_bits: 0,
+			c_alpha_bits: if display.alpha { 8 } else { 0 },
+			c_alpha_shift: 0, c_accum_bits: 0,
 			c_accum_red_bits: 0, c_accum_green_bits: 0,
 			c_accum_blue_bits: 0, c_accum_alpha_bits: 0,
 			c_depth_bits: 24,
@@ -219,20 +301,46 @@ impl Lib {
 			
 			let context = (self.gl.wglCreateContext)(dc);
 			(self.gl.wglMakeCurrent)(dc, context);
+			display.context = context;
 		}
 	}
 
 	/// Initialize the opengl (connect to the display) STEP 2
 	#[cfg(not(windows))]
 	pub fn init2(&self, display: &mut Display, window: EGLNativeWindowType){
-		// Create surface
+		display.window = Some(window);
+
+		if !self.window_surface(display, false) {
+			panic!("Couldn't create EGL surface.");
+		}
+
+		// Synchronize buffer swaps to monitor refresh rate.
+		unsafe { (self.gl.eglSwapInterval)(display.display, 1) };
+	}
+
+	/// Make (or remake) the window's surface, and make it current.  If
+	/// `srgb`, the surface is sRGB encoded.  `false` if it failed (and the
+	/// old surface is kept).
+	#[cfg(not(windows))]
+	fn window_surface(&self, display: &mut Display, srgb: bool) -> bool {
+		let window = match display.window {
+			Some(window) => window,
+			None => return false,
+		};
+
 		let surface = unsafe {
 			(self.gl.eglCreateWindowSurface)(display.display,
-				display.config, window, ptr::null())
+				display.config, window, if srgb {
+					[EGL_GL_COLORSPACE_KHR,
+						EGL_GL_COLORSPACE_SRGB_KHR,
+						EGL_NONE].as_ptr()
+				} else {
+					[EGL_NONE].as_ptr()
+				})
 		};
 
 		if surface.is_null() {
-			panic!("Couldn't create EGL surface.");
+			return false;
 		}
 
 		// Connect context to surface
@@ -243,13 +351,159 @@ impl Lib {
 			panic!("Couldn't make current");
 		}
 
-		// Synchronize buffer swaps to monitor refresh rate.
-		unsafe { (self.gl.eglSwapInterval)(display.display, 1) };
+		if let Some(old) = display.surface {
+			unsafe {
+				(self.gl.eglDestroySurface)(display.display,
+					old.as_ptr());
+			}
+		}
+
+		// Guaranteed to be `Some` because of conditional return above.
+		display.surface = ptr::NonNull::new(surface);
+		true
+	}
+
+	/// Switch the window's surface between sRGB and linear encoding.
+	/// `false` if it's not supported (`EGL_KHR_gl_colorspace`).
+	#[cfg(not(windows))]
+	pub fn set_srgb(&self, display: &mut Display, srgb: bool) -> bool {
+		let extensions = unsafe {
+			(self.gl.eglQueryString)(display.display,
+				EGL_EXTENSIONS)
+		};
+
+		if extensions.is_null() || !unsafe {
+			::std::ffi::CStr::from_ptr(extensions)
+		}.to_string_lossy().split(' ').any(|e| {
+			e == "EGL_KHR_gl_colorspace"
+		}) {
+			return !srgb;
+		}
+
+		self.window_surface(display, srgb)
+	}
+
+	/// Switch the window's surface between sRGB and linear encoding.
+	/// `false` if it's not supported.
+	#[cfg(windows)]
+	pub fn set_srgb(&self, _display: &mut Display, srgb: bool) -> bool {
+		// TODO: WGL_ARB_framebuffer_sRGB needs a new pixel format.
+		!srgb
+	}
+
+	/// Initialize the opengl STEP 2, rendering offscreen (no window).
+	#[cfg(not(windows))]
+	pub fn init2_headless(&self, display: &mut Display, wh: (u16, u16)) {
+		// Create surface
+		let surface = unsafe {
+			(self.gl.eglCreatePbufferSurface)(display.display,
+				display.config, [
+					EGL_WIDTH, wh.0 as i32,
+					EGL_HEIGHT, wh.1 as i32,
+					EGL_NONE
+				].as_ptr())
+		};
+
+		if surface.is_null() {
+			panic!("Couldn't create EGL pbuffer surface.");
+		}
+
+		// Connect context to surface
+		if unsafe {
+			(self.gl.eglMakeCurrent)(display.display, surface,
+				surface, display.context)
+		} == 0 {
+			panic!("Couldn't make current");
+		}
 
 		// Guaranteed to be `Some` because of conditional panic above.
 		display.surface = ptr::NonNull::new(surface);
 	}
 
+	/// Make the display's context current on this thread.
+	#[cfg(not(windows))]
+	pub fn make_current(&self, display: &Display) {
+		if let Some(surface) = display.surface {
+			unsafe {
+				(self.gl.eglMakeCurrent)(display.display,
+					surface.as_ptr(), surface.as_ptr(),
+					display.context);
+			}
+		}
+	}
+
+	/// Make the display's context current on this thread.
+	#[cfg(windows)]
+	pub fn make_current(&self, display: &Display) {
+		if let Some(dc) = display.dc {
+			unsafe {
+				(self.gl.wglMakeCurrent)(dc.as_ptr(),
+					display.context);
+			}
+		}
+	}
+
+	/// Set the swap interval (0 = no vsync, 1 = vsync).  `false` if it
+	/// failed.
+	#[cfg(not(windows))]
+	pub fn swap_interval(&self, display: &Display, interval: i32) -> bool {
+		unsafe {
+			(self.gl.eglSwapInterval)(display.display, interval) != 0
+		}
+	}
+
+	/// Set the swap interval (0 = no vsync, 1 = vsync).  `false` if it
+	/// failed.
+	#[cfg(windows)]
+	pub fn swap_interval(&self, _display: &Display, interval: i32) -> bool {
+		let fn_ptr = unsafe {
+			(self.gl.wglGetProcAddress)(b"wglSwapIntervalEXT\0"
+				.as_ptr() as LPCSTR)
+		};
+
+		// WGL_EXT_swap_control isn't supported.
+		if fn_ptr.is_null() {
+			return false;
+		}
+
+		let swap_interval: unsafe extern "system" fn(i32) -> BOOL =
+			unsafe { mem::transmute(fn_ptr) };
+
+		unsafe { swap_interval(interval) != 0 }
+	}
+
+	/// Whether a swap interval of -1 (adaptive vsync) is supported.
+	#[cfg(not(windows))]
+	pub fn swap_control_tear(&self, _display: &Display) -> bool {
+		// EGL clamps negative swap intervals to 0 (no vsync).
+		false
+	}
+
+	/// Whether a swap interval of -1 (adaptive vsync) is supported.
+	#[cfg(windows)]
+	pub fn swap_control_tear(&self, _display: &Display) -> bool {
+		let fn_ptr = unsafe {
+			(self.gl.wglGetProcAddress)(
+				b"wglGetExtensionsStringEXT\0".as_ptr()
+				as LPCSTR)
+		};
+
+		// WGL_EXT_extensions_string isn't supported.
+		if fn_ptr.is_null() {
+			return false;
+		}
+
+		let extensions_string: unsafe extern "system" fn()
+			-> *const i8 = unsafe { mem::transmute(fn_ptr) };
+		let extensions = unsafe { extensions_string() };
+
+		!extensions.is_null() && unsafe {
+			::std::ffi::CStr::from_ptr(extensions)
+		}.to_string_lossy().split(' ').any(|e| {
+			e == "WGL_EXT_swap_control_tear"
+		})
+	}
+
 	#[cfg(not(windows))]
 	fn load_check(&self, name: &[u8], fn_ptr: *const c_void) {
 		if fn_ptr.is_null() {
@@ -270,6 +524,35 @@ impl Lib {
 		unsafe { mem::transmute_copy::<*const c_void, T>(&fn_ptr) }
 	}
 
+	#[cfg(not(windows))]
+	// Load an OpenGL extension function, `None` if it's missing.
+	pub fn load_optional<T>(&self, name: &[u8]) -> Option<T> {
+		let fn_ptr: *const c_void = unsafe {
+			(self.gl.eglGetProcAddress)(name as *const _
+				as *const i8)
+		};
+
+		if fn_ptr.is_null() {
+			return None;
+		}
+
+		Some(unsafe { mem::transmute_copy::<*const c_void, T>(&fn_ptr) })
+	}
+
+	#[cfg(windows)]
+	// Load an OpenGL extension function, `None` if it's missing.
+	pub fn load_optional<T>(&self, name: &[u8]) -> Option<T> {
+		let fn_ptr: *const c_void = unsafe {
+			(self.gl.wglGetProcAddress)(name as *const _ as LPCSTR)
+		};
+
+		if fn_ptr.is_null() {
+			return None;
+		}
+
+		Some(unsafe { mem::transmute_copy::<*const c_void, T>(&fn_ptr) })
+	}
+
 	#[cfg(windows)]
 	// Load an OpenGL 3 / OpenGLES 2 function.
 	pub fn load<T>(&self, name: &[u8]) -> T {
@@ -20,7 +20,11 @@ macro_rules! gl {
 
 use std::os::raw::c_void;
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{ Cell, RefCell };
+
+// The context that's current on this thread (address of its OpenGLContext),
+// so that with more than one window, each context is made current before use.
+thread_local!(static CURRENT: Cell<usize> = Cell::new(0));
 
 mod loader;
 mod types;
@@ -46,13 +50,20 @@ pub enum Feature {
 	CullFace = 0x0B44,
 	Blend = 0x0BE2,
 	DepthTest = 0x0B71,
+	ScissorTest = 0x0C11,
+	FramebufferSrgb = 0x8DB9,
+	SampleAlphaToCoverage = 0x809E,
+	DebugOutput = 0x92E0,
+	DebugOutputSynchronous = 0x8242,
+	ProgramPointSize = 0x8642,
 }
 
 /// What the vertices represent
 #[repr(u32)]
+#[derive(Copy, Clone)]
 pub enum Topology {
-//	Points = 0x0000,
-//	Lines = 0x0001,
+	Points = 0x0000,
+	Lines = 0x0001,
 //	LineLoop = 0x0002,
 //	LineStrip = 0x0003,
 //	Triangles = 0x0004,
@@ -60,8 +71,9 @@ pub enum Topology {
 	TriangleFan = 0x0006,
 }
 
-/// The OpenGL context.
-#[derive(Clone)] pub struct OpenGL(Rc<RefCell<OpenGLContext>>);
+/// The OpenGL context, and whether `glGetError()` is checked after each call
+/// (outside of the `RefCell`, so it's cheap to skip).
+#[derive(Clone)] pub struct OpenGL(Rc<RefCell<OpenGLContext>>, Rc<Cell<bool>>);
 
 /// The OpenGL builder.
 pub struct OpenGLBuilder {
@@ -70,10 +82,18 @@ pub struct OpenGLBuilder {
 }
 
 impl OpenGLBuilder {
-	/// Begin the building.
-	pub fn new() -> Option<(OpenGLBuilder, i32)> {
+	/// Begin the building.  `native` is the native display to connect to
+	/// (null for the default).  `headless` is for `to_opengl_headless()`.
+	/// `api` is the context to ask for, falling back on OpenGLES 2.0.
+	/// `msaa` is the number of samples per pixel (0 for none).  `alpha` is
+	/// for a framebuffer with alpha (transparent windows).
+	pub fn new(native: EGLNativeDisplayType, headless: bool,
+		api: ::render::GlApi, msaa: u8, alpha: bool)
+		-> Option<(OpenGLBuilder, i32)>
+	{
 		if let Ok(lib) = loader::Lib::new() {
-			let (mut display, visual_id) = lib.init();
+			let (mut display, visual_id) = lib.init(native,
+				headless, api, msaa, alpha);
 
 			Some((OpenGLBuilder {
 				lib,
@@ -87,15 +107,25 @@ impl OpenGLBuilder {
 	/// Complete the building
 	pub fn to_opengl(mut self, window: EGLNativeWindowType) -> OpenGL {
 		self.lib.init2(&mut self.display, window);
+		self.finish()
+	}
 
-		OpenGL(Rc::new(RefCell::new(OpenGLContext {
+	/// Complete the building, rendering offscreen instead of to a window.
+	#[cfg(not(windows))]
+	pub fn to_opengl_headless(mut self, wh: (u16, u16)) -> OpenGL {
+		self.lib.init2_headless(&mut self.display, wh);
+		self.finish()
+	}
+
+	fn finish(self) -> OpenGL {
+		let opengl = OpenGL(Rc::new(RefCell::new(OpenGLContext {
 			earlier: Instant::now(),
 			// FFI OpenGL Functions.
 			clear: self.lib.load(b"glClear\0"),
 			clear_color: self.lib.load(b"glClearColor\0"),
 			disable: self.lib.load(b"glDisable\0"),
 			enable: self.lib.load(b"glEnable\0"),
-			#[cfg(debug_assertions)]
+			cull_face: self.lib.load(b"glCullFace\0"),
 			get_error: self.lib.load(b"glGetError\0"),
 			blend_func_separate:
 				self.lib.load(b"glBlendFuncSeparate\0"),
@@ -110,10 +140,10 @@ impl OpenGLBuilder {
 			bind_buffer: self.lib.load(b"glBindBuffer\0"),
 			buffer_data: self.lib.load(b"glBufferData\0"),
 			vdata: self.lib.load(b"glGetAttribLocation\0"),
-			#[cfg(debug_assertions)]
 			get_shader: self.lib.load(b"glGetShaderiv\0"),
-			#[cfg(debug_assertions)]
 			info_log: self.lib.load(b"glGetShaderInfoLog\0"),
+			get_program: self.lib.load(b"glGetProgramiv\0"),
+			program_info_log: self.lib.load(b"glGetProgramInfoLog\0"),
 			draw_arrays: self.lib.load(b"glDrawArrays\0"),
 			use_program: self.lib.load(b"glUseProgram\0"),
 			uniform_mat4: self.lib.load(b"glUniformMatrix4fv\0"),
@@ -128,24 +158,82 @@ impl OpenGLBuilder {
 			tex_params: self.lib.load(b"glTexParameteri\0"),
 			tex_image: self.lib.load(b"glTexImage2D\0"),
 			tex_subimage: self.lib.load(b"glTexSubImage2D\0"),
+			compressed_tex_image: self.lib.load(
+				b"glCompressedTexImage2D\0"),
 			enable_vdata: self.lib.load(b"glEnableVertexAttribArray\0"),
 			viewport: self.lib.load(b"glViewport\0"),
+			scissor: self.lib.load(b"glScissor\0"),
 			gen_mipmap: self.lib.load(b"glGenerateMipmap\0"),
 			detach_shader: self.lib.load(b"glDetachShader\0"),
+			delete_shader: self.lib.load(b"glDeleteShader\0"),
 			delete_program: self.lib.load(b"glDeleteProgram\0"),
 			delete_buffer: self.lib.load(b"glDeleteBuffers\0"),
 			delete_texture: self.lib.load(b"glDeleteTextures\0"),
+			read_pixels: self.lib.load(b"glReadPixels\0"),
+			get_string: self.lib.load(b"glGetString\0"),
+			gen_framebuffers: self.lib.load(b"glGenFramebuffers\0"),
+			bind_framebuffer: self.lib.load(b"glBindFramebuffer\0"),
+			framebuffer_texture: self.lib.load(
+				b"glFramebufferTexture2D\0"),
+			delete_framebuffers:
+				self.lib.load(b"glDeleteFramebuffers\0"),
+			gen_renderbuffers: self.lib.load(b"glGenRenderbuffers\0"),
+			bind_renderbuffer: self.lib.load(b"glBindRenderbuffer\0"),
+			renderbuffer_storage: self.lib.load(
+				b"glRenderbufferStorage\0"),
+			framebuffer_renderbuffer: self.lib.load(
+				b"glFramebufferRenderbuffer\0"),
+			delete_renderbuffers:
+				self.lib.load(b"glDeleteRenderbuffers\0"),
+			// OpenGLES doesn't have glGetTexImage.
+			get_tex_image: self.lib.load_optional(
+				b"glGetTexImage\0"),
+			// OpenGLES doesn't have glPolygonMode, only as extension.
+			polygon_mode: self.lib.load_optional(
+				b"glPolygonMode\0"),
+			polygon_mode_nv: self.lib.load_optional(
+				b"glPolygonModeNV\0"),
+			// Core since OpenGL 4.3, and GL_KHR_debug on desktop.
+			debug_message_callback: self.lib.load_optional(
+				b"glDebugMessageCallback\0"),
+			// GL_KHR_debug on OpenGLES.
+			debug_message_callback_khr: self.lib.load_optional(
+				b"glDebugMessageCallbackKHR\0"),
+			debug: None,
+			khr_debug: false,
 			// Other
 			display: self.display,
 			lib: self.lib,
-		})))
+		})), Rc::new(Cell::new(cfg!(debug_assertions))));
+
+		// `init2()` made the new context current.
+		let id = &*opengl.0.borrow() as *const OpenGLContext as usize;
+		CURRENT.with(|current| current.set(id));
+
+		// Core profile contexts can't draw without a vertex array
+		// object, so bind one for everything.
+		if !opengl.gles() {
+			let gen: Option<unsafe extern "system" fn(GLsizei,
+				*mut GLuint) -> ()> = opengl.get().lib
+				.load_optional(b"glGenVertexArrays\0");
+			let bind: Option<unsafe extern "system" fn(GLuint)
+				-> ()> = opengl.get().lib
+				.load_optional(b"glBindVertexArray\0");
+
+			if let (Some(gen), Some(bind)) = (gen, bind) {
+				let mut vao = 0;
+				gl!(opengl, gen(1, &mut vao));
+				gl!(opengl, bind(vao));
+			}
+		}
+
+		opengl
 	}
 }
 
 /// The OpenGL context.
 struct OpenGLContext {
 	earlier: Instant,
-	#[allow(unused)] // is used at drop.
 	lib: loader::Lib,
 	display: loader::Display,
 	clear: unsafe extern "system" fn(GLbitfield) -> (),
@@ -153,7 +241,8 @@ struct OpenGLContext {
 		GLfloat) -> (),
 	disable: unsafe extern "system" fn(GLenum) -> (),
 	enable: unsafe extern "system" fn(GLenum) -> (),
-	#[cfg(debug_assertions)] get_error: unsafe extern "system" fn() -> GLenum,
+	cull_face: unsafe extern "system" fn(GLenum) -> (),
+	get_error: unsafe extern "system" fn() -> GLenum,
 	blend_func_separate: unsafe extern "system" fn(GLenum, GLenum, GLenum,
 		GLenum) -> (),
 	create_shader: unsafe extern "system" fn(GLenum) -> GLuint,
@@ -169,11 +258,12 @@ struct OpenGLContext {
 	buffer_data: unsafe extern "system" fn(GLenum, GLsizeiptr,
 		*const c_void, GLenum) -> (),
 	vdata: unsafe extern "system" fn(GLuint, *const GLchar) -> GLint,
-	#[cfg(debug_assertions)]
 	get_shader: unsafe extern "system" fn(GLuint, GLenum, *mut GLint) -> (),
-	#[cfg(debug_assertions)]
 	info_log: unsafe extern "system" fn(GLuint, GLsizei, *mut GLsizei,
 		*mut GLchar) -> (),
+	get_program: unsafe extern "system" fn(GLuint, GLenum, *mut GLint) -> (),
+	program_info_log: unsafe extern "system" fn(GLuint, GLsizei,
+		*mut GLsizei, *mut GLchar) -> (),
 	draw_arrays: unsafe extern "system" fn(GLenum, GLint, GLsizei) -> (),
 	use_program: unsafe extern "system" fn(GLuint) -> (),
 	uniform_mat4: unsafe extern "system" fn(GLint, GLsizei, GLboolean,
@@ -194,33 +284,97 @@ struct OpenGLContext {
 		GLsizei, GLint, GLenum, GLenum, *const c_void) -> (),
 	tex_subimage: unsafe extern "system" fn(GLenum, GLint, GLint, GLint, GLsizei,
 		GLsizei, GLenum, GLenum, *const c_void) -> (),
+	compressed_tex_image: unsafe extern "system" fn(GLenum, GLint, GLenum,
+		GLsizei, GLsizei, GLint, GLsizei, *const c_void) -> (),
 	enable_vdata: unsafe extern "system" fn(GLuint) -> (),
 	viewport: unsafe extern "system" fn(GLint, GLint, GLsizei, GLsizei) -> (),
+	scissor: unsafe extern "system" fn(GLint, GLint, GLsizei, GLsizei) -> (),
 	gen_mipmap: unsafe extern "system" fn(GLenum) -> (),
 	detach_shader: unsafe extern "system" fn(GLuint, GLuint) -> (),
+	delete_shader: unsafe extern "system" fn(GLuint) -> (),
 	delete_program: unsafe extern "system" fn(GLuint) -> (),
 	delete_buffer: unsafe extern "system" fn(GLsizei, *const GLuint) -> (),
 	delete_texture: unsafe extern "system" fn(GLsizei, *const GLuint) -> (),
+	read_pixels: unsafe extern "system" fn(GLint, GLint, GLsizei, GLsizei,
+		GLenum, GLenum, *mut c_void) -> (),
+	get_string: unsafe extern "system" fn(GLenum) -> *const GLubyte,
+	gen_framebuffers: unsafe extern "system" fn(GLsizei, *mut GLuint) -> (),
+	bind_framebuffer: unsafe extern "system" fn(GLenum, GLuint) -> (),
+	framebuffer_texture: unsafe extern "system" fn(GLenum, GLenum, GLenum,
+		GLuint, GLint) -> (),
+	delete_framebuffers: unsafe extern "system" fn(GLsizei, *const GLuint)
+		-> (),
+	gen_renderbuffers: unsafe extern "system" fn(GLsizei, *mut GLuint) -> (),
+	bind_renderbuffer: unsafe extern "system" fn(GLenum, GLuint) -> (),
+	renderbuffer_storage: unsafe extern "system" fn(GLenum, GLenum, GLsizei,
+		GLsizei) -> (),
+	framebuffer_renderbuffer: unsafe extern "system" fn(GLenum, GLenum,
+		GLenum, GLuint) -> (),
+	delete_renderbuffers: unsafe extern "system" fn(GLsizei, *const GLuint)
+		-> (),
+	get_tex_image: Option<unsafe extern "system" fn(GLenum, GLint, GLenum,
+		GLenum, *mut c_void) -> ()>,
+	polygon_mode: Option<unsafe extern "system" fn(GLenum, GLenum) -> ()>,
+	polygon_mode_nv: Option<unsafe extern "system" fn(GLenum, GLenum)
+		-> ()>,
+	debug_message_callback: Option<unsafe extern "system" fn(
+		Option<DebugProc>, *const c_void) -> ()>,
+	debug_message_callback_khr: Option<unsafe extern "system" fn(
+		Option<DebugProc>, *const c_void) -> ()>,
+	// Boxed again, so it has a thin pointer to pass to OpenGL.
+	debug: Option<Box<Box<Fn(&str)>>>,
+	// Whether `debug` gets messages from GL_KHR_debug.
+	khr_debug: bool,
+}
+
+impl Drop for OpenGLContext {
+	fn drop(&mut self) {
+		// So a new context at the same address gets made current.
+		let id = self as *const OpenGLContext as usize;
+
+		CURRENT.with(|current| if current.get() == id {
+			current.set(0);
+		});
+	}
+}
+
+type DebugProc = extern "system" fn(GLenum, GLenum, GLuint, GLenum, GLsizei,
+	*const GLchar, *const c_void) -> ();
+
+// Called by OpenGL for GL_KHR_debug messages, `user` is `OpenGLContext.debug`.
+extern "system" fn debug_message(_source: GLenum, _kind: GLenum, _id: GLuint,
+	_severity: GLenum, length: GLsizei, message: *const GLchar,
+	user: *const c_void)
+{
+	let callback = unsafe { &*(user as *const Box<Fn(&str)>) };
+	let message = unsafe {
+		::std::slice::from_raw_parts(message as *const u8,
+			length as usize)
+	};
+
+	callback(&String::from_utf8_lossy(message))
 }
 
 impl OpenGL {
 	/// Set the color for `clear`.
-	pub fn color(&self, r: f32, g: f32, b: f32) {
-		gl!(self, (self.get().clear_color)(r, g, b, 1.0));
+	pub fn color(&self, r: f32, g: f32, b: f32, a: f32) {
+		gl!(self, (self.get().clear_color)(r, g, b, a));
 	}
 
 	/// Update the screen
-	pub fn update(&self) -> f32 {
-		// Swap Display
-		self.get().display.swap(
-			#[cfg(not(target_os = "windows"))]
-			&self.get().lib
-		);
+	pub fn update(&self, swap: bool) -> f32 {
+		// Swap Display (not if nothing new was drawn)
+		if swap {
+			self.get().display.swap(
+				#[cfg(not(target_os = "windows"))]
+				&self.get().lib
+			);
+		}
 
 		// Get the time step for the next frame.
 		let new = Instant::now();
-		let r = new.duration_since(self.get().earlier).subsec_nanos() as f32
-			/ 1_000_000_000.0;
+		let r = ::render::base::seconds(
+			new.duration_since(self.get().earlier));
 		self.0.borrow_mut().earlier = new;
 
 		// Clear Color & Depth
@@ -229,6 +383,236 @@ impl OpenGL {
 		r
 	}
 
+	/// Clear the color & depth buffers, without swapping.
+	pub fn clear(&self) {
+		gl!(self, (self.get().clear)(0x00000100 | 0x00004000));
+	}
+
+	/// Read one RGBA pixel of what's been drawn (from the bottom left).
+	pub fn read_pixel(&self, x: u16, y: u16) -> [u8; 4] {
+		const GL_RGBA: u32 = 0x1908;
+		const GL_UNSIGNED_BYTE: u32 = 0x1401;
+
+		let mut pixel = [0u8; 4];
+		gl!(self, (self.get().read_pixels)(x as GLint, y as GLint, 1, 1,
+			GL_RGBA, GL_UNSIGNED_BYTE,
+			pixel.as_mut_ptr() as *mut c_void));
+		pixel
+	}
+
+	/// Turn vsync on or off.  `false` if the driver doesn't let us.
+	pub fn vsync(&self, vsync: bool) -> bool {
+		let context = self.get();
+
+		context.lib.swap_interval(&context.display, vsync as i32)
+	}
+
+	/// Turn adaptive vsync (tears when a frame is late, instead of waiting
+	/// for the next one) on, or go back to vsync.  `false` if adaptive
+	/// vsync isn't supported, so it's regular vsync.
+	pub fn adaptive_vsync(&self, adaptive: bool) -> bool {
+		let context = self.get();
+
+		if adaptive && context.lib.swap_control_tear(&context.display)
+			&& context.lib.swap_interval(&context.display, -1)
+		{
+			return true;
+		}
+
+		context.lib.swap_interval(&context.display, 1);
+		!adaptive
+	}
+
+	/// Whether the driver has an OpenGL extension.
+	pub fn has_extension(&self, name: &str) -> bool {
+		let extensions = gl!(self, (self.get().get_string)(
+			GL_EXTENSIONS));
+
+		if extensions.is_null() {
+			return false;
+		}
+
+		unsafe { ::std::ffi::CStr::from_ptr(extensions as *const _) }
+			.to_string_lossy().split(' ').any(|e| e == name)
+	}
+
+	// The GL_VERSION string, empty if there isn't one.
+	// Whether it's at least desktop OpenGL `major.minor` (false on
+	// OpenGLES).
+	fn version_at_least(&self, major: u32, minor: u32) -> bool {
+		if self.gles() {
+			return false;
+		}
+
+		let version = self.version();
+		let mut numbers = version.split(|c: char| !c.is_digit(10))
+			.filter(|n| !n.is_empty())
+			.map(|n| n.parse::<u32>().unwrap_or(0));
+
+		match (numbers.next(), numbers.next()) {
+			(Some(a), Some(b)) => (a, b) >= (major, minor),
+			_ => false,
+		}
+	}
+
+	fn version(&self) -> String {
+		let version = gl!(self, (self.get().get_string)(GL_VERSION));
+
+		if version.is_null() {
+			return String::new();
+		}
+
+		unsafe { ::std::ffi::CStr::from_ptr(version as *const _) }
+			.to_string_lossy().into_owned()
+	}
+
+	/// Whether this is an OpenGLES context (rather than desktop OpenGL).
+	pub fn gles(&self) -> bool {
+		self.version().starts_with("OpenGL ES")
+	}
+
+	/// Whether DXT1 & DXT5 compressed textures can be uploaded.
+	pub fn s3tc_textures(&self) -> bool {
+		self.has_extension("GL_EXT_texture_compression_s3tc")
+	}
+
+	/// Whether ETC2 compressed textures can be uploaded (OpenGLES 3, or
+	/// desktop OpenGL with GL_ARB_ES3_compatibility).
+	pub fn etc2_textures(&self) -> bool {
+		self.version().starts_with("OpenGL ES 3")
+			|| self.has_extension("GL_ARB_ES3_compatibility")
+	}
+
+	/// Turn sRGB encoding of the framebuffer on or off.  `false` if the
+	/// surface can't be switched.
+	pub fn srgb(&self, srgb: bool) -> bool {
+		let switched = {
+			let mut context = self.0.borrow_mut();
+			let context = &mut *context;
+
+			context.lib.set_srgb(&mut context.display, srgb)
+		};
+
+		if !switched {
+			return false;
+		}
+
+		// The new surface was made current.
+		let id = &*self.0.borrow() as *const OpenGLContext as usize;
+		CURRENT.with(|current| current.set(id));
+
+		self.srgb_write(srgb);
+		true
+	}
+
+	/// Turn encoding to sRGB on writes on or off (while `srgb()` is on),
+	/// if the driver lets it be turned off (GL_EXT_sRGB_write_control).
+	pub fn srgb_write(&self, srgb: bool) {
+		// Encoding is only done while GL_FRAMEBUFFER_SRGB is enabled.
+		if self.has_extension("GL_EXT_sRGB_write_control") {
+			if srgb {
+				self.enable(Feature::FramebufferSrgb);
+			} else {
+				self.disable(Feature::FramebufferSrgb);
+			}
+		}
+	}
+
+	/// Whether textures can be uploaded as sRGB color.
+	pub fn srgb_textures(&self) -> bool {
+		self.has_extension("GL_EXT_sRGB")
+	}
+
+	/// Cull front faces instead of back faces (while CullFace is on).
+	pub fn cull_front(&self, front: bool) {
+		const GL_FRONT: u32 = 0x0404;
+		const GL_BACK: u32 = 0x0405;
+
+		gl!(self, (self.get().cull_face)(if front {
+			GL_FRONT
+		} else {
+			GL_BACK
+		}));
+	}
+
+	/// Draw triangles as lines (wireframe) or filled.  `false` on OpenGLES
+	/// if the driver doesn't have GL_NV_polygon_mode.
+	pub fn polygon_mode(&self, wireframe: bool) -> bool {
+		const GL_FRONT_AND_BACK: u32 = 0x0408;
+		const GL_LINE: u32 = 0x1B01;
+		const GL_FILL: u32 = 0x1B02;
+
+		let polygon_mode = if !self.gles() {
+			self.get().polygon_mode
+		} else if self.has_extension("GL_NV_polygon_mode") {
+			self.get().polygon_mode_nv
+		} else {
+			None
+		};
+		let polygon_mode = match polygon_mode {
+			Some(polygon_mode) => polygon_mode,
+			None => return false,
+		};
+
+		gl!(self, polygon_mode(GL_FRONT_AND_BACK,
+			if wireframe { GL_LINE } else { GL_FILL }));
+
+		true
+	}
+
+	/// Send errors to `callback`, instead of panicking (in debug builds).
+	/// With GL_KHR_debug, the driver's debug messages are sent too.
+	/// Without it, `glGetError()` is checked after every call.
+	pub fn set_debug_callback(&self, callback: Box<Fn(&str)>) {
+		let callback = Box::new(callback);
+		// Desktop OpenGL has it without a suffix, OpenGLES with.
+		let (plain, khr) = {
+			let context = self.get();
+			(context.debug_message_callback,
+				context.debug_message_callback_khr)
+		};
+		let (f, core) = if self.gles() {
+			(khr, false)
+		} else {
+			(plain, self.version_at_least(4, 3))
+		};
+		let supported = core || self.has_extension("GL_KHR_debug");
+		let khr_debug = match f {
+			Some(f) if supported => Some(f),
+			_ => None,
+		};
+
+		if let Some(debug_message_callback) = khr_debug {
+			let user = &*callback as *const Box<Fn(&str)>
+				as *const c_void;
+
+			self.enable(Feature::DebugOutput);
+			// Call it on this thread, right after the error.
+			self.enable(Feature::DebugOutputSynchronous);
+			gl!(self, debug_message_callback(Some(debug_message),
+				user));
+		}
+
+		let mut context = self.0.borrow_mut();
+		context.khr_debug = khr_debug.is_some();
+		context.debug = Some(callback);
+		self.1.set(!context.khr_debug);
+	}
+
+	/// Forget which program, texture & buffer are bound (they're only
+	/// bound again when they change), for after raw GL calls.
+	pub fn forget_bindings(&self) {
+		program::forget();
+		texture::forget();
+		buffer::forget();
+	}
+
+	// Make this the current context, which forgets the other context's
+	// bindings (do before checking them).
+	fn make_current(&self) {
+		let _ = self.get();
+	}
+
 	/// Enable something
 	pub fn enable(&self, what: Feature) {
 		gl!(self, (self.get().enable)(what as u32))
@@ -239,18 +623,28 @@ impl OpenGL {
 		gl!(self, (self.get().disable)(what as u32))
 	}
 
-	/// Configure blending
-	pub fn blend(&self) {
+	/// Configure blending, for straight or premultiplied alpha.
+	pub fn blend(&self, premultiplied: bool) {
+		const GL_ONE: u32 = 1;
 		const GL_SRC_ALPHA: u32 = 0x0302;
 		const GL_ONE_MINUS_SRC_ALPHA: u32 = 0x0303;
 		const GL_DST_ALPHA: u32 = 0x0304;
 
-		gl!(self, (self.get().blend_func_separate)(
-			GL_SRC_ALPHA,
-			GL_ONE_MINUS_SRC_ALPHA,
-			GL_SRC_ALPHA,
-			GL_DST_ALPHA
-		));
+		if premultiplied {
+			gl!(self, (self.get().blend_func_separate)(
+				GL_ONE,
+				GL_ONE_MINUS_SRC_ALPHA,
+				GL_ONE,
+				GL_ONE_MINUS_SRC_ALPHA
+			));
+		} else {
+			gl!(self, (self.get().blend_func_separate)(
+				GL_SRC_ALPHA,
+				GL_ONE_MINUS_SRC_ALPHA,
+				GL_SRC_ALPHA,
+				GL_DST_ALPHA
+			));
+		}
 	}
 
 	/// Create a new texture.
@@ -258,29 +652,56 @@ impl OpenGL {
 		Texture::new(self)
 	}
 
-	/// Update the viewport.
-	pub fn viewport(&self, w: u16, h: u16) {
-		gl!(self, (self.get().viewport)(0,0,w as GLsizei,h as GLsizei));
+	/// Update the viewport (in pixels, from the bottom left).
+	pub fn viewport(&self, x: i32, y: i32, w: i32, h: i32) {
+		gl!(self, (self.get().viewport)(x, y, w, h));
 	}
 
-	#[cfg(not(debug_assertions))]
-	unsafe fn error(&self) { /* Do nothing in release mode for speed. */ }
+	/// Set the scissor rectangle (in pixels, from the bottom left).
+	pub fn scissor(&self, x: i32, y: i32, w: i32, h: i32) {
+		gl!(self, (self.get().scissor)(x, y, w, h));
+	}
 
-	#[cfg(debug_assertions)]
 	unsafe fn error(&self) {
-		match (self.get().get_error)() {
+		// Do nothing in release mode for speed, unless asked (or if
+		// GL_KHR_debug already sent it to the callback).
+		if !self.1.get() {
+			return;
+		}
+
+		let context = self.get();
+
+		let message = match (context.get_error)() {
 			0 => return, // NO_ERROR
-			0x0500 => panic!("OpenGL Error: Invalid enum"),
-			0x0501 => panic!("OpenGL Error: Invalid value"),
-			0x0502 => panic!("OpenGL Error: Invalid operation"),
-			0x0503 => panic!("OpenGL Error: Stack overflow"),
-			0x0504 => panic!("OpenGL Error: Stack underflow"),
-			0x0505 => panic!("OpenGL Error: Out of memory"),
-			_ => panic!("OpenGL Error: Unknown"),
+			0x0500 => "OpenGL Error: Invalid enum",
+			0x0501 => "OpenGL Error: Invalid value",
+			0x0502 => "OpenGL Error: Invalid operation",
+			0x0503 => "OpenGL Error: Stack overflow",
+			0x0504 => "OpenGL Error: Stack underflow",
+			0x0505 => "OpenGL Error: Out of memory",
+			_ => "OpenGL Error: Unknown",
+		};
+
+		match context.debug {
+			Some(ref callback) => callback(message),
+			None => panic!("{}", message),
 		}
 	}
 
 	fn get(&self) -> ::std::cell::Ref<OpenGLContext> {
-		self.0.borrow()
+		let context = self.0.borrow();
+		let id = &*context as *const OpenGLContext as usize;
+
+		CURRENT.with(|current| if current.get() != id {
+			context.lib.make_current(&context.display);
+			current.set(id);
+			// GL object names belong to the context, so the other
+			// context's bindings could match names in this one.
+			program::forget();
+			texture::forget();
+			buffer::forget();
+		});
+
+		context
 	}
 }
@@ -12,15 +12,31 @@ use super::Topology;
 
 static mut CURRENT_PROGRAM: GLuint = 0; // 0 is always invalid program.
 
+// Forget the bound program, so the next `bind()` binds it again.
+pub(super) fn forget() {
+	unsafe { CURRENT_PROGRAM = 0; }
+}
+
 /// A loaded GPU program.
 #[derive(Clone)] pub struct Program(pub(crate) Rc<ProgramContext>);
 
 impl Program {
-	/// Load a shader program
-	pub fn new(opengl: &OpenGL, vertex: &[u8], fragment: &[u8]) -> Self {
+	/// Load a shader program.  Fails with the GLSL info log if a shader
+	/// doesn't compile, or they don't link.
+	pub fn new(opengl: &OpenGL, vertex: &[u8], fragment: &[u8])
+		-> Result<Self, String>
+	{
 		// Compile vertex & fragment shaders
-		let v_shader = shader_new(opengl, 0x8B31/*vertex*/, vertex);
-		let f_shader = shader_new(opengl, 0x8B30/*fragment*/, fragment);
+		let v_shader = shader_new(opengl, 0x8B31/*vertex*/, vertex)?;
+		let f_shader = match shader_new(opengl, 0x8B30/*fragment*/,
+			fragment)
+		{
+			Ok(f_shader) => f_shader,
+			Err(log) => {
+				gl!(opengl, (opengl.get().delete_shader)(v_shader));
+				return Err(log);
+			}
+		};
 		// Link shaders together.
 		let program = gl!(opengl, (opengl.get().create_program)());
 		gl!(opengl, (opengl.get().attach_shader)(program, v_shader));
@@ -28,8 +44,22 @@ impl Program {
 		gl!(opengl, (opengl.get().link_program)(program));
 		gl!(opengl, (opengl.get().detach_shader)(program, v_shader));
 		gl!(opengl, (opengl.get().detach_shader)(program, f_shader));
+		// The shaders aren't needed after linking.
+		gl!(opengl, (opengl.get().delete_shader)(v_shader));
+		gl!(opengl, (opengl.get().delete_shader)(f_shader));
+		// Deletes the program if it didn't link.
+		let context = ProgramContext(program, opengl.clone());
+
+		let mut status = 0;
+		gl!(opengl, (opengl.get().get_program)(program,
+			0x8B82 /*GL_LINK_STATUS*/, &mut status));
+		if status == 0 {
+			return Err(format!("Failed to link: {}",
+				info_log(opengl, program, true)));
+		}
+
 		// Return
-		Program(Rc::new(ProgramContext(program, opengl.clone())))
+		Ok(Program(Rc::new(context)))
 	}
 
 	/// Get a vertex data handle for this GPU program.
@@ -52,6 +82,7 @@ impl Program {
 
 	/// Bind a program to be used.
 	pub(crate) fn bind(&self) {
+		(*self.0).1.make_current();
 		let program = unsafe { self.get() };
 
 		if program != unsafe { CURRENT_PROGRAM } {
@@ -79,40 +110,53 @@ impl Drop for ProgramContext {
 	}
 }
 
-/// Compile a new shader.
-fn shader_new(opengl: &OpenGL, shader_type: GLenum, src: &[u8]) -> GLuint {
+/// Compile a new shader, or get the error message if it failed to compile.
+fn shader_new(opengl: &OpenGL, shader_type: GLenum, src: &[u8])
+	-> Result<GLuint, String>
+{
 	let shader = gl!(opengl, (opengl.get().create_shader)(shader_type));
 	gl!(opengl, (opengl.get().shader_source)(shader, 1 /*1 string*/,
 		[src.as_ptr() as *const _].as_ptr(), [src.len() as i32].as_ptr()
 	));
 	gl!(opengl, (opengl.get().compile_shader)(shader));
-	compile_errors(opengl, shader);
-	shader
+
+	let mut status = 0;
+	gl!(opengl, (opengl.get().get_shader)(shader,
+		0x8B81 /*GL_COMPILE_STATUS*/, &mut status));
+	if status == 0 {
+		let log = info_log(opengl, shader, false);
+		gl!(opengl, (opengl.get().delete_shader)(shader));
+		return Err(format!("Failed to compile: {}", log));
+	}
+
+	Ok(shader)
 }
 
-/// Evaluate and panic with error message if failed to compile, does nothing in
-/// release mode
-fn compile_errors(_opengl: &OpenGL, _shader: GLuint) {
-	#[cfg(debug_assertions)] {
-		let mut value = unsafe { ::std::mem::uninitialized() };
-
-		gl!(_opengl, (_opengl.get().get_shader)(_shader,
-			0x8B81 /*GL_COMPILE_STATUS*/, &mut value));
-
-		if value == 0 {
-			let mut value = unsafe { ::std::mem::uninitialized() };
-			gl!(_opengl, (_opengl.get().get_shader)(_shader,
-				0x8B84 /*GL_INFO_LOG_LENGTH*/,
-				&mut value));
-			let mut buffer: Vec<u8> =
-				vec![unsafe { ::std::mem::uninitialized() };
-					value as usize];
-			gl!(_opengl, (_opengl.get().info_log)(_shader,
-				value as GLsizei, ::std::ptr::null_mut(),
-				buffer.as_mut_ptr() as *mut _));
-			panic!("Failed to compile: {}.",
-				::std::str::from_utf8(buffer.as_slice())
-					.unwrap());
-		}
+/// Get the info log of a shader, or of a program if `program`.
+fn info_log(opengl: &OpenGL, object: GLuint, program: bool) -> String {
+	const GL_INFO_LOG_LENGTH: GLenum = 0x8B84;
+
+	let mut length = 0;
+	if program {
+		gl!(opengl, (opengl.get().get_program)(object,
+			GL_INFO_LOG_LENGTH, &mut length));
+	} else {
+		gl!(opengl, (opengl.get().get_shader)(object,
+			GL_INFO_LOG_LENGTH, &mut length));
 	}
+
+	let mut buffer = vec![0u8; length.max(1) as usize];
+	let mut written = 0;
+	if program {
+		gl!(opengl, (opengl.get().program_info_log)(object,
+			buffer.len() as GLsizei, &mut written,
+			buffer.as_mut_ptr() as *mut _));
+	} else {
+		gl!(opengl, (opengl.get().info_log)(object,
+			buffer.len() as GLsizei, &mut written,
+			buffer.as_mut_ptr() as *mut _));
+	}
+	buffer.truncate(written.max(0) as usize);
+
+	String::from_utf8_lossy(&buffer).trim_end().to_string()
 }
@@ -3,12 +3,17 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-use std::{ rc::Rc };
+use std::{ rc::Rc, cell::Cell };
 use super::OpenGL;
 use super::types::*;
 
 static mut CURRENT_TEXTURE: GLuint = 0; // 0 is always invalid texture.
 
+// Forget the bound texture, so the next `bind()` binds it again.
+pub(super) fn forget() {
+	unsafe { CURRENT_TEXTURE = 0; }
+}
+
 /// An GPU Texture handle.
 #[derive(Clone)] pub struct Texture(Rc<TextureContext>);
 
@@ -24,18 +29,78 @@ impl Texture {
 				GL_TEXTURE_MIN_FILTER, GL_LINEAR_MIPMAP_LINEAR)
 			);
 			a
-		}, opengl.clone())))
+		}, opengl.clone(), Cell::new(true), Cell::new(None))))
+	}
+
+	/// Set the bound texture's pixels.  If `srgb`, they're color (decoded
+	/// from sRGB when sampled), otherwise they're data.
+	pub fn set(&self, w: u16, h: u16, px: &[u8], srgb: bool) -> () {
+		let format = if srgb { GL_SRGB_ALPHA_EXT } else { GL_RGBA };
+
+		self.bind();
+		gl!((*self.0).1, ((*self.0).1.get().tex_image)(GL_TEXTURE_2D, 0,
+			format as i32, w as i32, h as i32, 0, format,
+			GL_UNSIGNED_BYTE, px.as_ptr() as *const _));
+
+		// GL_EXT_sRGB can't generate mipmaps.
+		(*self.0).2.set(!srgb);
+		if srgb {
+			gl!((*self.0).1, ((*self.0).1.get().tex_params)(
+				GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR));
+		} else {
+			gl!((*self.0).1, ((*self.0).1.get().tex_params)(
+				GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER,
+				GL_LINEAR_MIPMAP_LINEAR));
+			gl!((*self.0).1, ((*self.0).1.get().gen_mipmap)(
+				GL_TEXTURE_2D));
+		}
 	}
 
-	/// Set the bound texture's pixels
-	pub fn set(&self, w: u16, h: u16, px: &[u8]) -> () {
+	/// Make the texture `w` by `h` RGBA pixels, left undefined, for drawing
+	/// into with `draw_into()` (without mipmaps, and read back exactly).
+	pub fn set_target(&self, w: u16, h: u16) -> () {
 		self.bind();
 		gl!((*self.0).1, ((*self.0).1.get().tex_image)(GL_TEXTURE_2D, 0,
 			GL_RGBA as i32, w as i32, h as i32, 0, GL_RGBA,
+			GL_UNSIGNED_BYTE, ::std::ptr::null()));
+
+		(*self.0).2.set(false);
+		gl!((*self.0).1, ((*self.0).1.get().tex_params)(GL_TEXTURE_2D,
+			GL_TEXTURE_MIN_FILTER, GL_NEAREST));
+		gl!((*self.0).1, ((*self.0).1.get().tex_params)(GL_TEXTURE_2D,
+			GL_TEXTURE_MAG_FILTER, GL_NEAREST));
+	}
+
+	/// Set the bound texture's pixels from BGRA pixels, without converting
+	/// them (desktop OpenGL only, OpenGLES can't update them with RGBA).
+	pub fn set_bgra(&self, w: u16, h: u16, px: &[u8]) -> () {
+		self.bind();
+		gl!((*self.0).1, ((*self.0).1.get().tex_image)(GL_TEXTURE_2D, 0,
+			GL_RGBA as i32, w as i32, h as i32, 0, GL_BGRA,
 			GL_UNSIGNED_BYTE, px.as_ptr() as *const _));
+
+		(*self.0).2.set(true);
+		gl!((*self.0).1, ((*self.0).1.get().tex_params)(GL_TEXTURE_2D,
+			GL_TEXTURE_MIN_FILTER, GL_LINEAR_MIPMAP_LINEAR));
 		gl!((*self.0).1, ((*self.0).1.get().gen_mipmap)(GL_TEXTURE_2D));
 	}
 
+	/// Set the bound texture's pixels from compressed blocks of `format`
+	/// (a GL_COMPRESSED_* enum).
+	pub fn set_compressed(&self, w: u16, h: u16, format: u32, data: &[u8])
+		-> ()
+	{
+		self.bind();
+		gl!((*self.0).1, ((*self.0).1.get().compressed_tex_image)(
+			GL_TEXTURE_2D, 0, format, w as i32, h as i32, 0,
+			data.len() as i32, data.as_ptr() as *const _));
+
+		// Compressed textures can't generate mipmaps.
+		(*self.0).2.set(false);
+		gl!((*self.0).1, ((*self.0).1.get().tex_params)(GL_TEXTURE_2D,
+			GL_TEXTURE_MIN_FILTER, GL_LINEAR));
+	}
+
 	/// Update the pixels of an already bound & set texture.
 	pub fn update(&self, w: u16, h: u16, px: &[u8]) -> () {
 		self.bind();
@@ -44,8 +109,104 @@ impl Texture {
 			px.as_ptr() as *const _));
 	}
 
+	/// Read back the texture's pixels (RGBA, in the same order as `set()`).
+	pub fn read(&self, w: u16, h: u16) -> Vec<u8> {
+		let opengl = &(*self.0).1;
+		let mut px = vec![0u8; w as usize * h as usize * 4];
+		let get_tex_image = opengl.get().get_tex_image;
+
+		match get_tex_image {
+			Some(get_tex_image) if !opengl.gles() => {
+				self.bind();
+				gl!(opengl, get_tex_image(GL_TEXTURE_2D, 0,
+					GL_RGBA, GL_UNSIGNED_BYTE,
+					px.as_mut_ptr() as *mut _));
+			}
+			// OpenGLES can only read pixels from a framebuffer.
+			_ => {
+				let mut fbo = 0;
+				gl!(opengl, (opengl.get().gen_framebuffers)(1,
+					&mut fbo));
+				gl!(opengl, (opengl.get().bind_framebuffer)(
+					GL_FRAMEBUFFER, fbo));
+				gl!(opengl, (opengl.get().framebuffer_texture)(
+					GL_FRAMEBUFFER, GL_COLOR_ATTACHMENT0,
+					GL_TEXTURE_2D, self.get(), 0));
+				gl!(opengl, (opengl.get().read_pixels)(0, 0,
+					w as i32, h as i32, GL_RGBA,
+					GL_UNSIGNED_BYTE,
+					px.as_mut_ptr() as *mut _));
+				gl!(opengl, (opengl.get().bind_framebuffer)(
+					GL_FRAMEBUFFER, 0));
+				gl!(opengl, (opengl.get().delete_framebuffers)(
+					1, &fbo));
+			}
+		}
+
+		px
+	}
+
+	/// Draw into the texture instead of the window with `draw`, through a
+	/// framebuffer with a depth buffer (made the first time, and kept
+	/// until the texture is dropped).  The viewport is left at `w` & `h`.
+	pub fn draw_into<F: FnOnce()>(&self, w: u16, h: u16, draw: F) {
+		let opengl = &(*self.0).1;
+
+		match (*self.0).3.get() {
+			Some((fbo, _, fw, fh)) if (fw, fh) == (w, h) => {
+				gl!(opengl, (opengl.get().bind_framebuffer)(
+					GL_FRAMEBUFFER, fbo));
+			}
+			_ => self.framebuffer(w, h),
+		}
+
+		opengl.viewport(0, 0, w as i32, h as i32);
+		opengl.clear();
+		draw();
+
+		gl!(opengl, (opengl.get().bind_framebuffer)(GL_FRAMEBUFFER, 0));
+
+		// The mipmaps are out of date now.
+		if (*self.0).2.get() {
+			self.bind();
+			gl!(opengl, (opengl.get().gen_mipmap)(GL_TEXTURE_2D));
+		}
+	}
+
+	// Make (or remake, at a new size) & bind the framebuffer for
+	// `draw_into()`, with a depth buffer.
+	fn framebuffer(&self, w: u16, h: u16) {
+		let opengl = &(*self.0).1;
+		let (fbo, depth) = match (*self.0).3.get() {
+			Some((fbo, depth, _, _)) => (fbo, depth),
+			None => {
+				let (mut fbo, mut depth) = (0, 0);
+				gl!(opengl, (opengl.get().gen_framebuffers)(1,
+					&mut fbo));
+				gl!(opengl, (opengl.get().gen_renderbuffers)(1,
+					&mut depth));
+				(fbo, depth)
+			}
+		};
+
+		gl!(opengl, (opengl.get().bind_framebuffer)(GL_FRAMEBUFFER,
+			fbo));
+		gl!(opengl, (opengl.get().framebuffer_texture)(GL_FRAMEBUFFER,
+			GL_COLOR_ATTACHMENT0, GL_TEXTURE_2D, self.get(), 0));
+		gl!(opengl, (opengl.get().bind_renderbuffer)(GL_RENDERBUFFER,
+			depth));
+		gl!(opengl, (opengl.get().renderbuffer_storage)(GL_RENDERBUFFER,
+			GL_DEPTH_COMPONENT16, w as i32, h as i32));
+		gl!(opengl, (opengl.get().framebuffer_renderbuffer)(
+			GL_FRAMEBUFFER, GL_DEPTH_ATTACHMENT, GL_RENDERBUFFER,
+			depth));
+
+		(*self.0).3.set(Some((fbo, depth, w, h)));
+	}
+
 	/// Use a texture.
 	pub fn bind(&self) {
+		(*self.0).1.make_current();
 		let texture = self.get();
 
 		if texture != unsafe { CURRENT_TEXTURE } {
@@ -60,10 +221,19 @@ impl Texture {
 	}
 }
 
-pub struct TextureContext(u32, OpenGL);
+/// The texture, it's context, whether it has mipmaps, and the framebuffer &
+/// depth buffer (with their size) for `draw_into()`.
+pub struct TextureContext(u32, OpenGL, Cell<bool>,
+	Cell<Option<(u32, u32, u16, u16)>>);
 
 impl Drop for TextureContext {
 	fn drop(&mut self) {
+		if let Some((fbo, depth, _, _)) = self.3.get() {
+			gl!(self.1, (self.1.get().delete_renderbuffers)(1,
+				&depth));
+			gl!(self.1, (self.1.get().delete_framebuffers)(1,
+				&fbo));
+		}
 		gl!(self.1, (self.1.get().delete_texture)(1, [self.0].as_ptr()));
 	}
 }
@@ -38,14 +38,23 @@ use c_void;
 #[allow(unused)] pub const GL_NEAREST_MIPMAP_NEAREST: i32 = 0x2700;
 #[allow(unused)] pub const GL_NEAREST_MIPMAP_LINEAR: i32 = 0x2702;
 #[allow(unused)] pub const GL_RGBA: u32 = 0x1908;
+#[allow(unused)] pub const GL_BGRA: u32 = 0x80E1;
 #[allow(unused)] pub const GL_UNSIGNED_BYTE: u32 = 0x1401;
+#[allow(unused)] pub const GL_SRGB_ALPHA_EXT: u32 = 0x8C42;
+#[allow(unused)] pub const GL_EXTENSIONS: u32 = 0x1F03;
+#[allow(unused)] pub const GL_VERSION: u32 = 0x1F02;
+#[allow(unused)] pub const GL_FRAMEBUFFER: u32 = 0x8D40;
+#[allow(unused)] pub const GL_COLOR_ATTACHMENT0: u32 = 0x8CE0;
+#[allow(unused)] pub const GL_DEPTH_ATTACHMENT: u32 = 0x8D00;
+#[allow(unused)] pub const GL_RENDERBUFFER: u32 = 0x8D41;
+#[allow(unused)] pub const GL_DEPTH_COMPONENT16: u32 = 0x81A5;
 
 #[allow(unused)] pub const GL_ELEMENT_ARRAY_BUFFER: u32 = 0x8893;
 #[allow(unused)] pub const GL_ARRAY_BUFFER: u32 = 0x8892;
 #[allow(unused)] pub const GL_DYNAMIC_DRAW: u32 = 0x88E8;
 
 #[allow(unused)] pub const EGL_BUFFER_SIZE: i32 = 0x3020;
-#[allow(unused)] pub const EGL_ALPHA_SIZE: i32 = 0x3021;
+pub const EGL_ALPHA_SIZE: i32 = 0x3021;
 #[allow(unused)] pub const EGL_BLUE_SIZE: i32 = 0x3022;
 #[allow(unused)] pub const EGL_GREEN_SIZE: i32 = 0x3023;
 #[allow(unused)] pub const EGL_RED_SIZE: i32 = 0x3024;
@@ -109,7 +118,21 @@ use c_void;
 #[allow(unused)] pub const EGL_OPENGL_ES2_BIT: i32 = 0x0004;
 #[allow(unused)] pub const EGL_CONTEXT_CLIENT_VERSION: i32 = 0x3098;
 
+#[allow(unused)] pub const EGL_OPENGL_ES3_BIT: i32 = 0x0040;
+#[allow(unused)] pub const EGL_OPENGL_BIT: i32 = 0x0008;
+#[allow(unused)] pub const EGL_CONTEXT_MAJOR_VERSION: i32 = 0x3098;
+#[allow(unused)] pub const EGL_CONTEXT_MINOR_VERSION: i32 = 0x30FB;
+#[allow(unused)] pub const EGL_CONTEXT_OPENGL_PROFILE_MASK: i32 = 0x30FD;
+#[allow(unused)] pub const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: i32 = 0x0001;
+#[allow(unused)] pub const EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT: i32 =
+	0x0002;
+
 #[allow(unused)] pub const EGL_OPENGL_ES_API: u32 = 0x30A0;
+#[allow(unused)] pub const EGL_OPENGL_API: u32 = 0x30A2;
+
+#[allow(unused)] pub const EGL_GL_COLORSPACE_KHR: i32 = 0x309D;
+#[allow(unused)] pub const EGL_GL_COLORSPACE_SRGB_KHR: i32 = 0x3089;
+#[allow(unused)] pub const EGL_GL_COLORSPACE_LINEAR_KHR: i32 = 0x308A;
 
 #[allow(dead_code)] #[repr(C)] pub struct PixelFormatDescriptor {
 	pub n_size: u16, // word
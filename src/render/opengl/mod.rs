@@ -9,7 +9,7 @@ use std::cell::Cell;
 
 mod asi;
 
-use std::mem;
+use std::{ mem, ptr };
 use Matrix;
 use Rotation;
 
@@ -38,6 +38,8 @@ const SHADER_FADED_VERT: &'static [u8] = include_bytes!("shaders/faded-vert.glsl
 const SHADER_TINTED_FRAG: &'static [u8] = include_bytes!("shaders/tinted-frag.glsl");
 const SHADER_COMPLEX_VERT: &'static [u8] = include_bytes!("shaders/complex-vert.glsl");
 const SHADER_COMPLEX_FRAG: &'static [u8] = include_bytes!("shaders/complex-frag.glsl");
+const SHADER_PICK_FRAG: &'static [u8] = include_bytes!("shaders/pick-frag.glsl");
+const SHADER_TEXT_FRAG: &'static [u8] = include_bytes!("shaders/text-frag.glsl");
 
 const STYLE_GRADIENT: usize = 0;
 const STYLE_TEXTURE: usize = 1;
@@ -45,6 +47,7 @@ const STYLE_FADED: usize = 2;
 const STYLE_TINTED: usize = 3;
 const STYLE_SOLID: usize = 4;
 const STYLE_COMPLEX: usize = 5;
+const STYLE_TEXT: usize = 6;
 
 struct Style {
 	shader: Program,
@@ -56,15 +59,20 @@ struct Style {
 	range: UniformData,
 	alpha: UniformData,
 	color: UniformData,
+	tint: UniformData,
 	position: VertexData,
 	texpos: VertexData,
 	acolor: VertexData,
+	// From `set_uniform()` (by name), set before each shape's own.
+	uniforms: Vec<(Vec<u8>, UniformData, UniformValue)>,
 }
 
 impl Style {
-	// Create a new style.
-	fn new(context: &OpenGL, vert: &[u8], frag: &[u8]) -> Style {
-		let shader = Program::new(context, vert, frag);
+	// Create a new style, or get the GLSL info log if the shaders fail.
+	fn new(context: &OpenGL, vert: &[u8], frag: &[u8])
+		-> Result<Style, String>
+	{
+		let shader = Program::new(context, vert, frag)?;
 		let matrix_uniform = shader.uniform(b"models_tfm\0");
 		let has_camera = shader.uniform(b"has_camera\0");
 		let camera_uniform = shader.uniform(b"matrix\0");
@@ -73,14 +81,16 @@ impl Style {
 		let range = shader.uniform(b"range\0");
 		let alpha = shader.uniform(b"alpha\0");
 		let color = shader.uniform(b"color\0");
+		let tint = shader.uniform(b"tint\0");
 		let position = shader.vertex_data(b"position\0");
 		let texpos = shader.vertex_data(b"texpos\0");
 		let acolor = shader.vertex_data(b"acolor\0");
 
-		Style {
+		Ok(Style {
 			shader, matrix_uniform, has_camera, camera_uniform, fog,
-			range, position, texpos, alpha, has_fog, color, acolor,
-		}
+			range, position, texpos, alpha, has_fog, color, tint,
+			acolor, uniforms: vec![],
+		})
 	}
 }
 
@@ -90,10 +100,17 @@ struct ShapeData {
 	has_fog: bool,
 	alpha: Option<f32>,
 	color: Option<[f32; 4]>,
+	tint: Option<[f32; 4]>, // Multiplied with the style's output.
 	transform: Matrix, // Transformation matrix.
 	texture: Option<asi::Texture>,
 	vertex_buffer: Buffer,
 	fans: Vec<(u32, u32)>,
+	topology: Topology, // How the ranges in `fans` are drawn.
+	visible: bool, // Hidden shapes are skipped when drawing.
+	clip: Option<(f32, f32, f32, f32)>, // Scissor rectangle.
+	cull: Option<Cull>, // Faces not drawn, `None` for `Display.cull`.
+	layer: i32, // Draw order of GUI shapes (higher is in front).
+	uniforms: Vec<(UniformData, UniformValue)>, // For custom styles.
 }
 
 impl base::Point for ShapeData {
@@ -108,6 +125,7 @@ struct ModelData {
 	// TODO alot could be in base as duplicate
 	vertex_count: u32,
 	fans: Vec<(u32, u32)>,
+	topology: Topology,
 }
 
 struct TexcoordsData {
@@ -122,123 +140,300 @@ struct GradientData {
 
 struct TextureData {
 	t: asi::Texture,
+	srgb: bool, // Color (sRGB), not data.
 }
 
 /// To render anything with adi_gpu, you have to make a `Display`
 pub struct Display {
 	window: ::Window,
 	context: OpenGL,
-	color: (f32, f32, f32),
+	color: (f32, f32, f32, f32),
+	fog: ([f32; 4], f32, f32),
 	opaque_ind: Vec<u32>,
 	alpha_ind: Vec<u32>,
+	dirty: Cell<bool>, // Shapes need to be sorted again.
+	changed: Cell<bool>, // Something drawn changed since `render()`.
+	skipped: bool, // `render()` didn't draw, so don't swap.
+	premultiplied: bool, // Blending, from `set_blend_mode()`.
+	opaque_sort: bool,
 	opaque_vec: Cell<Vec<ShapeData>>,
 	alpha_vec: Cell<Vec<ShapeData>>,
 	gui_vec: Cell<Vec<ShapeData>>,
-	models: Vec<ModelData>,
-	texcoords: Vec<TexcoordsData>,
-	gradients: Vec<GradientData>,
-	textures: Vec<TextureData>,
-	styles: [Style; 6],
+	gui_ind: Vec<u32>, // GUI shapes, in drawing order.
+	models: Slots<ModelData>,
+	texcoords: Slots<TexcoordsData>,
+	gradients: Slots<GradientData>,
+	textures: Slots<TextureData>,
+	styles: Vec<Style>, // Built-in, then from `add_style()`.
+	style_pick: Style, // Draws shape IDs for `pick()`.
+	// Offscreen RGBA target for `pick()`, and it's size.
+	pick_target: Option<(asi::Texture, u16, u16)>,
 	xyz: Vector,
 	rotate_xyz: Vector,
+	view: Matrix,
+	frames: FrameTimes,
 	ar: f32,
+	// Drawn part of the window (in pixels, from the bottom left), smaller
+	// than the window when letterboxed to `fixed_aspect`.
+	viewport: (i32, i32, i32, i32),
+	fixed_aspect: Option<f32>,
 	projection: Matrix,
+	projection_mode: Projection,
+	clip: (f32, f32), // Near & far clip planes.
+	srgb: bool, // Gamma-correct rendering is on.
+	wireframe: bool, // Draw shapes as lines (debugging).
+	alpha_to_coverage: bool, // For alpha shapes (needs multisampling).
+	cull: Cull, // Faces not drawn, for shapes without their own.
+	// Drawing upside-down for `render_to()`, since framebuffer rows start
+	// at the bottom, and texture rows at the top.
+	flip_y: bool,
 }
 
-pub fn new() -> Result<Box<Display>, &'static str> {
-	if let Some(tuple) = OpenGLBuilder::new() {
+pub fn new(api: GlApi, hints: &::WindowHints, msaa: u8, alpha: bool)
+	-> Result<Box<Display>, String>
+{
+	// Without a visual (Wayland), make the window first, since EGL needs
+	// to connect to it's display.
+	let window = if ::Window::needs_visual() {
+		None
+	} else {
+		Some(::Window::new(None, hints))
+	};
+	let native = match window.as_ref().map(|w| w.get_connection()) {
+		Some(WindowConnection::Wayland(display, _, _)) => display,
+		_ => ptr::null_mut(),
+	};
+
+	if let Some(tuple) = OpenGLBuilder::new(native, false, api, msaa,
+		alpha)
+	{
 		let (builder, v) = tuple;
-		let window = ::Window::new(Some(v));
+		let window = window.unwrap_or_else(||
+			::Window::new(Some(v), hints));
 
-		let context = builder.to_opengl(match window.get_connection() {
-			WindowConnection::Xcb(_, window) => // |
-			//	WindowConnection::Windows(_, window) =>
-			{
-				unsafe {mem::transmute(window as usize)}
-			},
-			WindowConnection::Windows(_, window) => {
-				window
-			}
-			WindowConnection::Wayland => return Err(
-				"OpenGL support on Wayland is WIP"),
-			WindowConnection::DirectFB => return Err(
-				"OpenGL support on DirectFB is WIP"),
-			WindowConnection::Android => return Err(
-				"OpenGL support on Android is WIP"),
-			WindowConnection::IOS => return Err(
-				"OpenGL support on iOS is WIP"),
-			WindowConnection::AldaronsOS => return Err(
-				"AldaronsOS doesn't support OpenGL"),
-			WindowConnection::Arduino => return Err(
-				"Arduino doesn't support OpenGL"),
-			WindowConnection::Switch => return Err(
-				"Nintendo Switch doesn't support OpenGL"),
-			WindowConnection::Web => return Err(
-				"WebGL support is WIP"),
-			WindowConnection::NoOS => return Err(
-				"NoOS doesn't support OpenGL"),
-		});
+		let context = builder.to_opengl(
+			native_window(window.get_connection())?);
 
-		// Set the settings.
-		context.disable(Feature::Dither);
-		context.enable(Feature::CullFace);
-		context.enable(Feature::Blend);
-		context.blend();
-
-		// Load shaders
-		let style_solid = Style::new(&context,
-			SHADER_SOLID_VERT, SHADER_SOLID_FRAG);
-		let style_gradient = Style::new(&context,
-			SHADER_GRADIENT_VERT, SHADER_GRADIENT_FRAG);
-		let style_texture = Style::new(&context,
-			SHADER_TEX_VERT, SHADER_TEX_FRAG);
-		let style_faded = Style::new(&context,
-			SHADER_FADED_VERT, SHADER_TEX_FRAG);
-		let style_tinted = Style::new(&context,
-			SHADER_TEX_VERT, SHADER_TINTED_FRAG);
-		let style_complex = Style::new(&context,
-			SHADER_COMPLEX_VERT, SHADER_COMPLEX_FRAG);
-
-		let wh = window.wh();
-		let ar = wh.0 as f32 / wh.1 as f32;
-
-		let projection = base::projection(ar, 0.5 * PI);
-
-		// Adjust the viewport
-		context.viewport(wh.0, wh.1);
-
-		let mut display = self::Display {
-			window,
-			context,
-			color: (0.0, 0.0, 0.0),
-			alpha_ind: vec![],
-			opaque_ind: vec![],
-			alpha_vec: Cell::new(vec![]),
-			opaque_vec: Cell::new(vec![]),
-			gui_vec: Cell::new(vec![]),
-			models: vec![],
-			texcoords: vec![],
-			gradients: vec![],
-			textures: vec![],
-			styles: [
-				style_gradient,
-				style_texture,
-				style_faded,
-				style_tinted,
-				style_solid,
-				style_complex,
-			],
-			xyz: vector!(0.0, 0.0, 0.0),
-			rotate_xyz: vector!(0.0, 0.0, 0.0),
-			ar,
-			projection,
-		};
+		finish(window, context)
+	} else {
+		Err("Couldn't find OpenGL!".to_string())
+	}
+}
 
-		use self::base::Display;
+/// Create a `Display` that renders into a window that the app already made
+/// (with SDL, for example).  awi doesn't make a window or get input, so the
+/// app should call `resize()` when the window's size changes.  A second EGL
+/// context (and surface) is always made, even if the app already has one.
+pub fn from_existing(connection: WindowConnection, wh: (u16, u16))
+	-> Result<Box<Display>, String>
+{
+	let native = match connection {
+		WindowConnection::Wayland(display, _, _) => display,
+		_ => ptr::null_mut(),
+	};
+
+	if let Some((builder, _)) = OpenGLBuilder::new(native, false,
+		GlApi::Gles(2, 0), 0, false)
+	{
+		let context = builder.to_opengl(
+			native_window(connection.clone())?);
 
-		Ok(Box::new(display))
+		finish(::Window::existing(connection, wh), context)
 	} else {
-		Err("Couldn't find OpenGL!")
+		Err("Couldn't find OpenGL!".to_string())
+	}
+}
+
+// Get the native window handle to make the EGL surface with.
+fn native_window(connection: WindowConnection)
+	-> Result<*mut ::c_void, &'static str>
+{
+	Ok(match connection {
+		WindowConnection::Xcb(_, window) => // |
+		//	WindowConnection::Windows(_, window) =>
+		{
+			unsafe {mem::transmute(window as usize)}
+		},
+		WindowConnection::Windows(_, window) => {
+			window
+		}
+		WindowConnection::Wayland(_, _, egl_window) => {
+			egl_window
+		}
+		WindowConnection::DirectFB => return Err(
+			"OpenGL support on DirectFB is WIP"),
+		WindowConnection::Android => return Err(
+			"OpenGL support on Android is WIP"),
+		WindowConnection::IOS => return Err(
+			"OpenGL support on iOS is WIP"),
+		WindowConnection::AldaronsOS => return Err(
+			"AldaronsOS doesn't support OpenGL"),
+		WindowConnection::Arduino => return Err(
+			"Arduino doesn't support OpenGL"),
+		WindowConnection::Switch => return Err(
+			"Nintendo Switch doesn't support OpenGL"),
+		WindowConnection::Web => return Err(
+			"WebGL support is WIP"),
+		WindowConnection::NoOS => return Err(
+			"NoOS doesn't support OpenGL"),
+	})
+}
+
+/// Create a `Display` that renders offscreen, without an OS window.
+pub fn new_headless(wh: (u16, u16)) -> Result<Box<Display>, String> {
+	#[cfg(windows)]
+	{
+		let _ = wh;
+		return Err("Headless OpenGL on Windows is WIP".to_string());
+	}
+
+	#[cfg(not(windows))]
+	{
+		if let Some((builder, _)) = OpenGLBuilder::new(ptr::null_mut(),
+			true, GlApi::Gles(2, 0), 0, false)
+		{
+			let context = builder.to_opengl_headless(wh);
+
+			finish(::Window::headless(wh), context)
+		} else {
+			Err("Couldn't find OpenGL!".to_string())
+		}
+	}
+}
+
+fn finish(window: ::Window, context: OpenGL)
+	-> Result<Box<Display>, String>
+{
+	// Set the settings.
+	context.disable(Feature::Dither);
+	context.enable(Feature::CullFace);
+	context.enable(Feature::Blend);
+	context.blend(false);
+	// Desktop OpenGL ignores `gl_PointSize` without this (always on for
+	// OpenGLES).
+	if !context.gles() {
+		context.enable(Feature::ProgramPointSize);
+	}
+
+	// Load shaders (only fails with a broken driver).
+	let style = |vert, frag| Style::new(&context, vert, frag)
+		.map_err(|log| format!("Couldn't compile the built-in \
+			shaders: {}", log));
+	let style_solid = style(SHADER_SOLID_VERT, SHADER_SOLID_FRAG)?;
+	let style_gradient = style(SHADER_GRADIENT_VERT, SHADER_GRADIENT_FRAG)?;
+	let style_texture = style(SHADER_TEX_VERT, SHADER_TEX_FRAG)?;
+	let style_faded = style(SHADER_FADED_VERT, SHADER_TEX_FRAG)?;
+	let style_tinted = style(SHADER_TEX_VERT, SHADER_TINTED_FRAG)?;
+	let style_complex = style(SHADER_COMPLEX_VERT, SHADER_COMPLEX_FRAG)?;
+	let style_text = style(SHADER_TEX_VERT, SHADER_TEXT_FRAG)?;
+	let style_pick = style(SHADER_SOLID_VERT, SHADER_PICK_FRAG)?;
+
+	let wh = window.wh();
+	let ar = wh.0 as f32 / wh.1 as f32;
+	let viewport = (0, 0, wh.0 as i32, wh.1 as i32);
+
+	let projection_mode = Projection::default();
+	let projection = projection_mode.matrix(ar);
+
+	// Adjust the viewport
+	context.viewport(viewport.0, viewport.1, viewport.2, viewport.3);
+
+	let display = self::Display {
+		window,
+		context,
+		color: (0.0, 0.0, 0.0, 1.0),
+		fog: ([0.0, 0.0, 0.0, 1.0], 0.0, 100.0),
+		alpha_ind: vec![],
+		dirty: Cell::new(false),
+		changed: Cell::new(true),
+		skipped: false,
+		premultiplied: false,
+		opaque_sort: true,
+		opaque_ind: vec![],
+		alpha_vec: Cell::new(vec![]),
+		opaque_vec: Cell::new(vec![]),
+		gui_vec: Cell::new(vec![]),
+		gui_ind: vec![],
+		models: Slots::new(),
+		texcoords: Slots::new(),
+		gradients: Slots::new(),
+		textures: Slots::new(),
+		styles: vec![
+			style_gradient,
+			style_texture,
+			style_faded,
+			style_tinted,
+			style_solid,
+			style_complex,
+			style_text,
+		],
+		style_pick,
+		pick_target: None,
+		xyz: vector!(0.0, 0.0, 0.0),
+		rotate_xyz: vector!(0.0, 0.0, 0.0),
+		view: matrix!(),
+		frames: FrameTimes::new(),
+		ar,
+		viewport,
+		fixed_aspect: None,
+		projection,
+		projection_mode,
+		clip: base::CLIP_PLANES,
+		srgb: false,
+		wireframe: false,
+		alpha_to_coverage: false,
+		cull: Cull::Back,
+		flip_y: false,
+	};
+
+	Ok(Box::new(display))
+}
+
+impl Display {
+	fn model_topology(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>,
+		topology: Topology) -> Model
+	{
+		// TODO most is duplicate from other implementation.
+		let buffer = Buffer::new(&self.context);
+
+		let vertex_buffer = buffer;
+		vertex_buffer.set(vertices);
+
+
+		let index = self.models.add(ModelData {
+			vertex_buffer, vertex_count: vertices.len() as u32 / 4,
+			fans, topology
+		});
+
+		Model(index)
+	}
+
+	fn add_shape(&mut self, shape: ShapeData, blending: bool, camera: bool)
+		-> Shape
+	{
+		self.changed.set(true);
+		self.dirty.set(true);
+
+		base::new_shape(if !camera {
+			// GUI, drawn over everything else.
+			let gui_vec = self.gui_vec.get_mut();
+			let index = gui_vec.len() as u32;
+			gui_vec.push(shape);
+			self.gui_ind.push(index);
+			base::ShapeHandle::Gui(index)
+		} else if blending {
+			let alpha_vec = self.alpha_vec.get_mut();
+			let index = alpha_vec.len() as u32;
+			alpha_vec.push(shape);
+			self.alpha_ind.push(index);
+			base::ShapeHandle::Alpha(index)
+		} else {
+			let opaque_vec = self.opaque_vec.get_mut();
+			let index = opaque_vec.len() as u32;
+			opaque_vec.push(shape);
+			self.opaque_ind.push(index);
+			base::ShapeHandle::Opaque(index)
+		})
 	}
 }
 
@@ -250,65 +445,355 @@ fn as_mut(slf: &Cell<Vec<ShapeData>>) -> &mut Vec<ShapeData> {
 
 impl base::Display for Display {
 	fn color(&mut self, color: (u8, u8, u8)) {
+		self.color_rgba((color.0, color.1, color.2, 255))
+	}
+
+	fn color_rgba(&mut self, color: (u8, u8, u8, u8)) {
+		self.changed.set(true);
 		self.color = (color.0 as f32 / 255.0,
-			color.1 as f32 / 255.0, color.2 as f32 / 255.0);
-		self.context.color(self.color.0, self.color.1, self.color.2);
+			color.1 as f32 / 255.0, color.2 as f32 / 255.0,
+			color.3 as f32 / 255.0);
+		self.context.color(self.color.0, self.color.1, self.color.2,
+			self.color.3);
+	}
+
+	fn fog(&mut self, color: [f32; 4], near: f32, far: f32) -> () {
+		self.changed.set(true);
+		self.fog = (color, near, far);
+	}
+
+	fn camera(&mut self, position: Vector, rotation: Vector) -> () {
+		self.changed.set(true);
+		self.xyz = position;
+		self.rotate_xyz = rotation;
+		self.view = base::camera(position, rotation);
+		self.dirty.set(true);
+	}
+
+	fn set_timestep(&mut self, step: ::std::time::Duration) -> () {
+		self.window.set_timestep(step)
+	}
+
+	fn set_pause_halts_timestep(&mut self, halts: bool) -> () {
+		self.window.set_pause_halts_timestep(halts)
+	}
+
+	fn set_vsync(&mut self, vsync: bool) -> bool {
+		self.context.vsync(vsync)
+	}
+
+	fn set_latency_mode(&mut self, mode: LatencyMode) -> bool {
+		self.context.adaptive_vsync(mode == LatencyMode::LowLatency)
+	}
+
+	fn set_blend_mode(&mut self, mode: BlendMode) -> () {
+		self.changed.set(true);
+		self.premultiplied = mode == BlendMode::Premultiplied;
+		self.context.blend(self.premultiplied);
+	}
+
+	fn set_srgb(&mut self, srgb: bool) -> bool {
+		self.changed.set(true);
+		if !self.context.srgb(srgb) {
+			return false;
+		}
+
+		self.srgb = srgb;
+		true
+	}
+
+	fn set_wireframe(&mut self, wireframe: bool) -> bool {
+		self.changed.set(true);
+		// Try it now, OpenGLES needs GL_NV_polygon_mode.
+		if wireframe && !self.context.polygon_mode(false) {
+			return false;
+		}
+
+		self.wireframe = wireframe;
+		true
+	}
+
+	fn set_alpha_to_coverage(&mut self, enabled: bool) -> bool {
+		self.changed.set(true);
+		self.alpha_to_coverage = enabled;
+		true
+	}
+
+	fn set_cull(&mut self, cull: Cull) -> bool {
+		self.changed.set(true);
+		self.cull = cull;
+		true
+	}
+
+	fn set_debug_callback(&mut self, callback: Box<Fn(&str)>) -> bool {
+		self.context.set_debug_callback(callback);
+		true
+	}
+
+	fn set_opaque_sort(&mut self, sort: bool) -> () {
+		self.changed.set(true);
+		self.opaque_sort = sort;
+		self.dirty.set(true);
+	}
+
+	fn set_projection(&mut self, projection: Projection) -> () {
+		self.changed.set(true);
+		self.projection_mode = projection;
+		self.projection = projection.clipped(self.ar, self.clip);
+	}
+
+	fn set_clip_planes(&mut self, near: f32, far: f32) -> () {
+		self.changed.set(true);
+		self.clip = (near, far);
+		self.projection = self.projection_mode.clipped(self.ar,
+			self.clip);
+	}
+
+	fn projection(&self) -> Matrix {
+		self.projection
+	}
+
+	fn view(&self) -> Matrix {
+		self.view
 	}
 
 	fn input(&mut self) -> Option<base::Event> {
 		self.window.update()
 	}
 
-	fn update(&mut self) -> f32 {
+	fn wait_event(&mut self) -> Option<base::Event> {
+		self.window.wait_event()
+	}
+
+	fn wait_event_timeout(&mut self, timeout: ::std::time::Duration)
+		-> Option<base::Event>
+	{
+		self.window.wait_event_timeout(timeout)
+	}
+
+	fn drain(&mut self) -> base::Drain {
+		self.window.drain()
+	}
+
+	fn pop_matching(&mut self, pred: &Fn(&base::Event) -> bool)
+		-> Option<base::Event>
+	{
+		self.window.pop_matching(pred)
+	}
+
+	fn request_frame(&mut self) {
+		self.window.request_frame()
+	}
+
+	fn set_event_history(&mut self, capacity: usize) {
+		self.window.set_event_history(capacity)
+	}
+
+	fn event_history(&self) -> &[Event] {
+		self.window.event_history()
+	}
+
+	#[cfg(feature = "async")]
+	fn fd(&mut self) -> Option<i32> {
+		self.window.fd()
+	}
+
+	#[cfg(feature = "async")]
+	fn wake_on_input(&mut self, waker: ::std::task::Waker) -> bool {
+		self.window.wake_on_input(waker)
+	}
+
+	fn render(&mut self) {
+		// Nothing to draw to.
+		if self.window.is_closed() {
+			return;
+		}
+
+		// The last frame is still good if nothing changed, and the
+		// window system didn't lose any of it.
+		let redraw = self.window.take_redraw();
+		if !self.changed.replace(false) && !redraw {
+			self.skipped = true;
+			return;
+		}
+		self.skipped = false;
+
+		// Raw GL after the last `render()` could have changed what's
+		// bound, and the state awi sets.
+		self.context.forget_bindings();
+		self.context.disable(Feature::Dither);
+		self.context.enable(Feature::Blend);
+		self.context.blend(self.premultiplied);
+		self.context.viewport(self.viewport.0, self.viewport.1,
+			self.viewport.2, self.viewport.3);
+
 		// Opaque & Alpha Shapes need a camera.
+		let flip = if self.flip_y { flip_y() } else { matrix!() };
+		let camera = flip * self.projection * self.view;
 		for i in (&self.styles).iter() {
 			i.has_camera.set_int1(1);
+			i.camera_uniform.set_mat4(camera.into());
+		}
+
+		// Fog range is from where fog starts, to how far it reaches.
+		let (fog, near, far) = self.fog;
+		for i in (&self.styles).iter() {
+			i.fog.set_vec4(&fog);
+			i.range.set_vec2(&[near, far - near]);
+		}
+
+		// Black bars around the letterboxed viewport.
+		if self.fixed_aspect.is_some() {
+			draw_bars(&self.context, self.window.wh(),
+				self.viewport);
+			let (r, g, b, a) = self.color;
+			self.context.color(r, g, b, a);
 		}
 
 		// Enable for 3D depth testing
 		self.context.enable(Feature::DepthTest);
 
-		// sort nearest
-		base::zsort(&mut self.opaque_ind, self.opaque_vec.get_mut(),
-			true, self.xyz);
-		for shape in as_mut(&self.opaque_vec).iter() {
-			draw_shape(&self.styles[shape.style], shape);
+		// Drawn part of the window, for clip rectangles.
+		let viewport = self.viewport;
+
+		if self.wireframe {
+			self.context.polygon_mode(true);
+		}
+
+		// Only sort if something moved.
+		if self.dirty.get() {
+			// sort nearest
+			if self.opaque_sort {
+				base::zsort(&mut self.opaque_ind,
+					self.opaque_vec.get_mut(), true,
+					self.xyz);
+			}
+			// sort farthest
+			base::zsort(&mut self.alpha_ind, self.alpha_vec.get_mut(),
+				false, self.xyz);
+			// sort by layer (stable, so same layer keeps it's order)
+			let gui_vec = self.gui_vec.get_mut();
+			self.gui_ind.sort_by_key(|i| gui_vec[*i as usize].layer);
+			self.dirty.set(false);
+		}
+
+		for shape in self.opaque_ind.iter() {
+			let shape = &as_mut(&self.opaque_vec)[*shape as usize];
+			if shape.visible {
+				draw_clipped(&self.context, viewport,
+					self.cull, self.flip_y,
+					&self.styles[shape.style], shape);
+			}
+		}
+
+		if self.alpha_to_coverage {
+			self.context.enable(Feature::SampleAlphaToCoverage);
 		}
 
-		// sort farthest
-		base::zsort(&mut self.alpha_ind, &self.alpha_vec.get_mut(),
-			false, self.xyz);
-		for shape in as_mut(&self.alpha_vec).iter() {
-			draw_shape(&self.styles[shape.style], shape);
+		for shape in self.alpha_ind.iter() {
+			let shape = &as_mut(&self.alpha_vec)[*shape as usize];
+			if shape.visible {
+				draw_clipped(&self.context, viewport,
+					self.cull, self.flip_y,
+					&self.styles[shape.style], shape);
+			}
+		}
+
+		if self.alpha_to_coverage {
+			self.context.disable(Feature::SampleAlphaToCoverage);
 		}
 
 		// Disable Depth Testing for GUI
 		self.context.disable(Feature::DepthTest);
 
-		// No need to sort gui elements.
-		for shape in as_mut(&self.gui_vec).iter() {
-			draw_shape(&self.styles[shape.style], shape);
+		// GUI Shapes don't have a camera (except to flip them).
+		for i in (&self.styles).iter() {
+			if self.flip_y {
+				i.camera_uniform.set_mat4(flip.into());
+				i.has_camera.set_int1(1);
+			} else {
+				i.has_camera.set_int1(0);
+			}
+		}
+
+		for shape in self.gui_ind.iter() {
+			let shape = &as_mut(&self.gui_vec)[*shape as usize];
+			if shape.visible {
+				draw_clipped(&self.context, viewport,
+					self.cull, self.flip_y,
+					&self.styles[shape.style], shape);
+			}
 		}
 
-		self.context.update()
+		if self.wireframe {
+			self.context.polygon_mode(false);
+		}
 	}
 
-	fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>) -> Model {
-		// TODO most is duplicate from other implementation.
-		let index = self.models.len();
+	fn render_to(&mut self, texture: &Texture) -> bool {
+		let target = self.textures[texture.0].t.clone();
+		let (w, h) = (texture.1, texture.2);
+
+		// Draw as if the texture were the whole window (with it's
+		// aspect ratio), with rows in the same order as `texture()`.
+		let viewport = self.viewport;
+		let fixed_aspect = self.fixed_aspect.take();
+		let projection = self.projection;
+		self.viewport = (0, 0, w as i32, h as i32);
+		self.projection = self.projection_mode.clipped(
+			w as f32 / h as f32, self.clip);
+		self.flip_y = true;
+		self.changed.set(true);
+
+		target.draw_into(w, h, || self.render());
+
+		// Shapes with the texture have to be drawn again.
+		self.changed.set(true);
+		self.flip_y = false;
+		self.viewport = viewport;
+		self.fixed_aspect = fixed_aspect;
+		self.projection = projection;
+		self.context.viewport(viewport.0, viewport.1, viewport.2,
+			viewport.3);
+		true
+	}
 
-		let buffer = Buffer::new(&self.context);
+	fn present(&mut self) -> base::FrameStatus {
+		// Nothing to draw to.
+		if self.window.is_closed() {
+			return base::FrameStatus::default();
+		}
 
-		let vertex_buffer = buffer;
-		vertex_buffer.set(vertices);
+		let dt = self.context.update(!self.skipped);
+		self.frames.push(dt)
+	}
 
+	fn frame_time(&self) -> ::std::time::Duration {
+		self.frames.frame_time()
+	}
 
-		self.models.push(ModelData {
-			vertex_buffer, vertex_count: vertices.len() as u32 / 4,
-			fans
-		});
+	fn fps(&self) -> f32 {
+		self.frames.fps()
+	}
 
-		Model(index)
+	fn set_target_fps(&mut self, fps: f32) {
+		self.frames.set_target(fps);
+	}
+
+	fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>) -> Model {
+		self.model_topology(vertices, fans, Topology::TriangleFan)
+	}
+
+	fn model_lines(&mut self, vertices: &[f32]) -> Model {
+		let fans = vec![(0, vertices.len() as u32 / 4)];
+
+		self.model_topology(vertices, fans, Topology::Lines)
+	}
+
+	fn model_points(&mut self, vertices: &[f32]) -> Model {
+		let fans = vec![(0, vertices.len() as u32 / 4)];
+
+		self.model_topology(vertices, fans, Topology::Points)
 	}
 
 	fn texture(&mut self, wh: (u16,u16), graphic: &VFrame) -> Texture {
@@ -316,25 +801,84 @@ impl base::Display for Display {
 		let pixels = graphic.0.as_slice();
 
 		let t = self.context.texture();
+		let srgb = self.srgb && self.context.srgb_textures();
 
-		t.set(w, h, pixels);
-
-		let a = self.textures.len();
+		t.set(w, h, pixels, srgb);
 
-		self.textures.push(TextureData { t });
+		let a = self.textures.add(TextureData { t, srgb });
 
 		Texture(a, w, h)
 	}
 
+	fn texture_fmt(&mut self, wh: (u16,u16), format: PixelFormat,
+		data: &[u8]) -> Result<Texture, String>
+	{
+		format.check(wh, data)?;
+
+		// Desktop OpenGL can upload BGRA as it is (not as sRGB, which
+		// needs GL_EXT_sRGB's format).
+		let srgb = self.srgb && self.context.srgb_textures();
+		if format == PixelFormat::Bgra8 && !srgb && !self.context.gles()
+		{
+			let t = self.context.texture();
+
+			t.set_bgra(wh.0, wh.1, data);
+
+			let a = self.textures.add(TextureData { t, srgb });
+
+			return Ok(Texture(a, wh.0, wh.1));
+		}
+
+		Ok(self.texture(wh, &VFrame(format.to_rgba(data))))
+	}
+
+	fn texture_compressed(&mut self, wh: (u16,u16),
+		format: CompressedFormat, data: &[u8])
+		-> Result<Texture, String>
+	{
+		const GL_COMPRESSED_RGBA_S3TC_DXT1_EXT: u32 = 0x83F1;
+		const GL_COMPRESSED_RGBA_S3TC_DXT5_EXT: u32 = 0x83F3;
+		const GL_COMPRESSED_RGB8_ETC2: u32 = 0x9274;
+		const GL_COMPRESSED_RGBA8_ETC2_EAC: u32 = 0x9278;
+
+		let (supported, gl_format) = match format {
+			CompressedFormat::Dxt1 => (self.context.s3tc_textures(),
+				GL_COMPRESSED_RGBA_S3TC_DXT1_EXT),
+			CompressedFormat::Dxt5 => (self.context.s3tc_textures(),
+				GL_COMPRESSED_RGBA_S3TC_DXT5_EXT),
+			CompressedFormat::Etc2 => (self.context.etc2_textures(),
+				GL_COMPRESSED_RGB8_ETC2),
+			CompressedFormat::Etc2Alpha => (
+				self.context.etc2_textures(),
+				GL_COMPRESSED_RGBA8_ETC2_EAC),
+		};
+
+		if !supported {
+			return Err(format!("{:?} textures aren't supported by \
+				this driver", format));
+		}
+		if data.len() != format.size(wh) {
+			return Err(format!("{:?} data is {} bytes, but {}x{} \
+				needs {}", format, data.len(), wh.0, wh.1,
+				format.size(wh)));
+		}
+
+		let t = self.context.texture();
+
+		t.set_compressed(wh.0, wh.1, gl_format, data);
+
+		let a = self.textures.add(TextureData { t, srgb: false });
+
+		Ok(Texture(a, wh.0, wh.1))
+	}
+
 	fn gradient(&mut self, colors: &[f32]) -> Gradient {
 		// TODO: A lot of duplication here from adi_gpu_vulkan.  Put in
 		// base.
 		let vertex_buffer = Buffer::new(&self.context);
 		vertex_buffer.set(colors);
 
-		let a = self.gradients.len();
-
-		self.gradients.push(GradientData {
+		let a = self.gradients.add(GradientData {
 			vertex_buffer,
 			vertex_count: colors.len() as u32 / 4,
 		});
@@ -355,9 +899,7 @@ impl base::Display for Display {
 		}
 		vertex_buffer.set(buffer.as_slice());
 
-		let a = self.texcoords.len();
-
-		self.texcoords.push(TexcoordsData {
+		let a = self.texcoords.add(TexcoordsData {
 			vertex_buffer,
 			vertex_count: texcoords.len() as u32,
 		});
@@ -368,8 +910,42 @@ impl base::Display for Display {
 	fn set_texture(&mut self, texture: &mut Texture, wh: (u16,u16),
 		graphic: &VFrame)
 	{
-		self.textures[texture.0].t.set(wh.0, wh.1,
-			graphic.0.as_slice());
+		self.changed.set(true);
+		texture.1 = wh.0;
+		texture.2 = wh.1;
+
+		let texture = &self.textures[texture.0];
+
+		texture.t.set(wh.0, wh.1, graphic.0.as_slice(), texture.srgb);
+	}
+
+	fn read_texture(&self, texture: &Texture) -> (u16, u16, Vec<[u8; 4]>) {
+		let (w, h) = (texture.1, texture.2);
+		let px = self.textures[texture.0].t.read(w, h);
+
+		(w, h, px.chunks(4).map(|p| [p[0], p[1], p[2], p[3]]).collect())
+	}
+
+	fn set_model(&mut self, model: &Model, vertices: &[f32],
+		fans: Vec<(u32, u32)>)
+	{
+		self.changed.set(true);
+		let model = &mut self.models[model.0];
+
+		// Shapes share the buffer, so only their fans need updating.
+		model.vertex_buffer.set(vertices);
+		model.vertex_count = vertices.len() as u32 / 4;
+
+		let buffer = model.vertex_buffer.get();
+		for shape in self.opaque_vec.get_mut().iter_mut()
+			.chain(self.alpha_vec.get_mut().iter_mut())
+			.chain(self.gui_vec.get_mut().iter_mut())
+			.filter(|shape| shape.vertex_buffer.get() == buffer)
+		{
+			shape.fans = fans.clone();
+		}
+
+		model.fans = fans;
 	}
 
 	#[inline(always)]
@@ -387,34 +963,42 @@ impl base::Display for Display {
 			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
 			transform, // Transformation matrix.
 			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
 		};
 
-		base::new_shape(if blending {
-			let alpha_vec = self.alpha_vec.get_mut();
-			let index = alpha_vec.len() as u32;
-			alpha_vec.push(shape);
-			self.alpha_ind.push(index);
-			base::ShapeHandle::Alpha(index)
+		self.add_shape(shape, blending, camera)
+	}
+
+	fn shapes_solid(&mut self, shapes: &[(Model, Matrix, [f32; 4])],
+		blending: bool, fog: bool, camera: bool) -> Vec<Shape>
+	{
+		if blending {
+			self.alpha_vec.get_mut().reserve(shapes.len());
+			self.alpha_ind.reserve(shapes.len());
 		} else {
-			let opaque_vec = self.opaque_vec.get_mut();
-			let index = opaque_vec.len() as u32;
-			opaque_vec.push(shape);
-			self.opaque_ind.push(index);
-			base::ShapeHandle::Opaque(index)
-		})
+			self.opaque_vec.get_mut().reserve(shapes.len());
+			self.opaque_ind.reserve(shapes.len());
+		}
+
+		shapes.iter().map(|&(ref model, transform, color)| {
+			self.shape_solid(model, transform, color, blending, fog,
+				camera)
+		}).collect()
 	}
 
 	#[inline(always)]
 	fn shape_gradient(&mut self, model: &Model, transform: Matrix,
 		colors: Gradient, blending: bool, fog: bool, camera: bool)
-		-> Shape
+		-> Result<Shape, ShapeError>
 	{
-		// TODO: is copied from adi_gpu_vulkan, move to base
-		if self.models[model.0].vertex_count
-			!= self.gradients[colors.0].vertex_count
-		{
-			panic!("TexCoord length doesn't match gradient length");
-		}
+		base::check_gradient(self.models[model.0].vertex_count,
+			self.gradients[colors.0].vertex_count)?;
 
 		let shape = ShapeData {
 			style: STYLE_GRADIENT,
@@ -429,34 +1013,25 @@ impl base::Display for Display {
 			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
 			transform, // Transformation matrix.
 			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
 		};
 
-		base::new_shape(if blending {
-			let alpha_vec = self.alpha_vec.get_mut();
-			let index = alpha_vec.len() as u32;
-			alpha_vec.push(shape);
-			self.alpha_ind.push(index);
-			base::ShapeHandle::Alpha(index)
-		} else {
-			let opaque_vec = self.opaque_vec.get_mut();
-			let index = opaque_vec.len() as u32;
-			opaque_vec.push(shape);
-			self.opaque_ind.push(index);
-			base::ShapeHandle::Opaque(index)
-		})
+		Ok(self.add_shape(shape, blending, camera))
 	}
 
 	#[inline(always)]
 	fn shape_texture(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, blending: bool, fog: bool,
-		camera: bool) -> Shape
+		camera: bool) -> Result<Shape, ShapeError>
 	{
-		// TODO: from adi_gpu_vulkan, move to the base
-		if self.models[model.0].vertex_count
-			!= self.texcoords[tc.0].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		base::check_texcoords(self.models[model.0].vertex_count,
+			self.texcoords[tc.0].vertex_count)?;
 
 		let shape = ShapeData {
 			style: STYLE_TEXTURE,
@@ -471,34 +1046,25 @@ impl base::Display for Display {
 			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
 			transform, // Transformation matrix.
 			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
 		};
 
-		base::new_shape(if blending {
-			let alpha_vec = self.alpha_vec.get_mut();
-			let index = alpha_vec.len() as u32;
-			alpha_vec.push(shape);
-			self.alpha_ind.push(index);
-			base::ShapeHandle::Alpha(index)
-		} else {
-			let opaque_vec = self.opaque_vec.get_mut();
-			let index = opaque_vec.len() as u32;
-			opaque_vec.push(shape);
-			self.opaque_ind.push(index);
-			base::ShapeHandle::Opaque(index)
-		})
+		Ok(self.add_shape(shape, blending, camera))
 	}
 
 	#[inline(always)]
 	fn shape_faded(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, alpha: f32, fog: bool,
-		camera: bool) -> Shape
+		camera: bool) -> Result<Shape, ShapeError>
 	{
-		// TODO: from adi_gpu_vulkan, move to the base
-		if self.models[model.0].vertex_count
-			!= self.texcoords[tc.0].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		base::check_texcoords(self.models[model.0].vertex_count,
+			self.texcoords[tc.0].vertex_count)?;
 
 		let shape = ShapeData {
 			style: STYLE_FADED,
@@ -513,28 +1079,25 @@ impl base::Display for Display {
 			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
 			transform, // Transformation matrix.
 			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
 		};
 
-		base::new_shape({
-			let alpha_vec = self.alpha_vec.get_mut();
-			let index = alpha_vec.len() as u32;
-			alpha_vec.push(shape);
-			self.alpha_ind.push(index);
-			base::ShapeHandle::Alpha(index)
-		})
+		Ok(self.add_shape(shape, true, camera))
 	}
 
 	#[inline(always)]
 	fn shape_tinted(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, tint: [f32; 4], blending: bool,
-		fog: bool, camera: bool) -> Shape
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
 	{
-		// TODO: from adi_gpu_vulkan, move to the base
-		if self.models[model.0].vertex_count
-			!= self.texcoords[tc.0].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		base::check_texcoords(self.models[model.0].vertex_count,
+			self.texcoords[tc.0].vertex_count)?;
 
 		let shape = ShapeData {
 			style: STYLE_TINTED,
@@ -549,41 +1112,60 @@ impl base::Display for Display {
 			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
 			transform, // Transformation matrix.
 			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
 		};
 
-		base::new_shape(if blending {
-			let alpha_vec = self.alpha_vec.get_mut();
-			let index = alpha_vec.len() as u32;
-			alpha_vec.push(shape);
-			self.alpha_ind.push(index);
-			base::ShapeHandle::Alpha(index)
-		} else {
-			let opaque_vec = self.opaque_vec.get_mut();
-			let index = opaque_vec.len() as u32;
-			opaque_vec.push(shape);
-			self.opaque_ind.push(index);
-			base::ShapeHandle::Opaque(index)
-		})
+		Ok(self.add_shape(shape, blending, camera))
+	}
+
+	fn shape_text(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, color: [f32; 4], blending: bool,
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
+	{
+		base::check_texcoords(self.models[model.0].vertex_count,
+			self.texcoords[tc.0].vertex_count)?;
+
+		let shape = ShapeData {
+			style: STYLE_TEXT,
+			buffers: [
+				Some(self.texcoords[tc.0].vertex_buffer.clone()),
+				None,
+			],
+			has_fog: fog,
+			alpha: None,
+			color: Some(color),
+			texture: Some(self.textures[texture.0].t.clone()),
+			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
+			transform, // Transformation matrix.
+			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
+		};
+
+		Ok(self.add_shape(shape, blending, camera))
 	}
 
 	#[inline(always)]
 	fn shape_complex(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, tints: Gradient,
-		blending: bool, fog: bool, camera: bool) -> Shape
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
 	{
-		// TODO: from adi_gpu_vulkan, move to the base
-		if self.models[model.0].vertex_count
-			!= self.texcoords[tc.0].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
-
-		// TODO: is copied from adi_gpu_vulkan, move to base
-		if self.models[model.0].vertex_count
-			!= self.gradients[tints.0].vertex_count
-		{
-			panic!("TexCoord length doesn't match gradient length");
-		}
+		base::check_texcoords(self.models[model.0].vertex_count,
+			self.texcoords[tc.0].vertex_count)?;
+		base::check_gradient(self.models[model.0].vertex_count,
+			self.gradients[tints.0].vertex_count)?;
 
 		let shape = ShapeData {
 			style: STYLE_COMPLEX,
@@ -598,40 +1180,121 @@ impl base::Display for Display {
 			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
 			transform, // Transformation matrix.
 			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+			tint: None,
+			uniforms: vec![],
 		};
 
-		base::new_shape(if blending {
-			let alpha_vec = self.alpha_vec.get_mut();
-			let index = alpha_vec.len() as u32;
-			alpha_vec.push(shape);
-			self.alpha_ind.push(index);
-			base::ShapeHandle::Alpha(index)
-		} else {
-			let opaque_vec = self.opaque_vec.get_mut();
-			let index = opaque_vec.len() as u32;
-			opaque_vec.push(shape);
-			self.opaque_ind.push(index);
-			base::ShapeHandle::Opaque(index)
-		})
+		Ok(self.add_shape(shape, blending, camera))
 	}
 
 	#[inline(always)]
+	fn add_style(&mut self, vert: &[u8], frag: &[u8])
+		-> Result<StyleId, String>
+	{
+		self.styles.push(Style::new(&self.context, vert, frag)?);
+		Ok(StyleId(self.styles.len() - 1))
+	}
+
+	fn shape_custom(&mut self, model: &Model, transform: Matrix,
+		style: StyleId, uniforms: &[(&str, UniformValue)],
+		blending: bool, fog: bool, camera: bool) -> Shape
+	{
+		let uniforms = uniforms.iter().map(|&(name, value)| {
+			let mut name = name.as_bytes().to_vec();
+			name.push(b'\0');
+
+			(self.styles[style.0].shader.uniform(&name), value)
+		}).collect();
+
+		let shape = ShapeData {
+			style: style.0,
+			buffers: [None, None],
+			has_fog: fog,
+			alpha: None,
+			color: None,
+			texture: None,
+			vertex_buffer: self.models[model.0].vertex_buffer.clone(),
+			transform, // Transformation matrix.
+			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			cull: None,
+			tint: None,
+			layer: 0,
+			uniforms,
+		};
+
+		self.add_shape(shape, blending, camera)
+	}
+
+	fn set_uniform(&mut self, style: StyleId, name: &str,
+		value: UniformValue)
+	{
+		self.changed.set(true);
+		let mut name = name.as_bytes().to_vec();
+		name.push(b'\0');
+
+		// Kept, so shapes that set it too don't change it for the rest.
+		let style = &mut self.styles[style.0];
+		if let Some(uniform) = style.uniforms.iter_mut()
+			.find(|u| u.0 == name)
+		{
+			uniform.2 = value;
+			return;
+		}
+		let uniform = style.shader.uniform(&name);
+		style.uniforms.push((name, uniform, value));
+	}
+
 	fn drop_shape(&mut self, shape: &Shape) {
+		self.changed.set(true);
 		match get_shape(&shape) {
 			ShapeHandle::Opaque(x) => {
 				let index = self.opaque_ind.iter()
 					.position(|y| *y == x).unwrap();
 				self.opaque_ind.remove(index);
+				self.dirty.set(true);
 			}
 			ShapeHandle::Alpha(x) => {
 				let index = self.alpha_ind.iter()
 					.position(|y| *y == x).unwrap();
 				self.alpha_ind.remove(index);
+				self.dirty.set(true);
+			}
+			ShapeHandle::Gui(x) => {
+				let index = self.gui_ind.iter()
+					.position(|y| *y == x).unwrap();
+				self.gui_ind.remove(index);
 			}
 		}
 	}
 
+	fn drop_model(&mut self, model: Model) {
+		// Shapes hold their own reference to the buffer, so it's
+		// freed once the last shape using it is dropped.
+		self.models.remove(model.0);
+	}
+
+	fn drop_texture(&mut self, texture: Texture) {
+		self.textures.remove(texture.0);
+	}
+
+	fn drop_gradient(&mut self, gradient: Gradient) {
+		self.gradients.remove(gradient.0);
+	}
+
+	fn drop_texcoords(&mut self, texcoords: TexCoords) {
+		self.texcoords.remove(texcoords.0);
+	}
+
 	fn transform(&self, shape: &Shape, transform: Matrix) {
+		self.changed.set(true);
 		// TODO: put in base, some is copy from vulkan implementation.
 		match base::get_shape(shape) {
 			ShapeHandle::Opaque(x) => {
@@ -642,50 +1305,569 @@ impl base::Display for Display {
 				let x = x as usize; // for indexing
 				as_mut(&self.alpha_vec)[x].transform = transform;
 			}
+			ShapeHandle::Gui(x) => {
+				let x = x as usize; // for indexing
+				as_mut(&self.gui_vec)[x].transform = transform;
+			}
+		}
+		self.dirty.set(true);
+	}
+
+	fn set_clip(&mut self, shape: &Shape,
+		clip: Option<(f32, f32, f32, f32)>)
+	{
+		self.changed.set(true);
+		match base::get_shape(shape) {
+			ShapeHandle::Opaque(x) => {
+				let x = x as usize; // for indexing
+				self.opaque_vec.get_mut()[x].clip = clip;
+			}
+			ShapeHandle::Alpha(x) => {
+				let x = x as usize; // for indexing
+				self.alpha_vec.get_mut()[x].clip = clip;
+			}
+			ShapeHandle::Gui(x) => {
+				let x = x as usize; // for indexing
+				self.gui_vec.get_mut()[x].clip = clip;
+			}
+		}
+	}
+
+	fn set_shape_cull(&mut self, shape: &Shape, cull: Option<Cull>)
+		-> bool
+	{
+		self.changed.set(true);
+		match base::get_shape(shape) {
+			ShapeHandle::Opaque(x) => {
+				let x = x as usize; // for indexing
+				self.opaque_vec.get_mut()[x].cull = cull;
+			}
+			ShapeHandle::Alpha(x) => {
+				let x = x as usize; // for indexing
+				self.alpha_vec.get_mut()[x].cull = cull;
+			}
+			ShapeHandle::Gui(x) => {
+				let x = x as usize; // for indexing
+				self.gui_vec.get_mut()[x].cull = cull;
+			}
+		}
+		true
+	}
+
+	fn set_tint(&mut self, shape: &Shape, tint: Option<[f32; 4]>) -> bool {
+		self.changed.set(true);
+		match base::get_shape(shape) {
+			ShapeHandle::Opaque(x) => {
+				let x = x as usize; // for indexing
+				self.opaque_vec.get_mut()[x].tint = tint;
+			}
+			ShapeHandle::Alpha(x) => {
+				let x = x as usize; // for indexing
+				self.alpha_vec.get_mut()[x].tint = tint;
+			}
+			ShapeHandle::Gui(x) => {
+				let x = x as usize; // for indexing
+				self.gui_vec.get_mut()[x].tint = tint;
+			}
+		}
+		true
+	}
+
+	fn set_visible(&mut self, shape: &Shape, visible: bool) {
+		self.changed.set(true);
+		match base::get_shape(shape) {
+			ShapeHandle::Opaque(x) => {
+				let x = x as usize; // for indexing
+				self.opaque_vec.get_mut()[x].visible = visible;
+			}
+			ShapeHandle::Alpha(x) => {
+				let x = x as usize; // for indexing
+				self.alpha_vec.get_mut()[x].visible = visible;
+			}
+			ShapeHandle::Gui(x) => {
+				let x = x as usize; // for indexing
+				self.gui_vec.get_mut()[x].visible = visible;
+			}
+		}
+	}
+
+	fn set_layer(&mut self, shape: &Shape, layer: i32) {
+		self.changed.set(true);
+		match base::get_shape(shape) {
+			ShapeHandle::Gui(x) => {
+				self.gui_vec.get_mut()[x as usize].layer = layer;
+				self.dirty.set(true);
+			}
+			// Shapes with a camera are sorted by depth instead.
+			ShapeHandle::Opaque(_) | ShapeHandle::Alpha(_) => {}
 		}
 	}
 
 	fn resize(&mut self, wh: (u16, u16)) -> () {
+		self.changed.set(true);
 		let xyz = self.xyz;
 		let rotate_xyz = self.rotate_xyz;
 
-		self.ar = wh.0 as f32 / wh.1 as f32;
-		self.context.viewport(wh.0, wh.1);
+		let (x, y, w, h) = base::letterbox(wh, self.fixed_aspect);
+
+		self.ar = w as f32 / h as f32;
+		self.viewport = (x, y, w, h);
+		self.context.viewport(x, y, w, h);
+		self.window.set_wh(wh);
 
-		self.projection = super::base::projection(self.ar, 0.5 * PI);
+		self.projection = self.projection_mode.clipped(self.ar,
+			self.clip);
+	}
+
+	fn set_fixed_aspect(&mut self, aspect: Option<f32>) -> bool {
+		let wh = self.window.wh();
+
+		self.fixed_aspect = aspect;
+		self.resize(wh);
+		true
 	}
 
 	fn wh(&self) -> (u16, u16) {
 		self.window.wh()
 	}
 
+	fn clipboard_set(&mut self, text: &str) {
+		self.window.clipboard_set(text)
+	}
+
+	fn clipboard_get(&self) -> Option<String> {
+		self.window.clipboard_get()
+	}
+
+	fn set_cursor(&mut self, cursor: Cursor) {
+		self.window.set_cursor(cursor)
+	}
+
+	fn set_cursor_position(&mut self, xy: (f32, f32)) {
+		self.window.set_cursor_position(xy)
+	}
+
+	fn set_min_size(&mut self, wh: (u16, u16)) {
+		self.window.set_min_size(wh)
+	}
+
+	fn set_max_size(&mut self, wh: (u16, u16)) {
+		self.window.set_max_size(wh)
+	}
+
+	fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		self.window.set_aspect_ratio(aspect)
+	}
+
+	fn close(&mut self) {
+		self.window.close()
+	}
+
+	fn is_closed(&self) -> bool {
+		self.window.is_closed()
+	}
+
+	fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)> {
+		self.window.take_damage()
+	}
+
+	fn preedit(&self) -> &str {
+		self.window.preedit()
+	}
+
+	fn set_text_str(&mut self, enabled: bool) {
+		self.window.set_text_str(enabled)
+	}
+
+	fn set_shortcuts_enabled(&mut self, enabled: bool) {
+		self.window.set_shortcuts_enabled(enabled)
+	}
+
+	fn set_natural_scroll(&mut self, natural: bool) {
+		self.window.set_natural_scroll(natural)
+	}
+
+	fn text_str(&self, index: usize) -> &str {
+		self.window.text_str(index)
+	}
+
+	fn is_key_down(&self, key: u8) -> bool {
+		self.window.is_key_down(key)
+	}
+
+	fn set_always_on_top(&mut self, above: bool) -> bool {
+		self.window.set_always_on_top(above)
+	}
+
+	fn set_opacity(&mut self, opacity: f32) {
+		self.window.set_opacity(opacity)
+	}
+
+	fn set_class(&mut self, instance: &str, class: &str) {
+		self.window.set_class(instance, class)
+	}
+
+	fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		self.window.set_input_shape(region)
+	}
+
+	fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		self.window.set_keyboard_grab(grab)
+	}
+
+	fn minimize(&mut self) -> bool {
+		self.window.minimize()
+	}
+
+	fn maximize(&mut self) -> bool {
+		self.window.maximize()
+	}
+
+	fn restore(&mut self) -> bool {
+		self.window.restore()
+	}
+
+	fn scale_factor(&self) -> f32 {
+		self.window.scale_factor()
+	}
+
+	fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32)
+	{
+		self.window.set_stick_deadzone(controller, inner, outer)
+	}
+
+	fn set_controller_map(&mut self, controller: usize,
+		map: &[(ControllerButton, ControllerButton)])
+	{
+		self.window.set_controller_map(controller, map)
+	}
+
+	fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool) {
+		self.window.set_stick_as_dpad(controller, enabled)
+	}
+
+	fn controller_battery(&self, controller: usize) -> Option<f32> {
+		self.window.controller_battery(controller)
+	}
+
+	fn controller_guid(&self, controller: usize) -> Option<String> {
+		self.window.controller_guid(controller)
+	}
+
+	fn controller_capabilities(&self, controller: usize)
+		-> Option<::ControllerCaps>
+	{
+		self.window.controller_capabilities(controller)
+	}
+
+	fn position(&self) -> (i16, i16) {
+		self.window.position()
+	}
+
+	fn set_position(&mut self, xy: (i16, i16)) {
+		self.window.set_position(xy)
+	}
+
+	fn set_text_input_rect(&mut self, rect: Option<(i16, i16, u16, u16)>) {
+		self.window.set_text_input_rect(rect)
+	}
+
+	fn event_time(&self) -> ::std::time::Instant {
+		self.window.event_time()
+	}
+
+	fn set_decorated(&mut self, decorated: bool) {
+		self.window.set_decorated(decorated)
+	}
+
+	fn begin_drag(&mut self) -> bool {
+		self.window.begin_drag()
+	}
+
+	fn has_focus(&self) -> bool {
+		self.window.has_focus()
+	}
+
+	fn pixels(&self) -> Option<Vec<u8>> {
+		// TODO: glReadPixels before the swap.
+		None
+	}
+
 	fn draw(&self, _writer: &Fn(u16, u16) -> [u8; 4]) {
 		// TODO
 	}
+
+	fn pick(&mut self, x: f32, y: f32) -> Option<Shape> {
+		if self.window.is_closed() {
+			return None;
+		}
+
+		// From cursor coordinates to pixels from the bottom left.
+		let wh = self.window.wh();
+		let px = ((x + 1.0) * 0.5 * wh.0 as f32) as i32;
+		let py = ((1.0 - y) * 0.5 * wh.1 as f32) as i32;
+		let (vx, vy, vw, vh) = self.viewport;
+		if px < vx || py < vy || px >= vx + vw || py >= vy + vh {
+			return None;
+		}
+
+		// Draw the IDs offscreen, so the frame isn't lost, and they
+		// aren't blended by multisampling.
+		let target = match self.pick_target {
+			Some((ref target, w, h)) if (w, h) == wh => {
+				target.clone()
+			}
+			_ => {
+				let target = self.context.texture();
+				target.set_target(wh.0, wh.1);
+				self.pick_target = Some((target.clone(), wh.0,
+					wh.1));
+				target
+			}
+		};
+
+		let style = &self.style_pick;
+		style.has_camera.set_int1(1);
+		style.camera_uniform.set_mat4((self.projection * self.view)
+			.into());
+
+		// Draw IDs (without blending, so they aren't mixed, or sRGB
+		// encoding, so they aren't changed).
+		self.context.disable(Feature::Blend);
+		if self.srgb {
+			self.context.srgb_write(false);
+		}
+		self.context.color(0.0, 0.0, 0.0, 1.0);
+
+		let mut pixel = [0u8; 4];
+		target.draw_into(wh.0, wh.1, || {
+			let viewport = self.viewport;
+			let context = &self.context;
+			let pick = |shape: &ShapeData, id| {
+				clipped(context, viewport, self.cull, false,
+					shape, || draw_pick(style, shape, id));
+			};
+
+			context.viewport(viewport.0, viewport.1, viewport.2,
+				viewport.3);
+			context.enable(Feature::DepthTest);
+			for index in self.opaque_ind.iter() {
+				let shape = &as_mut(&self.opaque_vec)
+					[*index as usize];
+				if shape.visible {
+					pick(shape, pick_id(
+						ShapeHandle::Opaque(*index)));
+				}
+			}
+			for index in self.alpha_ind.iter() {
+				let shape = &as_mut(&self.alpha_vec)
+					[*index as usize];
+				if shape.visible {
+					pick(shape, pick_id(
+						ShapeHandle::Alpha(*index)));
+				}
+			}
+			context.disable(Feature::DepthTest);
+			style.has_camera.set_int1(0);
+			for index in self.gui_ind.iter() {
+				let shape = &as_mut(&self.gui_vec)
+					[*index as usize];
+				if shape.visible {
+					pick(shape, pick_id(
+						ShapeHandle::Gui(*index)));
+				}
+			}
+
+			pixel = context.read_pixel(px as u16, py as u16);
+		});
+
+		// Put back the settings.
+		let (r, g, b, a) = self.color;
+		self.context.color(r, g, b, a);
+		self.context.enable(Feature::Blend);
+		if self.srgb {
+			self.context.srgb_write(true);
+		}
+		self.context.viewport(self.viewport.0, self.viewport.1,
+			self.viewport.2, self.viewport.3);
+
+		pick_handle(pixel).map(base::new_shape)
+	}
+}
+
+// Bit 23 of a pick ID is set for alpha shapes, bit 22 for GUI shapes, the
+// rest is the index + 1 (0 is no shape).
+const PICK_ALPHA: u32 = 1 << 23;
+const PICK_GUI: u32 = 1 << 22;
+
+// Get the pick ID (24 bits) of a shape.
+fn pick_id(handle: ShapeHandle) -> u32 {
+	match handle {
+		ShapeHandle::Opaque(index) => index + 1,
+		ShapeHandle::Alpha(index) => (index + 1) | PICK_ALPHA,
+		ShapeHandle::Gui(index) => (index + 1) | PICK_GUI,
+	}
+}
+
+// Get the flat color that encodes a pick ID.
+fn pick_color(id: u32) -> [f32; 4] {
+	[
+		((id >> 16) & 0xFF) as f32 / 255.0,
+		((id >> 8) & 0xFF) as f32 / 255.0,
+		(id & 0xFF) as f32 / 255.0,
+		1.0,
+	]
+}
+
+// Get the shape from a pick ID read back as an RGB(A) pixel.
+fn pick_handle(pixel: [u8; 4]) -> Option<ShapeHandle> {
+	let id = (pixel[0] as u32) << 16 | (pixel[1] as u32) << 8
+		| pixel[2] as u32;
+
+	if id == 0 {
+		None
+	} else if id & PICK_ALPHA != 0 {
+		Some(ShapeHandle::Alpha((id & !PICK_ALPHA) - 1))
+	} else if id & PICK_GUI != 0 {
+		Some(ShapeHandle::Gui((id & !PICK_GUI) - 1))
+	} else {
+		Some(ShapeHandle::Opaque(id - 1))
+	}
+}
+
+// Draw a shape as a flat color, encoding `id` (24 bits).
+fn draw_pick(style: &Style, shape: &ShapeData, id: u32) {
+	style.matrix_uniform.set_mat4(shape.transform.into());
+	style.color.set_vec4(&pick_color(id));
+
+	// Set vertices for the program from the vertex buffer.
+	style.position.set(&shape.vertex_buffer);
+	for i in shape.fans.iter() {
+		style.shader.draw_arrays(shape.topology, i.0..i.1);
+	}
+}
+
+// Draw a shape, with it's cull & clip rectangle.  If `flip` (upside-down),
+// the faces & clip rectangle are flipped too.
+fn draw_clipped(context: &OpenGL, viewport: (i32, i32, i32, i32),
+	cull: Cull, flip: bool, style: &Style, shape: &ShapeData)
+{
+	clipped(context, viewport, cull, flip, shape,
+		|| draw_shape(style, shape))
+}
+
+// Call `draw` with the shape's cull & clip rectangle set, like
+// `draw_clipped()`.
+fn clipped<F: FnOnce()>(context: &OpenGL, viewport: (i32, i32, i32, i32),
+	cull: Cull, flip: bool, shape: &ShapeData, draw: F)
+{
+	match shape.cull.unwrap_or(cull) {
+		Cull::None => context.disable(Feature::CullFace),
+		Cull::Back => {
+			context.enable(Feature::CullFace);
+			context.cull_front(flip);
+		}
+		Cull::Front => {
+			context.enable(Feature::CullFace);
+			context.cull_front(!flip);
+		}
+	}
+
+	if let Some((x, y, w, h)) = shape.clip {
+		// Convert from -1 to 1 (y down) to pixels (y up).
+		let (ww, wh) = (viewport.2 as f32, viewport.3 as f32);
+		let l = viewport.0 + ((x + 1.0) * 0.5 * ww) as i32;
+		let b = if flip {
+			viewport.1 + ((y + 1.0) * 0.5 * wh) as i32
+		} else {
+			viewport.1 + ((1.0 - (y + h + 1.0) * 0.5) * wh) as i32
+		};
+		let w = (w * 0.5 * ww) as i32;
+		let h = (h * 0.5 * wh) as i32;
+
+		context.enable(Feature::ScissorTest);
+		context.scissor(l, b, w.max(0), h.max(0));
+		draw();
+		context.disable(Feature::ScissorTest);
+	} else {
+		draw();
+	}
+}
+
+// Flip the Y axis (after the projection).
+fn flip_y() -> Matrix {
+	Matrix::new(
+		1.0, 0.0, 0.0, 0.0,
+		0.0, -1.0, 0.0, 0.0,
+		0.0, 0.0, 1.0, 0.0,
+		0.0, 0.0, 0.0, 1.0,
+	)
+}
+
+// Clear the parts of the window outside of `viewport` to black.
+fn draw_bars(context: &OpenGL, wh: (u16, u16),
+	viewport: (i32, i32, i32, i32))
+{
+	let (w, h) = (wh.0 as i32, wh.1 as i32);
+	let (x, y, vw, vh) = viewport;
+
+	context.color(0.0, 0.0, 0.0, 1.0);
+	context.enable(Feature::ScissorTest);
+	for &(l, b, bw, bh) in [(0, 0, x, h), (x + vw, 0, w - x - vw, h),
+		(0, 0, w, y), (0, y + vh, w, h - y - vh)].iter()
+	{
+		if bw > 0 && bh > 0 {
+			context.scissor(l, b, bw, bh);
+			context.clear();
+		}
+	}
+	context.disable(Feature::ScissorTest);
 }
 
 fn draw_shape(style: &Style, shape: &ShapeData) {
 	style.matrix_uniform.set_mat4(shape.transform.into());
 
+	// Custom styles might not have a texture, colors, alpha or color.
 	if !style.texpos.is_none() {
-		// Set texpos for the program from the texpos buffer.
-		style.texpos.set(shape.buffers[0].as_ref().unwrap());
-		// Bind the texture
-		shape.texture.as_ref().unwrap().bind();
+		if let Some(ref buffer) = shape.buffers[0] {
+			// Set texpos for the program from the texpos buffer.
+			style.texpos.set(buffer);
+		}
+		if let Some(ref texture) = shape.texture {
+			// Bind the texture
+			texture.bind();
+		}
 	}
 
 	if !style.acolor.is_none() {
-		// Set colors for the program from the color buffer.
-		// TODO: probably shouldn't be same buffer as texpos.
-		style.acolor.set(shape.buffers[0].as_ref().unwrap());
+		if let Some(ref buffer) = shape.buffers[0] {
+			// Set colors for the program from the color buffer.
+			// TODO: probably shouldn't be same buffer as texpos.
+			style.acolor.set(buffer);
+		}
+	}
+
+	if let (false, Some(alpha)) = (style.alpha.is_none(), shape.alpha) {
+		style.alpha.set_vec1(alpha);
 	}
 
-	if !style.alpha.is_none() {
-		style.alpha.set_vec1(shape.alpha.unwrap());
+	if let (false, Some(ref color)) = (style.color.is_none(), shape.color) {
+		style.color.set_vec4(color);
 	}
 
-	if !style.color.is_none() {
-		style.color.set_vec4(&shape.color.unwrap());
+	// White if there's no tint, since the uniform stays set.
+	if !style.tint.is_none() {
+		style.tint.set_vec4(&shape.tint.unwrap_or([1.0; 4]));
+	}
+
+	for &(_, ref uniform, value) in style.uniforms.iter() {
+		set_uniform(uniform, value);
+	}
+	for &(ref uniform, value) in shape.uniforms.iter() {
+		set_uniform(uniform, value);
 	}
 
 	if shape.has_fog {
@@ -697,6 +1879,66 @@ fn draw_shape(style: &Style, shape: &ShapeData) {
 	// Set vertices for the program from the vertex buffer.
 	style.position.set(&shape.vertex_buffer);
 	for i in shape.fans.iter() {
-		style.shader.draw_arrays(Topology::TriangleFan, i.0..i.1);
+		style.shader.draw_arrays(shape.topology, i.0..i.1);
+	}
+}
+
+/// Set a uniform in a custom style, if the shader has it.
+fn set_uniform(uniform: &UniformData, value: UniformValue) {
+	if uniform.is_none() {
+		return;
+	}
+
+	match value {
+		UniformValue::Int(v) => uniform.set_int1(v),
+		UniformValue::Float(v) => uniform.set_vec1(v),
+		UniformValue::Vec2(v) => uniform.set_vec2(&v),
+		UniformValue::Vec3(v) => uniform.set_vec3(&v),
+		UniformValue::Vec4(v) => uniform.set_vec4(&v),
+		UniformValue::Mat4(v) => uniform.set_mat4(v),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ pick_id, pick_color, pick_handle, PICK_ALPHA, PICK_GUI };
+	use render::base::ShapeHandle;
+
+	// What the framebuffer stores for a pick color (8 bits per channel).
+	fn pixel(color: [f32; 4]) -> [u8; 4] {
+		let c = |v: f32| (v * 255.0).round() as u8;
+
+		[c(color[0]), c(color[1]), c(color[2]), c(color[3])]
+	}
+
+	#[test]
+	fn pick_id_bits() {
+		assert_eq!(pick_id(ShapeHandle::Opaque(0)), 1);
+		assert_eq!(pick_id(ShapeHandle::Alpha(0)), 1 | PICK_ALPHA);
+		assert_eq!(pick_id(ShapeHandle::Gui(0)), 1 | PICK_GUI);
+		assert_eq!(PICK_ALPHA, 1 << 23);
+		assert_eq!(PICK_GUI, 1 << 22);
+	}
+
+	#[test]
+	fn pick_round_trip() {
+		let max = PICK_GUI - 2; // The largest index that fits.
+
+		for index in [0, 1, 254, 255, 256, 65535, 65536, max].iter() {
+			for handle in [ShapeHandle::Opaque(*index),
+				ShapeHandle::Alpha(*index),
+				ShapeHandle::Gui(*index)].iter()
+			{
+				let id = pick_id(handle.clone());
+				let picked = pick_handle(pixel(pick_color(id)));
+
+				assert!(picked == Some(handle.clone()));
+			}
+		}
+	}
+
+	#[test]
+	fn pick_nothing() {
+		assert!(pick_handle([0, 0, 0, 255]) == None);
 	}
 }
@@ -14,12 +14,14 @@ varying vec4 vcolor;
 uniform int has_fog; // 0 no, 1 yes
 uniform vec4 fog; // The fog color.
 uniform vec2 range; // The range of fog (fog to far clip)
+uniform vec4 tint; // Multiplied with the output (white for none).
 
 varying float z;
 
 void main() {
 	vec4 sampled = texture2D(texture, texcoord.xy);
-	vec4 out_color = vec4(sampled.rgb, sampled.a * texcoord.a) * vcolor;
+	vec4 out_color = vec4(sampled.rgb, sampled.a * texcoord.a) * vcolor
+		* tint;
 
 	if(has_fog == 1) {
 		// Fog Calculation
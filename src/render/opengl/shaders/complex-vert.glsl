@@ -29,4 +29,5 @@ void main() {
 	vcolor = acolor;
 	texcoord = vec4(texpos.xyz, texpos.w);
 	z = length(gl_Position.xyz);
+	gl_PointSize = 1.0; // For `model_points()`
 }
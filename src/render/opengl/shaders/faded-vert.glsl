@@ -28,4 +28,5 @@ void main() {
 	gl_Position = vec4(place.x, -place.y, place.z, place.w);
 	texcoord = vec4(texpos.xyz, texpos.w * alpha);
 	z = length(place.xyz);
+	gl_PointSize = 1.0; // For `model_points()`
 }
@@ -11,11 +11,12 @@ varying vec4 vcolor;
 uniform int has_fog; // 0 no, 1 yes
 uniform vec4 fog; // The fog color.
 uniform vec2 range; // The range of fog (fog to far clip)
+uniform vec4 tint; // Multiplied with the output (white for none).
 
 varying float z;
 
 void main() {
-	vec4 out_color = vec4(vcolor.rgba);
+	vec4 out_color = vec4(vcolor.rgba) * tint;
 
 	if(has_fog == 1) {
 		// Fog Calculation
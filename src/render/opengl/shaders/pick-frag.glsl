@@ -0,0 +1,15 @@
+// Copyright Jeron A. Lau 2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+#version 100
+precision mediump float;
+
+uniform vec4 color; // The shape's ID, encoded as a color.
+
+varying float z;
+
+void main() {
+	gl_FragColor = color;
+}
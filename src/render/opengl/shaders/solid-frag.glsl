@@ -9,6 +9,7 @@ precision mediump float;
 uniform int has_fog; // 0 no, 1 yes
 uniform vec4 fog; // The fog color.
 uniform vec2 range; // The range of fog (fog to far clip)
+uniform vec4 tint; // Multiplied with the output (white for none).
 uniform vec4 color;
 
 varying float z;
@@ -18,8 +19,8 @@ void main() {
 		// Fog Calculation
 		float linear = clamp((z-range.x) / range.y, 0.0, 1.0);
 		float curved = linear * linear * linear;
-		gl_FragColor = mix(color, fog, curved);
+		gl_FragColor = mix(color * tint, fog, curved);
 	} else {
-		gl_FragColor = color;
+		gl_FragColor = color * tint;
 	}
 }
@@ -26,4 +26,5 @@ void main() {
 	gl_Position = vec4(place.x, -place.y, place.z, place.w);
 	texcoord = texpos;
 	z = length(gl_Position.xyz);
+	gl_PointSize = 1.0; // For `model_points()`
 }
@@ -0,0 +1,1267 @@
+// Copyright Jeron A. Lau 2018.
+// Dual-licensed under either the MIT License or the Boost Software License,
+// Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
+// https://www.boost.org/LICENSE_1_0.txt)
+
+//! Software (CPU) implementation for adi_gpu, for when there's no GPU.  It's
+//! slow, and only draws triangle fans, but works anywhere.
+
+use std::mem;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Instant;
+
+use Matrix;
+
+pub use self::base::Shape;
+pub use self::base::Gradient;
+pub use self::base::Model;
+pub use self::base::TexCoords;
+pub use self::base::Texture;
+
+use super::base;
+use super::base::*;
+
+/// How the ranges in a model's `fans` are drawn.
+#[derive(Copy, Clone, PartialEq)]
+enum Topology {
+	TriangleFan,
+	Lines,
+	Points,
+}
+
+struct ShapeData {
+	vertices: Rc<Vec<f32>>,
+	texcoords: Option<Rc<Vec<f32>>>, // (X, Y, UNUSED, ALPHA) per vertex.
+	colors: Option<Rc<Vec<f32>>>, // RGBA per vertex.
+	texture: Option<Rc<RefCell<TextureData>>>,
+	color: [f32; 4], // Multiplied with the texture & colors.
+	tint: [f32; 4], // Multiplied after `color` (white for none).
+	alpha_test: bool, // Leave out texels with alpha under 0.5 (text).
+	has_fog: bool,
+	transform: Matrix, // Transformation matrix.
+	fans: Vec<(u32, u32)>,
+	topology: Topology,
+	visible: bool, // Hidden shapes are skipped when drawing.
+	clip: Option<(f32, f32, f32, f32)>, // Scissor rectangle.
+	layer: i32, // Drawing order of GUI shapes.
+	cull: Option<Cull>, // Faces not drawn, `None` for `Display.cull`.
+}
+
+impl base::Point for ShapeData {
+	fn point(&self) -> Vector {
+		// Position vector at origin * object transform.
+		self.transform * (vector!(0f32, 0f32, 0f32), 1f32)
+	}
+}
+
+struct ModelData {
+	vertices: Rc<Vec<f32>>,
+	fans: Vec<(u32, u32)>,
+	topology: Topology,
+}
+
+struct TextureData {
+	w: u16,
+	h: u16,
+	pixels: Vec<u8>,
+}
+
+/// A vertex after the transform & projection, in pixels.
+#[derive(Copy, Clone)]
+struct Vertex {
+	x: f32,
+	y: f32,
+	z: f32, // Depth, -1 to 1.
+	w: f32, // 1 / clip space W, for perspective correct interpolation.
+	fog: f32, // Distance from the camera, for fog.
+	texcoord: [f32; 4],
+	color: [f32; 4],
+}
+
+/// To render anything with adi_gpu, you have to make a `Display`
+pub struct Display {
+	window: ::Window,
+	wh: (u16, u16),
+	pixels: Vec<u8>, // RGBA, top row first.
+	depth: Vec<f32>,
+	depth_test: bool, // Off for GUI shapes.
+	cull: Cull, // Faces not drawn, for shapes without their own.
+	overlay: RefCell<Option<Vec<u8>>>, // Set by `draw()`.
+	color: [f32; 4],
+	fog: ([f32; 4], f32, f32),
+	premultiplied: bool,
+	opaque_ind: Vec<u32>,
+	alpha_ind: Vec<u32>,
+	dirty: Cell<bool>, // Shapes need to be sorted again.
+	changed: Cell<bool>, // Something drawn changed since `render()`.
+	opaque_sort: bool,
+	opaque_vec: RefCell<Vec<ShapeData>>,
+	alpha_vec: RefCell<Vec<ShapeData>>,
+	gui_vec: RefCell<Vec<ShapeData>>,
+	gui_ind: Vec<u32>, // GUI shapes, in drawing order.
+	models: Slots<ModelData>,
+	texcoords: Slots<Rc<Vec<f32>>>,
+	gradients: Slots<Rc<Vec<f32>>>,
+	textures: Slots<Rc<RefCell<TextureData>>>,
+	xyz: Vector,
+	view: Matrix,
+	frames: FrameTimes,
+	earlier: Instant,
+	ar: f32,
+	// Drawn part of `pixels` (X, Y, width, height), smaller when
+	// letterboxed to `fixed_aspect`.
+	viewport: (i32, i32, i32, i32),
+	fixed_aspect: Option<f32>,
+	projection: Matrix,
+	projection_mode: Projection,
+	clip: (f32, f32), // Near & far clip planes.
+}
+
+/// Create a `Display` that renders on the CPU, offscreen.
+pub fn new(wh: (u16, u16)) -> Result<Box<Display>, &'static str> {
+	let ar = wh.0 as f32 / wh.1 as f32;
+	let projection_mode = Projection::default();
+	let projection = projection_mode.matrix(ar);
+	let size = wh.0 as usize * wh.1 as usize;
+
+	Ok(Box::new(Display {
+		window: ::Window::headless(wh),
+		wh,
+		pixels: vec![0; size * 4],
+		depth: vec![1.0; size],
+		depth_test: true,
+		cull: Cull::Back,
+		overlay: RefCell::new(None),
+		color: [0.0, 0.0, 0.0, 1.0],
+		fog: ([0.0, 0.0, 0.0, 1.0], 0.0, 100.0),
+		premultiplied: false,
+		opaque_ind: vec![],
+		alpha_ind: vec![],
+		dirty: Cell::new(false),
+		changed: Cell::new(true),
+		opaque_sort: true,
+		opaque_vec: RefCell::new(vec![]),
+		alpha_vec: RefCell::new(vec![]),
+		gui_vec: RefCell::new(vec![]),
+		gui_ind: vec![],
+		models: Slots::new(),
+		texcoords: Slots::new(),
+		gradients: Slots::new(),
+		textures: Slots::new(),
+		xyz: vector!(0.0, 0.0, 0.0),
+		view: matrix!(),
+		frames: FrameTimes::new(),
+		earlier: Instant::now(),
+		ar,
+		viewport: (0, 0, wh.0 as i32, wh.1 as i32),
+		fixed_aspect: None,
+		projection,
+		projection_mode,
+		clip: base::CLIP_PLANES,
+	}))
+}
+
+impl Display {
+	fn model_topology(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>,
+		topology: Topology) -> Model
+	{
+		Model(self.models.add(ModelData {
+			vertices: Rc::new(vertices.to_vec()), fans, topology
+		}))
+	}
+
+	fn add_shape(&mut self, shape: ShapeData, blending: bool, camera: bool)
+		-> Shape
+	{
+		self.changed.set(true);
+		self.dirty.set(true);
+
+		base::new_shape(if !camera {
+			let gui_vec = self.gui_vec.get_mut();
+			let index = gui_vec.len() as u32;
+			gui_vec.push(shape);
+			self.gui_ind.push(index);
+			base::ShapeHandle::Gui(index)
+		} else if blending {
+			let alpha_vec = self.alpha_vec.get_mut();
+			let index = alpha_vec.len() as u32;
+			alpha_vec.push(shape);
+			self.alpha_ind.push(index);
+			base::ShapeHandle::Alpha(index)
+		} else {
+			let opaque_vec = self.opaque_vec.get_mut();
+			let index = opaque_vec.len() as u32;
+			opaque_vec.push(shape);
+			self.opaque_ind.push(index);
+			base::ShapeHandle::Opaque(index)
+		})
+	}
+
+	fn shape(&self, model: &Model, transform: Matrix, fog: bool)
+		-> ShapeData
+	{
+		ShapeData {
+			vertices: self.models[model.0].vertices.clone(),
+			texcoords: None,
+			colors: None,
+			texture: None,
+			color: [1.0, 1.0, 1.0, 1.0],
+			tint: [1.0, 1.0, 1.0, 1.0],
+			alpha_test: false,
+			has_fog: fog,
+			transform,
+			fans: self.models[model.0].fans.clone(),
+			topology: self.models[model.0].topology,
+			visible: true,
+			clip: None,
+			layer: 0,
+			cull: None,
+		}
+	}
+
+	fn textured(&self, model: &Model, transform: Matrix, texture: &Texture,
+		tc: TexCoords, fog: bool) -> Result<ShapeData, ShapeError>
+	{
+		check_texcoords(self.models[model.0].vertices.len() as u32 / 4,
+			self.texcoords[tc.0].len() as u32 / 4)?;
+
+		let mut shape = self.shape(model, transform, fog);
+		shape.texcoords = Some(self.texcoords[tc.0].clone());
+		shape.texture = Some(self.textures[texture.0].clone());
+		Ok(shape)
+	}
+
+	fn with_shape<F: FnOnce(&mut ShapeData)>(&self, shape: &Shape, f: F) {
+		match base::get_shape(shape) {
+			ShapeHandle::Opaque(x) => {
+				f(&mut self.opaque_vec.borrow_mut()[x as usize])
+			}
+			ShapeHandle::Alpha(x) => {
+				f(&mut self.alpha_vec.borrow_mut()[x as usize])
+			}
+			ShapeHandle::Gui(x) => {
+				f(&mut self.gui_vec.borrow_mut()[x as usize])
+			}
+		}
+	}
+
+	/// Draw a shape into `pixels`.
+	fn draw_shape(&mut self, shape: &ShapeData, camera: &[f32; 16]) {
+		if shape.topology != Topology::TriangleFan {
+			// TODO: Lines & points.
+			return;
+		}
+
+		let transform: [f32; 16] = shape.transform.into();
+		let (vx, vy) = (self.viewport.0, self.viewport.1);
+		let (x0, y0) = (vx as f32, vy as f32);
+		let (w, h) = (self.viewport.2 as f32, self.viewport.3 as f32);
+
+		// Scissor rectangle, in pixels.
+		let clip = match shape.clip {
+			Some((x, y, cw, ch)) => (
+				vx + ((x + 1.0) * 0.5 * w) as i32,
+				vy + ((y + 1.0) * 0.5 * h) as i32,
+				vx + ((x + cw + 1.0) * 0.5 * w) as i32,
+				vy + ((y + ch + 1.0) * 0.5 * h) as i32,
+			),
+			None => (vx, vy, vx + self.viewport.2,
+				vy + self.viewport.3),
+		};
+
+		let vertex = |i: usize| -> Option<Vertex> {
+			let v = &shape.vertices[i * 4..i * 4 + 4];
+			let place = mul(&transform, [v[0], v[1], v[2], 1.0]);
+			let p = mul(camera, place);
+
+			// TODO: Clip triangles at the near plane, instead of
+			// leaving them out.
+			if p[3] <= 0.0 {
+				return None;
+			}
+
+			let attr = |a: &Option<Rc<Vec<f32>>>| match *a {
+				Some(ref a) => [a[i * 4], a[i * 4 + 1],
+					a[i * 4 + 2], a[i * 4 + 3]],
+				None => [1.0; 4],
+			};
+
+			Some(Vertex {
+				// Same as the shaders (flipped Y), to pixels.
+				x: x0 + (p[0] / p[3] + 1.0) * 0.5 * w,
+				y: y0 + (p[1] / p[3] + 1.0) * 0.5 * h,
+				z: p[2] / p[3],
+				w: 1.0 / p[3],
+				fog: (p[0] * p[0] + p[1] * p[1] + p[2] * p[2])
+					.sqrt(),
+				texcoord: attr(&shape.texcoords),
+				color: attr(&shape.colors),
+			})
+		};
+
+		for &(start, count) in shape.fans.iter() {
+			if count < 3 {
+				continue;
+			}
+
+			let start = start as usize;
+			let first = match vertex(start) {
+				Some(v) => v,
+				None => continue,
+			};
+
+			for i in start + 1..start + count as usize - 1 {
+				if let (Some(b), Some(c)) = (vertex(i), vertex(i+1))
+				{
+					self.triangle(shape, clip, [first, b, c]);
+				}
+			}
+		}
+	}
+
+	/// Rasterize one triangle.
+	fn triangle(&mut self, shape: &ShapeData, clip: (i32, i32, i32, i32),
+		v: [Vertex; 3])
+	{
+		let area = edge(&v[0], &v[1], v[2].x, v[2].y);
+
+		// Back faces are clockwise, since Y is flipped (like OpenGL).
+		let culled = match shape.cull.unwrap_or(self.cull) {
+			Cull::None => area == 0.0,
+			Cull::Back => area >= 0.0,
+			Cull::Front => area <= 0.0,
+		};
+		if culled {
+			return;
+		}
+
+		let min_x = v.iter().fold(clip.2 as f32, |a, v| a.min(v.x));
+		let max_x = v.iter().fold(clip.0 as f32, |a, v| a.max(v.x));
+		let min_y = v.iter().fold(clip.3 as f32, |a, v| a.min(v.y));
+		let max_y = v.iter().fold(clip.1 as f32, |a, v| a.max(v.y));
+		let x0 = (min_x.floor() as i32).max(clip.0).max(0);
+		let x1 = (max_x.ceil() as i32).min(clip.2)
+			.min(self.wh.0 as i32);
+		let y0 = (min_y.floor() as i32).max(clip.1).max(0);
+		let y1 = (max_y.ceil() as i32).min(clip.3)
+			.min(self.wh.1 as i32);
+
+		let texture = shape.texture.as_ref().map(|t| t.borrow());
+		let (fog, near, far) = self.fog;
+
+		for y in y0..y1 {
+			for x in x0..x1 {
+				// Sample at the pixel center.
+				let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+				let b0 = edge(&v[1], &v[2], px, py) / area;
+				let b1 = edge(&v[2], &v[0], px, py) / area;
+				let b2 = edge(&v[0], &v[1], px, py) / area;
+
+				if b0 < 0.0 || b1 < 0.0 || b2 < 0.0 {
+					continue;
+				}
+
+				let index = y as usize * self.wh.0 as usize
+					+ x as usize;
+				let z = b0 * v[0].z + b1 * v[1].z + b2 * v[2].z;
+
+				if z < -1.0 || z > 1.0 || (self.depth_test
+					&& z >= self.depth[index])
+				{
+					continue;
+				}
+
+				// Perspective correct weights.
+				let w = b0 * v[0].w + b1 * v[1].w + b2 * v[2].w;
+				let (p0, p1, p2) = (b0 * v[0].w / w,
+					b1 * v[1].w / w, b2 * v[2].w / w);
+				let lerp = |a: [f32; 4], b: [f32; 4], c: [f32; 4]| [
+					p0 * a[0] + p1 * b[0] + p2 * c[0],
+					p0 * a[1] + p1 * b[1] + p2 * c[1],
+					p0 * a[2] + p1 * b[2] + p2 * c[2],
+					p0 * a[3] + p1 * b[3] + p2 * c[3],
+				];
+
+				let mut out = lerp(v[0].color, v[1].color,
+					v[2].color);
+
+				if let Some(ref texture) = texture {
+					let tc = lerp(v[0].texcoord, v[1].texcoord,
+						v[2].texcoord);
+					let texel = sample(texture, tc[0], tc[1]);
+
+					if shape.alpha_test {
+						if texel[3] < 0.5 {
+							continue;
+						}
+					} else {
+						out = [out[0] * texel[0],
+							out[1] * texel[1],
+							out[2] * texel[2],
+							out[3] * texel[3] * tc[3]];
+					}
+				}
+
+				for i in 0..4 {
+					out[i] *= shape.color[i];
+					out[i] *= shape.tint[i];
+				}
+
+				if shape.has_fog {
+					let z = p0 * v[0].fog + p1 * v[1].fog
+						+ p2 * v[2].fog;
+					let linear = ((z - near) / (far - near))
+						.max(0.0).min(1.0);
+					let curved = linear * linear * linear;
+					for i in 0..4 {
+						out[i] += (fog[i] - out[i]) * curved;
+					}
+				}
+
+				self.depth[index] = z;
+				blend(&mut self.pixels[index * 4..index * 4 + 4],
+					out, self.premultiplied);
+			}
+		}
+	}
+}
+
+/// Multiply column-major `matrix` by `v`.
+fn mul(matrix: &[f32; 16], v: [f32; 4]) -> [f32; 4] {
+	let mut out = [0.0; 4];
+
+	for row in 0..4 {
+		for column in 0..4 {
+			out[row] += matrix[column * 4 + row] * v[column];
+		}
+	}
+
+	out
+}
+
+/// Which side of the line from `a` to `b` the point `x`, `y` is on (twice the
+/// area of the triangle they make).
+fn edge(a: &Vertex, b: &Vertex, x: f32, y: f32) -> f32 {
+	(b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Get the nearest texel at texture coordinates `u`, `v` (repeating).
+fn sample(texture: &TextureData, u: f32, v: f32) -> [f32; 4] {
+	let x = ((u - u.floor()) * texture.w as f32) as usize;
+	let y = ((v - v.floor()) * texture.h as f32) as usize;
+	let x = x.min(texture.w as usize - 1);
+	let y = y.min(texture.h as usize - 1);
+	let i = (y * texture.w as usize + x) * 4;
+	let p = &texture.pixels[i..i + 4];
+
+	[p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0,
+		p[3] as f32 / 255.0]
+}
+
+/// Blend `color` onto an RGBA pixel.
+fn blend(pixel: &mut [u8], color: [f32; 4], premultiplied: bool) {
+	let a = color[3].max(0.0).min(1.0);
+
+	for i in 0..3 {
+		let src = if premultiplied { color[i] } else { color[i] * a };
+		let dst = pixel[i] as f32 / 255.0;
+		let out = src + dst * (1.0 - a);
+
+		pixel[i] = (out.max(0.0).min(1.0) * 255.0) as u8;
+	}
+
+	let dst = pixel[3] as f32 / 255.0;
+	pixel[3] = ((a + dst * (1.0 - a)).min(1.0) * 255.0) as u8;
+}
+
+impl base::Display for Display {
+	fn color(&mut self, color: (u8, u8, u8)) {
+		self.color_rgba((color.0, color.1, color.2, 255))
+	}
+
+	fn color_rgba(&mut self, color: (u8, u8, u8, u8)) {
+		self.changed.set(true);
+		self.color = [color.0 as f32 / 255.0, color.1 as f32 / 255.0,
+			color.2 as f32 / 255.0, color.3 as f32 / 255.0];
+	}
+
+	fn fog(&mut self, color: [f32; 4], near: f32, far: f32) -> () {
+		self.changed.set(true);
+		self.fog = (color, near, far);
+	}
+
+	fn camera(&mut self, position: Vector, rotation: Vector) -> () {
+		self.changed.set(true);
+		self.xyz = position;
+		self.view = base::camera(position, rotation);
+		self.dirty.set(true);
+	}
+
+	fn set_timestep(&mut self, step: ::std::time::Duration) -> () {
+		self.window.set_timestep(step)
+	}
+
+	fn set_pause_halts_timestep(&mut self, halts: bool) -> () {
+		self.window.set_pause_halts_timestep(halts)
+	}
+
+	fn set_vsync(&mut self, _vsync: bool) -> bool {
+		// Nothing to sync with.
+		false
+	}
+
+	fn set_latency_mode(&mut self, _mode: LatencyMode) -> bool {
+		// Nothing to sync with.
+		false
+	}
+
+	fn set_blend_mode(&mut self, mode: BlendMode) -> () {
+		self.changed.set(true);
+		self.premultiplied = mode == BlendMode::Premultiplied;
+	}
+
+	fn set_srgb(&mut self, srgb: bool) -> bool {
+		self.changed.set(true);
+		// TODO: Blend in linear space.
+		!srgb
+	}
+
+	fn set_wireframe(&mut self, wireframe: bool) -> bool {
+		self.changed.set(true);
+		// TODO: Draw the edges, once lines can be drawn.
+		!wireframe
+	}
+
+	fn set_cull(&mut self, cull: Cull) -> bool {
+		self.changed.set(true);
+		self.cull = cull;
+		true
+	}
+
+	fn set_alpha_to_coverage(&mut self, enabled: bool) -> bool {
+		self.changed.set(true);
+		// Without multisampling there's no coverage.
+		!enabled
+	}
+
+	fn set_debug_callback(&mut self, _callback: Box<Fn(&str)>) -> bool {
+		// Nothing to go wrong, there's no driver.
+		true
+	}
+
+	fn set_opaque_sort(&mut self, sort: bool) -> () {
+		self.changed.set(true);
+		self.opaque_sort = sort;
+		self.dirty.set(true);
+	}
+
+	fn set_projection(&mut self, projection: Projection) -> () {
+		self.changed.set(true);
+		self.projection_mode = projection;
+		self.projection = projection.clipped(self.ar, self.clip);
+	}
+
+	fn set_clip_planes(&mut self, near: f32, far: f32) -> () {
+		self.changed.set(true);
+		self.clip = (near, far);
+		self.projection = self.projection_mode.clipped(self.ar,
+			self.clip);
+	}
+
+	fn projection(&self) -> Matrix {
+		self.projection
+	}
+
+	fn view(&self) -> Matrix {
+		self.view
+	}
+
+	fn input(&mut self) -> Option<base::Event> {
+		self.window.update()
+	}
+
+	fn wait_event(&mut self) -> Option<base::Event> {
+		self.window.wait_event()
+	}
+
+	fn wait_event_timeout(&mut self, timeout: ::std::time::Duration)
+		-> Option<base::Event>
+	{
+		self.window.wait_event_timeout(timeout)
+	}
+
+	fn drain(&mut self) -> base::Drain {
+		self.window.drain()
+	}
+
+	fn pop_matching(&mut self, pred: &Fn(&base::Event) -> bool)
+		-> Option<base::Event>
+	{
+		self.window.pop_matching(pred)
+	}
+
+	fn request_frame(&mut self) {
+		self.window.request_frame()
+	}
+
+	fn set_event_history(&mut self, capacity: usize) {
+		self.window.set_event_history(capacity)
+	}
+
+	fn event_history(&self) -> &[Event] {
+		self.window.event_history()
+	}
+
+	#[cfg(feature = "async")]
+	fn fd(&mut self) -> Option<i32> {
+		self.window.fd()
+	}
+
+	#[cfg(feature = "async")]
+	fn wake_on_input(&mut self, waker: ::std::task::Waker) -> bool {
+		self.window.wake_on_input(waker)
+	}
+
+	fn render(&mut self) {
+		// Nothing to draw to.
+		if self.window.is_closed() {
+			return;
+		}
+
+		// The last frame is still good if nothing changed, and the
+		// window system didn't lose any of it.
+		let redraw = self.window.take_redraw();
+		if !self.changed.replace(false) && !redraw {
+			return;
+		}
+
+		// Clear Color & Depth
+		let color = [
+			(self.color[0] * 255.0) as u8,
+			(self.color[1] * 255.0) as u8,
+			(self.color[2] * 255.0) as u8,
+			(self.color[3] * 255.0) as u8,
+		];
+		let (vx, vy, vw, vh) = self.viewport;
+		let w = self.wh.0 as usize;
+		for (i, pixel) in self.pixels.chunks_mut(4).enumerate() {
+			let (x, y) = ((i % w) as i32, (i / w) as i32);
+
+			// Black bars around the letterboxed viewport.
+			if x < vx || y < vy || x >= vx + vw || y >= vy + vh {
+				pixel.copy_from_slice(&[0, 0, 0, 255]);
+			} else {
+				pixel.copy_from_slice(&color);
+			}
+		}
+		for depth in self.depth.iter_mut() {
+			*depth = 1.0;
+		}
+
+		// Only sort if something moved.
+		if self.dirty.get() {
+			// sort nearest
+			if self.opaque_sort {
+				base::zsort(&mut self.opaque_ind,
+					self.opaque_vec.get_mut(), true,
+					self.xyz);
+			}
+			// sort farthest
+			base::zsort(&mut self.alpha_ind, self.alpha_vec.get_mut(),
+				false, self.xyz);
+			// sort by layer (stable, so same layer keeps it's order)
+			let gui_vec = self.gui_vec.get_mut();
+			self.gui_ind.sort_by_key(|i| gui_vec[*i as usize].layer);
+			self.dirty.set(false);
+		}
+
+		let camera: [f32; 16] = (self.projection * self.view).into();
+		let opaque_vec = self.opaque_vec.replace(vec![]);
+		let alpha_vec = self.alpha_vec.replace(vec![]);
+
+		for shape in self.opaque_ind.clone().iter() {
+			let shape = &opaque_vec[*shape as usize];
+			if shape.visible {
+				self.draw_shape(shape, &camera);
+			}
+		}
+
+		for shape in self.alpha_ind.clone().iter() {
+			let shape = &alpha_vec[*shape as usize];
+			if shape.visible {
+				self.draw_shape(shape, &camera);
+			}
+		}
+
+		// GUI Shapes don't have a camera, or depth testing.
+		let identity: [f32; 16] = matrix!().into();
+		let gui_vec = self.gui_vec.replace(vec![]);
+
+		self.depth_test = false;
+		for shape in self.gui_ind.clone().iter() {
+			let shape = &gui_vec[*shape as usize];
+			if shape.visible {
+				self.draw_shape(shape, &identity);
+			}
+		}
+		self.depth_test = true;
+
+		*self.opaque_vec.get_mut() = opaque_vec;
+		*self.alpha_vec.get_mut() = alpha_vec;
+		*self.gui_vec.get_mut() = gui_vec;
+
+		// 2D overlay from `draw()`.
+		if let Some(ref overlay) = *self.overlay.borrow() {
+			for (pixel, over) in self.pixels.chunks_mut(4)
+				.zip(overlay.chunks(4))
+			{
+				blend(pixel, [
+					over[0] as f32 / 255.0,
+					over[1] as f32 / 255.0,
+					over[2] as f32 / 255.0,
+					over[3] as f32 / 255.0,
+				], false);
+			}
+		}
+	}
+
+	fn render_to(&mut self, texture: &Texture) -> bool {
+		let target = self.textures[texture.0].clone();
+		let (w, h) = (texture.1, texture.2);
+		let size = w as usize * h as usize;
+
+		// Draw as if the texture were the whole window.
+		let wh = mem::replace(&mut self.wh, (w, h));
+		let viewport = mem::replace(&mut self.viewport,
+			(0, 0, w as i32, h as i32));
+		let pixels = mem::replace(&mut self.pixels, vec![0; size * 4]);
+		let depth = mem::replace(&mut self.depth, vec![1.0; size]);
+		let overlay = self.overlay.replace(None);
+		let projection = mem::replace(&mut self.projection,
+			self.projection_mode.clipped(w as f32 / h as f32,
+				self.clip));
+
+		self.changed.set(true);
+		self.render();
+		// Shapes with the texture have to be drawn again.
+		self.changed.set(true);
+
+		target.borrow_mut().pixels = mem::replace(&mut self.pixels,
+			pixels);
+		self.wh = wh;
+		self.viewport = viewport;
+		self.depth = depth;
+		self.overlay.replace(overlay);
+		self.projection = projection;
+		true
+	}
+
+	fn present(&mut self) -> base::FrameStatus {
+		// Nothing to draw to.
+		if self.window.is_closed() {
+			return base::FrameStatus::default();
+		}
+
+		// Get the time step for the next frame.
+		let new = Instant::now();
+		let dt = base::seconds(new.duration_since(self.earlier));
+		self.earlier = new;
+		self.frames.push(dt)
+	}
+
+	fn frame_time(&self) -> ::std::time::Duration {
+		self.frames.frame_time()
+	}
+
+	fn fps(&self) -> f32 {
+		self.frames.fps()
+	}
+
+	fn set_target_fps(&mut self, fps: f32) {
+		self.frames.set_target(fps);
+	}
+
+	fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>) -> Model {
+		self.model_topology(vertices, fans, Topology::TriangleFan)
+	}
+
+	fn model_lines(&mut self, vertices: &[f32]) -> Model {
+		let fans = vec![(0, vertices.len() as u32 / 4)];
+
+		self.model_topology(vertices, fans, Topology::Lines)
+	}
+
+	fn model_points(&mut self, vertices: &[f32]) -> Model {
+		let fans = vec![(0, vertices.len() as u32 / 4)];
+
+		self.model_topology(vertices, fans, Topology::Points)
+	}
+
+	fn texture(&mut self, wh: (u16,u16), graphic: &VFrame) -> Texture {
+		let a = self.textures.add(Rc::new(RefCell::new(TextureData {
+			w: wh.0, h: wh.1, pixels: graphic.0.clone(),
+		})));
+
+		Texture(a, wh.0, wh.1)
+	}
+
+	fn texture_compressed(&mut self, _wh: (u16,u16),
+		format: CompressedFormat, _data: &[u8])
+		-> Result<Texture, String>
+	{
+		Err(format!("The software renderer can't decompress {:?} \
+			textures", format))
+	}
+
+	fn gradient(&mut self, colors: &[f32]) -> Gradient {
+		Gradient(self.gradients.add(Rc::new(colors.to_vec())))
+	}
+
+	fn texcoords(&mut self, texcoords: &[(f32, f32)]) -> TexCoords {
+		let mut buffer = vec![];
+		for i in texcoords {
+			buffer.push(i.0);
+			buffer.push(i.1);
+			buffer.push(1.0);
+			buffer.push(1.0);
+		}
+
+		TexCoords(self.texcoords.add(Rc::new(buffer)))
+	}
+
+	fn set_texture(&mut self, texture: &mut Texture, wh: (u16,u16),
+		graphic: &VFrame)
+	{
+		self.changed.set(true);
+		// Shapes share the `TextureData`, so they see the new pixels.
+		*self.textures[texture.0].borrow_mut() = TextureData {
+			w: wh.0, h: wh.1, pixels: graphic.0.clone(),
+		};
+		texture.1 = wh.0;
+		texture.2 = wh.1;
+	}
+
+	fn read_texture(&self, texture: &Texture) -> (u16, u16, Vec<[u8; 4]>) {
+		let texture = self.textures[texture.0].borrow();
+
+		(texture.w, texture.h, texture.pixels.chunks(4)
+			.map(|p| [p[0], p[1], p[2], p[3]]).collect())
+	}
+
+	fn set_model(&mut self, model: &Model, vertices: &[f32],
+		fans: Vec<(u32, u32)>)
+	{
+		self.changed.set(true);
+		let old = self.models[model.0].vertices.clone();
+		let new = Rc::new(vertices.to_vec());
+
+		for shape in self.opaque_vec.get_mut().iter_mut()
+			.chain(self.alpha_vec.get_mut().iter_mut())
+			.chain(self.gui_vec.get_mut().iter_mut())
+			.filter(|shape| Rc::ptr_eq(&shape.vertices, &old))
+		{
+			shape.vertices = new.clone();
+			shape.fans = fans.clone();
+		}
+
+		self.models[model.0].vertices = new;
+		self.models[model.0].fans = fans;
+		self.dirty.set(true);
+	}
+
+	fn shape_solid(&mut self, model: &Model, transform: Matrix,
+		color: [f32; 4], blending: bool, fog: bool, camera: bool)
+		-> Shape
+	{
+		let mut shape = self.shape(model, transform, fog);
+		shape.color = color;
+		self.add_shape(shape, blending, camera)
+	}
+
+	fn shapes_solid(&mut self, shapes: &[(Model, Matrix, [f32; 4])],
+		blending: bool, fog: bool, camera: bool) -> Vec<Shape>
+	{
+		shapes.iter().map(|&(ref model, transform, color)| {
+			self.shape_solid(model, transform, color, blending, fog,
+				camera)
+		}).collect()
+	}
+
+	fn shape_gradient(&mut self, model: &Model, transform: Matrix,
+		colors: Gradient, blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
+	{
+		check_gradient(self.models[model.0].vertices.len() as u32 / 4,
+			self.gradients[colors.0].len() as u32 / 4)?;
+
+		let mut shape = self.shape(model, transform, fog);
+		shape.colors = Some(self.gradients[colors.0].clone());
+		Ok(self.add_shape(shape, blending, camera))
+	}
+
+	fn shape_texture(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, blending: bool, fog: bool,
+		camera: bool) -> Result<Shape, ShapeError>
+	{
+		let shape = self.textured(model, transform, texture, tc, fog)?;
+		Ok(self.add_shape(shape, blending, camera))
+	}
+
+	fn shape_faded(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, alpha: f32, fog: bool,
+		camera: bool) -> Result<Shape, ShapeError>
+	{
+		let mut shape = self.textured(model, transform, texture, tc,
+			fog)?;
+		shape.color[3] = alpha;
+		Ok(self.add_shape(shape, true, camera))
+	}
+
+	fn shape_tinted(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, tint: [f32; 4], blending: bool,
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
+	{
+		let mut shape = self.textured(model, transform, texture, tc,
+			fog)?;
+		shape.color = tint;
+		Ok(self.add_shape(shape, blending, camera))
+	}
+
+	fn shape_text(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, color: [f32; 4], blending: bool,
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
+	{
+		let mut shape = self.textured(model, transform, texture, tc,
+			fog)?;
+		shape.color = color;
+		shape.alpha_test = true;
+		Ok(self.add_shape(shape, blending, camera))
+	}
+
+	fn shape_complex(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, tints: Gradient,
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
+	{
+		check_gradient(self.models[model.0].vertices.len() as u32 / 4,
+			self.gradients[tints.0].len() as u32 / 4)?;
+
+		let mut shape = self.textured(model, transform, texture, tc,
+			fog)?;
+		shape.colors = Some(self.gradients[tints.0].clone());
+		Ok(self.add_shape(shape, blending, camera))
+	}
+
+	fn add_style(&mut self, _vert: &[u8], _frag: &[u8])
+		-> Result<StyleId, String>
+	{
+		Err("The software renderer can't run shaders".to_string())
+	}
+
+	fn shape_custom(&mut self, _model: &Model, _transform: Matrix,
+		_style: StyleId, _uniforms: &[(&str, UniformValue)],
+		_blending: bool, _fog: bool, _camera: bool) -> Shape
+	{
+		panic!("StyleId isn't from this Display (add_style() fails)")
+	}
+
+	fn set_uniform(&mut self, _style: StyleId, _name: &str,
+		_value: UniformValue)
+	{
+		self.changed.set(true);
+		panic!("StyleId isn't from this Display (add_style() fails)")
+	}
+
+	fn drop_shape(&mut self, shape: &Shape) {
+		self.changed.set(true);
+		match get_shape(&shape) {
+			ShapeHandle::Opaque(x) => {
+				let index = self.opaque_ind.iter()
+					.position(|y| *y == x).unwrap();
+				self.opaque_ind.remove(index);
+			}
+			ShapeHandle::Alpha(x) => {
+				let index = self.alpha_ind.iter()
+					.position(|y| *y == x).unwrap();
+				self.alpha_ind.remove(index);
+			}
+			ShapeHandle::Gui(x) => {
+				let index = self.gui_ind.iter()
+					.position(|y| *y == x).unwrap();
+				self.gui_ind.remove(index);
+			}
+		}
+		self.dirty.set(true);
+	}
+
+	fn drop_model(&mut self, model: Model) {
+		// Shapes hold their own reference to the vertices.
+		self.models.remove(model.0);
+	}
+
+	fn drop_texture(&mut self, texture: Texture) {
+		self.textures.remove(texture.0);
+	}
+
+	fn drop_gradient(&mut self, gradient: Gradient) {
+		self.gradients.remove(gradient.0);
+	}
+
+	fn drop_texcoords(&mut self, texcoords: TexCoords) {
+		self.texcoords.remove(texcoords.0);
+	}
+
+	fn transform(&self, shape: &Shape, transform: Matrix) {
+		self.changed.set(true);
+		self.with_shape(shape, |shape| shape.transform = transform);
+		self.dirty.set(true);
+	}
+
+	fn set_clip(&mut self, shape: &Shape,
+		clip: Option<(f32, f32, f32, f32)>)
+	{
+		self.changed.set(true);
+		self.with_shape(shape, |shape| shape.clip = clip);
+	}
+
+	fn set_shape_cull(&mut self, shape: &Shape, cull: Option<Cull>)
+		-> bool
+	{
+		self.changed.set(true);
+		self.with_shape(shape, |shape| shape.cull = cull);
+		true
+	}
+
+	fn set_tint(&mut self, shape: &Shape, tint: Option<[f32; 4]>) -> bool {
+		self.changed.set(true);
+		let tint = tint.unwrap_or([1.0; 4]);
+
+		self.with_shape(shape, |shape| shape.tint = tint);
+		true
+	}
+
+	fn set_visible(&mut self, shape: &Shape, visible: bool) {
+		self.changed.set(true);
+		self.with_shape(shape, |shape| shape.visible = visible);
+	}
+
+	fn set_layer(&mut self, shape: &Shape, layer: i32) {
+		self.changed.set(true);
+		match base::get_shape(shape) {
+			ShapeHandle::Gui(x) => {
+				self.gui_vec.get_mut()[x as usize].layer = layer;
+				self.dirty.set(true);
+			}
+			// Shapes with a camera are sorted by depth instead.
+			ShapeHandle::Opaque(_) | ShapeHandle::Alpha(_) => {}
+		}
+	}
+
+	fn resize(&mut self, wh: (u16, u16)) -> () {
+		self.changed.set(true);
+		let size = wh.0 as usize * wh.1 as usize;
+
+		self.wh = wh;
+		self.pixels = vec![0; size * 4];
+		self.depth = vec![1.0; size];
+		*self.overlay.get_mut() = None;
+		self.viewport = base::letterbox(wh, self.fixed_aspect);
+		self.ar = self.viewport.2 as f32 / self.viewport.3 as f32;
+		self.projection = self.projection_mode.clipped(self.ar,
+			self.clip);
+	}
+
+	fn set_fixed_aspect(&mut self, aspect: Option<f32>) -> bool {
+		let wh = self.wh;
+
+		self.fixed_aspect = aspect;
+		self.resize(wh);
+		true
+	}
+
+	fn wh(&self) -> (u16, u16) {
+		self.wh
+	}
+
+	fn clipboard_set(&mut self, text: &str) {
+		self.window.clipboard_set(text)
+	}
+
+	fn clipboard_get(&self) -> Option<String> {
+		self.window.clipboard_get()
+	}
+
+	fn set_cursor(&mut self, cursor: Cursor) {
+		self.window.set_cursor(cursor)
+	}
+
+	fn set_cursor_position(&mut self, xy: (f32, f32)) {
+		self.window.set_cursor_position(xy)
+	}
+
+	fn set_min_size(&mut self, wh: (u16, u16)) {
+		self.window.set_min_size(wh)
+	}
+
+	fn set_max_size(&mut self, wh: (u16, u16)) {
+		self.window.set_max_size(wh)
+	}
+
+	fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		self.window.set_aspect_ratio(aspect)
+	}
+
+	fn close(&mut self) {
+		self.window.close()
+	}
+
+	fn is_closed(&self) -> bool {
+		self.window.is_closed()
+	}
+
+	fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)> {
+		self.window.take_damage()
+	}
+
+	fn preedit(&self) -> &str {
+		self.window.preedit()
+	}
+
+	fn set_text_str(&mut self, enabled: bool) {
+		self.window.set_text_str(enabled)
+	}
+
+	fn set_shortcuts_enabled(&mut self, enabled: bool) {
+		self.window.set_shortcuts_enabled(enabled)
+	}
+
+	fn set_natural_scroll(&mut self, natural: bool) {
+		self.window.set_natural_scroll(natural)
+	}
+
+	fn text_str(&self, index: usize) -> &str {
+		self.window.text_str(index)
+	}
+
+	fn is_key_down(&self, key: u8) -> bool {
+		self.window.is_key_down(key)
+	}
+
+	fn set_always_on_top(&mut self, above: bool) -> bool {
+		self.window.set_always_on_top(above)
+	}
+
+	fn set_opacity(&mut self, opacity: f32) {
+		self.window.set_opacity(opacity)
+	}
+
+	fn set_class(&mut self, instance: &str, class: &str) {
+		self.window.set_class(instance, class)
+	}
+
+	fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		self.window.set_input_shape(region)
+	}
+
+	fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		self.window.set_keyboard_grab(grab)
+	}
+
+	fn minimize(&mut self) -> bool {
+		self.window.minimize()
+	}
+
+	fn maximize(&mut self) -> bool {
+		self.window.maximize()
+	}
+
+	fn restore(&mut self) -> bool {
+		self.window.restore()
+	}
+
+	fn scale_factor(&self) -> f32 {
+		self.window.scale_factor()
+	}
+
+	fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32)
+	{
+		self.window.set_stick_deadzone(controller, inner, outer)
+	}
+
+	fn set_controller_map(&mut self, controller: usize,
+		map: &[(ControllerButton, ControllerButton)])
+	{
+		self.window.set_controller_map(controller, map)
+	}
+
+	fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool) {
+		self.window.set_stick_as_dpad(controller, enabled)
+	}
+
+	fn controller_battery(&self, controller: usize) -> Option<f32> {
+		self.window.controller_battery(controller)
+	}
+
+	fn controller_guid(&self, controller: usize) -> Option<String> {
+		self.window.controller_guid(controller)
+	}
+
+	fn controller_capabilities(&self, controller: usize)
+		-> Option<::ControllerCaps>
+	{
+		self.window.controller_capabilities(controller)
+	}
+
+	fn position(&self) -> (i16, i16) {
+		self.window.position()
+	}
+
+	fn set_position(&mut self, xy: (i16, i16)) {
+		self.window.set_position(xy)
+	}
+
+	fn set_text_input_rect(&mut self, rect: Option<(i16, i16, u16, u16)>) {
+		self.window.set_text_input_rect(rect)
+	}
+
+	fn event_time(&self) -> ::std::time::Instant {
+		self.window.event_time()
+	}
+
+	fn set_decorated(&mut self, decorated: bool) {
+		self.window.set_decorated(decorated)
+	}
+
+	fn begin_drag(&mut self) -> bool {
+		self.window.begin_drag()
+	}
+
+	fn has_focus(&self) -> bool {
+		self.window.has_focus()
+	}
+
+	fn pixels(&self) -> Option<Vec<u8>> {
+		Some(self.pixels.clone())
+	}
+
+	fn draw(&self, writer: &Fn(u16, u16) -> [u8; 4]) {
+		let mut overlay = Vec::with_capacity(self.pixels.len());
+
+		for y in 0..self.wh.1 {
+			for x in 0..self.wh.0 {
+				overlay.extend_from_slice(&writer(x, y));
+			}
+		}
+
+		*self.overlay.borrow_mut() = Some(overlay);
+	}
+
+	fn pick(&mut self, _x: f32, _y: f32) -> Option<Shape> {
+		// TODO: Rasterize shape IDs, like OpenGL.
+		None
+	}
+}
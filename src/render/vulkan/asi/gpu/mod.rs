@@ -288,7 +288,9 @@ pub(crate) struct GpuContext {
 
 impl Gpu {
 	/// Create the GPU context, and optionally a window to render to.
-	pub(crate) fn new(rgb: Vector) -> Result<(Gpu, ::Window), String> { unsafe {
+	pub(crate) fn new(rgb: Vector, hints: &::WindowHints)
+		-> Result<(Gpu, ::Window), String>
+	{ unsafe {
 		// Load the Vulkan library
 		let api = VulkanApi::new()?;
 
@@ -297,8 +299,8 @@ impl Gpu {
 		);
 
 		// Create Surface
-		let window = ::Window::new(None);
-		let surface = surface::new(vk, &api, window.get_connection());
+		let window = ::Window::new(None, hints);
+		let surface = surface::new(vk, &api, window.get_connection())?;
 		let (gpu, pqi, sampled, format)
 			= device::get_gpu(vk, &api, surface)?;
 		let device = device::create_device(vk, &api, gpu, pqi);
@@ -16,17 +16,16 @@ use WindowConnection;
 use super::VulkanApi;
 
 pub(crate) fn new(vk: VkInstance, lib: &VulkanApi, connection: WindowConnection)
-	-> VkSurfaceKHR
+	-> Result<VkSurfaceKHR, String>
 {
-	match connection {
+	Ok(match connection {
 		WindowConnection::Xcb(connection,window) => {
 			if cfg!(unix) {
 				new_xcb(vk, lib, connection, window)
 			} else { unreachable!() }
 		}
-		WindowConnection::Wayland => {
-			println!("Wayland not supported yet");
-			unreachable!()
+		WindowConnection::Wayland(_, _, _) => {
+			return Err("Vulkan support on Wayland is WIP".to_string());
 		},
 		WindowConnection::DirectFB => {
 			println!("DirectFB not supported yet");
@@ -66,7 +65,7 @@ pub(crate) fn new(vk: VkInstance, lib: &VulkanApi, connection: WindowConnection)
 			println!("No OS not supported yet");
 			unreachable!()
 		},
-	}
+	})
 }
 
 #[repr(C)] struct SurfaceCreateInfoXcb {
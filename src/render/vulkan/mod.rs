@@ -26,14 +26,15 @@ use Matrix;
 pub struct Display {
 	window: ::Window,
 	renderer: renderer::Renderer,
+	frames: FrameTimes,
 }
 
-pub fn new() -> Result<Box<Display>, String> {
+pub fn new(hints: &::WindowHints) -> Result<Box<Display>, String> {
 	let (renderer, window) = renderer::Renderer::new(
-		vector!()
+		vector!(), hints
 	)?;
 
-	Ok(Box::new(Display { window, renderer }))
+	Ok(Box::new(Display { window, renderer, frames: FrameTimes::new() }))
 }
 
 impl base::Display for Display {
@@ -42,18 +43,190 @@ impl base::Display for Display {
 			color.1 as f32 / 255.0, color.2 as f32 / 255.0));
 	}
 
+	fn color_rgba(&mut self, color: (u8, u8, u8, u8)) {
+		// TODO: Alpha, needs a swapchain with composite alpha.
+		self.color((color.0, color.1, color.2))
+	}
+
+	fn fog(&mut self, _color: [f32; 4], _near: f32, _far: f32) -> () {
+		// TODO: Vulkan FogUniform
+	}
+
+	fn camera(&mut self, position: Vector, rotation: Vector) -> () {
+		self.renderer.camera(position, rotation);
+	}
+
+	fn set_timestep(&mut self, step: ::std::time::Duration) -> () {
+		self.window.set_timestep(step)
+	}
+
+	fn set_pause_halts_timestep(&mut self, halts: bool) -> () {
+		self.window.set_pause_halts_timestep(halts)
+	}
+
+	fn pick(&mut self, _x: f32, _y: f32) -> Option<Shape> {
+		// TODO: Render shape IDs to an offscreen image, and read back.
+		None
+	}
+
+	fn set_vsync(&mut self, _vsync: bool) -> bool {
+		// TODO: Recreate the swapchain with a different present mode.
+		false
+	}
+
+	fn set_latency_mode(&mut self, _mode: LatencyMode) -> bool {
+		// TODO: Recreate the swapchain with FIFO_RELAXED present mode.
+		false
+	}
+
+	fn set_blend_mode(&mut self, _mode: BlendMode) -> () {
+		// TODO: Blending is part of the pipelines (`Style`s).
+	}
+
+	fn set_srgb(&mut self, srgb: bool) -> bool {
+		// TODO: Recreate the swapchain & textures with sRGB formats.
+		!srgb
+	}
+
+	fn set_wireframe(&mut self, wireframe: bool) -> bool {
+		// TODO: The polygon mode is part of the pipelines (`Style`s).
+		!wireframe
+	}
+
+	fn set_cull(&mut self, cull: Cull) -> bool {
+		// TODO: The cull mode is part of the pipelines (`Style`s).
+		cull == Cull::Back
+	}
+
+	fn set_alpha_to_coverage(&mut self, enabled: bool) -> bool {
+		// TODO: The multisample state is part of the pipelines
+		// (`Style`s).
+		!enabled
+	}
+
+	fn set_debug_callback(&mut self, _callback: Box<Fn(&str)>) -> bool {
+		// TODO: VK_EXT_debug_utils messenger (validation layers only
+		// with the `checks` feature).
+		false
+	}
+
+	fn set_opaque_sort(&mut self, sort: bool) -> () {
+		self.renderer.set_opaque_sort(sort);
+	}
+
+	fn set_projection(&mut self, projection: Projection) -> () {
+		self.renderer.set_projection(projection);
+	}
+
+	fn set_clip_planes(&mut self, near: f32, far: f32) -> () {
+		self.renderer.set_clip_planes(near, far);
+	}
+
+	fn projection(&self) -> Matrix {
+		self.renderer.projection()
+	}
+
+	fn view(&self) -> Matrix {
+		self.renderer.view()
+	}
+
 	fn input(&mut self) -> Option<base::Event> {
 		self.window.update()
 	}
 
-	fn update(&mut self) -> f32 {
-		self.renderer.update()
+	fn wait_event(&mut self) -> Option<base::Event> {
+		self.window.wait_event()
+	}
+
+	fn wait_event_timeout(&mut self, timeout: ::std::time::Duration)
+		-> Option<base::Event>
+	{
+		self.window.wait_event_timeout(timeout)
+	}
+
+	fn drain(&mut self) -> base::Drain {
+		self.window.drain()
+	}
+
+	fn pop_matching(&mut self, pred: &Fn(&base::Event) -> bool)
+		-> Option<base::Event>
+	{
+		self.window.pop_matching(pred)
+	}
+
+	fn request_frame(&mut self) {
+		self.window.request_frame()
+	}
+
+	fn set_event_history(&mut self, capacity: usize) {
+		self.window.set_event_history(capacity)
+	}
+
+	fn event_history(&self) -> &[Event] {
+		self.window.event_history()
+	}
+
+	#[cfg(feature = "async")]
+	fn fd(&mut self) -> Option<i32> {
+		self.window.fd()
+	}
+
+	#[cfg(feature = "async")]
+	fn wake_on_input(&mut self, waker: ::std::task::Waker) -> bool {
+		self.window.wake_on_input(waker)
+	}
+
+	fn render(&mut self) {
+		// TODO: `Renderer::update()` draws & presents together, so
+		// everything happens in `present()` for now.
+	}
+
+	fn render_to(&mut self, _texture: &Texture) -> bool {
+		// TODO: Needs a render pass for the texture's image.
+		false
+	}
+
+	fn present(&mut self) -> base::FrameStatus {
+		// Nothing to draw to.
+		if self.window.is_closed() {
+			return base::FrameStatus::default();
+		}
+
+		let dt = self.renderer.update();
+		self.frames.push(dt)
+	}
+
+	fn frame_time(&self) -> ::std::time::Duration {
+		self.frames.frame_time()
+	}
+
+	fn fps(&self) -> f32 {
+		self.frames.fps()
+	}
+
+	fn set_target_fps(&mut self, fps: f32) {
+		self.frames.set_target(fps);
 	}
 
 	fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>) -> Model {
 		Model(self.renderer.model(vertices, fans))
 	}
 
+	fn model_lines(&mut self, vertices: &[f32]) -> Model {
+		// TODO: Topology is part of the pipelines (`Style`s), these are
+		// drawn as triangle fans for now.
+		let fans = vec![(0, vertices.len() as u32 / 4)];
+
+		Model(self.renderer.model(vertices, fans))
+	}
+
+	fn model_points(&mut self, vertices: &[f32]) -> Model {
+		// TODO: Same as `model_lines()`.
+		let fans = vec![(0, vertices.len() as u32 / 4)];
+
+		Model(self.renderer.model(vertices, fans))
+	}
+
 	fn texture(&mut self, wh: (u16,u16), graphic: &VFrame) -> Texture {
 		let (w, h) = wh;
 		let pixels = graphic.0.as_slice();
@@ -61,6 +234,14 @@ impl base::Display for Display {
 		Texture(self.renderer.texture(w, h, pixels), wh.0, wh.1)
 	}
 
+	fn texture_compressed(&mut self, _wh: (u16,u16),
+		format: CompressedFormat, _data: &[u8])
+		-> Result<Texture, String>
+	{
+		// TODO: VK_FORMAT_BC1/BC3/ETC2 images.
+		Err(format!("{:?} textures on Vulkan are WIP", format))
+	}
+
 	fn gradient(&mut self, colors: &[f32]) -> Gradient {
 		Gradient(self.renderer.colors(colors))
 	}
@@ -82,6 +263,17 @@ impl base::Display for Display {
 		}
 	}
 
+	fn read_texture(&self, texture: &Texture) -> (u16, u16, Vec<[u8; 4]>) {
+		// TODO: Copy the image to a host-visible buffer.
+		(texture.1, texture.2, vec![])
+	}
+
+	fn set_model(&mut self, model: &Model, vertices: &[f32],
+		fans: Vec<(u32, u32)>)
+	{
+		self.renderer.set_model(model.0, vertices, fans);
+	}
+
 	#[inline(always)]
 	fn shape_solid(&mut self, model: &Model, transform: Matrix,
 		color: [f32; 4], blending: bool, fog: bool,
@@ -91,68 +283,321 @@ impl base::Display for Display {
 			blending, fog, camera))
 	}
 
+	fn shapes_solid(&mut self, shapes: &[(Model, Matrix, [f32; 4])],
+		blending: bool, fog: bool, camera: bool) -> Vec<Shape>
+	{
+		self.renderer.reserve(shapes.len(), blending);
+
+		shapes.iter().map(|&(model, transform, color)| {
+			base::new_shape(self.renderer.solid(model.0, transform,
+				color, blending, fog, camera))
+		}).collect()
+	}
+
 	#[inline(always)]
 	fn shape_gradient(&mut self, model: &Model, transform: Matrix,
 		colors: Gradient, blending: bool, fog: bool,
-		camera: bool) -> Shape
+		camera: bool) -> Result<Shape, ShapeError>
 	{
-		base::new_shape(self.renderer.gradient(model.0, transform,
-			colors.0, blending, fog, camera))
+		Ok(base::new_shape(self.renderer.gradient(model.0, transform,
+			colors.0, blending, fog, camera)?))
 	}
 
 	#[inline(always)]
 	fn shape_texture(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, blending: bool,
-		fog: bool, camera: bool) -> Shape
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
 	{
-		base::new_shape(self.renderer.textured(model.0, transform,
-			texture.0, tc.0, blending, fog, camera))
+		Ok(base::new_shape(self.renderer.textured(model.0, transform,
+			texture.0, tc.0, blending, fog, camera)?))
 	}
 
 	#[inline(always)]
 	fn shape_faded(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, alpha: f32,
-		fog: bool, camera: bool) -> Shape
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
 	{
-		base::new_shape(self.renderer.faded(model.0, transform,
-			texture.0, tc.0, alpha, fog, camera))
+		Ok(base::new_shape(self.renderer.faded(model.0, transform,
+			texture.0, tc.0, alpha, fog, camera)?))
 	}
 
 	#[inline(always)]
 	fn shape_tinted(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, tint: [f32; 4], blending: bool,
-		fog: bool, camera: bool) -> Shape
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
 	{
-		base::new_shape(self.renderer.tinted(model.0, transform,
-			texture.0, tc.0, tint, blending, fog, camera))
+		Ok(base::new_shape(self.renderer.tinted(model.0, transform,
+			texture.0, tc.0, tint, blending, fog, camera)?))
+	}
+
+	fn shape_text(&mut self, model: &Model, transform: Matrix,
+		texture: &Texture, tc: TexCoords, color: [f32; 4], blending: bool,
+		fog: bool, camera: bool) -> Result<Shape, ShapeError>
+	{
+		// TODO: Alpha tested text style, only tinted for now.
+		Ok(base::new_shape(self.renderer.tinted(model.0, transform,
+			texture.0, tc.0, color, blending, fog, camera)?))
 	}
 
 	#[inline(always)]
 	fn shape_complex(&mut self, model: &Model, transform: Matrix,
 		texture: &Texture, tc: TexCoords, tints: Gradient,
-		blending: bool, fog: bool, camera: bool) -> Shape
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
 	{
-		base::new_shape(self.renderer.complex(model.0, transform,
-			texture.0, tc.0, tints.0, blending, fog, camera))
+		Ok(base::new_shape(self.renderer.complex(model.0, transform,
+			texture.0, tc.0, tints.0, blending, fog, camera)?))
 	}
 
 	#[inline(always)]
+	fn add_style(&mut self, _vert: &[u8], _frag: &[u8])
+		-> Result<StyleId, String>
+	{
+		// TODO: Compile GLSL to SPIR-V.
+		Err("Custom styles on Vulkan are WIP".to_string())
+	}
+
+	fn shape_custom(&mut self, _model: &Model, _transform: Matrix,
+		_style: StyleId, _uniforms: &[(&str, UniformValue)],
+		_blending: bool, _fog: bool, _camera: bool) -> Shape
+	{
+		panic!("StyleId isn't from this Display (add_style() fails)")
+	}
+
+	fn set_uniform(&mut self, _style: StyleId, _name: &str,
+		_value: UniformValue)
+	{
+		panic!("StyleId isn't from this Display (add_style() fails)")
+	}
+
 	fn drop_shape(&mut self, shape: &Shape) {
 		self.renderer.drop_shape(get_shape(&shape));
 	}
 
+	fn drop_model(&mut self, model: Model) {
+		self.renderer.drop_model(model.0);
+	}
+
+	fn drop_texture(&mut self, texture: Texture) {
+		self.renderer.drop_texture(texture.0);
+	}
+
+	fn drop_gradient(&mut self, gradient: Gradient) {
+		self.renderer.drop_colors(gradient.0);
+	}
+
+	fn drop_texcoords(&mut self, texcoords: TexCoords) {
+		self.renderer.drop_texcoords(texcoords.0);
+	}
+
 	fn transform(&self, shape: &Shape, transform: Matrix) {
 		self.renderer.transform(&base::get_shape(shape), transform);
 	}
 
+	fn set_clip(&mut self, _shape: &Shape,
+		_clip: Option<(f32, f32, f32, f32)>)
+	{
+		// TODO: Needs a dynamic scissor state on the pipelines.
+	}
+
+	fn set_shape_cull(&mut self, _shape: &Shape, cull: Option<Cull>)
+		-> bool
+	{
+		// TODO: Needs a pipeline for each cull mode.
+		cull.map_or(true, |cull| cull == Cull::Back)
+	}
+
+	fn set_tint(&mut self, _shape: &Shape, tint: Option<[f32; 4]>) -> bool {
+		// TODO: Needs a tint in the shaders' uniform buffers.
+		tint.is_none()
+	}
+
+	fn set_visible(&mut self, shape: &Shape, visible: bool) {
+		self.renderer.set_visible(&base::get_shape(shape), visible);
+	}
+
+	fn set_layer(&mut self, _shape: &Shape, _layer: i32) {
+		// TODO: Only matters once Vulkan has GUI shapes.
+	}
+
 	fn resize(&mut self, wh: (u16, u16)) -> () {
 		self.renderer.resize(wh);
 	}
 
+	fn set_fixed_aspect(&mut self, aspect: Option<f32>) -> bool {
+		// TODO: The viewport is part of the pipelines (`Style`s).
+		aspect.is_none()
+	}
+
 	fn wh(&self) -> (u16, u16) {
 		self.window.wh()
 	}
 
+	fn clipboard_set(&mut self, text: &str) {
+		self.window.clipboard_set(text)
+	}
+
+	fn clipboard_get(&self) -> Option<String> {
+		self.window.clipboard_get()
+	}
+
+	fn set_cursor(&mut self, cursor: Cursor) {
+		self.window.set_cursor(cursor)
+	}
+
+	fn set_cursor_position(&mut self, xy: (f32, f32)) {
+		self.window.set_cursor_position(xy)
+	}
+
+	fn set_min_size(&mut self, wh: (u16, u16)) {
+		self.window.set_min_size(wh)
+	}
+
+	fn set_max_size(&mut self, wh: (u16, u16)) {
+		self.window.set_max_size(wh)
+	}
+
+	fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		self.window.set_aspect_ratio(aspect)
+	}
+
+	fn close(&mut self) {
+		self.window.close()
+	}
+
+	fn is_closed(&self) -> bool {
+		self.window.is_closed()
+	}
+
+	fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)> {
+		self.window.take_damage()
+	}
+
+	fn preedit(&self) -> &str {
+		self.window.preedit()
+	}
+
+	fn set_text_str(&mut self, enabled: bool) {
+		self.window.set_text_str(enabled)
+	}
+
+	fn set_shortcuts_enabled(&mut self, enabled: bool) {
+		self.window.set_shortcuts_enabled(enabled)
+	}
+
+	fn set_natural_scroll(&mut self, natural: bool) {
+		self.window.set_natural_scroll(natural)
+	}
+
+	fn text_str(&self, index: usize) -> &str {
+		self.window.text_str(index)
+	}
+
+	fn is_key_down(&self, key: u8) -> bool {
+		self.window.is_key_down(key)
+	}
+
+	fn set_always_on_top(&mut self, above: bool) -> bool {
+		self.window.set_always_on_top(above)
+	}
+
+	fn set_opacity(&mut self, opacity: f32) {
+		self.window.set_opacity(opacity)
+	}
+
+	fn set_class(&mut self, instance: &str, class: &str) {
+		self.window.set_class(instance, class)
+	}
+
+	fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		self.window.set_input_shape(region)
+	}
+
+	fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		self.window.set_keyboard_grab(grab)
+	}
+
+	fn minimize(&mut self) -> bool {
+		self.window.minimize()
+	}
+
+	fn maximize(&mut self) -> bool {
+		self.window.maximize()
+	}
+
+	fn restore(&mut self) -> bool {
+		self.window.restore()
+	}
+
+	fn scale_factor(&self) -> f32 {
+		self.window.scale_factor()
+	}
+
+	fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32)
+	{
+		self.window.set_stick_deadzone(controller, inner, outer)
+	}
+
+	fn set_controller_map(&mut self, controller: usize,
+		map: &[(ControllerButton, ControllerButton)])
+	{
+		self.window.set_controller_map(controller, map)
+	}
+
+	fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool) {
+		self.window.set_stick_as_dpad(controller, enabled)
+	}
+
+	fn controller_battery(&self, controller: usize) -> Option<f32> {
+		self.window.controller_battery(controller)
+	}
+
+	fn controller_guid(&self, controller: usize) -> Option<String> {
+		self.window.controller_guid(controller)
+	}
+
+	fn controller_capabilities(&self, controller: usize)
+		-> Option<::ControllerCaps>
+	{
+		self.window.controller_capabilities(controller)
+	}
+
+	fn position(&self) -> (i16, i16) {
+		self.window.position()
+	}
+
+	fn set_position(&mut self, xy: (i16, i16)) {
+		self.window.set_position(xy)
+	}
+
+	fn set_text_input_rect(&mut self, rect: Option<(i16, i16, u16, u16)>) {
+		self.window.set_text_input_rect(rect)
+	}
+
+	fn event_time(&self) -> ::std::time::Instant {
+		self.window.event_time()
+	}
+
+	fn set_decorated(&mut self, decorated: bool) {
+		self.window.set_decorated(decorated)
+	}
+
+	fn begin_drag(&mut self) -> bool {
+		self.window.begin_drag()
+	}
+
+	fn has_focus(&self) -> bool {
+		self.window.has_focus()
+	}
+
+	fn pixels(&self) -> Option<Vec<u8>> {
+		// TODO: Copy the swapchain image to a host visible buffer.
+		None
+	}
+
 	fn draw(&self, writer: &Fn(u16, u16) -> [u8; 4]) {
 		self.renderer.draw(self.window.wh(), writer)
 	}
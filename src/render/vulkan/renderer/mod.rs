@@ -3,7 +3,7 @@
 // Version 1.0.  (See accompanying file LICENSE_1_0.txt or copy at
 // https://www.boost.org/LICENSE_1_0.txt)
 
-use std::cell::UnsafeCell;
+use std::cell::{ Cell, UnsafeCell };
 
 use std::{ mem };
 use std::time::Instant;
@@ -68,6 +68,7 @@ pub struct Shape {
 	instance: Sprite,
 	fans: Vec<(u32, u32)>,
 	transform: Matrix, // Transformation matrix.
+	visible: bool, // Hidden shapes are skipped when drawing.
 }
 
 impl super::base::Point for Shape {
@@ -230,8 +231,11 @@ fn set_texture(vw: &Vw, texture: &mut Texture, writer: &Fn(u16, u16) -> [u8; 4])
 }
 
 impl Vw {
-	pub(crate) fn new(rgb: Vector) -> Result<(Vw, ::Window), String> {
-		let (mut connection, window) = super::asi::Gpu::new(rgb)?;
+	pub(crate) fn new(rgb: Vector, hints: &::WindowHints)
+		-> Result<(Vw, ::Window), String>
+	{
+		let (mut connection, window) = super::asi::Gpu::new(rgb,
+			hints)?;
 
 		// END BLOCK 2
 		let mut image_count = unsafe {
@@ -285,13 +289,15 @@ pub struct Renderer {
 	ar: f32,
 	opaque_ind: Vec<u32>,
 	alpha_ind: Vec<u32>,
+	dirty: Cell<bool>, // Shapes need to be sorted again.
+	opaque_sort: bool,
 	opaque_vec: UnsafeCell<Vec<Shape>>,
 	alpha_vec: UnsafeCell<Vec<Shape>>,
 	gui: Shape,
-	models: Vec<Model>,
-	texcoords: Vec<TexCoords>,
-	gradients: Vec<Gradient>,
-	textures: Vec<Texture>,
+	models: Slots<Model>,
+	texcoords: Slots<TexCoords>,
+	gradients: Slots<Gradient>,
+	textures: Slots<Texture>,
 	gui_texture: UnsafeCell<Texture>,
 	style_solid: Style,
 	style_nasolid: Style,
@@ -306,6 +312,8 @@ pub struct Renderer {
 	style_nacomplex: Style,
 	style_gui: Style,
 	projection: Matrix,
+	projection_mode: Projection,
+	clip: (f32, f32), // Near & far clip planes.
 	clear_color: (f32, f32, f32),
 	xyz: Vector,
 	rotate_xyz: Vector,
@@ -328,8 +336,10 @@ impl Renderer {
 			writer);
 	}
 
-	pub(crate) fn new(rgb: Vector) -> Result<(Renderer, ::Window), String> {
-		let (mut vw, window) = Vw::new(rgb)?;
+	pub(crate) fn new(rgb: Vector, hints: &::WindowHints)
+		-> Result<(Renderer, ::Window), String>
+	{
+		let (mut vw, window) = Vw::new(rgb, hints)?;
 
 		let solid_vert = super::asi::ShaderModule::new(
 			&mut vw.connection, include_bytes!(
@@ -407,7 +417,8 @@ impl Renderer {
 			2, true);
 
 		let ar = vw.connection.ar();
-		let projection = super::base::projection(ar, 0.5 * PI);
+		let projection_mode = Projection::default();
+		let projection = projection_mode.matrix(ar);
 
 		// Add GUI
 		let wh = window.wh();
@@ -459,6 +470,7 @@ impl Renderer {
 			],
 			fans: vec![(0, 4)], // make a rectangle (4 vertices)
 			transform: unsafe { ::std::mem::uninitialized() },
+			visible: true,
 		};
 
 		::std::mem::forget(shape);
@@ -466,16 +478,19 @@ impl Renderer {
 
 		let mut renderer = Renderer {
 			earlier: Instant::now(),
-			vw, ar, projection,
+			vw, ar, projection, projection_mode,
+			clip: super::base::CLIP_PLANES,
 			alpha_ind: Vec::new(),
 			opaque_ind: Vec::new(),
+			dirty: Cell::new(false),
+			opaque_sort: true,
 			alpha_vec: UnsafeCell::new(Vec::new()),
 			opaque_vec: UnsafeCell::new(Vec::new()),
 			gui,
-			gradients: Vec::new(),
-			models: Vec::new(),
-			texcoords: Vec::new(),
-			textures: Vec::new(),
+			gradients: Slots::new(),
+			models: Slots::new(),
+			texcoords: Slots::new(),
+			textures: Slots::new(),
 			gui_texture: UnsafeCell::new(gui_texture),
 			style_solid, style_nasolid,
 			style_texture, style_natexture,
@@ -514,20 +529,31 @@ impl Renderer {
 			);
 		}
 
-		// sort nearest
-		super::base::zsort(&mut self.opaque_ind, unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())},
-			true, self.xyz);
+		// Only sort if something moved.
+		if self.dirty.get() {
+			// sort nearest
+			if self.opaque_sort {
+				super::base::zsort(&mut self.opaque_ind, unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())},
+					true, self.xyz);
+			}
+			// sort farthest
+			super::base::zsort(&mut self.alpha_ind, unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())},
+				false, self.xyz);
+			self.dirty.set(false);
+		}
+
 		for shape in self.opaque_ind.iter() {
 			let shape = &unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}[*shape as usize];
-			draw_shape(&self.vw.connection, shape);
+			if shape.visible {
+				draw_shape(&self.vw.connection, shape);
+			}
 		}
 
-		// sort farthest
-		super::base::zsort(&mut self.alpha_ind, unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())},
-			false, self.xyz);
 		for shape in self.alpha_ind.iter() {
 			let shape = &unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}[*shape as usize];
-			draw_shape(&self.vw.connection, shape);
+			if shape.visible {
+				draw_shape(&self.vw.connection, shape);
+			}
 		}
 
 		draw_shape(&self.vw.connection, &self.gui);
@@ -568,12 +594,42 @@ impl Renderer {
 
 		// Get the time step for the next frame.
 		let new = Instant::now();
-		let r = new.duration_since(self.earlier).subsec_nanos() as f32
-			/ 1_000_000_000.0;
+		let r = seconds(new.duration_since(self.earlier));
 		self.earlier = new;
 		r
 	}
 
+	pub fn camera(&mut self, xyz: Vector, rotate_xyz: Vector) {
+		// TODO: Update the transform uniforms with the camera matrix.
+		self.xyz = xyz;
+		self.rotate_xyz = rotate_xyz;
+		self.dirty.set(true);
+	}
+
+	pub fn set_opaque_sort(&mut self, sort: bool) {
+		self.opaque_sort = sort;
+		self.dirty.set(true);
+	}
+
+	pub fn set_projection(&mut self, projection: Projection) {
+		self.projection_mode = projection;
+		self.projection = projection.clipped(self.ar, self.clip);
+	}
+
+	pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+		self.clip = (near, far);
+		self.projection = self.projection_mode.clipped(self.ar,
+			self.clip);
+	}
+
+	pub fn projection(&self) -> Matrix {
+		self.projection
+	}
+
+	pub fn view(&self) -> Matrix {
+		super::base::camera(self.xyz, self.rotate_xyz)
+	}
+
 	pub fn resize(&mut self, size: (u16, u16)) {
 		swapchain_delete(&mut self.vw);
 		let (depth_image, ms_image, render_pass)
@@ -588,7 +644,8 @@ impl Renderer {
 		self.vw.ms_image = ms_image;
 		self.vw.render_pass = render_pass;
 
-		self.projection = super::base::projection(self.ar, 0.5 * PI);
+		self.projection = self.projection_mode.clipped(self.ar,
+			self.clip);
 
 		println!("YAA");
 	}
@@ -607,9 +664,7 @@ impl Renderer {
 			[r, g, b, a]
 		});
 
-		let a = self.textures.len();
-		self.textures.push(texture);
-		a
+		self.textures.add(texture)
 	}
 
 	pub fn set_texture(&mut self, texture: usize, rgba: &[u8]) {
@@ -649,15 +704,43 @@ impl Renderer {
 				vertices)
 		};
 
-		let a = self.models.len();
-
-		self.models.push(Model {
+		self.models.add(Model {
 			shape,
 			vertex_count: vertices.len() as u32 / 4,
 			fans,
-		});
+		})
+	}
+
+	/// Replace the vertices of a model (and the fans of it's shapes).
+	pub fn set_model(&mut self, model: usize, vertices: &[f32],
+		fans: Vec<(u32, u32)>)
+	{
+		let old = self.models[model].shape.buffer();
+		let vertex_count = vertices.len() as u32 / 4;
+
+		if vertex_count == self.models[model].vertex_count {
+			self.models[model].shape.update(vertices,
+				&self.vw.connection);
+		} else {
+			// Doesn't fit, so make a new buffer.
+			// TODO: Wait for frames in flight using the old buffer.
+			self.models[model].shape = unsafe {
+				super::asi::new_buffer(&self.vw.connection,
+					vertices)
+			};
+		}
 
-		a
+		let new = self.models[model].shape.buffer();
+		let shapes = unsafe { &mut *self.opaque_vec.get() }.iter_mut()
+			.chain(unsafe { &mut *self.alpha_vec.get() }.iter_mut());
+		for shape in shapes.filter(|shape| shape.buffers[0] == old) {
+			shape.buffers[0] = new;
+			shape.fans = fans.clone();
+		}
+
+		self.models[model].vertex_count = vertex_count;
+		self.models[model].fans = fans;
+		self.dirty.set(true);
 	}
 
 	/// Push texture coordinates (collection of vertices) into graphics
@@ -679,14 +762,10 @@ impl Renderer {
 			)
 		};
 
-		let a = self.texcoords.len();
-
-		self.texcoords.push(TexCoords {
+		self.texcoords.add(TexCoords {
 			vertex_buffer,
 			vertex_count: texcoords.len() as u32,
-		});
-
-		a
+		})
 	}
 
 	/// Push colors per vertex into graphics memory.
@@ -698,25 +777,18 @@ impl Renderer {
 			)
 		};
 
-		let a = self.gradients.len();
-
-		self.gradients.push(Gradient {
+		self.gradients.add(Gradient {
 			vertex_buffer,
 			vertex_count: colors.len() as u32 / 4,
-		});
-
-		a
+		})
 	}
 
 	pub(crate) fn textured(&mut self, model: usize, mat: Matrix,
 		texture: usize, texcoords: usize, alpha: bool,
-		fog: bool, camera: bool) -> ShapeHandle
+		fog: bool, camera: bool) -> Result<ShapeHandle, ShapeError>
 	{
-		if self.models[model].vertex_count
-			!= self.texcoords[texcoords].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		check_texcoords(self.models[model].vertex_count,
+			self.texcoords[texcoords].vertex_count)?;
 
 		// Add an instance
 		let instance = unsafe {
@@ -748,18 +820,32 @@ impl Renderer {
 			],
 			fans: self.models[model].fans.clone(),
 			transform: mat,
+			visible: true,
 		};
 
 		if alpha {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.push(shape);
 			self.alpha_ind.push(index);
-			ShapeHandle::Alpha(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Alpha(index))
 		} else {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.push(shape);
 			self.opaque_ind.push(index);
-			ShapeHandle::Opaque(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Opaque(index))
+		}
+	}
+
+	/// Reserve space for `additional` more shapes.
+	pub(crate) fn reserve(&mut self, additional: usize, alpha: bool) {
+		if alpha {
+			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.reserve(additional);
+			self.alpha_ind.reserve(additional);
+		} else {
+			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.reserve(additional);
+			self.opaque_ind.reserve(additional);
 		}
 	}
 
@@ -796,30 +882,30 @@ impl Renderer {
 			],
 			fans: self.models[model].fans.clone(),
 			transform: mat,
+			visible: true,
 		};
 
 		if alpha {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.push(shape);
 			self.alpha_ind.push(index);
+			self.dirty.set(true);
 			ShapeHandle::Alpha(index)
 		} else {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.push(shape);
 			self.opaque_ind.push(index);
+			self.dirty.set(true);
 			ShapeHandle::Opaque(index)
 		}
 	}
 
 	pub(crate) fn gradient(&mut self, model: usize, mat: Matrix,
 		colors: usize, alpha: bool, fog: bool, camera: bool)
-		-> ShapeHandle
+		-> Result<ShapeHandle, ShapeError>
 	{
-		if self.models[model].vertex_count
-			!= self.gradients[colors].vertex_count
-		{
-			panic!("TexCoord length doesn't match gradient length");
-		}
+		check_gradient(self.models[model].vertex_count,
+			self.gradients[colors].vertex_count)?;
 
 		// Add an instance
 		let instance = unsafe {
@@ -849,30 +935,30 @@ impl Renderer {
 			],
 			fans: self.models[model].fans.clone(),
 			transform: mat,
+			visible: true,
 		};
 
 		if alpha {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.push(shape);
 			self.alpha_ind.push(index);
-			ShapeHandle::Alpha(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Alpha(index))
 		} else {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.push(shape);
 			self.opaque_ind.push(index);
-			ShapeHandle::Opaque(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Opaque(index))
 		}
 	}
 
 	pub(crate) fn faded(&mut self, model: usize, mat: Matrix,
 		texture: usize, texcoords: usize, fade_factor: f32, fog: bool,
-		camera: bool) -> ShapeHandle
+		camera: bool) -> Result<ShapeHandle, ShapeError>
 	{
-		if self.models[model].vertex_count
-			!= self.texcoords[texcoords].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		check_texcoords(self.models[model].vertex_count,
+			self.texcoords[texcoords].vertex_count)?;
 
 		// Add an instance
 		let instance = unsafe {
@@ -901,24 +987,23 @@ impl Renderer {
 			],
 			fans: self.models[model].fans.clone(),
 			transform: mat,
+			visible: true,
 		};
 
 		let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.len() as u32;
 		unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.push(shape);
 		self.alpha_ind.push(index);
-		ShapeHandle::Alpha(index)
+		self.dirty.set(true);
+		Ok(ShapeHandle::Alpha(index))
 	}
 
 	pub(crate) fn tinted(&mut self, model: usize, mat: Matrix,
 		texture: usize, texcoords: usize, color: [f32; 4],
 		alpha: bool, fog: bool, camera: bool)
-		-> ShapeHandle
+		-> Result<ShapeHandle, ShapeError>
 	{
-		if self.models[model].vertex_count
-			!= self.texcoords[texcoords].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		check_texcoords(self.models[model].vertex_count,
+			self.texcoords[texcoords].vertex_count)?;
 
 		// Add an instance
 		let instance = unsafe {
@@ -951,32 +1036,32 @@ impl Renderer {
 			],
 			fans: self.models[model].fans.clone(),
 			transform: mat,
+			visible: true,
 		};
 
 		if alpha {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.push(shape);
 			self.alpha_ind.push(index);
-			ShapeHandle::Alpha(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Alpha(index))
 		} else {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.push(shape);
 			self.opaque_ind.push(index);
-			ShapeHandle::Opaque(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Opaque(index))
 		}
 	}
 
 	pub(crate) fn complex(&mut self, model: usize, mat: Matrix,
 		texture: usize, texcoords: usize, colors: usize, alpha: bool,
-		fog: bool, camera: bool) -> ShapeHandle
+		fog: bool, camera: bool) -> Result<ShapeHandle, ShapeError>
 	{
-		if self.models[model].vertex_count
-			!= self.texcoords[texcoords].vertex_count ||
-			self.models[model].vertex_count
-			!= self.gradients[colors].vertex_count
-		{
-			panic!("TexCoord length doesn't match vertex length");
-		}
+		check_texcoords(self.models[model].vertex_count,
+			self.texcoords[texcoords].vertex_count)?;
+		check_gradient(self.models[model].vertex_count,
+			self.gradients[colors].vertex_count)?;
 
 		// Add an instance
 		let instance = unsafe {
@@ -1008,18 +1093,21 @@ impl Renderer {
 			],
 			fans: self.models[model].fans.clone(),
 			transform: mat,
+			visible: true,
 		};
 
 		if alpha {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}.push(shape);
 			self.alpha_ind.push(index);
-			ShapeHandle::Alpha(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Alpha(index))
 		} else {
 			let index = unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.len() as u32;
 			unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.opaque_vec.get())}.push(shape);
 			self.opaque_ind.push(index);
-			ShapeHandle::Opaque(index)
+			self.dirty.set(true);
+			Ok(ShapeHandle::Opaque(index))
 		}
 	}
 
@@ -1029,12 +1117,51 @@ impl Renderer {
 				let index = self.opaque_ind.iter()
 					.position(|y| *y == x).unwrap();
 				self.opaque_ind.remove(index);
+				self.dirty.set(true);
 			}
 			ShapeHandle::Alpha(x) => {
 				let index = self.alpha_ind.iter()
 					.position(|y| *y == x).unwrap();
 				self.alpha_ind.remove(index);
+				self.dirty.set(true);
+			}
+			// TODO: GUI shapes, Vulkan draws everything with `camera`.
+			ShapeHandle::Gui(_) => {}
+		}
+	}
+
+	/// Free a model from graphics memory.
+	pub(crate) fn drop_model(&mut self, model: usize) {
+		self.models.remove(model);
+	}
+
+	/// Free a texture from graphics memory.
+	pub(crate) fn drop_texture(&mut self, texture: usize) {
+		self.textures.remove(texture);
+	}
+
+	/// Free texture coordinates from graphics memory.
+	pub(crate) fn drop_texcoords(&mut self, texcoords: usize) {
+		self.texcoords.remove(texcoords);
+	}
+
+	/// Free colors per vertex from graphics memory.
+	pub(crate) fn drop_colors(&mut self, colors: usize) {
+		self.gradients.remove(colors);
+	}
+
+	pub(crate) fn set_visible(&mut self, shape: &ShapeHandle, visible: bool) {
+		match shape {
+			ShapeHandle::Opaque(x) => {
+				let x = *x as usize; // for indexing
+				self.opaque_vec.get_mut()[x].visible = visible;
+			}
+			ShapeHandle::Alpha(x) => {
+				let x = *x as usize; // for indexing
+				self.alpha_vec.get_mut()[x].visible = visible;
 			}
+			// TODO: GUI shapes, Vulkan draws everything with `camera`.
+			ShapeHandle::Gui(_) => {}
 		}
 	}
 
@@ -1058,7 +1185,10 @@ impl Renderer {
 					unsafe{::std::mem::transmute::<_, &mut Vec<Shape>>(self.alpha_vec.get())}[x].instance.uniform_memory.memory(),
 					&uniform);
 			}
+			// TODO: GUI shapes, Vulkan draws everything with `camera`.
+			ShapeHandle::Gui(_) => {}
 		}
+		self.dirty.set(true);
 	}
 }
 
@@ -6,15 +6,20 @@
 //! Screen Module (Computer Monitor / Phone Screen / etc.)
 
 #[cfg(not(target_arch="wasm32"))]
-use render::{Display, new_display};
+use render::{Display, DisplayBuilder};
 #[cfg(not(target_arch="wasm32"))]
-pub use render::{Shape, Gradient, Model, Texture, TexCoords};
+pub use render::{Shape, Gradient, Model, Texture, TexCoords, Projection,
+	BlendMode, Cull, ShapeError, StyleId, UniformValue, FrameStatus,
+	LatencyMode, CompressedFormat, PixelFormat};
 
 use render::{Event};
 use afi::{VFrame, PathOp};
 
+use std::time::Duration;
+
 use Matrix;
 use Vector;
+use Cursor;
 
 #[cfg(target_arch="wasm32")] mod win {mod wasm32; pub use self::wasm32::*;}
 
@@ -23,13 +28,13 @@ use self::win::{Display};
 #[cfg(target_arch="wasm32")]
 pub use self::win::{Shape, Gradient, Model, Texture, TexCoords};
 
+#[cfg(not(target_arch="wasm32"))] type NativeDisplay = Box<Display>;
+#[cfg(target_arch="wasm32")] type NativeDisplay = Display;
+
 /// A Window to the Screen.
 pub struct Screen<Ctx> where Ctx: Default {
 	// The platform-dependant implementation.
-	#[cfg(not(target_arch="wasm32"))]
-	display: Box<Display>,
-	#[cfg(target_arch="wasm32")]
-	display: Display,
+	display: NativeDisplay,
 
 	/* * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
@@ -40,12 +45,19 @@ pub struct Screen<Ctx> where Ctx: Default {
 	// current function pointer.
 	run: fn(&mut Screen<Ctx>, Event, f32),
 	running: bool,
+	// Whether `Event::Timestep` comes at a fixed rate from the `Display`.
+	fixed_timestep: bool,
+	// How long each fixed `Event::Timestep` is, in seconds.
+	step: f32,
+	// What the `Display` measured about the last frame.
+	frame: FrameStatus,
 }
 
 /// An error in the connection to the screen.
 #[derive(Debug)]
 pub enum ScreenError {
-	
+	/// No backend could open the window (why each failed).
+	Display(String),
 }
 
 impl<Ctx> Screen<Ctx> where Ctx: Default {
@@ -53,34 +65,70 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 	pub fn start(run: fn(&mut Screen<Ctx>, Event, f32))
 		-> Result<(), ScreenError>
 	{
-		let mut screen = Screen::new(run);
+		#[cfg(not(target_arch="wasm32"))]
+		let display = DisplayBuilder::new().build()
+			.map_err(ScreenError::Display)?;
+		#[cfg(target_arch="wasm32")]
+		let display = Display::new();
+
+		Screen::new(run, display).main_loop()
+	}
+
+	/// Start the program, with the window's title, size, etc. picked with
+	/// `builder` (instead of `new_display()`'s).
+	#[cfg(not(target_arch="wasm32"))]
+	pub fn with_builder(builder: DisplayBuilder,
+		run: fn(&mut Screen<Ctx>, Event, f32))
+		-> Result<(), ScreenError>
+	{
+		let display = builder.build().map_err(ScreenError::Display)?;
+
+		Screen::new(run, display).main_loop()
+	}
+
+	// Run until the window is closed.
+	fn main_loop(self) -> Result<(), ScreenError> {
+		let mut screen = self;
 		let mut dt = 0.0;
 
 		while screen.running {
 			while let Some(input) = screen.display.input() {
+				// Fixed timesteps are all the same length.
+				let dt = match input {
+					Event::Timestep => screen.step,
+					_ => dt,
+				};
 				(screen.run)(&mut screen, input, dt);
 			}
 
-			(screen.run)(&mut screen, Event::Timestep, dt);
-			dt = screen.display.update();
+			// After `Event::Exit` from `close()`.
+			if screen.display.is_closed() {
+				break;
+			}
+
+			if !screen.fixed_timestep {
+				(screen.run)(&mut screen, Event::Timestep, dt);
+			}
+			screen.frame = screen.display.update();
+			dt = screen.frame.dt;
 		}
 
 		Ok(())
 	}
 
 	/// Open a new Window to the Screen.
-	fn new(run: fn(&mut Screen<Ctx>, Event, f32)) -> Self {
+	fn new(run: fn(&mut Screen<Ctx>, Event, f32), display: NativeDisplay)
+		-> Self
+	{
 		let mut screen = Screen {
 			ctx: Ctx::default(),
 			vframe: VFrame(vec![]),
-
-			#[cfg(not(target_arch="wasm32"))]
-			display: new_display().unwrap(),
-			#[cfg(target_arch="wasm32")]
-			display: Display::new(),
-
+			display,
 			run,
 			running: true,
+			fixed_timestep: false,
+			step: 0.0,
+			frame: FrameStatus::default(),
 		};
 
 		let wh = screen.display.wh();
@@ -94,6 +142,22 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		::std::process::exit(0);
 	}
 
+	/// Get `Event::Timestep` every `step`, instead of once per frame (zero
+	/// to go back).  Good for deterministic physics, each step is exactly
+	/// `step` long.
+	pub fn set_timestep(&mut self, step: Duration) {
+		self.fixed_timestep = step != Duration::new(0, 0);
+		self.step = step.as_secs() as f32
+			+ step.subsec_nanos() as f32 / 1_000_000_000.0;
+		self.display.set_timestep(step)
+	}
+
+	/// Stop the fixed timestep while paused (out of focus), so it doesn't
+	/// fast-forward on resume (default off).
+	pub fn set_pause_halts_timestep(&mut self, halts: bool) {
+		self.display.set_pause_halts_timestep(halts)
+	}
+
 	/// Switch the run function
 	pub fn switch(&mut self, run: fn(&mut Screen<Ctx>, Event, f32)) {
 		self.run = run;
@@ -104,6 +168,103 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.display.color(color)
 	}
 
+	/// Update the clear color of the Window, with alpha for transparency.
+	pub fn clear_rgba(&mut self, color: (u8, u8, u8, u8)) {
+		self.display.color_rgba(color)
+	}
+
+	/// Set the fog color, and the distances that it starts and ends at.
+	pub fn fog(&mut self, color: [f32; 4], near: f32, far: f32) {
+		self.display.fog(color, near, far)
+	}
+
+	/// Set the camera position, and rotation (radians around X, Y, Z axis).
+	pub fn camera(&mut self, position: Vector, rotation: Vector) {
+		self.display.camera(position, rotation)
+	}
+
+	/// Set the projection (default is perspective).
+	pub fn set_projection(&mut self, projection: Projection) {
+		self.display.set_projection(projection)
+	}
+
+	/// Set the near & far clip planes of the perspective projection
+	/// (default 0.1 & 100), to fit the scene's scale.
+	pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+		self.display.set_clip_planes(near, far)
+	}
+
+	/// Get the current projection matrix.
+	pub fn projection(&self) -> Matrix {
+		self.display.projection()
+	}
+
+	/// Get the current view (camera) matrix.
+	pub fn view(&self) -> Matrix {
+		self.display.view()
+	}
+
+	/// Get the point in the world at cursor coordinates `xy` and `depth`
+	/// (-1.0 near to 1.0 far), for click-to-world picking.
+	pub fn unproject(&self, xy: (f32, f32), depth: f32) -> Vector {
+		self.display.unproject(xy, depth)
+	}
+
+	/// Set whether opaque shapes are sorted (default true).
+	pub fn set_opaque_sort(&mut self, sort: bool) {
+		self.display.set_opaque_sort(sort)
+	}
+
+	/// Turn vsync on or off (default on), `false` if that failed.
+	pub fn set_vsync(&mut self, vsync: bool) -> bool {
+		self.display.set_vsync(vsync)
+	}
+
+	/// Use adaptive vsync for less latency (`LatencyMode::LowLatency`), or
+	/// vsync (`LatencyMode::Smooth`, default).  `false` if adaptive vsync
+	/// isn't supported.
+	pub fn set_latency_mode(&mut self, mode: LatencyMode) -> bool {
+		self.display.set_latency_mode(mode)
+	}
+
+	/// Turn gamma-correct (sRGB) rendering on or off (default off),
+	/// `false` if that failed.
+	pub fn set_srgb(&mut self, srgb: bool) -> bool {
+		self.display.set_srgb(srgb)
+	}
+
+	/// Draw shapes as wireframes, for debugging (default off), `false` if
+	/// that's not supported.
+	pub fn set_wireframe(&mut self, wireframe: bool) -> bool {
+		self.display.set_wireframe(wireframe)
+	}
+
+	/// Draw alpha shapes with alpha to coverage, so cutouts (foliage) don't
+	/// need sorting (default off).  Needs a multisampled window.  `false`
+	/// if it's not supported.
+	pub fn set_alpha_to_coverage(&mut self, enabled: bool) -> bool {
+		self.display.set_alpha_to_coverage(enabled)
+	}
+
+	/// Set which faces of triangles aren't drawn (default `Cull::Back`),
+	/// `false` if that's not supported.
+	pub fn set_cull(&mut self, cull: Cull) -> bool {
+		self.display.set_cull(cull)
+	}
+
+	/// Send rendering errors to `callback`, for debugging a black screen.
+	/// `false` if that's not supported.
+	pub fn set_debug_callback<F: Fn(&str) + 'static>(&mut self,
+		callback: F) -> bool
+	{
+		self.display.set_debug_callback(Box::new(callback))
+	}
+
+	/// Set how blended shapes are blended (default is straight alpha).
+	pub fn set_blend_mode(&mut self, mode: BlendMode) {
+		self.display.set_blend_mode(mode)
+	}
+
 	/// Upload a model to the GPU.
 	pub fn model(&mut self, vertices: &[f32], fans: Vec<(u32, u32)>)
 		-> Model
@@ -111,11 +272,38 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.display.model(vertices, fans)
 	}
 
+	/// Upload a model of lines to the GPU (a line per pair of vertices).
+	pub fn model_lines(&mut self, vertices: &[f32]) -> Model {
+		self.display.model_lines(vertices)
+	}
+
+	/// Upload a model of points to the GPU (a point per vertex).
+	pub fn model_points(&mut self, vertices: &[f32]) -> Model {
+		self.display.model_points(vertices)
+	}
+
 	/// Upload a texture to the GPU.
 	pub fn texture(&mut self, wh: (u16, u16), graphic: &VFrame) -> Texture {
 		self.display.texture(wh, graphic)
 	}
 
+	/// Upload a texture with pixels in another `format` (BGRA, or one
+	/// channel), converting them to RGBA.
+	pub fn texture_fmt(&mut self, wh: (u16, u16), format: PixelFormat,
+		data: &[u8]) -> Result<Texture, String>
+	{
+		self.display.texture_fmt(wh, format, data)
+	}
+
+	/// Upload an already compressed texture to the GPU, which stays
+	/// compressed there.  Fails if the GPU doesn't have `format`.
+	pub fn texture_compressed(&mut self, wh: (u16, u16),
+		format: CompressedFormat, data: &[u8])
+		-> Result<Texture, String>
+	{
+		self.display.texture_compressed(wh, format, data)
+	}
+
 	/// Create gradient object.
 	pub fn gradient(&mut self, colors: &[f32]) -> Gradient {
 		self.display.gradient(colors)
@@ -126,6 +314,14 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.display.texcoords(texcoords)
 	}
 
+	/// Create texture coordinates for a quad of pixels `(x, y, w, h)` in
+	/// an atlas texture.
+	pub fn atlas_texcoords(&mut self, tex: &Texture,
+		pixel_rect: (u16, u16, u16, u16)) -> TexCoords
+	{
+		self.display.atlas_texcoords(tex, pixel_rect)
+	}
+
 	/// Set the pixels of a texture to something other than the original.
 	pub fn set_texture(&mut self, texture: &mut Texture, wh: (u16, u16),
 		graphic: &VFrame)
@@ -133,6 +329,28 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 		self.display.set_texture(texture, wh, graphic)
 	}
 
+	/// Read back the pixels of a texture (for undo, or checking what was
+	/// uploaded).
+	pub fn read_texture(&self, texture: &Texture)
+		-> (u16, u16, Vec<[u8; 4]>)
+	{
+		self.display.read_texture(texture)
+	}
+
+	/// Draw the shapes into `texture` instead of the window, for mirrors,
+	/// minimaps or post-processing.  `false` if that's not supported.
+	pub fn render_to(&mut self, texture: &Texture) -> bool {
+		self.display.render_to(texture)
+	}
+
+	/// Replace the vertices & fans of a model, changing shapes made from
+	/// it (for animated meshes).
+	pub fn set_model(&mut self, model: &Model, vertices: &[f32],
+		fans: Vec<(u32, u32)>)
+	{
+		self.display.set_model(model, vertices, fans)
+	}
+
 	/// Make a shape with solid color.
 	pub fn shape_solid(&mut self, model: &Model, matrix: Matrix,
 		color: [f32; 4], blending: bool, fog: bool, camera: bool)
@@ -142,10 +360,18 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 			camera)
 	}
 
-	/// Make a shape with gradient
+	/// Make many shapes with solid colors at once (faster for loading).
+	pub fn shapes_solid(&mut self, shapes: &[(Model, Matrix, [f32; 4])],
+		blending: bool, fog: bool, camera: bool) -> Vec<Shape>
+	{
+		self.display.shapes_solid(shapes, blending, fog, camera)
+	}
+
+	/// Make a shape with gradient.  Fails (like the textured shapes) if
+	/// there isn't one color / texture coordinate per vertex.
 	pub fn shape_gradient(&mut self, model: &Model, matrix: Matrix,
 		gradient: Gradient, blending: bool, fog: bool, camera: bool)
-		-> Shape
+		-> Result<Shape, ShapeError>
 	{
 		self.display.shape_gradient(model, matrix, gradient,
 			blending, fog, camera)
@@ -154,7 +380,7 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 	/// Make a shape will solid texture.
 	pub fn shape_texture(&mut self, model: &Model, matrix: Matrix,
 		texture: &Texture, tc: TexCoords, blending: bool, fog: bool, 
-		camera: bool) -> Shape
+		camera: bool) -> Result<Shape, ShapeError>
 	{
 		self.display.shape_texture(model, matrix, texture, tc,
 			blending, fog, camera)
@@ -163,7 +389,7 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 	/// Make a shape will texture and transparency
 	pub fn shape_faded(&mut self, model: &Model, matrix: Matrix,
 		texture: &Texture, tc: TexCoords, alpha: f32, fog: bool,
-		camera: bool) -> Shape
+		camera: bool) -> Result<Shape, ShapeError>
 	{
 		self.display.shape_faded(model, matrix, texture, tc, alpha,
 			fog, camera)
@@ -172,42 +398,420 @@ impl<Ctx> Screen<Ctx> where Ctx: Default {
 	/// Make a shape with texture, and tint (color)
 	pub fn shape_tinted(&mut self, model: &Model, matrix: Matrix, 
 		texture: &Texture, tc: TexCoords, tint: [f32; 4],
-		blending: bool, fog: bool, camera: bool) -> Shape
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
 	{
 		self.display.shape_tinted(model, matrix, texture, tc, tint,
 			blending, fog, camera)
 	}
 
+	/// Make a shape of text (see `text_model()`), in a single color.
+	pub fn shape_text(&mut self, model: &Model, matrix: Matrix,
+		texture: &Texture, tc: TexCoords, color: [f32; 4],
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
+	{
+		self.display.shape_text(model, matrix, texture, tc, color,
+			blending, fog, camera)
+	}
+
+	/// Make the model & texture coordinates for a line of glyph quads,
+	/// `(rect in the atlas, rect to draw at)`.
+	pub fn text_model(&mut self,
+		glyphs: &[((f32, f32, f32, f32), (f32, f32, f32, f32))])
+		-> (Model, TexCoords)
+	{
+		self.display.text_model(glyphs)
+	}
+
 	/// Make a shape with texture, and gradent
 	pub fn shape_complex(&mut self, model: &Model, matrix: Matrix, 
 		texture: &Texture, tc: TexCoords, gradient: Gradient,
-		blending: bool, fog: bool, camera: bool) -> Shape
+		blending: bool, fog: bool, camera: bool)
+		-> Result<Shape, ShapeError>
 	{
 		self.display.shape_complex(model, matrix, texture, tc,
 			gradient, blending, fog, camera)
 	}
 
+	/// Compile a custom style from GLSL shaders, `Err` with the info log.
+	pub fn add_style(&mut self, vert: &[u8], frag: &[u8])
+		-> Result<StyleId, String>
+	{
+		self.display.add_style(vert, frag)
+	}
+
+	/// Make a shape drawn with a custom style, setting `uniforms`.
+	pub fn shape_custom(&mut self, model: &Model, matrix: Matrix,
+		style: StyleId, uniforms: &[(&str, UniformValue)],
+		blending: bool, fog: bool, camera: bool) -> Shape
+	{
+		self.display.shape_custom(model, matrix, style, uniforms,
+			blending, fog, camera)
+	}
+
+	/// Set a uniform of a custom style, for all of it's shapes.
+	pub fn set_uniform(&mut self, style: StyleId, name: &str,
+		value: UniformValue)
+	{
+		self.display.set_uniform(style, name, value)
+	}
+
 	/// Stop drawing a shape.
 	pub fn drop_shape(&mut self, shape: &Shape) {
 		self.display.drop_shape(shape)
 	}
 
+	/// Free a model from the GPU.
+	pub fn drop_model(&mut self, model: Model) {
+		self.display.drop_model(model)
+	}
+
+	/// Free a texture from the GPU.
+	pub fn drop_texture(&mut self, texture: Texture) {
+		self.display.drop_texture(texture)
+	}
+
+	/// Free a gradient from the GPU.
+	pub fn drop_gradient(&mut self, gradient: Gradient) {
+		self.display.drop_gradient(gradient)
+	}
+
+	/// Free texture coordinates from the GPU.
+	pub fn drop_texcoords(&mut self, texcoords: TexCoords) {
+		self.display.drop_texcoords(texcoords)
+	}
+
+	/// Get the shape under the cursor at `x`, `y` (like `Event::Cursor`).
+	pub fn pick(&mut self, x: f32, y: f32) -> Option<Shape> {
+		self.display.pick(x, y)
+	}
+
 	/// Apply a matrix transform to a shape.
 	pub fn transform(&self, shape: &Shape, matrix: Matrix) {
 		self.display.transform(shape, matrix)
 	}
 
+	/// Show or hide a shape, without dropping it.
+	pub fn set_visible(&mut self, shape: &Shape, visible: bool) {
+		self.display.set_visible(shape, visible)
+	}
+
+	/// Set the drawing order of a GUI shape, higher layers are in front.
+	pub fn set_layer(&mut self, shape: &Shape, layer: i32) {
+		self.display.set_layer(shape, layer)
+	}
+
+	/// Clip a shape to a rectangle `(x, y, w, h)`, or `None` to unclip.
+	pub fn set_clip(&mut self, shape: &Shape,
+		clip: Option<(f32, f32, f32, f32)>)
+	{
+		self.display.set_clip(shape, clip)
+	}
+
+	/// Multiply a shape's color by `tint`, like flashing it white on hit.
+	/// `None` removes it.  `false` if tints aren't supported.
+	pub fn set_tint(&mut self, shape: &Shape, tint: Option<[f32; 4]>)
+		-> bool
+	{
+		self.display.set_tint(shape, tint)
+	}
+
+	/// Set which faces of a shape aren't drawn, like `Cull::None` for
+	/// double-sided shapes.  `None` uses the one from `set_cull()`.
+	/// `false` if that's not supported.
+	pub fn set_shape_cull(&mut self, shape: &Shape, cull: Option<Cull>)
+		-> bool
+	{
+		self.display.set_shape_cull(shape, cull)
+	}
+
 	/// Call this function when you get a resize event.
 	pub fn resize(&mut self, wh: (u16, u16)) {
 		self.vframe.0.resize((wh.0 as usize * wh.1 as usize) * 4, 0);
 		self.display.resize(wh);
 	}
 
+	/// Letterbox to a fixed aspect ratio (width / height), `None` to
+	/// stretch to fill the window (default).  `false` if that's not
+	/// supported.
+	pub fn set_fixed_aspect(&mut self, aspect: Option<f32>) -> bool {
+		self.display.set_fixed_aspect(aspect)
+	}
+
+	/// Get the average frames per second.
+	pub fn fps(&self) -> f32 {
+		self.display.fps()
+	}
+
+	/// Set the frame rate to keep up with (0 for none, the default).
+	pub fn set_target_fps(&mut self, fps: f32) {
+		self.display.set_target_fps(fps)
+	}
+
+	/// Get what was measured about the last frame (whether it's over the
+	/// `set_target_fps()` budget).
+	pub fn frame_status(&self) -> FrameStatus {
+		self.frame
+	}
+
 	/// Get the width and height of the window.
 	pub fn wh(&self) -> (u16, u16) {
 		self.display.wh()
 	}
 
+	/// Get the part of the window `(x, y, w, h)` that was uncovered or
+	/// resized since the last call, `None` if nothing was.
+	pub fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)> {
+		self.display.take_damage()
+	}
+
+	/// Copy `text` to the system clipboard (for `Event::Copy`/`Cut`).
+	pub fn clipboard_set(&mut self, text: &str) {
+		self.display.clipboard_set(text)
+	}
+
+	/// Get the text on the system clipboard (for `Event::Paste`).
+	pub fn clipboard_get(&self) -> Option<String> {
+		self.display.clipboard_get()
+	}
+
+	/// Set the shape of the mouse cursor (default is `Cursor::Arrow`).
+	pub fn set_cursor(&mut self, cursor: Cursor) {
+		self.display.set_cursor(cursor)
+	}
+
+	/// Move the mouse cursor to `xy` (-1 to 1, like `Event::Cursor`),
+	/// without sending an `Event::Cursor`.
+	pub fn set_cursor_position(&mut self, xy: (f32, f32)) {
+		self.display.set_cursor_position(xy)
+	}
+
+	/// Set the minimum size of the window, `(0, 0)` for no minimum.  Resize
+	/// events still report whatever size the window manager grants.
+	pub fn set_min_size(&mut self, wh: (u16, u16)) {
+		self.display.set_min_size(wh)
+	}
+
+	/// Set the maximum size of the window, `(0, 0)` for no maximum.
+	pub fn set_max_size(&mut self, wh: (u16, u16)) {
+		self.display.set_max_size(wh)
+	}
+
+	/// Keep the window's aspect ratio `(w, h)` while the user resizes it,
+	/// `None` for any (default).  `false` if the window manager couldn't
+	/// be asked.
+	pub fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		self.display.set_aspect_ratio(aspect)
+	}
+
+	/// Close the window (like for "File > Quit").  The `run` function gets
+	/// `Event::Exit`, and then `start()` returns.
+	pub fn close(&mut self) {
+		self.display.close()
+	}
+
+	/// Get the text being composed with an input method (IME), for
+	/// `Event::Preedit`.  Committed text still comes as `Event::Text`.
+	/// Always empty on X11 and Wayland for now.
+	pub fn preedit(&self) -> &str {
+		self.display.preedit()
+	}
+
+	/// Get committed text as one `Event::TextStr`, instead of an
+	/// `Event::Text` for each `char` (default off).
+	pub fn set_text_str(&mut self, enabled: bool) {
+		self.display.set_text_str(enabled)
+	}
+
+	/// Turn the built-in keyboard shortcuts (like Ctrl-C for
+	/// `Event::Copy`) on or off (default on).
+	pub fn set_shortcuts_enabled(&mut self, enabled: bool) {
+		self.display.set_shortcuts_enabled(enabled)
+	}
+
+	/// Flip the direction of scroll events (default off), for an "invert
+	/// scrolling" setting when the OS has natural scrolling on.
+	pub fn set_natural_scroll(&mut self, natural: bool) {
+		self.display.set_natural_scroll(natural)
+	}
+
+	/// Get the text of an `Event::TextStr`.
+	pub fn text_str(&self, index: usize) -> &str {
+		self.display.text_str(index)
+	}
+
+	/// Whether a key is held down (like 15 for `Event::W`), without keeping
+	/// track of the key events.
+	pub fn is_key_down(&self, key: u8) -> bool {
+		self.display.is_key_down(key)
+	}
+
+	/// Keep the last `capacity` events (0 for off), for bug reports.
+	pub fn set_event_history(&mut self, capacity: usize) {
+		self.display.set_event_history(capacity)
+	}
+
+	/// Get the events kept by `set_event_history()`, oldest first.
+	pub fn event_history(&self) -> &[Event] {
+		self.display.event_history()
+	}
+
+	/// Get all of the input that hasn't gone to `run` yet, at once.
+	pub fn drain(&mut self) -> ::Drain {
+		self.display.drain()
+	}
+
+	/// Get the first input that `pred` matches, before it goes to `run`
+	/// (the rest still do).
+	pub fn pop_matching<F: Fn(&Event) -> bool>(&mut self, pred: F)
+		-> Option<Event>
+	{
+		self.display.pop_matching(&pred)
+	}
+
+	/// Keep the window above other windows (or stop), `false` on failure.
+	pub fn set_always_on_top(&mut self, above: bool) -> bool {
+		self.display.set_always_on_top(above)
+	}
+
+	/// Set how see-through the whole window is, from 0 to 1 (opaque).  Does
+	/// nothing without a compositor.
+	pub fn set_opacity(&mut self, opacity: f32) {
+		self.display.set_opacity(opacity)
+	}
+
+	/// Set the window's class (instance & class name), for taskbar grouping
+	/// and icons.
+	pub fn set_class(&mut self, instance: &str, class: &str) {
+		self.display.set_class(instance, class)
+	}
+
+	/// Set the rectangles where clicks go to the window (`None` for all of
+	/// it), `false` if it's not supported.
+	pub fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		self.display.set_input_shape(region)
+	}
+
+	/// Grab the keyboard for the window manager's shortcuts (let go on
+	/// `Event::Pause`), `false` on failure.  A hang while it's grabbed
+	/// locks out the keyboard.
+	pub fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		self.display.set_keyboard_grab(grab)
+	}
+
+	/// Minimize the window (`Event::Pause` follows), `false` on failure.
+	pub fn minimize(&mut self) -> bool {
+		self.display.minimize()
+	}
+
+	/// Maximize the window (`Event::Resize` follows), `false` on failure.
+	pub fn maximize(&mut self) -> bool {
+		self.display.maximize()
+	}
+
+	/// Restore from minimized or maximized, `false` on failure.
+	pub fn restore(&mut self) -> bool {
+		self.display.restore()
+	}
+
+	/// Get the content scale factor for HiDPI (1.0 at 96 DPI).
+	pub fn scale_factor(&self) -> f32 {
+		self.display.scale_factor()
+	}
+
+	/// Set the deadzone for a controller's sticks & throttles, so resting
+	/// drift doesn't move anything.
+	pub fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32)
+	{
+		self.display.set_stick_deadzone(controller, inner, outer)
+	}
+
+	/// Remap a controller's buttons, `(from, to)`.  An empty `map` goes
+	/// back to the default layout.
+	pub fn set_controller_map(&mut self, controller: usize,
+		map: &[(::ControllerButton, ::ControllerButton)])
+	{
+		self.display.set_controller_map(controller, map)
+	}
+
+	/// Send D-pad events when a controller's main stick is pushed past
+	/// halfway, to navigate menus with it.
+	pub fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool) {
+		self.display.set_stick_as_dpad(controller, enabled)
+	}
+
+	/// Get the battery level of a controller (0.0 to 1.0), `None` if it's
+	/// unknown or wired.
+	pub fn controller_battery(&self, controller: usize) -> Option<f32> {
+		self.display.controller_battery(controller)
+	}
+
+	/// Get the GUID of a controller's device, `None` if there's no
+	/// controller with that number.  It keeps it's number when plugged
+	/// back in.  Two of the same kind of controller have the same GUID.
+	pub fn controller_guid(&self, controller: usize) -> Option<String> {
+		self.display.controller_guid(controller)
+	}
+
+	/// Get how many axes, buttons & hats a controller has, and whether it
+	/// can rumble.  `None` if it's unplugged, or it's unknown.
+	pub fn controller_capabilities(&self, controller: usize)
+		-> Option<::ControllerCaps>
+	{
+		self.display.controller_capabilities(controller)
+	}
+
+	/// Get where the window's top-left is on the screen, in pixels.
+	pub fn position(&self) -> (i16, i16) {
+		self.display.position()
+	}
+
+	/// Move the window's top-left to `xy` on the screen, in pixels.
+	pub fn set_position(&mut self, xy: (i16, i16)) {
+		self.display.set_position(xy)
+	}
+
+	/// Set where text is being entered, in pixels, or `None` to stop
+	/// getting `Event::Text` (for game controls).  The position is only
+	/// used on Windows for now.
+	pub fn set_text_input_rect(&mut self,
+		rect: Option<(i16, i16, u16, u16)>)
+	{
+		self.display.set_text_input_rect(rect)
+	}
+
+	/// Get when the last event happened, for timing double clicks and
+	/// gestures or measuring input latency.
+	pub fn event_time(&self) -> ::std::time::Instant {
+		self.display.event_time()
+	}
+
+	/// Show or hide the title bar & borders.
+	pub fn set_decorated(&mut self, decorated: bool) {
+		self.display.set_decorated(decorated)
+	}
+
+	/// Start moving the window with the cursor (call when the mouse button
+	/// is pressed over a custom title bar), `false` on failure.
+	pub fn begin_drag(&mut self) -> bool {
+		self.display.begin_drag()
+	}
+
+	/// Whether the window has keyboard focus right now.
+	pub fn has_focus(&self) -> bool {
+		self.display.has_focus()
+	}
+
+	/// Get the pixels of the last frame (RGBA, top row first), if they can
+	/// be read back.
+	pub fn pixels(&self) -> Option<Vec<u8>> {
+		self.display.pixels()
+	}
+
 	/// Update 2D overlay with writer function.
 	pub fn draw(&self, writer: &Fn(u16, u16) -> [u8; 4]) {
 		self.display.draw(writer)
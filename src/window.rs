@@ -7,39 +7,380 @@
 
 use os;
 
+/// What the window should look like when it's first shown (from
+/// `DisplayBuilder`), so nothing changes after it's mapped.
+#[derive(Clone)]
+pub(crate) struct WindowHints {
+	// Empty for no title.
+	pub title: String,
+	// `None` for maximized.
+	pub wh: Option<(u16, u16)>,
+	// Width, height & ARGB pixels.
+	pub icon: Option<(u16, u16, Vec<u32>)>,
+	pub decorated: bool,
+}
+
+impl Default for WindowHints {
+	fn default() -> Self {
+		WindowHints {
+			title: String::new(),
+			wh: None,
+			icon: None,
+			decorated: false,
+		}
+	}
+}
+
 /// A graphics window on a computer, linked to a rendering API.
 pub(crate) struct Window {
-	os_window: os::Window/* *mut c_void */,
+	os_window: Option<os::Window>, // `None` if headless
+	headless_wh: (u16, u16),
+	// Handles from the app, when it owns the window (else `NoOS`).
+	connection: ::WindowConnection,
 	input_queue: ::input::InputQueue,
 	keyboard: ::Keyboard,
 	reset: bool,
+	closed: bool,
+	// When the last event from `update()` happened.
+	time: ::std::time::Instant,
 	cm: ::stick::ControllerManager,
+	// Thread waiting on `fd()` for `next_event()`, once it's used.
+	#[cfg(feature = "async")]
+	waiter: Option<::render::Waiter>,
 }
 
 impl Window {
-	/// Create a window, using `title` as the title, and `icon` as the
-	/// window icon.  The format of icon is as follows:
-	/// `(width, height, pixels)`.  You can load icons with aci.  `v` should
-	/// be either `None` or `Some(visual_id from EGL)`.
-	pub fn new(v: Option<i32>) -> Window {
-		let os_window = os::Window::new(v);
+	/// Create a window, with the title, size, icon & decorations from
+	/// `hints`.  `v` should be either `None` or `Some(visual_id from EGL)`.
+	pub fn new(v: Option<i32>, hints: &WindowHints) -> Window {
+		Self::with(Some(os::Window::new(v, hints)), (0, 0),
+			::WindowConnection::NoOS)
+	}
+
+	/// Create a window that isn't shown, for offscreen rendering.  It
+	/// never gets any input.
+	pub fn headless(wh: (u16, u16)) -> Window {
+		Self::with(None, wh, ::WindowConnection::NoOS)
+	}
+
+	/// Wrap a window that the app already made (and gets input for) with
+	/// another library.  Like `headless()`, it never gets any input.
+	pub fn existing(connection: ::WindowConnection, wh: (u16, u16))
+		-> Window
+	{
+		Self::with(None, wh, connection)
+	}
+
+	fn with(os_window: Option<os::Window>, headless_wh: (u16, u16),
+		connection: ::WindowConnection) -> Window
+	{
 		let input_queue = ::input::InputQueue::new();
 		let keyboard = ::Keyboard::new();
 		let reset = false;
+		let closed = false;
+		let time = ::std::time::Instant::now();
 		let cm = ::stick::ControllerManager::new(vec![]);
 
-		Window { os_window, input_queue, keyboard, reset, cm }
+		Window { os_window, headless_wh, connection, input_queue, keyboard, reset,
+			closed, time, cm,
+			#[cfg(feature = "async")]
+			waiter: None,
+		}
+	}
+
+	/// Whether `new()` needs `Some(visual_id from EGL)`.  If not, the
+	/// window should be made first, so EGL can connect to it's display.
+	pub fn needs_visual() -> bool {
+		os::Window::needs_visual()
 	}
 
 	/// Get the type of connection, plus native window and connection
 	/// handles to pass to ffi.  See `WindowConnection` for more details.
 	pub fn get_connection(&self) -> ::WindowConnection {
-		self.os_window.get_connection()
+		match self.os_window {
+			Some(ref os_window) => os_window.get_connection(),
+			None => self.connection.clone(),
+		}
 	}
 
 	/// Get the width and height of the window, as a tuple.
 	pub fn wh(&self) -> (u16, u16) {
-		self.os_window.wh()
+		match self.os_window {
+			Some(ref os_window) => os_window.wh(),
+			None => self.headless_wh,
+		}
+	}
+
+	/// Set the size `wh()` returns, when awi doesn't own the window.
+	pub fn set_wh(&mut self, wh: (u16, u16)) {
+		if self.os_window.is_none() {
+			self.headless_wh = wh;
+		}
+	}
+
+	/// Get where the window's top-left is on the screen, in pixels.
+	pub fn position(&self) -> (i16, i16) {
+		match self.os_window {
+			Some(ref os_window) => os_window.position(),
+			None => (0, 0),
+		}
+	}
+
+	/// Move the window's top-left to `xy` on the screen, in pixels.
+	pub fn set_position(&mut self, xy: (i16, i16)) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_position(xy)
+		}
+	}
+
+	/// Get the content scale factor (1.0 at 96 DPI).  `wh()` is still in
+	/// physical pixels.
+	pub fn scale_factor(&self) -> f32 {
+		match self.os_window {
+			Some(ref os_window) => os_window.scale_factor(),
+			None => 1.0,
+		}
+	}
+
+	/// Whether the window has keyboard focus (never when headless).
+	pub fn has_focus(&self) -> bool {
+		match self.os_window {
+			Some(ref os_window) => os_window.has_focus(),
+			None => false,
+		}
+	}
+
+	/// Put `text` on the system clipboard.
+	pub fn clipboard_set(&mut self, text: &str) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.clipboard_set(text)
+		}
+	}
+
+	/// Get the text on the system clipboard, `None` if there isn't any.
+	pub fn clipboard_get(&self) -> Option<String> {
+		self.os_window.as_ref()?.clipboard_get()
+	}
+
+	/// Set the shape of the mouse cursor over the window.
+	pub fn set_cursor(&mut self, cursor: ::Cursor) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_cursor(cursor)
+		}
+	}
+
+	/// Move the cursor to `xy`, in the same -1 to 1 coordinates as
+	/// `Event::Cursor`.  The move doesn't make an `Event::Cursor`.
+	pub fn set_cursor_position(&mut self, xy: (f32, f32)) {
+		let wh = self.wh();
+
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_cursor_position((
+				((xy.0 + 1.0) * 0.5 * wh.0 as f32) as i16,
+				((xy.1 + 1.0) * 0.5 * wh.1 as f32) as i16,
+			))
+		}
+	}
+
+	/// Set the minimum size of the window, `(0, 0)` for no minimum.
+	pub fn set_min_size(&mut self, wh: (u16, u16)) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_min_size(wh)
+		}
+	}
+
+	/// Set the maximum size of the window, `(0, 0)` for no maximum.
+	pub fn set_max_size(&mut self, wh: (u16, u16)) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_max_size(wh)
+		}
+	}
+
+	/// Keep the window's aspect ratio `(w, h)` while the user resizes it,
+	/// `None` for any.  Returns `false` if the window manager couldn't be
+	/// asked.
+	pub fn set_aspect_ratio(&mut self, aspect: Option<(u16, u16)>) -> bool {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_aspect_ratio(aspect)
+		} else {
+			aspect.is_none()
+		}
+	}
+
+	/// Keep the window above other windows (or stop).  Returns `false` if
+	/// the window manager couldn't be asked.
+	pub fn set_always_on_top(&mut self, above: bool) -> bool {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_always_on_top(above)
+		} else {
+			false
+		}
+	}
+
+	/// Set how see-through the whole window is, from 0 (invisible) to 1
+	/// (opaque, the default).  On X11 it's up to the compositor, so
+	/// without one it does nothing.
+	pub fn set_opacity(&mut self, opacity: f32) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_opacity(opacity)
+		}
+	}
+
+	/// Set the window class, for the taskbar to group windows by.  Only
+	/// `class` is used on Wayland (as the app ID).
+	pub fn set_class(&mut self, instance: &str, class: &str) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_class(instance, class)
+		}
+	}
+
+	/// Set the rectangles `(x, y, w, h)` where clicks & touches go to the
+	/// window (the rest go through to what's behind it), `None` for the
+	/// whole window.  Returns `false` if it's not supported.
+	pub fn set_input_shape(&mut self,
+		region: Option<&[(i16, i16, u16, u16)]>) -> bool
+	{
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_input_shape(region)
+		} else {
+			false
+		}
+	}
+
+	/// Grab the keyboard, so the window manager's shortcuts come to the
+	/// window instead (or let go of it).  It's let go when the window loses
+	/// focus.  Returns `false` if it couldn't be grabbed.
+	pub fn set_keyboard_grab(&mut self, grab: bool) -> bool {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_keyboard_grab(grab)
+		} else {
+			false
+		}
+	}
+
+	/// Show or hide the title bar & borders.
+	pub fn set_decorated(&mut self, decorated: bool) {
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_decorated(decorated)
+		}
+	}
+
+	/// Start moving the window with the cursor, for custom title bars.
+	/// Call when the mouse button is pressed.  Returns `false` if the
+	/// window manager couldn't be asked.
+	pub fn begin_drag(&mut self) -> bool {
+		match self.os_window {
+			Some(ref mut os_window) => os_window.begin_drag(),
+			None => false,
+		}
+	}
+
+	/// Minimize (iconify) the window.  Returns `false` if the window
+	/// manager couldn't be asked.
+	pub fn minimize(&mut self) -> bool {
+		match self.os_window {
+			Some(ref mut os_window) => os_window.minimize(),
+			None => false,
+		}
+	}
+
+	/// Maximize the window.  Returns `false` if the window manager
+	/// couldn't be asked.
+	pub fn maximize(&mut self) -> bool {
+		match self.os_window {
+			Some(ref mut os_window) => os_window.maximize(),
+			None => false,
+		}
+	}
+
+	/// Restore the window from being minimized or maximized.  Returns
+	/// `false` if the window manager couldn't be asked.
+	pub fn restore(&mut self) -> bool {
+		match self.os_window {
+			Some(ref mut os_window) => os_window.restore(),
+			None => false,
+		}
+	}
+
+	/// Close the OS window.  `update()` returns `Event::Exit` once, then
+	/// only `None`.
+	pub fn close(&mut self) {
+		if self.closed {
+			return;
+		}
+
+		self.closed = true;
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.close();
+		}
+
+		// Events for the closed window don't matter anymore.
+		while let Some(_) = self.input_queue.pop() {}
+		self.input_queue.exit();
+	}
+
+	/// Whether `close()` has been called.
+	pub fn is_closed(&self) -> bool {
+		self.closed
+	}
+
+	/// Set where text is being entered `(x, y, w, h)`, in pixels, so the
+	/// input method can put its popup there.  `None` turns off text input
+	/// (no `Event::Text` or `Event::Preedit`).  The position is only used
+	/// on Windows for now.
+	pub fn set_text_input_rect(&mut self,
+		rect: Option<(i16, i16, u16, u16)>)
+	{
+		self.input_queue.set_text_input(rect.is_some());
+		if let Some(ref mut os_window) = self.os_window {
+			os_window.set_text_input_rect(rect)
+		}
+	}
+
+	/// Get the part of the window `(x, y, w, h)` that has to be redrawn,
+	/// because it was uncovered or resized since the last call.  `None` if
+	/// nothing has to be.
+	pub fn take_damage(&mut self) -> Option<(u16, u16, u16, u16)> {
+		self.input_queue.take_damage()
+	}
+
+	/// Whether the window has to be redrawn, since it was uncovered or
+	/// resized after the last call.
+	pub fn take_redraw(&mut self) -> bool {
+		self.input_queue.take_redraw()
+	}
+
+	/// Whether a key is held down (cleared when the window loses focus).
+	pub fn is_key_down(&self, key: u8) -> bool {
+		self.input_queue.is_key_down(key)
+	}
+
+	/// Get the text being composed with an input method (see
+	/// `Event::Preedit`).  Always empty on X11 and Wayland for now.
+	pub fn preedit(&self) -> &str {
+		self.input_queue.get_preedit()
+	}
+
+	/// Send text as `Event::TextStr`, instead of `Event::Text` for each
+	/// `char`.
+	pub fn set_text_str(&mut self, enabled: bool) {
+		self.input_queue.set_text_str(enabled)
+	}
+
+	/// Turn the built-in keyboard shortcuts (like Ctrl-C for
+	/// `Event::Copy`) on or off, for apps with their own.
+	pub fn set_shortcuts_enabled(&mut self, enabled: bool) {
+		self.input_queue.set_shortcuts_enabled(enabled)
+	}
+
+	/// Flip the direction of scroll events, to undo natural scrolling.
+	pub fn set_natural_scroll(&mut self, natural: bool) {
+		self.input_queue.set_natural_scroll(natural)
+	}
+
+	/// Get the text of an `Event::TextStr`.
+	pub fn text_str(&self, index: usize) -> &str {
+		self.input_queue.get_text_str(index)
 	}
 
 	/// Poll window input, return `None` when finished.  After returning
@@ -48,7 +389,8 @@ impl Window {
 		// First, update & get events
 		// Next, cycle them
 		// Then, Return None when through event loop.
-		if let Some(input) = self.input_queue.pop() {
+		if let Some((time, input)) = self.input_queue.pop_timed() {
+			self.time = time;
 			return Some(input);
 		} else if self.reset {
 			self.reset = false;
@@ -57,14 +399,231 @@ impl Window {
 
 		// New Frame
 		self.reset = true;
-		self.get_events();
+		if !self.closed {
+			self.get_events();
+		}
 		self.update()
 	}
 
+	/// Block until there's an event, instead of polling like `update()`,
+	/// so apps that only redraw on input can idle.  Returns `None` once the
+	/// window is closed, or if nothing could ever come (headless without a
+	/// timestep).  Controllers aren't checked while waiting.
+	pub fn wait_event(&mut self) -> Option<::Event> {
+		self.wait(None)
+	}
+
+	/// Like `wait_event()`, but return `None` after `timeout` without an
+	/// event.
+	pub fn wait_event_timeout(&mut self, timeout: ::std::time::Duration)
+		-> Option<::Event>
+	{
+		self.wait(Some(::std::time::Instant::now() + timeout))
+	}
+
+	/// Get all of the events at once, in the same order as `update()`.
+	pub fn drain(&mut self) -> ::Drain {
+		if !self.closed {
+			self.get_events();
+		}
+
+		self.input_queue.drain_timed(&mut self.time)
+	}
+
+	/// Get the first event that `pred` matches, in the same order as
+	/// `update()`, leaving the others for `update()`.
+	pub fn pop_matching<F: Fn(&::Event) -> bool>(&mut self, pred: F)
+		-> Option<::Event>
+	{
+		let mut popped = self.input_queue.pop_matching(&pred);
+		if popped.is_none() && !self.closed {
+			self.get_events();
+			popped = self.input_queue.pop_matching(&pred);
+		}
+
+		let (time, input) = popped?;
+		self.time = time;
+		Some(input)
+	}
+
+	fn wait(&mut self, until: Option<::std::time::Instant>)
+		-> Option<::Event>
+	{
+		loop {
+			if let Some((time, input)) = self.input_queue.pop_timed()
+			{
+				self.time = time;
+				return Some(input);
+			} else if self.closed {
+				return None;
+			}
+
+			// How long to wait, waking up for fixed timesteps too.
+			let now = ::std::time::Instant::now();
+			let timeout = match until {
+				Some(until) if until <= now => return None,
+				Some(until) => Some(until - now),
+				None => None,
+			};
+			let timeout = match (timeout,
+				self.input_queue.next_timestep())
+			{
+				(Some(a), Some(b)) => Some(a.min(b)),
+				(a, b) => a.or(b),
+			};
+
+			match self.os_window {
+				Some(ref mut os_window) => os_window.wait(timeout),
+				None => match timeout {
+					Some(timeout) => ::std::thread::sleep(timeout),
+					None => return None,
+				},
+			}
+
+			self.get_events();
+		}
+	}
+
+	/// Send one `Event::Timestep` on the next update (waking up
+	/// `wait_event()`), for animating without input.
+	pub fn request_frame(&mut self) {
+		self.input_queue.request_frame()
+	}
+
+	/// Keep the last `capacity` events from `update()` (0 for off).
+	pub fn set_event_history(&mut self, capacity: usize) {
+		self.input_queue.set_history(capacity)
+	}
+
+	/// Get the last events from `update()`, oldest first.
+	pub fn event_history(&self) -> &[::Event] {
+		self.input_queue.history()
+	}
+
+	/// Flush, and get the file descriptor of the window system's
+	/// connection, to wait on for events.  `None` if headless, or there
+	/// isn't one.
+	#[cfg(feature = "async")]
+	pub fn fd(&mut self) -> Option<i32> {
+		#[cfg(target_os = "linux")]
+		{
+			self.os_window.as_mut().map(|os_window| os_window.fd())
+		}
+
+		#[cfg(not(target_os = "linux"))]
+		{
+			None
+		}
+	}
+
+	/// Wake `waker` when `fd()` can be read, from a thread that's kept
+	/// until the window is dropped.  `false` if there's no `fd()`.
+	#[cfg(feature = "async")]
+	pub fn wake_on_input(&mut self, waker: ::std::task::Waker) -> bool {
+		let fd = match self.fd() {
+			Some(fd) => fd,
+			None => return false,
+		};
+
+		self.waiter.get_or_insert_with(|| ::render::Waiter::new(fd))
+			.wake_on_input(waker);
+		true
+	}
+
+	/// Get when the last event from `update()` happened.
+	pub fn event_time(&self) -> ::std::time::Instant {
+		self.time
+	}
+
+	/// Send `Event::Timestep` every `step`, instead of once per frame
+	/// (zero to go back).
+	pub fn set_timestep(&mut self, step: ::std::time::Duration) {
+		self.input_queue.set_timestep(step)
+	}
+
+	/// Stop `Event::Timestep` between `Event::Pause` & `Event::Resume`,
+	/// instead of catching up on resume (default off).
+	pub fn set_pause_halts_timestep(&mut self, halts: bool) {
+		self.input_queue.set_pause_halts_timestep(halts)
+	}
+
+	/// Set the deadzone for a controller's sticks & throttles.
+	pub fn set_stick_deadzone(&mut self, controller: usize, inner: f32,
+		outer: f32)
+	{
+		self.input_queue.set_stick_deadzone(controller, inner, outer)
+	}
+
+	/// Remap a controller's buttons, `(from, to)`.
+	pub fn set_controller_map(&mut self, controller: usize,
+		map: &[(::ControllerButton, ::ControllerButton)])
+	{
+		self.input_queue.set_controller_map(controller, map)
+	}
+
+	/// Send D-pad events for a controller's main stick.
+	pub fn set_stick_as_dpad(&mut self, controller: usize, enabled: bool) {
+		self.input_queue.set_stick_as_dpad(controller, enabled)
+	}
+
+	/// Get the battery level of a controller, from 0 to 1.  `None` if it's
+	/// unknown, or the controller is wired.
+	pub fn controller_battery(&self, controller: usize) -> Option<f32> {
+		let index = self.input_queue.controller_index(controller)?;
+
+		// stick doesn't know about batteries, so ask the OS.
+		#[cfg(target_os = "linux")]
+		{
+			os::controller_battery(index)
+		}
+
+		#[cfg(not(target_os = "linux"))]
+		{
+			let _ = index;
+			None
+		}
+	}
+
+	/// Get how many axes, buttons & hats a controller has, and whether it
+	/// can rumble.  `None` if it's unplugged, or it's unknown.
+	pub fn controller_capabilities(&self, controller: usize)
+		-> Option<::ControllerCaps>
+	{
+		let index = self.input_queue.controller_index(controller)?;
+
+		// stick only has the standard layout, so ask the OS.
+		#[cfg(target_os = "linux")]
+		{
+			os::controller_caps(index)
+		}
+
+		#[cfg(not(target_os = "linux"))]
+		{
+			let _ = index;
+			None
+		}
+	}
+
+	/// Get the GUID of a controller's device, which keeps it's number when
+	/// it's plugged back in.  `None` if there's no such controller.  Only
+	/// the vendor & product, so it's not unique to the device.
+	pub fn controller_guid(&self, controller: usize) -> Option<String> {
+		self.input_queue.controller_guid(controller)
+	}
+
 	/// Poll for events.
 	fn get_events(&mut self) {
+		// Fixed timesteps, even without input.
+		self.input_queue.timestep();
+
+		// Headless windows don't get input.
+		let os_window = match self.os_window {
+			Some(ref mut os_window) => os_window,
+			None => return,
+		};
+
 		// Get window events, and update keyboard state.
-		while self.os_window.poll_event(&mut self.input_queue,
+		while os_window.poll_event(&mut self.input_queue,
 			&mut self.keyboard) {}
 
 		// Generate keyboard events from keyboard state.
@@ -10,8 +10,9 @@ use c_void;
 pub enum WindowConnection {
 	/// XCB Window Handles
 	Xcb(*mut c_void, u32),
-	/// Wayland Window Handles
-	Wayland,
+	/// Wayland Window Handles (display, `wl_surface`, then the
+	/// `wl_egl_window` for EGL)
+	Wayland(*mut c_void, *mut c_void, *mut c_void),
 	/// DirectFB Window Handles
 	DirectFB,
 	/// Windows Window Handles
@@ -31,3 +32,84 @@ pub enum WindowConnection {
 	/// No OS Window Handles
 	NoOS,
 }
+
+#[cfg(feature="raw-window-handle")]
+unsafe impl ::raw_window_handle::HasRawDisplayHandle for WindowConnection {
+	fn raw_display_handle(&self) -> ::raw_window_handle::RawDisplayHandle {
+		use raw_window_handle::*;
+
+		match *self {
+			WindowConnection::Xcb(connection, _) => {
+				// A null connection would only crash later, deep
+				// inside of the graphics API.
+				if connection.is_null() {
+					panic!("awi: XCB connection is null, can't \
+						make a display handle!");
+				}
+
+				let mut handle = XcbDisplayHandle::empty();
+				handle.connection = connection;
+				handle.screen = 0; // awi uses the first screen.
+				RawDisplayHandle::Xcb(handle)
+			}
+			WindowConnection::Wayland(display, _, _) => {
+				if display.is_null() {
+					panic!("awi: Wayland display is null, \
+						can't make a display handle!");
+				}
+
+				let mut handle = WaylandDisplayHandle::empty();
+				handle.display = display;
+				RawDisplayHandle::Wayland(handle)
+			}
+			WindowConnection::Windows(_, _) => {
+				RawDisplayHandle::Windows(
+					WindowsDisplayHandle::empty())
+			}
+			WindowConnection::Android => {
+				RawDisplayHandle::Android(
+					AndroidDisplayHandle::empty())
+			}
+			WindowConnection::Web => {
+				RawDisplayHandle::Web(WebDisplayHandle::empty())
+			}
+			_ => panic!("awi: No display handle for this platform!"),
+		}
+	}
+}
+
+#[cfg(all(test, feature="raw-window-handle"))]
+mod tests {
+	use raw_window_handle::*;
+	use std::ptr::null_mut;
+	use super::WindowConnection;
+
+	#[test]
+	#[should_panic(expected = "XCB connection is null")]
+	fn xcb_null_connection() {
+		WindowConnection::Xcb(null_mut(), 0).raw_display_handle();
+	}
+
+	#[test]
+	#[should_panic(expected = "Wayland display is null")]
+	fn wayland_null_display() {
+		WindowConnection::Wayland(null_mut(), null_mut(), null_mut())
+			.raw_display_handle();
+	}
+
+	#[test]
+	fn xcb_connection() {
+		let mut connection = 0u8;
+		let connection = &mut connection as *mut u8 as *mut _;
+
+		let xcb = WindowConnection::Xcb(connection, 1);
+
+		match xcb.raw_display_handle() {
+			RawDisplayHandle::Xcb(handle) => {
+				assert_eq!(handle.connection, connection);
+				assert_eq!(handle.screen, 0);
+			}
+			_ => panic!("Not an XCB display handle!"),
+		}
+	}
+}